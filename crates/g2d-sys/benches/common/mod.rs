@@ -0,0 +1,238 @@
+// SPDX-FileCopyrightText: Copyright 2025 Au-Zone Technologies
+// SPDX-License-Identifier: Apache-2.0
+
+//! Shared benchmark infrastructure for G2D criterion benchmarks.
+//!
+//! DMA buffers come from [`g2d_sys::dma`]; this module only adds surface
+//! helpers and the benchmark configuration matrix.
+
+#![allow(dead_code)]
+
+use criterion::Throughput;
+#[cfg(feature = "dma-heap")]
+pub use g2d_sys::dma::{DmaBuffer, HeapType};
+use g2d_sys::{
+    g2d_format_G2D_NV12, g2d_format_G2D_RGBA8888, g2d_format_G2D_YUYV, g2d_rotation_G2D_ROTATION_0,
+    G2DSurface, Rect, G2D,
+};
+use std::sync::OnceLock;
+
+// =============================================================================
+// Hardware Availability Cache
+// =============================================================================
+
+static G2D_AVAILABLE: OnceLock<bool> = OnceLock::new();
+
+/// Check if G2D hardware is available (cached).
+pub fn g2d_available() -> bool {
+    *G2D_AVAILABLE.get_or_init(|| G2D::new("libg2d.so.2").is_ok())
+}
+
+// =============================================================================
+// Heap labels
+// =============================================================================
+
+/// Short heap label used in benchmark IDs ("uncached" / "cached").
+pub fn heap_name(heap_type: HeapType) -> &'static str {
+    match heap_type {
+        HeapType::Uncached => "uncached",
+        HeapType::Cached => "cached",
+    }
+}
+
+// =============================================================================
+// Surface creation helpers
+// =============================================================================
+
+pub fn create_surface(buf: &DmaBuffer, width: usize, height: usize, format: u32) -> G2DSurface {
+    G2DSurface {
+        format,
+        planes: [buf.address(), 0, 0],
+        left: 0,
+        top: 0,
+        right: width as i32,
+        bottom: height as i32,
+        stride: width as i32,
+        width: width as i32,
+        height: height as i32,
+        blendfunc: 0,
+        global_alpha: 255,
+        clrcolor: 0,
+        rot: g2d_rotation_G2D_ROTATION_0,
+    }
+}
+
+pub fn create_nv12_surface(buf: &DmaBuffer, width: usize, height: usize) -> G2DSurface {
+    let uv_addr = buf.address() + (width * height) as u64;
+    G2DSurface::nv12(
+        buf.address(),
+        uv_addr,
+        width as i32,
+        height as i32,
+        width as i32,
+    )
+}
+
+// =============================================================================
+// Source format constants
+// =============================================================================
+
+pub const SRC_FMT_NV12: u32 = g2d_format_G2D_NV12;
+pub const SRC_FMT_YUYV: u32 = g2d_format_G2D_YUYV;
+pub const SRC_FMT_RGBA: u32 = g2d_format_G2D_RGBA8888;
+pub const DST_FMT_RGBA: u32 = g2d_format_G2D_RGBA8888;
+
+// =============================================================================
+// Benchmark Configuration
+// =============================================================================
+
+#[derive(Clone)]
+pub struct BenchConfig {
+    pub in_w: usize,
+    pub in_h: usize,
+    pub out_w: usize,
+    pub out_h: usize,
+    pub in_fmt: u32,
+    pub out_fmt: u32,
+}
+
+impl BenchConfig {
+    pub fn new(
+        in_w: usize,
+        in_h: usize,
+        out_w: usize,
+        out_h: usize,
+        in_fmt: u32,
+        out_fmt: u32,
+    ) -> Self {
+        Self {
+            in_w,
+            in_h,
+            out_w,
+            out_h,
+            in_fmt,
+            out_fmt,
+        }
+    }
+
+    pub fn id(&self) -> String {
+        if self.in_w == self.out_w && self.in_h == self.out_h {
+            format!(
+                "{}x{}/{}->{}",
+                self.in_w,
+                self.in_h,
+                format_name(self.in_fmt),
+                format_name(self.out_fmt)
+            )
+        } else {
+            format!(
+                "{}x{}/{}->{}x{}/{}",
+                self.in_w,
+                self.in_h,
+                format_name(self.in_fmt),
+                self.out_w,
+                self.out_h,
+                format_name(self.out_fmt)
+            )
+        }
+    }
+
+    pub fn throughput(&self) -> Throughput {
+        Throughput::Bytes(self.src_buf_size() as u64)
+    }
+
+    /// Buffer size in bytes for the source format.
+    pub fn src_buf_size(&self) -> usize {
+        buf_size(self.in_w, self.in_h, self.in_fmt)
+    }
+
+    /// Buffer size in bytes for the destination format (always RGBA).
+    pub fn dst_buf_size(&self) -> usize {
+        buf_size(self.out_w, self.out_h, self.out_fmt)
+    }
+}
+
+/// Calculate buffer size in bytes for a given resolution and G2D format.
+pub fn buf_size(width: usize, height: usize, fmt: u32) -> usize {
+    match fmt {
+        f if f == SRC_FMT_NV12 => width * height * 3 / 2,
+        f if f == SRC_FMT_YUYV => width * height * 2,
+        f if f == SRC_FMT_RGBA => width * height * 4,
+        _ => width * height * 4,
+    }
+}
+
+/// Human-readable name for a G2D format constant.
+pub fn format_name(fmt: u32) -> &'static str {
+    match fmt {
+        f if f == SRC_FMT_NV12 => "NV12",
+        f if f == SRC_FMT_YUYV => "YUYV",
+        f if f == SRC_FMT_RGBA => "RGBA",
+        _ => "???",
+    }
+}
+
+// =============================================================================
+// Letterbox Calculation
+// =============================================================================
+
+/// Calculate letterbox dimensions to fit source into destination while
+/// preserving aspect ratio.
+///
+/// Returns (left_offset, top_offset, scaled_width, scaled_height).
+pub fn calculate_letterbox(
+    src_w: usize,
+    src_h: usize,
+    dst_w: usize,
+    dst_h: usize,
+) -> (usize, usize, usize, usize) {
+    let inner = Rect::new(0, 0, dst_w as i32, dst_h as i32).fit(src_w as i32, src_h as i32);
+    (
+        inner.left as usize,
+        inner.top as usize,
+        inner.width() as usize,
+        inner.height() as usize,
+    )
+}
+
+// =============================================================================
+// Source Buffer Initialization
+// =============================================================================
+
+/// Initialize a source DMA buffer with uniform data appropriate for the format.
+pub fn init_source_buffer(buf: &mut DmaBuffer, width: usize, height: usize, fmt: u32) {
+    buf.write_with(|data| match fmt {
+        f if f == SRC_FMT_NV12 => {
+            let y_size = width * height;
+            data[..y_size].fill(128); // Y plane: neutral gray
+            data[y_size..].fill(128); // UV plane: neutral chroma
+        }
+        f if f == SRC_FMT_YUYV => {
+            // YUYV: [Y0, U, Y1, V] macropixels
+            for chunk in data.chunks_exact_mut(4) {
+                chunk[0] = 128; // Y0
+                chunk[1] = 128; // U
+                chunk[2] = 128; // Y1
+                chunk[3] = 128; // V
+            }
+        }
+        _ => {
+            // RGBA: neutral gray with full alpha
+            for chunk in data.chunks_exact_mut(4) {
+                chunk[0] = 128; // R
+                chunk[1] = 128; // G
+                chunk[2] = 128; // B
+                chunk[3] = 255; // A
+            }
+        }
+    });
+}
+
+/// Create a source surface for the given format, handling NV12 specially.
+pub fn create_source_surface(buf: &DmaBuffer, width: usize, height: usize, fmt: u32) -> G2DSurface {
+    if fmt == SRC_FMT_NV12 {
+        create_nv12_surface(buf, width, height)
+    } else {
+        create_surface(buf, width, height, fmt)
+    }
+}
@@ -21,8 +21,9 @@
 mod common;
 
 use common::{
-    calculate_letterbox, create_source_surface, create_surface, g2d_available, init_source_buffer,
-    BenchConfig, DmaBuffer, HeapType, DST_FMT_RGBA, SRC_FMT_NV12, SRC_FMT_RGBA, SRC_FMT_YUYV,
+    calculate_letterbox, create_source_surface, create_surface, g2d_available, heap_name,
+    init_source_buffer, BenchConfig, DmaBuffer, HeapType, DST_FMT_RGBA, SRC_FMT_NV12, SRC_FMT_RGBA,
+    SRC_FMT_YUYV,
 };
 use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
 use g2d_sys::G2D;
@@ -69,7 +70,7 @@ fn bench_convert(c: &mut Criterion) {
                 let src_size = config.src_buf_size();
                 let dst_size = config.dst_buf_size();
 
-                let src_buf = match DmaBuffer::new(heap_type, src_size) {
+                let mut src_buf = match DmaBuffer::new(heap_type, src_size) {
                     Ok(b) => b,
                     Err(e) => {
                         eprintln!(
@@ -92,7 +93,7 @@ fn bench_convert(c: &mut Criterion) {
                     }
                 };
 
-                init_source_buffer(&src_buf, width, height, fmt);
+                init_source_buffer(&mut src_buf, width, height, fmt);
 
                 let mut g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
                 g2d.set_bt709_colorspace()
@@ -103,7 +104,7 @@ fn bench_convert(c: &mut Criterion) {
 
                 group.throughput(config.throughput());
                 group.bench_with_input(
-                    BenchmarkId::new(heap_type.name(), config.id()),
+                    BenchmarkId::new(heap_name(heap_type), config.id()),
                     &config,
                     |b, _| {
                         b.iter(|| {
@@ -148,7 +149,7 @@ fn bench_resize(c: &mut Criterion) {
                 let src_size = config.src_buf_size();
                 let dst_size = config.dst_buf_size();
 
-                let src_buf = match DmaBuffer::new(heap_type, src_size) {
+                let mut src_buf = match DmaBuffer::new(heap_type, src_size) {
                     Ok(b) => b,
                     Err(e) => {
                         eprintln!(
@@ -171,7 +172,7 @@ fn bench_resize(c: &mut Criterion) {
                     }
                 };
 
-                init_source_buffer(&src_buf, src_w, src_h, fmt);
+                init_source_buffer(&mut src_buf, src_w, src_h, fmt);
 
                 let mut g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
                 if fmt != SRC_FMT_RGBA {
@@ -184,7 +185,7 @@ fn bench_resize(c: &mut Criterion) {
 
                 group.throughput(config.throughput());
                 group.bench_with_input(
-                    BenchmarkId::new(heap_type.name(), config.id()),
+                    BenchmarkId::new(heap_name(heap_type), config.id()),
                     &config,
                     |b, _| {
                         b.iter(|| {
@@ -234,7 +235,7 @@ fn bench_letterbox(c: &mut Criterion) {
                     let src_size = config.src_buf_size();
                     let dst_size = config.dst_buf_size();
 
-                    let src_buf = match DmaBuffer::new(heap_type, src_size) {
+                    let mut src_buf = match DmaBuffer::new(heap_type, src_size) {
                         Ok(b) => b,
                         Err(e) => {
                             eprintln!(
@@ -257,7 +258,7 @@ fn bench_letterbox(c: &mut Criterion) {
                         }
                     };
 
-                    init_source_buffer(&src_buf, src_w, src_h, fmt);
+                    init_source_buffer(&mut src_buf, src_w, src_h, fmt);
 
                     let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
 
@@ -287,7 +288,7 @@ fn bench_letterbox(c: &mut Criterion) {
 
                     group.throughput(config.throughput());
                     group.bench_with_input(
-                        BenchmarkId::new(heap_type.name(), config.id()),
+                        BenchmarkId::new(heap_name(heap_type), config.id()),
                         &config,
                         |b, _| {
                             b.iter(|| {
@@ -372,7 +373,7 @@ fn bench_partial_clear(c: &mut Criterion) {
 
         // CPU partial fill: write only the bar regions via mmap
         {
-            let buf = match DmaBuffer::new(HeapType::Cached, dst_size) {
+            let mut buf = match DmaBuffer::new(HeapType::Cached, dst_size) {
                 Ok(b) => b,
                 Err(e) => {
                     eprintln!("Skipping CPU {}: alloc failed: {e}", config.name);
@@ -433,7 +434,7 @@ fn bench_partial_clear(c: &mut Criterion) {
                 })
                 .collect();
 
-            let g2d_id = format!("g2d/{}", heap_type.name());
+            let g2d_id = format!("g2d/{}", heap_name(heap_type));
             group.bench_function(BenchmarkId::new(&g2d_id, config.name), |b| {
                 let mut bar_surfaces = bars.clone();
                 b.iter(|| {
@@ -450,11 +451,203 @@ fn bench_partial_clear(c: &mut Criterion) {
     group.finish();
 }
 
+// =============================================================================
+// Small Clear Benchmarks — clear_auto CPU fill vs GPU clear
+// =============================================================================
+
+/// Compare the two `clear_auto` paths for small square regions, forcing each
+/// path via the CPU threshold. The crossover point is what
+/// `calibrate_cpu_clear_threshold` estimates at runtime.
+fn bench_small_clear(c: &mut Criterion) {
+    if !g2d_available() {
+        eprintln!("G2D not available, skipping small clear benchmarks");
+        return;
+    }
+
+    let mut group = c.benchmark_group("small_clear");
+    group.sample_size(200);
+
+    let gray = [114u8, 114, 114, 255];
+    let (dst_w, dst_h) = (640usize, 640usize);
+    let sizes = [8usize, 32, 128];
+
+    for heap_type in [HeapType::Uncached, HeapType::Cached] {
+        if !heap_type.is_available() {
+            continue;
+        }
+
+        let mut buf = match DmaBuffer::new(heap_type, dst_w * dst_h * 4) {
+            Ok(b) => b,
+            Err(e) => {
+                eprintln!("Skipping small_clear/{heap_type}: alloc failed: {e}");
+                continue;
+            }
+        };
+        let heap_name = heap_name(heap_type);
+
+        for (path, threshold) in [("cpu", usize::MAX), ("g2d", 0)] {
+            let mut g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+            g2d.set_cpu_clear_threshold(threshold);
+
+            for &size in &sizes {
+                let mut surface = g2d_sys::G2DSurface {
+                    format: DST_FMT_RGBA,
+                    planes: [buf.address(), 0, 0],
+                    right: size as i32,
+                    bottom: size as i32,
+                    stride: dst_w as i32,
+                    width: dst_w as i32,
+                    height: dst_h as i32,
+                    ..Default::default()
+                };
+
+                let id = format!("{path}/{heap_name}");
+                group.bench_function(BenchmarkId::new(&id, format!("{size}x{size}")), |b| {
+                    b.iter(|| {
+                        g2d.clear_auto(&mut surface, &mut buf, gray)
+                            .expect("clear_auto failed");
+                        g2d.finish().expect("finish failed");
+                        black_box(&buf);
+                    });
+                });
+            }
+        }
+    }
+
+    group.finish();
+}
+
+// =============================================================================
+// Black/White Clear Benchmarks — byte fill vs per-pixel fill vs GPU clear
+// =============================================================================
+
+/// Compare `clear_black` on each path against a per-pixel `clear_auto` CPU
+/// fill of the same color, for a full-width region where the byte fill
+/// covers the whole span at once.
+fn bench_black_clear(c: &mut Criterion) {
+    if !g2d_available() {
+        eprintln!("G2D not available, skipping black clear benchmarks");
+        return;
+    }
+
+    let mut group = c.benchmark_group("black_clear");
+    let (dst_w, dst_h) = (640usize, 640usize);
+    let rows = [8usize, 64, 640];
+
+    for heap_type in [HeapType::Uncached, HeapType::Cached] {
+        if !heap_type.is_available() {
+            continue;
+        }
+
+        let mut buf = match DmaBuffer::new(heap_type, dst_w * dst_h * 4) {
+            Ok(b) => b,
+            Err(e) => {
+                eprintln!("Skipping black_clear/{heap_type}: alloc failed: {e}");
+                continue;
+            }
+        };
+        let heap_name = heap_name(heap_type);
+
+        for (path, threshold) in [("memset", usize::MAX), ("g2d", 0), ("pixel", usize::MAX)] {
+            let mut g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+            g2d.set_cpu_clear_threshold(threshold);
+
+            for &height in &rows {
+                let mut surface = g2d_sys::G2DSurface {
+                    format: DST_FMT_RGBA,
+                    planes: [buf.address(), 0, 0],
+                    right: dst_w as i32,
+                    bottom: height as i32,
+                    stride: dst_w as i32,
+                    width: dst_w as i32,
+                    height: dst_h as i32,
+                    ..Default::default()
+                };
+
+                let id = format!("{path}/{heap_name}");
+                group.bench_function(BenchmarkId::new(&id, format!("{dst_w}x{height}")), |b| {
+                    b.iter(|| {
+                        if path == "pixel" {
+                            g2d.clear_auto(&mut surface, &mut buf, [0, 0, 0, 0])
+                                .expect("clear_auto failed");
+                        } else {
+                            g2d.clear_black(&mut surface, &mut buf)
+                                .expect("clear_black failed");
+                        }
+                        g2d.finish().expect("finish failed");
+                        black_box(&buf);
+                    });
+                });
+            }
+        }
+    }
+
+    group.finish();
+}
+
+// =============================================================================
+// Copy Benchmarks — g2d_copy vs same-format blit
+// =============================================================================
+
+/// Compare `G2D::copy` with a full-frame blit for a same-format,
+/// same-size 1080p RGBA duplication.
+fn bench_copy(c: &mut Criterion) {
+    if !g2d_available() {
+        eprintln!("G2D not available, skipping copy benchmarks");
+        return;
+    }
+
+    let mut group = c.benchmark_group("copy");
+    group.sample_size(50);
+    let (width, height) = (1920usize, 1080usize);
+
+    for heap_type in [HeapType::Uncached, HeapType::Cached] {
+        if !heap_type.is_available() {
+            continue;
+        }
+
+        let (src_buf, dst_buf) = match (
+            DmaBuffer::new(heap_type, width * height * 4),
+            DmaBuffer::new(heap_type, width * height * 4),
+        ) {
+            (Ok(src), Ok(dst)) => (src, dst),
+            (Err(e), _) | (_, Err(e)) => {
+                eprintln!("Skipping copy/{heap_type}: alloc failed: {e}");
+                continue;
+            }
+        };
+        let heap_name = heap_name(heap_type);
+        let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+        let src = create_surface(&src_buf, width, height, SRC_FMT_RGBA);
+        let dst = create_surface(&dst_buf, width, height, DST_FMT_RGBA);
+
+        for path in ["copy", "blit"] {
+            let id = format!("{path}/{heap_name}");
+            group.bench_function(BenchmarkId::new(&id, format!("{width}x{height}")), |b| {
+                b.iter(|| {
+                    if path == "copy" {
+                        g2d.copy(&src, &dst).expect("copy failed");
+                    } else {
+                        g2d.blit(&src, &dst).expect("blit failed");
+                    }
+                    g2d.finish().expect("finish failed");
+                    black_box(&dst_buf);
+                });
+            });
+        }
+    }
+
+    group.finish();
+}
+
 criterion_group!(
     benches,
     bench_convert,
     bench_resize,
     bench_letterbox,
-    bench_partial_clear
+    bench_partial_clear,
+    bench_small_clear,
+    bench_black_clear,
+    bench_copy
 );
 criterion_main!(benches);
@@ -0,0 +1,192 @@
+// SPDX-FileCopyrightText: Copyright 2025 Au-Zone Technologies
+// SPDX-License-Identifier: Apache-2.0
+
+//! Recorded batches of clears and blits, submitted with a single `finish`.
+//!
+//! Recording operations before queuing them lets the batch drop work whose
+//! result is never seen. Currently that is a full-surface clear immediately
+//! followed by a full-surface blit into the same surface, the usual shape
+//! of naive letterbox code that clears the whole frame and then blits over
+//! all of it.
+
+use crate::{g2d_cap_mode_G2D_BLEND, G2DSurface, Rect, Result, G2D};
+
+#[derive(Debug, Clone, Copy)]
+enum Op {
+    Clear {
+        dst: G2DSurface,
+        color: [u8; 4],
+    },
+    Blit {
+        src: G2DSurface,
+        dst: G2DSurface,
+    },
+    Overlay {
+        base: G2DSurface,
+        overlay: G2DSurface,
+        x: i32,
+        y: i32,
+    },
+}
+
+/// Counts of the operations a [`Batch`] queued and elided.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BatchStats {
+    pub clears: usize,
+    pub blits: usize,
+    /// Blended overlays (see [`G2D::overlay`]).
+    pub overlays: usize,
+    /// Clears dropped because the next operation overwrote the whole
+    /// surface.
+    pub elided_clears: usize,
+}
+
+/// Operations recorded for submission on one [`G2D`] handle.
+///
+/// Nothing reaches the GPU until [`submit()`](Self::submit), which queues
+/// every operation in order and waits once for all of them.
+#[derive(Debug)]
+pub struct Batch<'a> {
+    g2d: &'a G2D,
+    ops: Vec<Op>,
+    coalesce_clears: bool,
+}
+
+impl<'a> Batch<'a> {
+    pub fn new(g2d: &'a G2D) -> Self {
+        Batch {
+            g2d,
+            ops: Vec::new(),
+            coalesce_clears: true,
+        }
+    }
+
+    /// Whether to drop redundant clears on submit (on by default).
+    pub fn with_coalesce_clears(mut self, coalesce: bool) -> Self {
+        self.coalesce_clears = coalesce;
+        self
+    }
+
+    /// Record a clear of the crop of `dst` to `color`.
+    pub fn clear(&mut self, dst: &G2DSurface, color: [u8; 4]) -> &mut Self {
+        self.ops.push(Op::Clear { dst: *dst, color });
+        self
+    }
+
+    /// Record a blit from `src` to `dst`.
+    pub fn blit(&mut self, src: &G2DSurface, dst: &G2DSurface) -> &mut Self {
+        self.ops.push(Op::Blit {
+            src: *src,
+            dst: *dst,
+        });
+        self
+    }
+
+    /// Record a blended overlay of `overlay` onto `base` at `(x, y)`, as
+    /// queued by [`G2D::overlay`].
+    pub fn overlay(
+        &mut self,
+        base: &G2DSurface,
+        overlay: &G2DSurface,
+        x: i32,
+        y: i32,
+    ) -> &mut Self {
+        self.ops.push(Op::Overlay {
+            base: *base,
+            overlay: *overlay,
+            x,
+            y,
+        });
+        self
+    }
+
+    /// Number of recorded operations.
+    pub fn len(&self) -> usize {
+        self.ops.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    /// Queue the recorded operations and wait for them to complete.
+    ///
+    /// With coalescing enabled, a clear is skipped when its crop covers the
+    /// whole surface and the next operation is a blit whose destination is
+    /// the same surface, also with a full-surface crop. `G2D_BLEND` makes
+    /// the blit read the cleared pixels, so nothing is elided while it is
+    /// enabled.
+    ///
+    /// Stops queuing at the first failing operation; operations queued
+    /// before it are still completed.
+    pub fn submit(self) -> Result<BatchStats> {
+        let coalesce = self.coalesce_clears
+            && self.ops.windows(2).any(|w| overwrites(&w[0], &w[1]))
+            && !self.g2d.cap_enabled(g2d_cap_mode_G2D_BLEND)?;
+
+        let mut stats = BatchStats::default();
+        let queued = self
+            .ops
+            .iter()
+            .enumerate()
+            .try_for_each(|(i, op)| match op {
+                Op::Clear { dst, color } => {
+                    if coalesce && self.ops.get(i + 1).is_some_and(|next| overwrites(op, next)) {
+                        stats.elided_clears += 1;
+                        return Ok(());
+                    }
+                    stats.clears += 1;
+                    let mut dst = *dst;
+                    self.g2d.clear(&mut dst, *color)
+                }
+                Op::Blit { src, dst } => {
+                    stats.blits += 1;
+                    self.g2d.blit(src, dst)
+                }
+                Op::Overlay {
+                    base,
+                    overlay,
+                    x,
+                    y,
+                } => {
+                    stats.overlays += 1;
+                    self.g2d.overlay(base, overlay, *x, *y)
+                }
+            });
+        let finished = self.g2d.finish();
+        queued.and(finished)?;
+        Ok(stats)
+    }
+}
+
+/// Whether `next` fully overwrites what the clear `op` writes.
+fn overwrites(op: &Op, next: &Op) -> bool {
+    let (Op::Clear { dst: cleared, .. }, Op::Blit { dst, .. }) = (op, next) else {
+        return false;
+    };
+    is_full(cleared)
+        && is_full(dst)
+        && cleared.planes == dst.planes
+        && cleared.format == dst.format
+        && cleared.stride == dst.stride
+        && cleared.dimensions() == dst.dimensions()
+}
+
+fn is_full(surface: &G2DSurface) -> bool {
+    let (width, height) = surface.dimensions();
+    surface.crop_rect() == Rect::new(0, 0, width, height)
+}
+
+impl G2D {
+    /// Start recording a [`Batch`] of operations on this handle.
+    pub fn batch(&self) -> Batch<'_> {
+        Batch::new(self)
+    }
+
+    /// Record operations with `f` and submit them as one [`Batch`].
+    pub fn scope(&self, f: impl FnOnce(&mut Batch<'_>)) -> Result<BatchStats> {
+        let mut batch = self.batch();
+        f(&mut batch);
+        batch.submit()
+    }
+}
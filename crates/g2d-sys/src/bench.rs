@@ -0,0 +1,184 @@
+// SPDX-FileCopyrightText: Copyright 2025 Au-Zone Technologies
+// SPDX-License-Identifier: Apache-2.0
+
+//! Runtime benchmarks of a single conversion or a caller's workload, for
+//! logging or choosing a pipeline configuration on the running board
+//! without criterion.
+
+use crate::{
+    dma::{DmaBuffer, HeapType},
+    Error, G2DFormat, Result, G2D,
+};
+use std::time::{Duration, Instant};
+
+/// Timing of repeated blit + `finish` round trips.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BenchResult {
+    /// Number of timed iterations.
+    pub iterations: usize,
+    pub mean: Duration,
+    pub min: Duration,
+    pub max: Duration,
+    /// Output pixels converted per second, at the mean time.
+    pub pixels_per_sec: f64,
+    /// Source and destination bytes moved per second, at the mean time (see
+    /// [`metrics::bytes_moved`](crate::metrics::bytes_moved)).
+    pub bytes_per_sec: f64,
+}
+
+/// Timing of repeated runs of a workload on one DMA heap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeapTiming {
+    /// Number of timed iterations.
+    pub iterations: usize,
+    pub mean: Duration,
+    pub min: Duration,
+    pub max: Duration,
+}
+
+/// Timing of the same workload on each DMA heap, from [`compare_heaps`].
+///
+/// A heap that is not available on the running board is `None`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeapComparison {
+    pub uncached: Option<HeapTiming>,
+    pub cached: Option<HeapTiming>,
+}
+
+impl HeapComparison {
+    /// Timing for `heap_type`, if it was run.
+    pub fn get(&self, heap_type: HeapType) -> Option<HeapTiming> {
+        match heap_type {
+            HeapType::Uncached => self.uncached,
+            HeapType::Cached => self.cached,
+        }
+    }
+
+    /// The heap with the lower mean time, or the only heap that was run.
+    /// Ties go to the uncached heap, which needs no cache maintenance.
+    pub fn faster(&self) -> Option<HeapType> {
+        match (self.uncached, self.cached) {
+            (Some(uncached), Some(cached)) if cached.mean < uncached.mean => Some(HeapType::Cached),
+            (Some(_), _) => Some(HeapType::Uncached),
+            (None, Some(_)) => Some(HeapType::Cached),
+            (None, None) => None,
+        }
+    }
+}
+
+/// Time a caller's workload on the uncached and the cached DMA heap.
+///
+/// For each available heap, `setup` is called once with the heap type to
+/// allocate its buffers and build surfaces, and returns the workload to
+/// time. The workload runs once untimed as a warm-up and then `iterations`
+/// times (clamped to at least 1), each run timed whole; it should cover the
+/// full cycle being compared, such as blit, [`G2D::finish`] and reading the
+/// result back with [`DmaBuffer::read_with`], since uncached CPU reads can
+/// cost more than cache invalidation on a cached heap for CPU-heavy access
+/// patterns. Fails with the first error from `setup` or the workload, or
+/// with [`Error::HeapUnavailable`] if neither heap is available.
+pub fn compare_heaps<S, W>(iterations: usize, mut setup: S) -> Result<HeapComparison>
+where
+    S: FnMut(HeapType) -> Result<W>,
+    W: FnMut() -> Result<()>,
+{
+    let mut run = |heap_type: HeapType| -> Result<Option<HeapTiming>> {
+        if !heap_type.is_available() {
+            return Ok(None);
+        }
+        let mut workload = setup(heap_type)?;
+        workload()?;
+        time_iterations(iterations, workload).map(Some)
+    };
+    let comparison = HeapComparison {
+        uncached: run(HeapType::Uncached)?,
+        cached: run(HeapType::Cached)?,
+    };
+    if comparison.faster().is_none() {
+        return Err(no_heap());
+    }
+    Ok(comparison)
+}
+
+/// Time `iterations` runs of `f`, clamped to at least one.
+fn time_iterations(iterations: usize, mut f: impl FnMut() -> Result<()>) -> Result<HeapTiming> {
+    let iterations = iterations.max(1);
+    let mut total = Duration::ZERO;
+    let mut min = Duration::MAX;
+    let mut max = Duration::ZERO;
+    for _ in 0..iterations {
+        let start = Instant::now();
+        f()?;
+        let elapsed = start.elapsed();
+        total += elapsed;
+        min = min.min(elapsed);
+        max = max.max(elapsed);
+    }
+    Ok(HeapTiming {
+        iterations,
+        mean: total / iterations as u32,
+        min,
+        max,
+    })
+}
+
+fn no_heap() -> Error {
+    Error::HeapUnavailable {
+        heap: format!("{} or {}", HeapType::Uncached, HeapType::Cached),
+        source: std::io::ErrorKind::NotFound.into(),
+    }
+}
+
+impl G2D {
+    /// Time `iterations` full-frame `width`×`height` blits from `src_format`
+    /// to `dst_format`, each followed by [`finish()`](Self::finish).
+    ///
+    /// One source and one destination buffer are allocated (from the
+    /// uncached CMA heap, or the cached one if it is unavailable, as by
+    /// [`DmaBuffer::new_auto`]) and reused for every iteration, after one
+    /// untimed warm-up blit, so the result measures the conversion rather
+    /// than allocation. Surfaces are tightly packed. `iterations` is
+    /// clamped to at least 1.
+    pub fn benchmark_convert(
+        &self,
+        src_format: G2DFormat,
+        dst_format: G2DFormat,
+        width: usize,
+        height: usize,
+        iterations: usize,
+    ) -> Result<BenchResult> {
+        let size = |format: G2DFormat| -> Result<usize> {
+            Ok(format.plane_layout(width, height, 1, 1)?.size())
+        };
+        let mut src_buf = DmaBuffer::new_auto(size(src_format)?)?;
+        // Both buffers on the same heap, so the timing is of one heap.
+        let dst_buf = DmaBuffer::new(src_buf.heap_type(), size(dst_format)?)?;
+        let src = src_buf.reinterpret_surface(src_format, width, height, 0)?;
+        let dst = dst_buf.reinterpret_surface(dst_format, width, height, 0)?;
+        // Mid-grey in RGB, neutral chroma in YUV.
+        src_buf.write_with(|data| data.fill(0x80));
+
+        self.blit(&src, &dst)?;
+        self.finish()?;
+
+        let HeapTiming {
+            iterations,
+            mean,
+            min,
+            max,
+        } = time_iterations(iterations, || {
+            self.blit(&src, &dst)?;
+            self.finish()
+        })?;
+
+        let per_sec = |amount: f64| amount / mean.as_secs_f64().max(f64::MIN_POSITIVE);
+        Ok(BenchResult {
+            iterations,
+            mean,
+            min,
+            max,
+            pixels_per_sec: per_sec((width * height) as f64),
+            bytes_per_sec: per_sec(crate::metrics::bytes_moved(&src, &dst) as f64),
+        })
+    }
+}
@@ -0,0 +1,119 @@
+// SPDX-FileCopyrightText: Copyright 2025 Au-Zone Technologies
+// SPDX-License-Identifier: Apache-2.0
+
+//! Physically contiguous buffers allocated by libg2d itself, for systems
+//! without `/dev/dma_heap`.
+//!
+//! `g2d_alloc` takes memory from the GPU driver's contiguous pool and maps
+//! it into the process, so these buffers need neither the `dma-heap`
+//! feature nor a DMA heap device. They are not dma-bufs: they cannot be
+//! shared with other devices or processes, only blitted by G2D and read and
+//! written by the CPU.
+
+use crate::{
+    g2d, g2d_buf, g2d_cache_mode, g2d_cache_mode_G2D_CACHE_CLEAN,
+    g2d_cache_mode_G2D_CACHE_INVALIDATE, Error, Result, G2D,
+};
+use std::{
+    os::raw::{c_int, c_ulong},
+    rc::Rc,
+};
+
+/// A contiguous buffer from `g2d_alloc`, freed with `g2d_free` on drop.
+///
+/// A cacheable buffer's CPU caches are not kept coherent with G2D: call
+/// [`clean()`](Self::clean) after writing it from the CPU and before a
+/// blit reads it, and [`invalidate()`](Self::invalidate) after a blit
+/// writes it and before reading it from the CPU. Both are no-ops for a
+/// write-combined (non-cacheable) buffer.
+#[derive(Debug)]
+pub struct G2DBuffer {
+    lib: Rc<g2d>,
+    buf: *mut g2d_buf,
+    size: usize,
+    cacheable: bool,
+}
+
+impl G2DBuffer {
+    /// Allocate `size` bytes through the library `g2d` was opened on,
+    /// mapped cacheable or write-combined.
+    ///
+    /// Fails with [`Error::InvalidSurface`] for a size of zero or one
+    /// libg2d cannot express, and with [`Error::AllocationFailed`] when
+    /// the driver refuses the allocation.
+    pub fn new(g2d: &G2D, size: usize, cacheable: bool) -> Result<Self> {
+        let request = c_int::try_from(size)
+            .ok()
+            .filter(|&size| size > 0)
+            .ok_or_else(|| {
+                Error::InvalidSurface(format!("cannot g2d_alloc a {size}-byte buffer"))
+            })?;
+        let buf = unsafe { g2d.lib.g2d_alloc(request, cacheable as c_int) };
+        if buf.is_null() {
+            return Err(Error::AllocationFailed {
+                heap: "g2d_alloc".to_string(),
+                size,
+                source: std::io::Error::last_os_error(),
+            });
+        }
+        Ok(G2DBuffer {
+            lib: g2d.lib.clone(),
+            buf,
+            size,
+            cacheable,
+        })
+    }
+
+    /// Physical address of the buffer, suitable for `G2DSurface::planes`.
+    pub fn address(&self) -> c_ulong {
+        unsafe { (*self.buf).buf_paddr }
+    }
+
+    /// Size of the buffer in bytes.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Whether the CPU mapping is cached; see the type documentation.
+    pub fn is_cacheable(&self) -> bool {
+        self.cacheable
+    }
+
+    /// The buffer's CPU mapping.
+    pub fn as_slice(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts((*self.buf).buf_vaddr as *const u8, self.size) }
+    }
+
+    /// The buffer's CPU mapping, for writing.
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+        unsafe { std::slice::from_raw_parts_mut((*self.buf).buf_vaddr as *mut u8, self.size) }
+    }
+
+    /// Write CPU caches back to memory so G2D reads what the CPU wrote.
+    pub fn clean(&self) -> Result<()> {
+        self.cache_op(g2d_cache_mode_G2D_CACHE_CLEAN)
+    }
+
+    /// Drop stale CPU cache lines so the CPU reads what G2D wrote.
+    pub fn invalidate(&self) -> Result<()> {
+        self.cache_op(g2d_cache_mode_G2D_CACHE_INVALIDATE)
+    }
+
+    fn cache_op(&self, op: g2d_cache_mode) -> Result<()> {
+        if !self.cacheable {
+            return Ok(());
+        }
+        if unsafe { self.lib.g2d_cache_op(self.buf, op) } != 0 {
+            return Err(std::io::Error::last_os_error().into());
+        }
+        Ok(())
+    }
+}
+
+impl Drop for G2DBuffer {
+    fn drop(&mut self) {
+        if unsafe { self.lib.g2d_free(self.buf) } != 0 {
+            log::warn!("g2d_free of a {}-byte buffer failed", self.size);
+        }
+    }
+}
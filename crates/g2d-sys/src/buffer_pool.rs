@@ -0,0 +1,139 @@
+// SPDX-FileCopyrightText: Copyright 2025 Au-Zone Technologies
+// SPDX-License-Identifier: Apache-2.0
+
+//! Reusable DMA buffers handed out as owned guards, for "frame in, frame
+//! out" conversions with [`G2D::convert_owned`].
+
+use crate::{
+    dma::{DmaBuffer, HeapType},
+    G2DFormat, G2DSurface, Result, G2D,
+};
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
+
+type FreeList = Rc<RefCell<Vec<DmaBuffer>>>;
+
+/// Buffers from one heap that are reused once their [`PooledBuffer`] guard
+/// is dropped.
+///
+/// The pool grows on demand: [`acquire()`](Self::acquire) reuses a free
+/// buffer that is large enough and allocates a new one only when none is.
+/// Like [`G2D`], the pool and its buffers stay on one thread.
+#[derive(Debug)]
+pub struct BufferPool {
+    heap_type: HeapType,
+    free: FreeList,
+    allocated: Cell<usize>,
+}
+
+/// A buffer borrowed from a [`BufferPool`], returned to it on drop.
+#[derive(Debug)]
+pub struct PooledBuffer {
+    buffer: Option<DmaBuffer>,
+    surface: G2DSurface,
+    free: FreeList,
+}
+
+impl BufferPool {
+    /// Empty pool allocating from `heap_type`.
+    pub fn new(heap_type: HeapType) -> Self {
+        BufferPool {
+            heap_type,
+            free: Rc::default(),
+            allocated: Cell::new(0),
+        }
+    }
+
+    /// A buffer of at least `size` bytes: the smallest free one that fits,
+    /// or a newly allocated one. Its surface is left empty; see
+    /// [`PooledBuffer::surface`].
+    pub fn acquire(&self, size: usize) -> Result<PooledBuffer> {
+        let reused = {
+            let mut free = self.free.borrow_mut();
+            free.iter()
+                .enumerate()
+                .filter(|(_, buffer)| buffer.size() >= size)
+                .min_by_key(|(_, buffer)| buffer.size())
+                .map(|(i, _)| i)
+                .map(|i| free.swap_remove(i))
+        };
+        let buffer = match reused {
+            Some(buffer) => buffer,
+            None => {
+                let buffer = DmaBuffer::new(self.heap_type, size)?;
+                self.allocated.set(self.allocated.get() + 1);
+                buffer
+            }
+        };
+        Ok(PooledBuffer {
+            buffer: Some(buffer),
+            surface: G2DSurface::default(),
+            free: self.free.clone(),
+        })
+    }
+
+    /// Heap the pool allocates from.
+    pub fn heap_type(&self) -> HeapType {
+        self.heap_type
+    }
+
+    /// Buffers waiting in the pool to be reused.
+    pub fn available(&self) -> usize {
+        self.free.borrow().len()
+    }
+
+    /// Buffers allocated by the pool so far, whether free or handed out.
+    pub fn allocated(&self) -> usize {
+        self.allocated.get()
+    }
+}
+
+impl PooledBuffer {
+    /// Buffer holding the pixels.
+    pub fn buffer(&self) -> &DmaBuffer {
+        self.buffer.as_ref().expect("buffer present until drop")
+    }
+
+    /// Surface describing the image in the buffer, as set by
+    /// [`G2D::convert_owned`].
+    pub fn surface(&self) -> &G2DSurface {
+        &self.surface
+    }
+}
+
+impl Drop for PooledBuffer {
+    fn drop(&mut self) {
+        if let Some(buffer) = self.buffer.take() {
+            self.free.borrow_mut().push(buffer);
+        }
+    }
+}
+
+impl G2D {
+    /// Convert the crop of `src` to a `dst_dims` (width, height) image of
+    /// `dst_format` in a buffer from `pool`, wait for it, and return the
+    /// buffer ready to read.
+    ///
+    /// The image is laid out as by [`DmaBuffer::reinterpret_surface`] and
+    /// described by [`PooledBuffer::surface`]. Dropping the result returns
+    /// the buffer to `pool`; so does a failed conversion.
+    pub fn convert_owned(
+        &self,
+        src: &G2DSurface,
+        dst_format: G2DFormat,
+        dst_dims: (usize, usize),
+        pool: &BufferPool,
+    ) -> Result<PooledBuffer> {
+        let (width, height) = dst_dims;
+        let size = dst_format.plane_layout(width, height, 1, 1)?.size();
+        let mut output = pool.acquire(size)?;
+        output.surface = output
+            .buffer()
+            .reinterpret_surface(dst_format, width, height, 0)?;
+        self.blit(src, &output.surface)?;
+        self.finish()?;
+        Ok(output)
+    }
+}
@@ -0,0 +1,276 @@
+// SPDX-FileCopyrightText: Copyright 2025 Au-Zone Technologies
+// SPDX-License-Identifier: Apache-2.0
+
+//! One-call setup of a configured G2D handle, and of surfaces whose
+//! geometry is checked before it reaches the GPU.
+
+use crate::{
+    g2d_cap_mode, g2d_cap_mode_G2D_BLEND, g2d_cap_mode_G2D_DITHER, Colorspace, Error, G2DFormat,
+    G2DSurface, Range, Rect, Result, G2D,
+};
+use std::{
+    ffi::{OsStr, OsString},
+    fmt,
+    os::raw::c_ulong,
+};
+
+/// Library loaded by [`G2DBuilder`] when no path is given.
+pub const DEFAULT_LIBRARY: &str = "libg2d.so.2";
+
+/// Builder for a [`G2D`] handle with an explicit initial driver state.
+///
+/// Every setting has a default, and all of them are applied by
+/// [`build()`](Self::build), so the resulting handle's state does not depend
+/// on libg2d defaults:
+///
+/// | Setting | Default |
+/// |---------|---------|
+/// | library | [`DEFAULT_LIBRARY`] |
+/// | colorspace | [`Colorspace::Bt601`] (limited range) |
+/// | dither | off |
+/// | blend | off |
+///
+/// ```no_run
+/// use g2d_sys::{Colorspace, G2D};
+///
+/// let g2d = G2D::builder()
+///     .library("libg2d.so.2")
+///     .colorspace(Colorspace::Bt709)
+///     .dither(true)
+///     .build()?;
+/// # Ok::<(), g2d_sys::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct G2DBuilder {
+    library: OsString,
+    colorspace: Colorspace,
+    dither: bool,
+    blend: bool,
+}
+
+impl Default for G2DBuilder {
+    fn default() -> Self {
+        G2DBuilder {
+            library: DEFAULT_LIBRARY.into(),
+            colorspace: Colorspace::default(),
+            dither: false,
+            blend: false,
+        }
+    }
+}
+
+impl G2DBuilder {
+    /// Path or soname of the libg2d library to load.
+    pub fn library<P: AsRef<OsStr>>(mut self, path: P) -> Self {
+        self.library = path.as_ref().to_owned();
+        self
+    }
+
+    /// YUV matrix used for YUV/RGB conversions.
+    pub fn colorspace(mut self, colorspace: Colorspace) -> Self {
+        self.colorspace = colorspace;
+        self
+    }
+
+    /// Enable `G2D_DITHER` when converting to lower bit-depth formats.
+    pub fn dither(mut self, enable: bool) -> Self {
+        self.dither = enable;
+        self
+    }
+
+    /// Enable `G2D_BLEND`, blending each blit using the surfaces'
+    /// `blendfunc` factors.
+    pub fn blend(mut self, enable: bool) -> Self {
+        self.blend = enable;
+        self
+    }
+
+    /// Load the library, open a handle and apply the configuration.
+    pub fn build(&self) -> Result<G2D> {
+        let g2d = G2D::new(&self.library)?;
+        self.configure(&g2d)?;
+        Ok(g2d)
+    }
+
+    /// Apply this configuration (everything except the library) to an
+    /// already open handle, e.g. one from [`G2D::from_loaded`].
+    pub fn configure(&self, g2d: &G2D) -> Result<()> {
+        g2d.set_yuv_matrix(self.colorspace, Range::Limited)?;
+        set_cap(g2d, g2d_cap_mode_G2D_DITHER, self.dither)?;
+        set_cap(g2d, g2d_cap_mode_G2D_BLEND, self.blend)
+    }
+}
+
+fn set_cap(g2d: &G2D, cap: g2d_cap_mode, enable: bool) -> Result<()> {
+    if enable {
+        g2d.enable_cap(cap)
+    } else {
+        g2d.disable_cap(cap)
+    }
+}
+
+impl G2D {
+    /// Start configuring a handle; see [`G2DBuilder`].
+    pub fn builder() -> G2DBuilder {
+        G2DBuilder::default()
+    }
+}
+
+/// Builder for a [`G2DSurface`] whose geometry is validated by
+/// [`build()`](Self::build).
+///
+/// The stride defaults to the width and the crop to the whole surface.
+///
+/// ```
+/// use g2d_sys::{builder::SurfaceBuilder, G2DFormat, Rect, RGBA};
+///
+/// let surface = SurfaceBuilder::new()
+///     .format(G2DFormat::try_from(RGBA).unwrap())
+///     .size(640, 480)
+///     .stride(704)
+///     .crop(0, 0, 320, 240)
+///     .planes([0x1000_0000, 0, 0])
+///     .build()?;
+/// assert_eq!(surface.crop_rect(), Rect::new(0, 0, 320, 240));
+/// # Ok::<(), g2d_sys::builder::SurfaceError>(())
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SurfaceBuilder {
+    format: Option<G2DFormat>,
+    width: i32,
+    height: i32,
+    stride: Option<i32>,
+    crop: Option<Rect>,
+    planes: [c_ulong; 3],
+}
+
+/// Geometry rejected by [`SurfaceBuilder::build`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SurfaceError {
+    /// No format was set.
+    MissingFormat,
+    /// Width or height is not positive.
+    InvalidSize { width: i32, height: i32 },
+    /// The stride, in pixels, is smaller than the width.
+    StrideTooSmall { stride: i32, width: i32 },
+    /// The crop is empty or extends outside the surface.
+    InvalidCrop { crop: Rect, width: i32, height: i32 },
+    /// A plane the format needs has no address.
+    MissingPlane(usize),
+}
+
+impl fmt::Display for SurfaceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SurfaceError::MissingFormat => write!(f, "no format set"),
+            SurfaceError::InvalidSize { width, height } => {
+                write!(f, "invalid size {width}x{height}")
+            }
+            SurfaceError::StrideTooSmall { stride, width } => {
+                write!(f, "stride {stride} is smaller than width {width}")
+            }
+            SurfaceError::InvalidCrop {
+                crop,
+                width,
+                height,
+            } => write!(
+                f,
+                "crop {crop:?} is empty or outside the {width}x{height} surface"
+            ),
+            SurfaceError::MissingPlane(index) => write!(f, "plane {index} has no address"),
+        }
+    }
+}
+
+impl std::error::Error for SurfaceError {}
+
+impl From<SurfaceError> for Error {
+    fn from(e: SurfaceError) -> Self {
+        Error::InvalidSurface(e.to_string())
+    }
+}
+
+impl SurfaceBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn format(mut self, format: G2DFormat) -> Self {
+        self.format = Some(format);
+        self
+    }
+
+    /// Width and height of the surface in pixels.
+    pub fn size(mut self, width: i32, height: i32) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    /// Row pitch of the first plane in pixels.
+    pub fn stride(mut self, stride: i32) -> Self {
+        self.stride = Some(stride);
+        self
+    }
+
+    /// Region blits read or write, with exclusive `right` and `bottom`.
+    pub fn crop(mut self, left: i32, top: i32, right: i32, bottom: i32) -> Self {
+        self.crop = Some(Rect::new(left, top, right, bottom));
+        self
+    }
+
+    /// Physical addresses of the planes; unused planes are 0.
+    pub fn planes(mut self, planes: [c_ulong; 3]) -> Self {
+        self.planes = planes;
+        self
+    }
+
+    /// Check the geometry and build the surface.
+    ///
+    /// Fails if no format is set, the size is not positive, the stride is
+    /// below the width, the crop is empty or outside the surface, or a
+    /// plane the format uses (per [`G2DFormat::plane_layout`]) is 0.
+    pub fn build(&self) -> std::result::Result<G2DSurface, SurfaceError> {
+        let format = self.format.ok_or(SurfaceError::MissingFormat)?;
+        let (width, height) = (self.width, self.height);
+        if width <= 0 || height <= 0 {
+            return Err(SurfaceError::InvalidSize { width, height });
+        }
+        let stride = self.stride.unwrap_or(width);
+        if stride < width {
+            return Err(SurfaceError::StrideTooSmall { stride, width });
+        }
+        let crop = self.crop.unwrap_or(Rect::new(0, 0, width, height));
+        if crop.is_empty()
+            || crop.left < 0
+            || crop.top < 0
+            || crop.right > width
+            || crop.bottom > height
+        {
+            return Err(SurfaceError::InvalidCrop {
+                crop,
+                width,
+                height,
+            });
+        }
+        let planes = format
+            .plane_layout(width as usize, height as usize, 1, 1)
+            .map_or(1, |layout| layout.planes().len());
+        if let Some(index) = (0..planes).find(|&i| self.planes[i] == 0) {
+            return Err(SurfaceError::MissingPlane(index));
+        }
+
+        Ok(G2DSurface {
+            format: format.format(),
+            planes: self.planes,
+            left: crop.left,
+            top: crop.top,
+            right: crop.right,
+            bottom: crop.bottom,
+            stride,
+            width,
+            height,
+            ..Default::default()
+        })
+    }
+}
@@ -0,0 +1,200 @@
+// SPDX-FileCopyrightText: Copyright 2025 Au-Zone Technologies
+// SPDX-License-Identifier: Apache-2.0
+
+//! Scissor-style clipping for blits.
+//!
+//! libg2d has no scissor state, so clipping is emulated by shrinking the
+//! destination crop to the clip rectangle and shrinking the source crop by
+//! the same proportion, which keeps the source-to-destination scale fixed.
+//!
+//! Crops of chroma-subsampled YUV surfaces are also snapped to the chroma
+//! grid here, per the handle's [`CropAlignment`] policy.
+
+use crate::{
+    g2d_format_G2D_I420, g2d_format_G2D_NV12, g2d_format_G2D_NV16, g2d_format_G2D_NV21,
+    g2d_format_G2D_NV61, g2d_format_G2D_UYVY, g2d_format_G2D_VYUY, g2d_format_G2D_YUYV,
+    g2d_format_G2D_YV12, g2d_format_G2D_YVYU, g2d_rotation_G2D_ROTATION_0, CropAlignment, Error,
+    G2DSurface, Rect, Result, G2D,
+};
+
+impl G2D {
+    /// Policy for blit crops that split a chroma sample of a subsampled YUV
+    /// surface; [`CropAlignment::Reject`] unless changed.
+    pub fn crop_alignment(&self) -> CropAlignment {
+        self.crop_alignment.get()
+    }
+
+    /// Set the [`CropAlignment`] policy for subsequent blits on this handle.
+    pub fn set_crop_alignment(&mut self, policy: CropAlignment) {
+        self.crop_alignment.set(policy);
+    }
+
+    /// Blit `src` to `dst` with the usual crop mapping, but only write
+    /// destination pixels inside `clip`.
+    ///
+    /// `clip` is in destination surface coordinates. A clip that does not
+    /// overlap the destination crop queues nothing. Only unrotated
+    /// destinations are supported.
+    ///
+    /// Source edges are mapped to the nearest pixel, so a clipped blit that
+    /// scales may sample slightly differently at the clip boundary than the
+    /// same region of an unclipped blit.
+    pub fn blit_clipped(&self, src: &G2DSurface, dst: &G2DSurface, clip: Rect) -> Result<()> {
+        match clip_surfaces(src, dst, clip)? {
+            Some((src, dst)) => self.blit(&src, &dst),
+            None => Ok(()),
+        }
+    }
+
+    /// Blit the `src` crop unscaled with its top-left corner at `(x, y)` in
+    /// `dst`, ignoring the crop of `dst`.
+    ///
+    /// The placement is clipped to the destination surface bounds, so a
+    /// source partly (or entirely) off the edge writes only the visible
+    /// part; see [`BlitOptions::clip_to_bounds`](crate::options::BlitOptions)
+    /// for the same clipping on plain blits. Only unrotated destinations are
+    /// supported.
+    pub fn place(&self, src: &G2DSurface, dst: &G2DSurface, x: i32, y: i32) -> Result<()> {
+        let src_crop = src.crop_rect();
+        let mut placed = *dst;
+        placed.left = x;
+        placed.top = y;
+        placed.right = x.saturating_add(src_crop.width());
+        placed.bottom = y.saturating_add(src_crop.height());
+        let (width, height) = dst.dimensions();
+        self.blit_clipped(src, &placed, Rect::new(0, 0, width, height))
+    }
+
+    /// Queue a clear of `rect` within `dst` to `color`, leaving every pixel
+    /// outside `rect` untouched.
+    ///
+    /// `rect` is in surface coordinates and replaces the crop of `dst`; it
+    /// must be non-empty and lie within the surface. See
+    /// [`clear_rect_checked()`](Self::clear_rect_checked) for a variant that
+    /// verifies the pixels outside `rect` in debug builds.
+    pub fn clear_rect(&self, dst: &G2DSurface, rect: Rect, color: [u8; 4]) -> Result<()> {
+        if rect.is_empty()
+            || rect.left < 0
+            || rect.top < 0
+            || rect.right > dst.width
+            || rect.bottom > dst.height
+        {
+            return Err(Error::InvalidSurface(format!(
+                "clear rect {rect:?} is empty or exceeds {}x{} surface",
+                dst.width, dst.height
+            )));
+        }
+
+        let mut region = *dst;
+        region.left = rect.left;
+        region.top = rect.top;
+        region.right = rect.right;
+        region.bottom = rect.bottom;
+        self.clear(&mut region, color)
+    }
+}
+
+/// Shrink the crop of `dst` to `clip` and the crop of `src` in proportion,
+/// as [`G2D::blit_clipped`] does. Returns `None` when nothing is visible.
+pub(crate) fn clip_surfaces(
+    src: &G2DSurface,
+    dst: &G2DSurface,
+    clip: Rect,
+) -> Result<Option<(G2DSurface, G2DSurface)>> {
+    if dst.rot != g2d_rotation_G2D_ROTATION_0 {
+        return Err(Error::InvalidSurface(
+            "clipping does not support rotated destinations".to_string(),
+        ));
+    }
+
+    let dst_crop = dst.crop_rect();
+    let src_crop = src.crop_rect();
+    if dst_crop.is_empty() || src_crop.is_empty() {
+        return Err(Error::InvalidSurface(format!(
+            "empty crop: src {src_crop:?}, dst {dst_crop:?}"
+        )));
+    }
+    let Some(visible) = dst_crop.intersect(&clip) else {
+        return Ok(None);
+    };
+    if visible == dst_crop {
+        return Ok(Some((*src, *dst)));
+    }
+
+    let map = |offset: i32, src_len: i32, dst_len: i32| -> i32 {
+        ((offset as i64 * src_len as i64 + dst_len as i64 / 2) / dst_len as i64) as i32
+    };
+    let (sw, sh) = (src_crop.width(), src_crop.height());
+    let (dw, dh) = (dst_crop.width(), dst_crop.height());
+
+    let mut clipped_src = *src;
+    clipped_src.left = src_crop.left + map(visible.left - dst_crop.left, sw, dw);
+    clipped_src.top = src_crop.top + map(visible.top - dst_crop.top, sh, dh);
+    clipped_src.right = src_crop.left + map(visible.right - dst_crop.left, sw, dw);
+    clipped_src.bottom = src_crop.top + map(visible.bottom - dst_crop.top, sh, dh);
+    // Heavy downscales can round a thin clip to zero source pixels.
+    clipped_src.right = clipped_src.right.max(clipped_src.left + 1);
+    clipped_src.bottom = clipped_src.bottom.max(clipped_src.top + 1);
+
+    let mut clipped_dst = *dst;
+    clipped_dst.left = visible.left;
+    clipped_dst.top = visible.top;
+    clipped_dst.right = visible.right;
+    clipped_dst.bottom = visible.bottom;
+
+    Ok(Some((clipped_src, clipped_dst)))
+}
+
+/// Horizontal and vertical chroma subsampling factors of `surface`.
+pub(crate) fn chroma_subsampling(surface: &G2DSurface) -> (i32, i32) {
+    match surface.format {
+        g2d_format_G2D_NV12 | g2d_format_G2D_NV21 | g2d_format_G2D_I420 | g2d_format_G2D_YV12 => {
+            (2, 2)
+        }
+        g2d_format_G2D_NV16 | g2d_format_G2D_NV61 | g2d_format_G2D_YUYV | g2d_format_G2D_YVYU
+        | g2d_format_G2D_UYVY | g2d_format_G2D_VYUY => (2, 1),
+        _ => (1, 1),
+    }
+}
+
+/// Apply `policy` to a crop of `surface` that splits a chroma sample.
+/// Surfaces without subsampling, and aligned crops, are returned unchanged.
+pub(crate) fn align_crop(surface: &G2DSurface, policy: CropAlignment) -> Result<G2DSurface> {
+    let (sx, sy) = chroma_subsampling(surface);
+    let crop = surface.crop_rect();
+    let aligned = |v: i32, step: i32| v.rem_euclid(step) == 0;
+    if aligned(crop.left, sx)
+        && aligned(crop.right, sx)
+        && aligned(crop.top, sy)
+        && aligned(crop.bottom, sy)
+    {
+        return Ok(*surface);
+    }
+
+    if policy == CropAlignment::Reject {
+        return Err(Error::InvalidSurface(format!(
+            "crop {crop:?} of g2d_format {} splits its {sx}x{sy} chroma subsampling",
+            surface.format
+        )));
+    }
+
+    let floor = |v: i32, step: i32| v - v.rem_euclid(step);
+    let snap = |v: i32, step: i32, limit: i32| match policy {
+        CropAlignment::Floor => floor(v, step),
+        _ if aligned(v, step) => v,
+        _ if floor(v, step) + step > limit => floor(v, step),
+        _ => floor(v, step) + step,
+    };
+
+    let mut snapped = *surface;
+    snapped.left = snap(crop.left, sx, surface.width);
+    snapped.right = snap(crop.right, sx, surface.width);
+    snapped.top = snap(crop.top, sy, surface.height);
+    snapped.bottom = snap(crop.bottom, sy, surface.height);
+    if snapped.crop_rect().is_empty() {
+        return Err(Error::InvalidSurface(format!(
+            "crop {crop:?} is empty once aligned to {sx}x{sy} chroma subsampling"
+        )));
+    }
+    Ok(snapped)
+}
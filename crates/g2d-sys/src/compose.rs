@@ -0,0 +1,128 @@
+// SPDX-FileCopyrightText: Copyright 2025 Au-Zone Technologies
+// SPDX-License-Identifier: Apache-2.0
+
+//! Compositing helpers built from clear + blended blit, and letterboxing.
+
+use crate::{
+    g2d_blend_func_G2D_ONE_MINUS_SRC_ALPHA, g2d_blend_func_G2D_SRC_ALPHA, g2d_cap_mode_G2D_BLEND,
+    g2d_cap_mode_G2D_GLOBAL_ALPHA, G2DSurface, Rect, Result, G2D,
+};
+
+impl G2D {
+    /// Flatten `src` onto a solid `bg_color` in the destination crop.
+    ///
+    /// Queues a clear of the destination crop to `bg_color` followed by a
+    /// source-over blended blit (`SRC_ALPHA`, `ONE_MINUS_SRC_ALPHA`), so
+    /// transparent source regions show the background. Blending is enabled
+    /// only for this blit. The destination format must be supported by
+    /// [`clear()`](Self::clear).
+    pub fn blit_over_color(
+        &self,
+        src: &G2DSurface,
+        dst: &mut G2DSurface,
+        bg_color: [u8; 4],
+    ) -> Result<()> {
+        self.clear(dst, bg_color)?;
+
+        let mut src = *src;
+        src.blendfunc = g2d_blend_func_G2D_SRC_ALPHA;
+        let mut blend_dst = *dst;
+        blend_dst.blendfunc = g2d_blend_func_G2D_ONE_MINUS_SRC_ALPHA;
+
+        self.enable_cap(g2d_cap_mode_G2D_BLEND)?;
+        let result = self.blit(&src, &blend_dst);
+        let disabled = self.disable_cap(g2d_cap_mode_G2D_BLEND);
+        result.and(disabled)
+    }
+
+    /// Blend `overlay` onto `base` with its top-left corner at `(x, y)`,
+    /// such as a pre-rendered timestamp or caption bitmap on a video frame.
+    ///
+    /// The overlay is placed unscaled as by [`place()`](Self::place),
+    /// clipped to the bounds of `base`, and blended source-over
+    /// (`SRC_ALPHA`, `ONE_MINUS_SRC_ALPHA`), so the overlay's alpha sets its
+    /// coverage; use a fully transparent alpha for keyed-out pixels, as
+    /// libg2d has no color-key state. Blending is enabled only for this
+    /// blit. The operation is queued like [`blit()`](Self::blit); to submit
+    /// it with a frame's other work, record it with
+    /// [`Batch::overlay`](crate::batch::Batch::overlay).
+    pub fn overlay(&self, base: &G2DSurface, overlay: &G2DSurface, x: i32, y: i32) -> Result<()> {
+        let mut src = *overlay;
+        src.blendfunc = g2d_blend_func_G2D_SRC_ALPHA;
+        let mut blend_dst = *base;
+        blend_dst.blendfunc = g2d_blend_func_G2D_ONE_MINUS_SRC_ALPHA;
+
+        self.enable_cap(g2d_cap_mode_G2D_BLEND)?;
+        let result = self.place(&src, &blend_dst, x, y);
+        let disabled = self.disable_cap(g2d_cap_mode_G2D_BLEND);
+        result.and(disabled)
+    }
+
+    /// Blit `src` onto `dst`, blending each pixel with the destination
+    /// using the surfaces' blend factors: `src` scaled by its
+    /// [`blend_func()`](G2DSurface::blend_func) plus `dst` scaled by its
+    /// own. Set them with [`G2DSurface::set_blend_func`]; a semi-transparent
+    /// overlay composites with [`BlendFunc::SrcAlpha`](crate::BlendFunc) on
+    /// `src` and [`BlendFunc::OneMinusSrcAlpha`](crate::BlendFunc) on `dst`.
+    ///
+    /// When `src.global_alpha` is below 255, `G2D_GLOBAL_ALPHA` is enabled
+    /// too and the source alpha is scaled by `global_alpha / 255`, fading
+    /// the whole source. Blending is enabled only for this blit.
+    pub fn blend(&self, src: &G2DSurface, dst: &G2DSurface) -> Result<()> {
+        let global_alpha = src.global_alpha < 255;
+        self.enable_cap(g2d_cap_mode_G2D_BLEND)?;
+        if global_alpha {
+            if let Err(e) = self.enable_cap(g2d_cap_mode_G2D_GLOBAL_ALPHA) {
+                let _ = self.disable_cap(g2d_cap_mode_G2D_BLEND);
+                return Err(e);
+            }
+        }
+
+        let result = self.blit(src, dst);
+        let disabled = if global_alpha {
+            self.disable_cap(g2d_cap_mode_G2D_GLOBAL_ALPHA)
+        } else {
+            Ok(())
+        };
+        result
+            .and(disabled)
+            .and(self.disable_cap(g2d_cap_mode_G2D_BLEND))
+    }
+
+    /// Scale the crop of `src` into the crop of `dst` preserving its aspect
+    /// ratio, and fill the bars left over with `fill`.
+    ///
+    /// The content area is [`Rect::fit`] of the destination crop to the
+    /// source crop: centered, with an odd leftover giving the right or
+    /// bottom bar one more pixel. Only the bars are cleared, so no pixel is
+    /// written twice, and a source whose aspect ratio matches the crop gets
+    /// a plain blit with no clear. Rotation is not taken into account. The
+    /// clears and the blit are queued like [`blit()`](Self::blit), to be
+    /// waited for with one [`finish()`](Self::finish); the destination
+    /// format must be supported by [`clear()`](Self::clear).
+    pub fn letterbox(&self, src: &G2DSurface, dst: &G2DSurface, fill: [u8; 4]) -> Result<()> {
+        let outer = dst.crop_rect();
+        let src_crop = src.crop_rect();
+        let inner = outer.fit(src_crop.width(), src_crop.height());
+
+        let bars = [
+            Rect::new(outer.left, outer.top, outer.right, inner.top),
+            Rect::new(outer.left, inner.bottom, outer.right, outer.bottom),
+            Rect::new(outer.left, inner.top, inner.left, inner.bottom),
+            Rect::new(inner.right, inner.top, outer.right, inner.bottom),
+        ];
+        for bar in bars.into_iter().filter(|bar| !bar.is_empty()) {
+            self.clear_rect(dst, bar, fill)?;
+        }
+        if inner.is_empty() {
+            return Ok(());
+        }
+
+        let mut content = *dst;
+        content.left = inner.left;
+        content.top = inner.top;
+        content.right = inner.right;
+        content.bottom = inner.bottom;
+        self.blit(src, &content)
+    }
+}
@@ -0,0 +1,132 @@
+// SPDX-FileCopyrightText: Copyright 2025 Au-Zone Technologies
+// SPDX-License-Identifier: Apache-2.0
+
+//! Which format conversions a single blit can perform on this hardware.
+//!
+//! libg2d has no per-pair query, so the matrix combines the known support
+//! table ([`G2DFormat::is_valid_destination`]) with the `G2D_SRC_YUV` and
+//! `G2D_DST_YUV` feature queries, which are made once when it is built.
+
+use crate::{
+    g2d_feature_G2D_DST_YUV, g2d_feature_G2D_SRC_YUV, g2d_format, g2d_format_G2D_ABGR8888,
+    g2d_format_G2D_ARGB8888, g2d_format_G2D_BGR565, g2d_format_G2D_BGR888, g2d_format_G2D_BGRA5551,
+    g2d_format_G2D_BGRA8888, g2d_format_G2D_BGRX5551, g2d_format_G2D_BGRX8888,
+    g2d_format_G2D_GRAY10, g2d_format_G2D_GRAY8, g2d_format_G2D_I420, g2d_format_G2D_NV12,
+    g2d_format_G2D_NV16, g2d_format_G2D_NV21, g2d_format_G2D_NV61, g2d_format_G2D_RGB565,
+    g2d_format_G2D_RGB888, g2d_format_G2D_RGBA1010102, g2d_format_G2D_RGBA5551,
+    g2d_format_G2D_RGBA8888, g2d_format_G2D_RGBX5551, g2d_format_G2D_RGBX8888, g2d_format_G2D_UYVY,
+    g2d_format_G2D_VYUY, g2d_format_G2D_XBGR8888, g2d_format_G2D_XRGB8888, g2d_format_G2D_YUYV,
+    g2d_format_G2D_YV12, g2d_format_G2D_YVYU, G2DFormat, G2D,
+};
+
+/// Every format libg2d defines, in `g2d_format` order.
+const FORMATS: [g2d_format; 29] = [
+    g2d_format_G2D_RGB565,
+    g2d_format_G2D_RGBA8888,
+    g2d_format_G2D_RGBX8888,
+    g2d_format_G2D_BGRA8888,
+    g2d_format_G2D_BGRX8888,
+    g2d_format_G2D_BGR565,
+    g2d_format_G2D_ARGB8888,
+    g2d_format_G2D_ABGR8888,
+    g2d_format_G2D_XRGB8888,
+    g2d_format_G2D_XBGR8888,
+    g2d_format_G2D_RGB888,
+    g2d_format_G2D_BGR888,
+    g2d_format_G2D_RGBA5551,
+    g2d_format_G2D_RGBX5551,
+    g2d_format_G2D_BGRA5551,
+    g2d_format_G2D_BGRX5551,
+    g2d_format_G2D_RGBA1010102,
+    g2d_format_G2D_GRAY10,
+    g2d_format_G2D_GRAY8,
+    g2d_format_G2D_NV12,
+    g2d_format_G2D_I420,
+    g2d_format_G2D_YV12,
+    g2d_format_G2D_NV21,
+    g2d_format_G2D_YUYV,
+    g2d_format_G2D_YVYU,
+    g2d_format_G2D_UYVY,
+    g2d_format_G2D_VYUY,
+    g2d_format_G2D_NV16,
+    g2d_format_G2D_NV61,
+];
+
+/// Whether a direct blit is possible for every (source, destination) pair
+/// of formats, returned by [`G2D::conversion_matrix`].
+#[derive(Debug, Clone)]
+pub struct ConversionMatrix {
+    /// Row-major over [`FORMATS`]: `supported[src * len + dst]`.
+    supported: Vec<bool>,
+}
+
+impl ConversionMatrix {
+    /// Every format the matrix covers, supported or not.
+    pub fn formats(&self) -> impl Iterator<Item = G2DFormat> {
+        FORMATS.into_iter().map(G2DFormat)
+    }
+
+    /// Whether one blit can convert `src` into `dst`. Formats outside
+    /// [`formats()`](Self::formats) are never supported.
+    pub fn supports(&self, src: G2DFormat, dst: G2DFormat) -> bool {
+        match (index(src), index(dst)) {
+            (Some(s), Some(d)) => self.supported[s * FORMATS.len() + d],
+            _ => false,
+        }
+    }
+
+    /// Formats that `src` can be blitted into.
+    pub fn destinations(&self, src: G2DFormat) -> impl Iterator<Item = G2DFormat> + '_ {
+        self.formats().filter(move |&dst| self.supports(src, dst))
+    }
+
+    /// Formats that can be blitted into `dst`.
+    pub fn sources(&self, dst: G2DFormat) -> impl Iterator<Item = G2DFormat> + '_ {
+        self.formats().filter(move |&src| self.supports(src, dst))
+    }
+}
+
+fn index(format: G2DFormat) -> Option<usize> {
+    FORMATS.iter().position(|&f| f == format.format())
+}
+
+impl G2D {
+    /// Build the [`ConversionMatrix`] for this hardware.
+    ///
+    /// A pair is supported when the destination is writable and, for YUV
+    /// sources or destinations, the hardware reports `G2D_SRC_YUV` or
+    /// `G2D_DST_YUV`. A failed feature query counts as unsupported.
+    pub fn conversion_matrix(&self) -> ConversionMatrix {
+        let src_yuv = self.query_feature(g2d_feature_G2D_SRC_YUV).unwrap_or(false);
+        let dst_yuv = self.query_feature(g2d_feature_G2D_DST_YUV).unwrap_or(false);
+
+        let mut supported = Vec::with_capacity(FORMATS.len() * FORMATS.len());
+        for src in FORMATS.map(G2DFormat) {
+            for dst in FORMATS.map(G2DFormat) {
+                supported.push(
+                    dst.is_valid_destination()
+                        && (src_yuv || !src.is_yuv())
+                        && (dst_yuv || !dst.is_yuv()),
+                );
+            }
+        }
+        ConversionMatrix { supported }
+    }
+
+    /// Every format `g2d_clear` accepts as a target on this driver (see
+    /// [`G2DFormat::is_clearable`]), leaving out YUYV and UYVY on a driver
+    /// version without
+    /// [`Version::supports_yuv_clear`](crate::Version::supports_yuv_clear).
+    ///
+    /// Other writable formats need a CPU fill (`clear_auto`) or a blit from
+    /// a solid-color source instead.
+    pub fn clearable_formats(&self) -> Vec<G2DFormat> {
+        let yuv_clear = self.version().supports_yuv_clear();
+        FORMATS
+            .map(G2DFormat)
+            .into_iter()
+            .filter(G2DFormat::is_clearable)
+            .filter(|format| yuv_clear || !format.is_yuv())
+            .collect()
+    }
+}
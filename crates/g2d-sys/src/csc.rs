@@ -0,0 +1,59 @@
+// SPDX-FileCopyrightText: Copyright 2025 Au-Zone Technologies
+// SPDX-License-Identifier: Apache-2.0
+
+//! Blits with explicitly declared source and destination colorspaces.
+//!
+//! libg2d has no programmable color transform: its only color state is the
+//! choice among four built-in YUV matrices (BT.601 or BT.709, limited or
+//! full range; the `G2D_YUV_BT_*` caps), and no `g2d_cap_mode` or
+//! `g2d_feature` advertises a color matrix. Apply white balance and other
+//! color corrections on the CPU or in a shader instead.
+//!
+//! YUV to RGB output is always clamped to 0–255; libg2d has no control for
+//! extended-range RGB output.
+
+use crate::{Colorspace, Error, G2DSurface, Range, Result, G2D};
+
+impl G2D {
+    /// Blit `src` to `dst`, declaring the colorspace of each side.
+    ///
+    /// libg2d has a single YUV matrix per handle rather than independent
+    /// source and destination matrices, so the matrix is chosen from the
+    /// YUV side of the conversion:
+    ///
+    /// - YUV to RGB decodes with `src_cs`. RGB output carries no matrix, so
+    ///   `dst_cs` only labels the result; the values are what `src_cs`
+    ///   decoding produces (no gamut mapping between primaries is applied).
+    /// - RGB to YUV encodes with `dst_cs`, and `src_cs` only labels the input.
+    /// - RGB to RGB involves no matrix and is a plain [`blit()`](Self::blit).
+    /// - YUV to YUV requires `src_cs == dst_cs`. Re-encoding between matrices
+    ///   needs two passes; blit to an RGB intermediate with `src_cs`, then
+    ///   from it with `dst_cs`.
+    ///
+    /// The selected matrix (limited range) stays set on the handle after the
+    /// call.
+    pub fn convert_csc(
+        &self,
+        src: &G2DSurface,
+        dst: &G2DSurface,
+        src_cs: Colorspace,
+        dst_cs: Colorspace,
+    ) -> Result<()> {
+        let matrix = match (src.format().is_yuv(), dst.format().is_yuv()) {
+            (true, false) => Some(src_cs),
+            (false, true) => Some(dst_cs),
+            (false, false) => None,
+            (true, true) if src_cs == dst_cs => Some(src_cs),
+            (true, true) => {
+                return Err(Error::InvalidFormat(format!(
+                    "cannot convert YUV {src_cs:?} to YUV {dst_cs:?} in one pass; \
+                     blit through an RGB intermediate"
+                )))
+            }
+        };
+        if let Some(colorspace) = matrix {
+            self.set_yuv_matrix(colorspace, Range::Limited)?;
+        }
+        self.blit(src, dst)
+    }
+}
@@ -0,0 +1,292 @@
+// SPDX-FileCopyrightText: Copyright 2025 Au-Zone Technologies
+// SPDX-License-Identifier: Apache-2.0
+
+//! Scanout buffers registered as DRM framebuffers, for blitting straight
+//! into what the display shows.
+//!
+//! A [`DisplayTarget`] allocates a [`DmaBuffer::new_scanout`] buffer,
+//! imports it into a KMS device with `DRM_IOCTL_PRIME_FD_TO_HANDLE` and
+//! registers it with `DRM_IOCTL_MODE_ADDFB2`. The compositor attaches
+//! [`framebuffer_id()`](DisplayTarget::framebuffer_id) to a plane once;
+//! after that [`G2D::present`] writes each frame into the buffer and marks
+//! the framebuffer dirty.
+//!
+//! A [`DoubleBuffer`] instead keeps two scanout buffers, so each frame is
+//! rendered into the one not being shown and swapped in once complete.
+
+use crate::{
+    dma::{DmaBuffer, DrmAttachment, HeapType},
+    fourcc::drm_code,
+    Error, G2DFormat, G2DSurface, Result, G2D,
+};
+use nix::{errno::Errno, ioctl_readwrite};
+use std::{
+    fs::OpenOptions,
+    os::fd::{AsFd, AsRawFd, OwnedFd},
+    path::Path,
+};
+
+// =============================================================================
+// KMS framebuffer ioctls (drm/drm_mode.h)
+// =============================================================================
+
+#[repr(C)]
+struct drm_mode_fb_cmd2 {
+    fb_id: u32,
+    width: u32,
+    height: u32,
+    pixel_format: u32,
+    flags: u32,
+    handles: [u32; 4],
+    pitches: [u32; 4],
+    offsets: [u32; 4],
+    modifier: [u64; 4],
+}
+
+#[repr(C)]
+struct drm_mode_fb_dirty_cmd {
+    fb_id: u32,
+    flags: u32,
+    color: u32,
+    num_clips: u32,
+    clips_ptr: u64,
+}
+
+const DRM_IOCTL_BASE: u8 = b'd';
+ioctl_readwrite!(ioctl_mode_rmfb, DRM_IOCTL_BASE, 0xaf, u32);
+ioctl_readwrite!(
+    ioctl_mode_dirtyfb,
+    DRM_IOCTL_BASE,
+    0xb1,
+    drm_mode_fb_dirty_cmd
+);
+ioctl_readwrite!(ioctl_mode_addfb2, DRM_IOCTL_BASE, 0xb8, drm_mode_fb_cmd2);
+
+/// Allocate a scanout buffer and a surface over it whose stride is the
+/// scanout pitch.
+fn scanout(
+    heap_type: HeapType,
+    format: G2DFormat,
+    width: usize,
+    height: usize,
+) -> Result<(DmaBuffer, G2DSurface)> {
+    let buffer = DmaBuffer::new_scanout(heap_type, format, width, height)?;
+    let pitch = buffer.pitch().unwrap_or_default();
+    let bpp = format.bytes_per_pixel().unwrap_or(1);
+    let mut surface = buffer.reinterpret_surface(format, width, height, 0)?;
+    surface.stride = (pitch / bpp) as i32;
+    Ok((buffer, surface))
+}
+
+fn drm_error(what: &str, e: Errno) -> Error {
+    Error::IoError(std::io::Error::new(
+        std::io::Error::from(e).kind(),
+        format!("{what} failed: {e}"),
+    ))
+}
+
+/// A scanout buffer registered as a DRM framebuffer on one KMS device.
+///
+/// The framebuffer is removed and the GEM handle closed on drop, before
+/// the buffer itself is released.
+#[derive(Debug)]
+pub struct DisplayTarget {
+    fb_id: u32,
+    attachment: DrmAttachment,
+    surface: G2DSurface,
+    buffer: DmaBuffer,
+}
+
+impl DisplayTarget {
+    /// Allocate a `width`×`height` scanout buffer of `format` from
+    /// `heap_type` and register it as a framebuffer on the KMS device at
+    /// `card` (e.g. `/dev/dri/card0`).
+    ///
+    /// `format` must be a writable packed format with a DRM equivalent.
+    /// Registering a framebuffer needs a primary node, not a render node;
+    /// errors from the device are returned as [`Error::IoError`].
+    pub fn new(
+        card: impl AsRef<Path>,
+        heap_type: HeapType,
+        format: G2DFormat,
+        width: usize,
+        height: usize,
+    ) -> Result<Self> {
+        if !format.is_valid_destination() {
+            return Err(Error::UnsupportedDestinationFormat(format));
+        }
+        let fourcc = drm_code(format.format()).ok_or_else(|| {
+            Error::InvalidFormat(format!(
+                "g2d_format {} has no DRM scanout equivalent",
+                format.format()
+            ))
+        })?;
+        let (buffer, surface) = scanout(heap_type, format, width, height)?;
+        let pitch = buffer.pitch().unwrap_or_default();
+
+        let card = OwnedFd::from(OpenOptions::new().read(true).write(true).open(card)?);
+        let attachment = DrmAttachment::import(card, buffer.as_fd())?;
+
+        let mut cmd = drm_mode_fb_cmd2 {
+            fb_id: 0,
+            width: width as u32,
+            height: height as u32,
+            pixel_format: fourcc,
+            flags: 0,
+            handles: [attachment.gem_handle(), 0, 0, 0],
+            pitches: [pitch as u32, 0, 0, 0],
+            offsets: [0; 4],
+            modifier: [0; 4],
+        };
+        unsafe { ioctl_mode_addfb2(attachment.drm_fd().as_raw_fd(), &mut cmd) }
+            .map_err(|e| drm_error("DRM_IOCTL_MODE_ADDFB2", e))?;
+
+        Ok(DisplayTarget {
+            fb_id: cmd.fb_id,
+            attachment,
+            surface,
+            buffer,
+        })
+    }
+
+    /// KMS framebuffer ID, for attaching to a plane in a mode set or atomic
+    /// commit.
+    pub fn framebuffer_id(&self) -> u32 {
+        self.fb_id
+    }
+
+    /// GEM handle of the imported buffer on the KMS device.
+    pub fn gem_handle(&self) -> u32 {
+        self.attachment.gem_handle()
+    }
+
+    /// Surface covering the whole scanout buffer.
+    pub fn surface(&self) -> &G2DSurface {
+        &self.surface
+    }
+
+    pub fn buffer(&self) -> &DmaBuffer {
+        &self.buffer
+    }
+
+    /// Tell the KMS driver the framebuffer contents changed.
+    ///
+    /// Drivers that scan out continuously do not implement dirty tracking;
+    /// their `ENOSYS` is treated as success.
+    fn mark_dirty(&self) -> Result<()> {
+        let mut cmd = drm_mode_fb_dirty_cmd {
+            fb_id: self.fb_id,
+            flags: 0,
+            color: 0,
+            num_clips: 0,
+            clips_ptr: 0,
+        };
+        match unsafe { ioctl_mode_dirtyfb(self.attachment.drm_fd().as_raw_fd(), &mut cmd) } {
+            Ok(_) | Err(Errno::ENOSYS) => Ok(()),
+            Err(e) => Err(drm_error("DRM_IOCTL_MODE_DIRTYFB", e)),
+        }
+    }
+}
+
+impl Drop for DisplayTarget {
+    fn drop(&mut self) {
+        let mut fb_id = self.fb_id;
+        if let Err(e) = unsafe { ioctl_mode_rmfb(self.attachment.drm_fd().as_raw_fd(), &mut fb_id) }
+        {
+            log::warn!(
+                "DRM_IOCTL_MODE_RMFB of framebuffer {} failed: {e}",
+                self.fb_id
+            );
+        }
+    }
+}
+
+/// Two scanout buffers used as front (shown) and back (being rendered).
+///
+/// Render each frame into the back buffer with
+/// [`render_to_back()`](Self::render_to_back), then
+/// [`swap()`](Self::swap) to make it the front buffer and hand it to the
+/// display. `swap()` waits for the rendered work first, so the front
+/// buffer never holds a partly drawn frame. The previous front becomes the
+/// back buffer; if the display may still be scanning it out, wait for the
+/// page flip before rendering the next frame.
+#[derive(Debug)]
+pub struct DoubleBuffer {
+    buffers: [(DmaBuffer, G2DSurface); 2],
+    /// Index of the front buffer.
+    front: usize,
+    swaps: u64,
+}
+
+impl DoubleBuffer {
+    /// Allocate two `width`×`height` scanout buffers of packed `format`
+    /// from `heap_type`, as by [`DmaBuffer::new_scanout`]. Neither holds a
+    /// frame until the first swap.
+    pub fn new(
+        heap_type: HeapType,
+        format: G2DFormat,
+        width: usize,
+        height: usize,
+    ) -> Result<Self> {
+        Ok(DoubleBuffer {
+            buffers: [
+                scanout(heap_type, format, width, height)?,
+                scanout(heap_type, format, width, height)?,
+            ],
+            front: 0,
+            swaps: 0,
+        })
+    }
+
+    /// Run `render` on the surface of the back buffer, queueing the
+    /// frame's blits and clears into it, and return its result.
+    pub fn render_to_back<T>(
+        &mut self,
+        render: impl FnOnce(&G2DSurface) -> Result<T>,
+    ) -> Result<T> {
+        render(&self.buffers[1 - self.front].1)
+    }
+
+    /// Wait for the work queued on `g2d` to finish, then exchange the front
+    /// and back buffers.
+    ///
+    /// If the wait fails the buffers are not swapped, so the display keeps
+    /// the last complete frame.
+    pub fn swap(&mut self, g2d: &G2D) -> Result<()> {
+        g2d.finish()?;
+        self.front = 1 - self.front;
+        self.swaps += 1;
+        Ok(())
+    }
+
+    /// Buffer and surface of the frame being shown.
+    pub fn front(&self) -> (&DmaBuffer, &G2DSurface) {
+        let (buffer, surface) = &self.buffers[self.front];
+        (buffer, surface)
+    }
+
+    /// Buffer and surface the next frame is rendered into.
+    pub fn back(&self) -> (&DmaBuffer, &G2DSurface) {
+        let (buffer, surface) = &self.buffers[1 - self.front];
+        (buffer, surface)
+    }
+
+    /// Total swaps since the double buffer was created.
+    pub fn swaps(&self) -> u64 {
+        self.swaps
+    }
+}
+
+impl G2D {
+    /// Blit `src` into the scanout buffer of `target`, wait for it, and
+    /// signal the display that the framebuffer changed.
+    ///
+    /// The crop of `src` is scaled to the whole framebuffer. Pointing a
+    /// plane at [`framebuffer_id()`](DisplayTarget::framebuffer_id) is left
+    /// to the compositor.
+    pub fn present(&self, target: &DisplayTarget, src: &G2DSurface) -> Result<()> {
+        self.blit(src, target.surface())?;
+        self.finish()?;
+        target.mark_dirty()
+    }
+}
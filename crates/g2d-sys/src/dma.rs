@@ -0,0 +1,642 @@
+// SPDX-FileCopyrightText: Copyright 2025 Au-Zone Technologies
+// SPDX-License-Identifier: Apache-2.0
+
+//! DMA-buf buffers with a persistent mapping and `DMA_BUF_IOCTL_SYNC`
+//! bracketed CPU access.
+//!
+//! See [ARCHITECTURE.md](https://github.com/EdgeFirstAI/g2d-rs/blob/main/ARCHITECTURE.md#cpu-cache-coherency)
+//! for the cache coherency protocol implemented here.
+
+use crate::{
+    pixel::Pixel,
+    sync::{
+        CacheSync, DMA_BUF_SYNC_END, DMA_BUF_SYNC_READ, DMA_BUF_SYNC_START, DMA_BUF_SYNC_WRITE,
+    },
+    Error, FinishToken, G2DFormat, G2DPhysical, G2DSurface, Result,
+};
+use dma_heap::{Heap, HeapKind};
+use std::{
+    os::fd::{AsFd, AsRawFd, BorrowedFd, FromRawFd, OwnedFd},
+    ptr,
+};
+
+// =============================================================================
+// DRM PRIME import — creates persistent dma_buf_attach for cache maintenance
+// =============================================================================
+//
+// The CMA heap's begin_cpu_access iterates over buffer->attachments to perform
+// cache maintenance via dma_sync_sgtable_for_cpu(). Without any active
+// attachments, DMA_BUF_IOCTL_SYNC is a no-op.
+//
+// By importing the DMA-buf fd through the DRM/GPU driver (DRM_IOCTL_PRIME_FD_TO_HANDLE),
+// the GPU driver creates a persistent dma_buf_attach(). This makes
+// DMA_BUF_IOCTL_SYNC actually perform cache invalidation/flush.
+
+const DRM_IOCTL_BASE: u8 = b'd';
+
+// DRM_IOCTL_PRIME_FD_TO_HANDLE = _IOWR('d', 0x2e, struct drm_prime_handle)
+#[repr(C)]
+struct DrmPrimeHandle {
+    handle: u32,
+    flags: u32,
+    fd: i32,
+}
+
+const DRM_IOCTL_PRIME_FD_TO_HANDLE: libc::c_ulong = (3 << 30) // _IOWR
+    | ((std::mem::size_of::<DrmPrimeHandle>() as libc::c_ulong) << 16)
+    | ((DRM_IOCTL_BASE as libc::c_ulong) << 8)
+    | 0x2e;
+
+// DRM_IOCTL_GEM_CLOSE = _IOW('d', 0x09, struct drm_gem_close)
+#[repr(C)]
+struct DrmGemClose {
+    handle: u32,
+    pad: u32,
+}
+
+const DRM_IOCTL_GEM_CLOSE: libc::c_ulong = (1 << 30) // _IOW
+    | ((std::mem::size_of::<DrmGemClose>() as libc::c_ulong) << 16)
+    | ((DRM_IOCTL_BASE as libc::c_ulong) << 8)
+    | 0x09;
+
+/// Holds a DRM GEM handle that keeps a persistent dma_buf_attach alive.
+/// When dropped, closes the GEM handle (which detaches the DMA-buf).
+#[derive(Debug)]
+pub(crate) struct DrmAttachment {
+    drm_fd: OwnedFd,
+    gem_handle: u32,
+}
+
+impl DrmAttachment {
+    /// Import a DMA-buf fd through the GPU DRM driver to create a persistent
+    /// dma_buf_attach. Returns None if /dev/dri/renderD128 is not available.
+    fn new(dma_buf_fd: &OwnedFd) -> Option<Self> {
+        let path = b"/dev/dri/renderD128\0";
+        let raw_fd = unsafe {
+            libc::open(
+                path.as_ptr() as *const libc::c_char,
+                libc::O_RDWR | libc::O_CLOEXEC,
+            )
+        };
+        if raw_fd < 0 {
+            log::warn!(
+                "DrmAttachment: /dev/dri/renderD128 not available: {}",
+                std::io::Error::last_os_error()
+            );
+            return None;
+        }
+        let drm_fd = unsafe { OwnedFd::from_raw_fd(raw_fd) };
+
+        match Self::import(drm_fd, dma_buf_fd.as_fd()) {
+            Ok(attachment) => Some(attachment),
+            Err(e) => {
+                log::warn!("DrmAttachment: PRIME_FD_TO_HANDLE failed: {e}");
+                None
+            }
+        }
+    }
+
+    /// Import a DMA-buf fd as a GEM handle on an already open DRM device.
+    pub(crate) fn import(drm_fd: OwnedFd, dma_buf_fd: BorrowedFd<'_>) -> std::io::Result<Self> {
+        let mut prime = DrmPrimeHandle {
+            handle: 0,
+            flags: 0,
+            fd: dma_buf_fd.as_raw_fd(),
+        };
+
+        let ret =
+            unsafe { libc::ioctl(drm_fd.as_raw_fd(), DRM_IOCTL_PRIME_FD_TO_HANDLE, &mut prime) };
+        if ret == -1 {
+            return Err(std::io::Error::last_os_error());
+        }
+
+        log::debug!("DrmAttachment: imported as GEM handle {}", prime.handle);
+
+        Ok(Self {
+            drm_fd,
+            gem_handle: prime.handle,
+        })
+    }
+
+    /// The DRM device the handle belongs to.
+    pub(crate) fn drm_fd(&self) -> BorrowedFd<'_> {
+        self.drm_fd.as_fd()
+    }
+
+    pub(crate) fn gem_handle(&self) -> u32 {
+        self.gem_handle
+    }
+}
+
+impl Drop for DrmAttachment {
+    fn drop(&mut self) {
+        let close = DrmGemClose {
+            handle: self.gem_handle,
+            pad: 0,
+        };
+        unsafe { libc::ioctl(self.drm_fd.as_raw_fd(), DRM_IOCTL_GEM_CLOSE, &close) };
+    }
+}
+
+// =============================================================================
+// Heap type abstraction
+// =============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeapType {
+    /// `/dev/dma_heap/linux,cma-uncached` — non-cacheable mapping, GPU writes
+    /// are immediately visible to CPU reads without cache maintenance.
+    Uncached,
+    /// `/dev/dma_heap/linux,cma` — cached mapping, requires DMA_BUF_IOCTL_SYNC
+    /// for CPU cache coherency after GPU DMA writes.
+    Cached,
+}
+
+impl HeapType {
+    pub fn name(&self) -> &str {
+        match self {
+            HeapType::Uncached => "linux,cma-uncached",
+            HeapType::Cached => "linux,cma",
+        }
+    }
+
+    fn heap_kind(&self) -> HeapKind {
+        match self {
+            HeapType::Uncached => {
+                HeapKind::Custom(std::path::PathBuf::from("/dev/dma_heap/linux,cma-uncached"))
+            }
+            HeapType::Cached => HeapKind::Cma,
+        }
+    }
+
+    /// Whether this heap can be opened on the running system.
+    pub fn is_available(&self) -> bool {
+        Heap::new(self.heap_kind()).is_ok()
+    }
+}
+
+impl std::fmt::Display for HeapType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+// =============================================================================
+// Display scanout alignment
+// =============================================================================
+
+/// Row pitch alignment in bytes for [`DmaBuffer::new_scanout`] buffers, as
+/// required by the i.MX display controllers for framebuffer import. It is
+/// the [`G2DFormat::required_stride_alignment`] of every packed format.
+pub const SCANOUT_PITCH_ALIGN: usize = crate::stride::PITCH_ALIGN;
+
+/// Total size alignment in bytes (one 4 KiB page) for
+/// [`DmaBuffer::new_scanout`] buffers.
+pub const SCANOUT_SIZE_ALIGN: usize = 4096;
+
+// =============================================================================
+// DMA Buffer with persistent mmap and proper DMA_BUF_IOCTL_SYNC
+// =============================================================================
+
+/// DMA buffer with persistent mmap and correct DMA_BUF_IOCTL_SYNC usage.
+///
+/// The buffer is mmapped once on creation and munmapped on drop. CPU access
+/// is bracketed by SYNC_START/SYNC_END ioctls with full return value checking.
+///
+/// This follows the Linux DMA-buf CPU access protocol:
+/// 1. `DMA_BUF_IOCTL_SYNC` with `SYNC_START` — begin CPU access
+/// 2. CPU reads/writes via the persistent mmap
+/// 3. `DMA_BUF_IOCTL_SYNC` with `SYNC_END` — end CPU access
+///
+/// For cached heaps the buffer is also imported through the GPU DRM driver
+/// so that the sync ioctls actually perform cache maintenance.
+#[derive(Debug)]
+pub struct DmaBuffer {
+    fd: OwnedFd,
+    phys: G2DPhysical,
+    ptr: *mut u8,
+    size: usize,
+    heap_type: HeapType,
+    /// Row pitch in bytes for buffers allocated with image geometry.
+    pitch: Option<usize>,
+    /// DRM PRIME import handle — keeps a persistent dma_buf_attach alive so that
+    /// DMA_BUF_IOCTL_SYNC actually performs cache maintenance on cached heaps.
+    _drm_attachment: Option<DrmAttachment>,
+}
+
+impl DmaBuffer {
+    /// Allocate `size` bytes from the given heap and map them persistently.
+    ///
+    /// Returns [`Error::HeapUnavailable`] if the heap cannot be opened,
+    /// [`Error::AllocationFailed`] if it refuses the allocation,
+    /// [`Error::PhysicalAddress`] if the buffer has no physical address, and
+    /// [`Error::MmapFailed`] if the allocation succeeds but cannot be
+    /// mapped; the allocation is released first.
+    pub fn new(heap_type: HeapType, size: usize) -> Result<Self> {
+        let heap = Heap::new(heap_type.heap_kind()).map_err(|source| Error::HeapUnavailable {
+            heap: heap_type.to_string(),
+            source,
+        })?;
+
+        let fd = heap
+            .allocate(size)
+            .map_err(|source| Error::AllocationFailed {
+                heap: heap_type.to_string(),
+                size,
+                source,
+            })?;
+
+        let phys = G2DPhysical::new(fd.as_raw_fd())?;
+
+        // Persistent mmap — mapped once for the buffer's lifetime
+        let ptr = unsafe {
+            libc::mmap(
+                ptr::null_mut(),
+                size,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_SHARED,
+                fd.as_raw_fd(),
+                0,
+            )
+        };
+        if ptr == libc::MAP_FAILED {
+            let source = std::io::Error::last_os_error();
+            log::warn!("mmap failed for {heap_type} heap buffer ({size} bytes): {source}");
+            // Close the dma-buf now so the heap memory is released before the
+            // caller sees the error.
+            drop(fd);
+            return Err(Error::MmapFailed { size, source });
+        }
+
+        // For cached heaps, create a persistent DRM PRIME import so that
+        // DMA_BUF_IOCTL_SYNC actually performs cache maintenance.
+        // Without this, begin_cpu_access iterates an empty attachment list.
+        let drm_attachment = if heap_type == HeapType::Cached {
+            DrmAttachment::new(&fd)
+        } else {
+            None
+        };
+
+        log::debug!(
+            "DmaBuffer: {size} bytes from {heap_type} heap, phys=0x{:x}, drm_attach={}",
+            phys.address(),
+            drm_attachment.is_some()
+        );
+
+        Ok(Self {
+            fd,
+            phys,
+            ptr: ptr as *mut u8,
+            size,
+            heap_type,
+            pitch: None,
+            _drm_attachment: drm_attachment,
+        })
+    }
+
+    /// Allocate `size` bytes from the uncached CMA heap, or from the cached
+    /// one when the uncached heap is missing or refuses the allocation.
+    ///
+    /// Check [`heap_type()`](Self::heap_type) or
+    /// [`requires_sync()`](Self::requires_sync) for which one was used.
+    /// [`read_with()`](Self::read_with) and [`write_with()`](Self::write_with)
+    /// are correct for either. If both heaps fail, the cached heap's error
+    /// is returned.
+    pub fn new_auto(size: usize) -> Result<Self> {
+        Self::new_preferring(HeapType::Uncached, size)
+    }
+
+    /// Allocate `size` bytes from `heap_type`, or from the other CMA heap
+    /// when it is missing or refuses the allocation, as
+    /// [`new_auto()`](Self::new_auto) does for the uncached heap. If both
+    /// heaps fail, the other heap's error is returned.
+    pub fn new_preferring(heap_type: HeapType, size: usize) -> Result<Self> {
+        let other = match heap_type {
+            HeapType::Uncached => HeapType::Cached,
+            HeapType::Cached => HeapType::Uncached,
+        };
+        Self::new(heap_type, size).or_else(|e| {
+            log::debug!("DmaBuffer: {heap_type} allocation failed ({e}), trying {other} heap");
+            Self::new(other, size)
+        })
+    }
+
+    /// Allocate a `width`×`height` image of packed `format` whose row pitch
+    /// and total size meet the display scanout constraints, so the buffer
+    /// can be imported directly as a DRM framebuffer.
+    ///
+    /// The pitch is that of [`G2DFormat::aligned_stride`]: a multiple of
+    /// [`SCANOUT_PITCH_ALIGN`] that is also a whole number of pixels (192
+    /// bytes for 3-byte formats), and
+    /// the size to a multiple of [`SCANOUT_SIZE_ALIGN`]. Use
+    /// [`pitch()`](Self::pitch) for the framebuffer pitch; the matching
+    /// `G2DSurface::stride` is `pitch / bytes_per_pixel`.
+    pub fn new_scanout(
+        heap_type: HeapType,
+        format: G2DFormat,
+        width: usize,
+        height: usize,
+    ) -> Result<Self> {
+        let bpp = format.bytes_per_pixel().ok_or_else(|| {
+            Error::InvalidFormat(format!(
+                "{:?} is not a packed scanout format",
+                format.format()
+            ))
+        })?;
+        let pitch = format.aligned_stride(width) * bpp;
+        let size = (pitch * height).next_multiple_of(SCANOUT_SIZE_ALIGN);
+
+        let mut buffer = Self::new(heap_type, size)?;
+        buffer.pitch = Some(pitch);
+        Ok(buffer)
+    }
+
+    /// Allocate a buffer holding exactly a `width`×`height` image of
+    /// `format` and copy `data` into it, ready to use as a blit source.
+    ///
+    /// `data` must be laid out as by [`reinterpret_surface`] at offset 0,
+    /// which describes the result: tightly packed planes back to back, with
+    /// the size from [`G2DFormat::plane_layout`] with no alignment. A slice
+    /// of any other length fails with [`Error::InvalidSurface`] before
+    /// anything is allocated. The copy is bracketed by
+    /// `DMA_BUF_SYNC_WRITE`, so the GPU sees it without further syncing.
+    ///
+    /// This is the input counterpart of
+    /// [`G2D::convert_into_vec`](crate::G2D::convert_into_vec).
+    ///
+    /// [`reinterpret_surface`]: Self::reinterpret_surface
+    pub fn from_slice(
+        heap_type: HeapType,
+        format: G2DFormat,
+        width: usize,
+        height: usize,
+        data: &[u8],
+    ) -> Result<Self> {
+        let size = format.plane_layout(width, height, 1, 1)?.size();
+        if width == 0 || height == 0 || data.len() != size {
+            return Err(Error::InvalidSurface(format!(
+                "{} bytes given for a {width}x{height} g2d_format {} image of {size} bytes",
+                data.len(),
+                format.format()
+            )));
+        }
+
+        let mut buffer = Self::new(heap_type, size)?;
+        buffer.write_with(|mapped| mapped.copy_from_slice(data));
+        Ok(buffer)
+    }
+
+    /// Physical address of the buffer, suitable for `G2DSurface::planes`.
+    pub fn address(&self) -> u64 {
+        self.phys.address()
+    }
+
+    /// Byte offset of a physical address within this buffer, or `None` if
+    /// the address lies outside it.
+    pub fn offset_of(&self, address: u64) -> Option<usize> {
+        let offset = address.checked_sub(self.address())? as usize;
+        (offset < self.size).then_some(offset)
+    }
+
+    /// Size of the buffer in bytes.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Row pitch in bytes, for buffers allocated with image geometry such as
+    /// [`new_scanout()`](Self::new_scanout); `None` for plain allocations.
+    pub fn pitch(&self) -> Option<usize> {
+        self.pitch
+    }
+
+    /// Heap the buffer was allocated from.
+    pub fn heap_type(&self) -> HeapType {
+        self.heap_type
+    }
+
+    /// Whether CPU access needs `DMA_BUF_IOCTL_SYNC` cache maintenance to
+    /// see GPU writes (and for the GPU to see CPU writes).
+    ///
+    /// Determined by the heap: uncached mappings are coherent, cached ones
+    /// are not. No runtime coherency probe is made, since a probe passing
+    /// once does not prove that a stale line can never be observed.
+    /// [`read_with()`](Self::read_with) and [`write_with()`](Self::write_with)
+    /// always sync, even on uncached buffers, because `SYNC_START` also
+    /// waits for fences other devices have attached to the buffer; callers
+    /// holding their own mapping and tracking completion themselves can use
+    /// this to skip the ioctls on buffers that do not need them.
+    pub fn requires_sync(&self) -> bool {
+        match self.heap_type {
+            HeapType::Uncached => false,
+            HeapType::Cached => true,
+        }
+    }
+
+    /// [`read_with()`](Self::read_with), but only if `token` shows that
+    /// the G2D work writing this buffer has completed.
+    ///
+    /// Returns [`Error::NotFinished`] without reading if any clear or blit
+    /// was queued on the token's handle after it was issued. The check is
+    /// per handle, not per buffer: queue the writes, take a token with
+    /// [`G2D::finish_token`](crate::G2D::finish_token), then read.
+    ///
+    /// ```no_run
+    /// # use g2d_sys::{dma::DmaBuffer, G2DSurface, G2D};
+    /// # fn run(g2d: &G2D, dst: &mut G2DSurface, buf: &DmaBuffer) -> g2d_sys::Result<()> {
+    /// g2d.clear(dst, [255, 0, 0, 255])?;
+    /// let token = g2d.finish_token()?;
+    /// let first = buf.read_finished(&token, |data| data[0])?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn read_finished<F: FnOnce(&[u8]) -> T, T>(
+        &self,
+        token: &FinishToken<'_>,
+        f: F,
+    ) -> Result<T> {
+        if !token.is_current() {
+            return Err(Error::NotFinished);
+        }
+        Ok(self.read_with(f))
+    }
+
+    /// View the start of the buffer as a tightly packed `width`×`height`
+    /// image of `P` pixels, for reading after a blit or clear.
+    ///
+    /// CPU read access begins here (`DMA_BUF_SYNC_READ` start, as in
+    /// [`read_with()`](Self::read_with)) and ends when the returned
+    /// [`Pixels`] is dropped, so keep it for no longer than the read. The
+    /// pixel type is not checked against the buffer contents; pick the one
+    /// matching the surface format that was written. Fails with
+    /// [`Error::InvalidSurface`] if the image does not fit the buffer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a `DMA_BUF_IOCTL_SYNC` call fails, as `read_with` does.
+    pub fn as_pixels<P: Pixel>(&self, width: usize, height: usize) -> Result<Pixels<'_, P>> {
+        let count = width
+            .checked_mul(height)
+            .filter(|&n| {
+                n.checked_mul(size_of::<P>())
+                    .is_some_and(|b| b <= self.size)
+            })
+            .ok_or_else(|| {
+                Error::InvalidSurface(format!(
+                    "{width}x{height} image of {} byte pixels exceeds {} byte buffer",
+                    size_of::<P>(),
+                    self.size
+                ))
+            })?;
+
+        self.sync_start(DMA_BUF_SYNC_READ);
+        // SAFETY: `Pixel` is sealed and only implemented by
+        // `#[repr(transparent)]` byte arrays (alignment 1, no invalid bit
+        // patterns), and `count` pixels fit the mapping.
+        let pixels = unsafe { std::slice::from_raw_parts(self.ptr as *const P, count) };
+        Ok(Pixels {
+            buffer: self,
+            pixels,
+        })
+    }
+
+    /// Build a full-frame surface that interprets the bytes at
+    /// `plane_offset` as a `width`×`height` image of `format`, without
+    /// copying.
+    ///
+    /// This is for format aliasing, such as reading the Y plane of an NV12
+    /// frame as a `G2D_GRAY8` source. Planes are placed by
+    /// [`G2DFormat::plane_layout`] without alignment: tightly packed
+    /// (`stride == width`, rounded up to even for subsampled YUV) and back
+    /// to back as with [`G2DSurface::i420`]. Fails with
+    /// [`Error::InvalidSurface`] if the image would extend past the end of
+    /// the buffer, and with [`Error::InvalidFormat`] for formats of unknown
+    /// size.
+    pub fn reinterpret_surface(
+        &self,
+        format: G2DFormat,
+        width: usize,
+        height: usize,
+        plane_offset: usize,
+    ) -> Result<G2DSurface> {
+        let layout = format.plane_layout(width, height, 1, 1)?;
+        let end = layout.size().checked_add(plane_offset);
+        if width == 0 || height == 0 || end.is_none_or(|end| end > self.size) {
+            return Err(Error::InvalidSurface(format!(
+                "{width}x{height} g2d_format {} at offset {plane_offset} does not fit a {} byte buffer",
+                format.format(),
+                self.size
+            )));
+        }
+
+        let addr = self.address() + plane_offset as u64;
+        let mut planes = [0; 3];
+        for (address, plane) in planes.iter_mut().zip(layout.planes()) {
+            *address = addr + plane.offset as u64;
+        }
+        Ok(G2DSurface {
+            format: format.format(),
+            planes,
+            right: width as i32,
+            bottom: height as i32,
+            stride: layout.stride() as i32,
+            width: width as i32,
+            height: height as i32,
+            ..Default::default()
+        })
+    }
+
+    /// Perform DMA_BUF_IOCTL_SYNC with full error checking.
+    fn dma_buf_sync(&self, flags: u64) {
+        if let Err(err) = CacheSync::new(&self.fd).sync(flags) {
+            panic!(
+                "DMA_BUF_IOCTL_SYNC (flags=0x{flags:x}) failed on {heap} heap: {err}",
+                heap = self.heap_type
+            );
+        }
+    }
+
+    /// Begin CPU access with the given direction flags.
+    fn sync_start(&self, flags: u64) {
+        self.dma_buf_sync(flags | DMA_BUF_SYNC_START);
+    }
+
+    /// End CPU access with the given direction flags.
+    fn sync_end(&self, flags: u64) {
+        self.dma_buf_sync(flags | DMA_BUF_SYNC_END);
+    }
+
+    /// Write to the buffer with proper sync bracketing.
+    ///
+    /// Uses `DMA_BUF_SYNC_WRITE` — tells the kernel the CPU will write,
+    /// so it can clean/flush caches on SYNC_END. Takes `&mut self` so the
+    /// mapping cannot be read through [`read_with()`](Self::read_with) or
+    /// [`as_pixels()`](Self::as_pixels) while it is being written.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a `DMA_BUF_IOCTL_SYNC` call fails, since continuing would
+    /// silently hand stale data to the GPU.
+    pub fn write_with<F: FnOnce(&mut [u8]) -> T, T>(&mut self, f: F) -> T {
+        self.sync_start(DMA_BUF_SYNC_WRITE);
+        let result = f(unsafe { std::slice::from_raw_parts_mut(self.ptr, self.size) });
+        self.sync_end(DMA_BUF_SYNC_WRITE);
+        result
+    }
+
+    /// Read from the buffer with proper sync bracketing.
+    ///
+    /// Uses `DMA_BUF_SYNC_READ` — tells the kernel the CPU will read,
+    /// so it can invalidate caches on SYNC_START to see GPU/DMA writes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a `DMA_BUF_IOCTL_SYNC` call fails, since continuing would
+    /// silently return stale data.
+    pub fn read_with<F: FnOnce(&[u8]) -> T, T>(&self, f: F) -> T {
+        self.sync_start(DMA_BUF_SYNC_READ);
+        let result = f(unsafe { std::slice::from_raw_parts(self.ptr, self.size) });
+        self.sync_end(DMA_BUF_SYNC_READ);
+        result
+    }
+}
+
+/// Typed read view of a [`DmaBuffer`] returned by
+/// [`DmaBuffer::as_pixels`]; ends CPU read access when dropped.
+#[derive(Debug)]
+pub struct Pixels<'a, P> {
+    buffer: &'a DmaBuffer,
+    pixels: &'a [P],
+}
+
+impl<P> std::ops::Deref for Pixels<'_, P> {
+    type Target = [P];
+
+    fn deref(&self) -> &[P] {
+        self.pixels
+    }
+}
+
+impl<P> Drop for Pixels<'_, P> {
+    fn drop(&mut self) {
+        self.buffer.sync_end(DMA_BUF_SYNC_READ);
+    }
+}
+
+impl AsFd for DmaBuffer {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.fd.as_fd()
+    }
+}
+
+impl Drop for DmaBuffer {
+    fn drop(&mut self) {
+        let ret = unsafe { libc::munmap(self.ptr as *mut libc::c_void, self.size) };
+        if ret != 0 {
+            log::warn!(
+                "munmap failed for {heap} heap buffer: {err}",
+                heap = self.heap_type,
+                err = std::io::Error::last_os_error()
+            );
+        }
+    }
+}
@@ -0,0 +1,152 @@
+// SPDX-FileCopyrightText: Copyright 2025 Au-Zone Technologies
+// SPDX-License-Identifier: Apache-2.0
+
+//! Sync-file fences for handing G2D output to other devices without a CPU
+//! wait.
+//!
+//! libg2d has no fence API of its own. Instead, [`G2D::submit_with_fence`]
+//! flushes the queued work and exports the destination dma-buf's implicit
+//! fences with `DMA_BUF_IOCTL_EXPORT_SYNC_FILE` (Linux 6.0+). The resulting
+//! sync_file can be passed to a KMS atomic commit (`IN_FENCE_FD`), imported
+//! into another API, or waited on with [`wait`] or [`wait_with`].
+//!
+//! This is the crate's counterpart of a `g2d_create_fence_fd()` call: the
+//! libg2d versions it loads export no such function, so the fence comes from
+//! the destination buffer rather than the handle. Use [`G2D::flush`] alone
+//! to submit without a fence.
+
+use crate::{Error, Result, WaitMode, G2D};
+use nix::ioctl_readwrite;
+use std::{
+    os::fd::{AsFd, AsRawFd, FromRawFd, OwnedFd},
+    time::{Duration, Instant},
+};
+
+/// Export the fences a reader must wait for (linux/dma-buf.h).
+const DMA_BUF_SYNC_READ: u32 = 1 << 0;
+
+#[repr(C)]
+struct dma_buf_export_sync_file {
+    flags: u32,
+    fd: i32,
+}
+
+const DMA_BUF_BASE: u8 = b'b';
+const DMA_BUF_IOCTL_EXPORT_SYNC_FILE: u8 = 2;
+ioctl_readwrite!(
+    ioctl_export_sync_file,
+    DMA_BUF_BASE,
+    DMA_BUF_IOCTL_EXPORT_SYNC_FILE,
+    dma_buf_export_sync_file
+);
+
+impl G2D {
+    /// Flush queued operations and return a sync_file fence that signals
+    /// when `dst` is ready to read.
+    ///
+    /// `dst` is the dma-buf the queued operations write to. The fence covers
+    /// every write fence attached to that buffer, which includes the G2D
+    /// work only if the GPU driver attaches its jobs to the dma-buf
+    /// reservation (implicit sync); otherwise the fence may signal before
+    /// G2D has finished. Verify this on the target kernel before relying on
+    /// the fence alone.
+    ///
+    /// The fence, or [`finish()`](Self::finish), must be waited on before
+    /// the CPU reads `dst`: the cache sync around
+    /// [`DmaBuffer::read_with`](crate::dma::DmaBuffer::read_with) does not
+    /// wait for queued G2D work.
+    ///
+    /// # Fallback
+    ///
+    /// Kernels before 6.0 lack the export ioctl and this returns
+    /// [`Error::IoError`] (`ENOTTY`) after the flush. The work is still
+    /// queued; call [`finish()`](Self::finish) to wait for it on the CPU
+    /// instead.
+    pub fn submit_with_fence(&self, dst: &impl AsFd) -> Result<OwnedFd> {
+        self.flush()?;
+
+        let mut export = dma_buf_export_sync_file {
+            flags: DMA_BUF_SYNC_READ,
+            fd: -1,
+        };
+        unsafe { ioctl_export_sync_file(dst.as_fd().as_raw_fd(), &mut export) }.map_err(|e| {
+            Error::IoError(std::io::Error::new(
+                std::io::Error::from(e).kind(),
+                format!("DMA_BUF_IOCTL_EXPORT_SYNC_FILE failed: {e}"),
+            ))
+        })?;
+        Ok(unsafe { OwnedFd::from_raw_fd(export.fd) })
+    }
+
+    /// [`finish()`](Self::finish), waiting for `dst` through its fence in
+    /// this handle's [`WaitMode`].
+    ///
+    /// `dst` is the dma-buf the queued operations write to, as for
+    /// [`submit_with_fence()`](Self::submit_with_fence). Once the fence
+    /// signals, `g2d_finish` retires the queue without sleeping. When the
+    /// fence cannot be exported (kernels before 6.0), this blocks in
+    /// `finish()` whatever the mode.
+    pub fn finish_for(&self, dst: &impl AsFd) -> Result<()> {
+        if let Ok(fence) = self.submit_with_fence(dst) {
+            wait_with(&fence, None, self.wait_mode())?;
+        }
+        self.finish()
+    }
+}
+
+/// Wait for a sync_file fence to signal.
+///
+/// Returns `Ok(true)` once the fence has signaled, or `Ok(false)` if
+/// `timeout` elapsed first. `None` waits indefinitely.
+pub fn wait(fence: &impl AsFd, timeout: Option<Duration>) -> Result<bool> {
+    let timeout_ms = timeout.map_or(-1, |t| t.as_millis().min(i32::MAX as u128) as i32);
+    poll(fence, timeout_ms)
+}
+
+/// [`wait`] in the given [`WaitMode`]: `Block` sleeps in `poll`, `Spin`
+/// polls with a zero timeout until the fence signals or `timeout` elapses.
+pub fn wait_with(fence: &impl AsFd, timeout: Option<Duration>, mode: WaitMode) -> Result<bool> {
+    match mode {
+        WaitMode::Block => wait(fence, timeout),
+        WaitMode::Spin => {
+            let start = Instant::now();
+            loop {
+                if poll(fence, 0)? {
+                    return Ok(true);
+                }
+                if timeout.is_some_and(|t| start.elapsed() >= t) {
+                    return Ok(false);
+                }
+                std::hint::spin_loop();
+            }
+        }
+    }
+}
+
+/// Poll `fence` for up to `timeout_ms` (-1 waits indefinitely), retrying
+/// on `EINTR`.
+fn poll(fence: &impl AsFd, timeout_ms: i32) -> Result<bool> {
+    let mut pollfd = libc::pollfd {
+        fd: fence.as_fd().as_raw_fd(),
+        events: libc::POLLIN,
+        revents: 0,
+    };
+    loop {
+        match unsafe { libc::poll(&mut pollfd, 1, timeout_ms) } {
+            -1 => {
+                let err = std::io::Error::last_os_error();
+                if err.kind() != std::io::ErrorKind::Interrupted {
+                    return Err(err.into());
+                }
+            }
+            0 => return Ok(false),
+            _ if pollfd.revents & libc::POLLIN != 0 => return Ok(true),
+            _ => {
+                return Err(Error::IoError(std::io::Error::other(format!(
+                    "poll on fence returned revents 0x{:x}",
+                    pollfd.revents
+                ))))
+            }
+        }
+    }
+}
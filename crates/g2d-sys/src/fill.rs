@@ -0,0 +1,367 @@
+// SPDX-FileCopyrightText: Copyright 2025 Au-Zone Technologies
+// SPDX-License-Identifier: Apache-2.0
+
+//! CPU fill fallback for small clears.
+//!
+//! A GPU clear costs a fixed submit + `finish` round trip regardless of the
+//! region size, so below some pixel count a cache-synced CPU fill is faster.
+
+use crate::{
+    dma::{DmaBuffer, HeapType},
+    g2d_format_G2D_RGBA8888, Error, G2DFormat, G2DSurface, Rect, Result, G2D,
+};
+use std::time::{Duration, Instant};
+
+/// Side length of the square RGBA region used for calibration.
+const CALIBRATION_SIZE: usize = 64;
+const CALIBRATION_ROUNDS: usize = 3;
+/// Upper bound for a calibrated threshold (a 2048×2048 region).
+const MAX_CPU_CLEAR_THRESHOLD: usize = 2048 * 2048;
+
+impl G2D {
+    /// Pixel count below which [`clear_auto()`](Self::clear_auto) fills on
+    /// the CPU, or `None` if not yet set or calibrated.
+    pub fn cpu_clear_threshold(&self) -> Option<usize> {
+        self.cpu_clear_threshold.get()
+    }
+
+    /// Set the CPU fill threshold for [`clear_auto()`](Self::clear_auto).
+    ///
+    /// `0` always uses the GPU; `usize::MAX` always uses the CPU for formats
+    /// it can fill.
+    pub fn set_cpu_clear_threshold(&mut self, pixels: usize) {
+        self.cpu_clear_threshold.set(Some(pixels));
+    }
+
+    /// Measure the GPU clear round trip against a synced CPU fill on a
+    /// scratch buffer from `heap_type` and store the break-even pixel count.
+    ///
+    /// Called automatically by the first [`clear_auto()`](Self::clear_auto)
+    /// when no threshold has been set.
+    pub fn calibrate_cpu_clear_threshold(&self, heap_type: HeapType) -> Result<usize> {
+        let pixels = CALIBRATION_SIZE * CALIBRATION_SIZE;
+        let mut buf = DmaBuffer::new(heap_type, pixels * 4)?;
+        let mut surface = G2DSurface {
+            format: g2d_format_G2D_RGBA8888,
+            planes: [buf.address(), 0, 0],
+            right: CALIBRATION_SIZE as i32,
+            bottom: CALIBRATION_SIZE as i32,
+            stride: CALIBRATION_SIZE as i32,
+            width: CALIBRATION_SIZE as i32,
+            height: CALIBRATION_SIZE as i32,
+            ..Default::default()
+        };
+
+        let mut gpu = Duration::MAX;
+        let mut cpu = Duration::MAX;
+        // The first round warms up the GPU context and page tables.
+        for _ in 0..=CALIBRATION_ROUNDS {
+            let start = Instant::now();
+            self.clear(&mut surface, [0, 0, 0, 255])?;
+            self.finish()?;
+            gpu = gpu.min(start.elapsed());
+
+            let start = Instant::now();
+            cpu_fill(&surface, &mut buf, [0, 0, 0, 255])?;
+            cpu = cpu.min(start.elapsed());
+        }
+
+        let threshold = if cpu.is_zero() {
+            MAX_CPU_CLEAR_THRESHOLD
+        } else {
+            ((pixels as u128 * gpu.as_nanos()) / cpu.as_nanos())
+                .min(MAX_CPU_CLEAR_THRESHOLD as u128) as usize
+        };
+        log::debug!(
+            "clear calibration on {heap_type}: gpu={gpu:?} cpu={cpu:?} for {pixels} px, \
+             threshold={threshold} px"
+        );
+        self.cpu_clear_threshold.set(Some(threshold));
+        Ok(threshold)
+    }
+
+    /// Clear the surface crop region, using a synced CPU fill when the
+    /// region is smaller than the [CPU threshold](Self::cpu_clear_threshold)
+    /// and a GPU [`clear()`](Self::clear) otherwise.
+    ///
+    /// `buf` must be the buffer backing `dst.planes[0]`. Packed formats
+    /// `g2d_clear` rejects (RGB888/BGR888) always take the CPU path, at any
+    /// size; YUV formats always use the GPU.
+    ///
+    /// The CPU path completes before returning and first calls
+    /// [`finish()`](Self::finish) so that earlier queued GPU operations on
+    /// the buffer are ordered before the fill. The GPU path only queues the
+    /// clear, as with [`clear()`](Self::clear).
+    pub fn clear_auto(
+        &self,
+        dst: &mut G2DSurface,
+        buf: &mut DmaBuffer,
+        color: [u8; 4],
+    ) -> Result<()> {
+        let format = dst.format();
+        if format.pack_rgba(color).is_none() {
+            return self.clear(dst, color);
+        }
+
+        if format.is_clearable() {
+            let threshold = match self.cpu_clear_threshold.get() {
+                Some(threshold) => threshold,
+                None => self.calibrate_cpu_clear_threshold(buf.heap_type())?,
+            };
+            let pixels =
+                dst.crop_rect().width().max(0) as usize * dst.crop_rect().height().max(0) as usize;
+            if pixels >= threshold {
+                return self.clear(dst, color);
+            }
+        }
+        self.finish()?;
+        cpu_fill(dst, buf, color)
+    }
+
+    /// Clear the surface crop region to black: every byte of a packed
+    /// pixel is `0x00`.
+    ///
+    /// For formats with an alpha channel this is transparent black; use
+    /// [`clear()`](Self::clear) with `[0, 0, 0, 255]` for opaque black.
+    /// See [`clear_white()`](Self::clear_white) for how the path is chosen.
+    pub fn clear_black(&self, dst: &mut G2DSurface, buf: &mut DmaBuffer) -> Result<()> {
+        self.clear_uniform(dst, buf, 0x00)
+    }
+
+    /// Clear the surface crop region to opaque white: every byte of a packed
+    /// pixel is `0xFF`.
+    ///
+    /// Like [`clear_auto()`](Self::clear_auto), regions below the
+    /// [CPU threshold](Self::cpu_clear_threshold) are filled on the CPU, but
+    /// with a byte fill rather than a per-pixel copy, and a crop spanning
+    /// whole rows is filled in one go. Packed formats `g2d_clear` rejects
+    /// (RGB888/BGR888) always take the CPU path; YUV formats always use the
+    /// GPU. `buf` must be the buffer backing `dst.planes[0]`.
+    pub fn clear_white(&self, dst: &mut G2DSurface, buf: &mut DmaBuffer) -> Result<()> {
+        self.clear_uniform(dst, buf, 0xFF)
+    }
+
+    fn clear_uniform(&self, dst: &mut G2DSurface, buf: &mut DmaBuffer, byte: u8) -> Result<()> {
+        let color = [byte; 4];
+        let format = dst.format();
+        let Some((_, bpp)) = format.pack_rgba(color) else {
+            return self.clear(dst, color);
+        };
+
+        if format.is_clearable() {
+            let threshold = match self.cpu_clear_threshold.get() {
+                Some(threshold) => threshold,
+                None => self.calibrate_cpu_clear_threshold(buf.heap_type())?,
+            };
+            let pixels =
+                dst.crop_rect().width().max(0) as usize * dst.crop_rect().height().max(0) as usize;
+            if pixels >= threshold {
+                return self.clear(dst, color);
+            }
+        }
+        self.finish()?;
+        cpu_fill_bytes(dst, buf, bpp, byte)
+    }
+
+    /// [`clear_rect()`](Self::clear_rect) followed by
+    /// [`finish()`](Self::finish).
+    ///
+    /// `buf` must be the buffer backing `dst.planes[0]`, and `dst` must use a
+    /// packed format. In debug builds the whole buffer is snapshotted before
+    /// the clear and the call panics if any byte outside `rect` changed,
+    /// catching drivers that write past the crop (for example by rounding
+    /// it up to a tile). Release builds skip the snapshot.
+    pub fn clear_rect_checked(
+        &self,
+        dst: &G2DSurface,
+        buf: &DmaBuffer,
+        rect: Rect,
+        color: [u8; 4],
+    ) -> Result<()> {
+        let bpp = dst.format().bytes_per_pixel().ok_or_else(|| {
+            Error::InvalidFormat(format!(
+                "clear_rect_checked requires a packed format, not g2d_format {}",
+                dst.format
+            ))
+        })?;
+        let mut region = *dst;
+        region.left = rect.left;
+        region.top = rect.top;
+        region.right = rect.right;
+        region.bottom = rect.bottom;
+        let layout = CropLayout::new(&region, buf, bpp)?;
+
+        let before = cfg!(debug_assertions).then(|| buf.read_with(|data| data.to_vec()));
+        self.clear_rect(dst, rect, color)?;
+        self.finish()?;
+
+        if let (Some(mut expected), Some(layout)) = (before, layout) {
+            buf.read_with(|data| {
+                // Only the rect may differ, so take it from the result and
+                // compare the rest byte for byte.
+                for row in layout.rows() {
+                    expected[row.clone()].copy_from_slice(&data[row]);
+                }
+                let changed = expected.iter().zip(data).position(|(a, b)| a != b);
+                debug_assert!(
+                    changed.is_none(),
+                    "clear of {rect:?} modified byte {} outside the rect",
+                    changed.unwrap_or(0)
+                );
+            });
+        }
+        Ok(())
+    }
+}
+
+impl DmaBuffer {
+    /// Allocate a `width`×`height` image of `format` filled with `color`,
+    /// ready to use as a blit source (overlays, backgrounds, test patterns).
+    ///
+    /// Formats `g2d_clear` can write are cleared on the GPU through `g2d`,
+    /// which waits for the clear; RGB888/BGR888 are filled on the CPU.
+    /// Other formats return [`Error::InvalidFormat`]. The surface is laid
+    /// out as by [`reinterpret_surface()`](Self::reinterpret_surface).
+    pub fn solid(
+        g2d: &G2D,
+        heap_type: HeapType,
+        format: G2DFormat,
+        width: usize,
+        height: usize,
+        color: [u8; 4],
+    ) -> Result<(DmaBuffer, G2DSurface)> {
+        let gpu = format.is_clearable();
+        if !gpu && format.pack_rgba(color).is_none() {
+            return Err(Error::InvalidFormat(format!(
+                "cannot fill g2d_format {} with a solid color",
+                format.format()
+            )));
+        }
+        let size = format.plane_layout(width, height, 1, 1)?.size();
+        let mut buf = DmaBuffer::new(heap_type, size)?;
+        let mut surface = buf.reinterpret_surface(format, width, height, 0)?;
+        if gpu {
+            g2d.clear(&mut surface, color)?;
+            g2d.finish()?;
+        } else {
+            cpu_fill(&surface, &mut buf, color)?;
+        }
+        Ok((buf, surface))
+    }
+}
+
+/// Byte layout of a packed-format surface's crop region within its buffer.
+pub(crate) struct CropLayout {
+    base: usize,
+    row_pitch: usize,
+    bpp: usize,
+    rect: Rect,
+}
+
+impl CropLayout {
+    /// Validate the crop of `surface` against `buf` for `bpp` bytes per
+    /// pixel. Returns `None` for an empty crop.
+    pub(crate) fn new(surface: &G2DSurface, buf: &DmaBuffer, bpp: usize) -> Result<Option<Self>> {
+        let base = buf.offset_of(surface.planes[0]).ok_or_else(|| {
+            Error::InvalidSurface(format!(
+                "plane 0 address 0x{:x} is outside the buffer",
+                surface.planes[0]
+            ))
+        })?;
+
+        let rect = surface.crop_rect();
+        if rect.left < 0
+            || rect.top < 0
+            || rect.right > surface.width
+            || rect.bottom > surface.height
+        {
+            return Err(Error::InvalidSurface(format!(
+                "crop {rect:?} exceeds {}x{} surface",
+                surface.width, surface.height
+            )));
+        }
+        if rect.is_empty() {
+            return Ok(None);
+        }
+
+        let row_pitch = surface.stride as usize * bpp;
+        let end = base + (rect.bottom as usize - 1) * row_pitch + rect.right as usize * bpp;
+        if end > buf.size() {
+            return Err(Error::InvalidSurface(format!(
+                "crop {rect:?} ends at byte {end} of a {} byte buffer",
+                buf.size()
+            )));
+        }
+
+        Ok(Some(CropLayout {
+            base,
+            row_pitch,
+            bpp,
+            rect,
+        }))
+    }
+
+    /// Byte range of each crop row, top to bottom.
+    pub(crate) fn rows(&self) -> impl Iterator<Item = std::ops::Range<usize>> + '_ {
+        (self.rect.top as usize..self.rect.bottom as usize).map(|row| {
+            let start = self.base + row * self.row_pitch + self.rect.left as usize * self.bpp;
+            start..start + self.rect.width() as usize * self.bpp
+        })
+    }
+
+    /// Byte range of the whole crop when its rows are contiguous in the
+    /// buffer, i.e. it spans the full row pitch.
+    pub(crate) fn contiguous(&self) -> Option<std::ops::Range<usize>> {
+        (self.rect.left == 0 && self.rect.width() as usize * self.bpp == self.row_pitch).then(
+            || {
+                self.base + self.rect.top as usize * self.row_pitch
+                    ..self.base + self.rect.bottom as usize * self.row_pitch
+            },
+        )
+    }
+
+    /// Number of pixels in the crop.
+    pub(crate) fn pixels(&self) -> usize {
+        self.rect.width() as usize * self.rect.height() as usize
+    }
+}
+
+/// Fill the crop region of a packed-format surface on the CPU.
+pub(crate) fn cpu_fill(dst: &G2DSurface, buf: &mut DmaBuffer, color: [u8; 4]) -> Result<()> {
+    let (pixel, bpp) = dst.format().pack_rgba(color).ok_or_else(|| {
+        Error::InvalidFormat(format!(
+            "CPU fill does not support g2d_format {}",
+            dst.format
+        ))
+    })?;
+    let Some(layout) = CropLayout::new(dst, buf, bpp)? else {
+        return Ok(());
+    };
+
+    buf.write_with(|data| {
+        for row in layout.rows() {
+            for chunk in data[row].chunks_exact_mut(bpp) {
+                chunk.copy_from_slice(&pixel[..bpp]);
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Fill the crop region of a packed-format surface with `bpp`-byte pixels
+/// whose bytes are all `byte`.
+fn cpu_fill_bytes(dst: &G2DSurface, buf: &mut DmaBuffer, bpp: usize, byte: u8) -> Result<()> {
+    let Some(layout) = CropLayout::new(dst, buf, bpp)? else {
+        return Ok(());
+    };
+
+    buf.write_with(|data| match layout.contiguous() {
+        Some(span) => data[span].fill(byte),
+        None => {
+            for row in layout.rows() {
+                data[row].fill(byte);
+            }
+        }
+    });
+    Ok(())
+}
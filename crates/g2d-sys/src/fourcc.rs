@@ -0,0 +1,92 @@
+// SPDX-FileCopyrightText: Copyright 2025 Au-Zone Technologies
+// SPDX-License-Identifier: Apache-2.0
+
+//! Conversions between G2D formats and the `drm-fourcc` codes used by KMS,
+//! V4L2 and Wayland.
+//!
+//! G2D names packed RGB formats by their bytes in memory order, while DRM
+//! names them by the channels of a little-endian word from the most
+//! significant bits down. The two orders are reversed, so G2D `RGBA8888`
+//! (bytes R, G, B, A) is DRM `ABGR8888` (`AB24`) and G2D `BGRX8888` is DRM
+//! `XRGB8888` (`XR24`). The 16-bit 565 formats are named by their word in
+//! both APIs and keep their names; YUV formats are named alike as well.
+//!
+//! The table is always built, and [`drm_code()`] gives the raw code KMS
+//! ioctls take. With the `drm-fourcc` feature, [`to_drm_fourcc()`] and
+//! `TryFrom<DrmFourcc> for G2DFormat` convert to and from the typed
+//! [`DrmFourcc`]. The inherent [`G2DFormat::try_from`] takes a
+//! [`FourCharCode`](four_char_code::FourCharCode), so convert a
+//! `DrmFourcc` with `try_into()` or `<G2DFormat as TryFrom<_>>::try_from`.
+
+use crate::{
+    g2d_format, g2d_format_G2D_ABGR8888, g2d_format_G2D_ARGB8888, g2d_format_G2D_BGR565,
+    g2d_format_G2D_BGR888, g2d_format_G2D_BGRA8888, g2d_format_G2D_BGRX8888, g2d_format_G2D_GRAY8,
+    g2d_format_G2D_I420, g2d_format_G2D_NV12, g2d_format_G2D_NV16, g2d_format_G2D_NV21,
+    g2d_format_G2D_NV61, g2d_format_G2D_RGB565, g2d_format_G2D_RGB888, g2d_format_G2D_RGBA8888,
+    g2d_format_G2D_RGBX8888, g2d_format_G2D_UYVY, g2d_format_G2D_VYUY, g2d_format_G2D_XBGR8888,
+    g2d_format_G2D_XRGB8888, g2d_format_G2D_YUYV, g2d_format_G2D_YV12, g2d_format_G2D_YVYU,
+};
+#[cfg(feature = "drm-fourcc")]
+use crate::{Error, G2DFormat};
+#[cfg(feature = "drm-fourcc")]
+pub use drm_fourcc::DrmFourcc;
+
+/// Every G2D format with a DRM equivalent, paired with its DRM code.
+const FORMATS: &[(g2d_format, &[u8; 4])] = &[
+    (g2d_format_G2D_RGBA8888, b"AB24"),
+    (g2d_format_G2D_RGBX8888, b"XB24"),
+    (g2d_format_G2D_BGRA8888, b"AR24"),
+    (g2d_format_G2D_BGRX8888, b"XR24"),
+    (g2d_format_G2D_ARGB8888, b"BA24"),
+    (g2d_format_G2D_XRGB8888, b"BX24"),
+    (g2d_format_G2D_ABGR8888, b"RA24"),
+    (g2d_format_G2D_XBGR8888, b"RX24"),
+    (g2d_format_G2D_RGB888, b"BG24"),
+    (g2d_format_G2D_BGR888, b"RG24"),
+    (g2d_format_G2D_RGB565, b"RG16"),
+    (g2d_format_G2D_BGR565, b"BG16"),
+    (g2d_format_G2D_GRAY8, b"R8  "),
+    (g2d_format_G2D_NV12, b"NV12"),
+    (g2d_format_G2D_NV21, b"NV21"),
+    (g2d_format_G2D_NV16, b"NV16"),
+    (g2d_format_G2D_NV61, b"NV61"),
+    (g2d_format_G2D_I420, b"YU12"),
+    (g2d_format_G2D_YV12, b"YV12"),
+    (g2d_format_G2D_YUYV, b"YUYV"),
+    (g2d_format_G2D_YVYU, b"YVYU"),
+    (g2d_format_G2D_UYVY, b"UYVY"),
+    (g2d_format_G2D_VYUY, b"VYUY"),
+];
+
+/// DRM code for `format` as the little-endian `u32` KMS ioctls take, or
+/// `None` for a G2D format DRM has no exact equivalent for, such as the
+/// 5551 and 10-bit formats.
+pub fn drm_code(format: g2d_format) -> Option<u32> {
+    FORMATS
+        .iter()
+        .find(|(g2d, _)| *g2d == format)
+        .map(|&(_, code)| u32::from_le_bytes(*code))
+}
+
+/// Typed [`drm_code()`].
+#[cfg(feature = "drm-fourcc")]
+pub fn to_drm_fourcc(format: g2d_format) -> Option<DrmFourcc> {
+    drm_code(format).and_then(|code| DrmFourcc::try_from(code).ok())
+}
+
+#[cfg(feature = "drm-fourcc")]
+impl TryFrom<DrmFourcc> for G2DFormat {
+    type Error = Error;
+
+    /// G2D format with the memory layout of `fourcc`; fails with
+    /// [`Error::InvalidFormat`] for one G2D cannot handle.
+    fn try_from(fourcc: DrmFourcc) -> Result<Self, Self::Error> {
+        FORMATS
+            .iter()
+            .find(|(_, code)| u32::from_le_bytes(**code) == fourcc as u32)
+            .map(|&(g2d, _)| G2DFormat(g2d))
+            .ok_or_else(|| {
+                Error::InvalidFormat(format!("DRM format {fourcc} has no G2D equivalent"))
+            })
+    }
+}
@@ -0,0 +1,116 @@
+// SPDX-FileCopyrightText: Copyright 2025 Au-Zone Technologies
+// SPDX-License-Identifier: Apache-2.0
+
+//! Convert-then-analyze: a GPU blit followed by a subsampled CPU histogram
+//! of the result, for auto-exposure and white-balance feedback.
+
+use crate::{dma::DmaBuffer, fill::CropLayout, Error, G2DSurface, Result, G2D};
+
+/// 256-bin histograms of a subsampled RGB image.
+///
+/// Luma uses BT.601 weights on the gamma-encoded values, as is usual for
+/// exposure metering.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Histogram {
+    pub luma: [u32; 256],
+    pub red: [u32; 256],
+    pub green: [u32; 256],
+    pub blue: [u32; 256],
+    /// Number of pixels sampled.
+    pub samples: u32,
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Histogram {
+            luma: [0; 256],
+            red: [0; 256],
+            green: [0; 256],
+            blue: [0; 256],
+            samples: 0,
+        }
+    }
+}
+
+impl Histogram {
+    /// Luma bin with the most samples (the lowest one on ties).
+    pub fn luma_peak(&self) -> u8 {
+        peak(&self.luma)
+    }
+
+    /// Mean luma of the sampled pixels, or `None` if nothing was sampled.
+    pub fn mean_luma(&self) -> Option<f32> {
+        if self.samples == 0 {
+            return None;
+        }
+        let sum: u64 = self
+            .luma
+            .iter()
+            .enumerate()
+            .map(|(bin, &count)| bin as u64 * count as u64)
+            .sum();
+        Some(sum as f32 / self.samples as f32)
+    }
+
+    fn add(&mut self, r: u8, g: u8, b: u8) {
+        let luma = (77 * r as u32 + 150 * g as u32 + 29 * b as u32 + 128) >> 8;
+        self.luma[luma as usize] += 1;
+        self.red[r as usize] += 1;
+        self.green[g as usize] += 1;
+        self.blue[b as usize] += 1;
+        self.samples += 1;
+    }
+}
+
+fn peak(bins: &[u32; 256]) -> u8 {
+    let max = bins.iter().max().copied().unwrap_or(0);
+    bins.iter().position(|&count| count == max).unwrap_or(0) as u8
+}
+
+impl G2D {
+    /// Blit `src` into `dst`, wait for it, and build a histogram from every
+    /// `sample_stride`-th pixel of every `sample_stride`-th row of the
+    /// destination crop.
+    ///
+    /// `dst_buf` must back `dst`, whose format must be a 24- or 32-bit RGB
+    /// format. The subsample is read under a single `DMA_BUF_IOCTL_SYNC`
+    /// bracket. A `sample_stride` of 0 is treated as 1.
+    pub fn convert_and_sample(
+        &self,
+        src: &G2DSurface,
+        dst: &G2DSurface,
+        dst_buf: &DmaBuffer,
+        sample_stride: usize,
+    ) -> Result<Histogram> {
+        let format = dst.format();
+        let (rgb, bpp) = format
+            .pack_rgba([1, 2, 3, 0])
+            .filter(|&(_, bpp)| bpp >= 3)
+            .ok_or_else(|| {
+                Error::InvalidFormat(format!(
+                    "cannot sample g2d_format {}; a 24- or 32-bit RGB destination is required",
+                    dst.format
+                ))
+            })?;
+        let offset = |channel: u8| rgb.iter().position(|&c| c == channel).unwrap_or(0);
+        let (r, g, b) = (offset(1), offset(2), offset(3));
+
+        let layout = CropLayout::new(dst, dst_buf, bpp)?;
+        self.blit(src, dst)?;
+        self.finish()?;
+
+        let mut histogram = Histogram::default();
+        let Some(layout) = layout else {
+            return Ok(histogram);
+        };
+        let step = sample_stride.max(1);
+        dst_buf.read_with(|data| {
+            for row in layout.rows().step_by(step) {
+                for px in data[row].chunks_exact(bpp).step_by(step) {
+                    histogram.add(px[r], px[g], px[b]);
+                }
+            }
+        });
+        Ok(histogram)
+    }
+}
@@ -0,0 +1,147 @@
+// SPDX-FileCopyrightText: Copyright 2025 Au-Zone Technologies
+// SPDX-License-Identifier: Apache-2.0
+
+//! Moving images between the `image` crate and DMA buffers, for tests and
+//! tools.
+
+use crate::{
+    dma::{DmaBuffer, HeapType},
+    g2d_format_G2D_RGBA8888, Error, G2DSurface, Result,
+};
+use image::RgbaImage;
+use std::path::Path;
+
+/// Decode a PNG or JPEG file into a new RGBA8888 DMA buffer.
+///
+/// Returns the buffer together with a full-frame surface describing it,
+/// ready to use as a blit source. The pixels are written under
+/// `DMA_BUF_SYNC_WRITE` bracketing, so the GPU sees them without further
+/// syncing.
+pub fn load_to_dma<P: AsRef<Path>>(
+    path: P,
+    heap_type: HeapType,
+) -> Result<(DmaBuffer, G2DSurface)> {
+    let path = path.as_ref();
+    let image = image::open(path)
+        .map_err(|e| {
+            Error::IoError(std::io::Error::other(format!(
+                "failed to decode {}: {e}",
+                path.display()
+            )))
+        })?
+        .into_rgba8();
+    let (width, height) = image.dimensions();
+
+    let mut buf = DmaBuffer::new(heap_type, image.as_raw().len())?;
+    buf.write_with(|data| data.copy_from_slice(image.as_raw()));
+
+    let surface = G2DSurface {
+        format: g2d_format_G2D_RGBA8888,
+        planes: [buf.address(), 0, 0],
+        right: width as i32,
+        bottom: height as i32,
+        stride: width as i32,
+        width: width as i32,
+        height: height as i32,
+        global_alpha: 255,
+        ..Default::default()
+    };
+    Ok((buf, surface))
+}
+
+/// Read the RGBA8888 image described by `surface` out of `buf`.
+///
+/// `image` rows are tightly packed while the surface's rows are `stride`
+/// pixels apart, so the rows are copied one at a time. The whole surface
+/// is read, not just its crop. Fails with [`Error::InvalidFormat`] for a
+/// surface that is not RGBA8888 and with [`Error::InvalidSurface`] when
+/// the image does not lie within `buf`.
+pub fn surface_to_rgba_image(buf: &DmaBuffer, surface: &G2DSurface) -> Result<RgbaImage> {
+    let rows = RgbaRows::of(buf, surface)?;
+    let mut pixels = vec![0u8; rows.row_len * rows.height];
+    buf.read_with(|data| {
+        for (y, row) in pixels.chunks_exact_mut(rows.row_len).enumerate() {
+            let start = rows.offset + y * rows.pitch;
+            row.copy_from_slice(&data[start..start + rows.row_len]);
+        }
+    });
+    let (width, height) = surface.dimensions();
+    Ok(RgbaImage::from_raw(width as u32, height as u32, pixels).expect("pixels match dimensions"))
+}
+
+/// Write `image` into `buf` as the RGBA8888 image described by `surface`,
+/// row by row so a stride-padded surface keeps its padding untouched.
+///
+/// The surface must have the image's dimensions. Fails with
+/// [`Error::InvalidFormat`] for a surface that is not RGBA8888 and with
+/// [`Error::InvalidSurface`] for a size mismatch or when the image does
+/// not fit within `buf`.
+pub fn rgba_image_to_surface(
+    image: &RgbaImage,
+    buf: &mut DmaBuffer,
+    surface: &G2DSurface,
+) -> Result<()> {
+    let (width, height) = image.dimensions();
+    if surface.dimensions() != (width as i32, height as i32) {
+        return Err(Error::InvalidSurface(format!(
+            "{width}x{height} image does not match a {}x{} surface",
+            surface.width, surface.height
+        )));
+    }
+    let rows = RgbaRows::of(buf, surface)?;
+    buf.write_with(|data| {
+        for (y, row) in image.as_raw().chunks_exact(rows.row_len).enumerate() {
+            let start = rows.offset + y * rows.pitch;
+            data[start..start + rows.row_len].copy_from_slice(row);
+        }
+    });
+    Ok(())
+}
+
+/// Where the rows of an RGBA8888 surface lie within its buffer.
+struct RgbaRows {
+    offset: usize,
+    pitch: usize,
+    row_len: usize,
+    height: usize,
+}
+
+impl RgbaRows {
+    fn of(buf: &DmaBuffer, surface: &G2DSurface) -> Result<Self> {
+        if surface.format != g2d_format_G2D_RGBA8888 {
+            return Err(Error::InvalidFormat(format!(
+                "g2d_format {} is not RGBA8888",
+                surface.format
+            )));
+        }
+        let (width, height) = surface.dimensions();
+        if width <= 0 || height <= 0 || surface.stride < width {
+            return Err(Error::InvalidSurface(format!(
+                "{width}x{height} surface with stride {} has no rows to copy",
+                surface.stride
+            )));
+        }
+        let (width, height) = (width as usize, height as usize);
+        let pitch = surface.stride as usize * 4;
+        let offset = buf.offset_of(surface.planes[0]).ok_or_else(|| {
+            Error::InvalidSurface(format!(
+                "surface address {:#x} is outside the buffer",
+                surface.planes[0]
+            ))
+        })?;
+        let end = offset + pitch * (height - 1) + width * 4;
+        if end > buf.size() {
+            return Err(Error::InvalidSurface(format!(
+                "{width}x{height} surface with stride {} overruns the {}-byte buffer",
+                surface.stride,
+                buf.size()
+            )));
+        }
+        Ok(RgbaRows {
+            offset,
+            pitch,
+            row_len: width * 4,
+            height,
+        })
+    }
+}
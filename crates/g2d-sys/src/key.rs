@@ -0,0 +1,101 @@
+// SPDX-FileCopyrightText: Copyright 2025 Au-Zone Technologies
+// SPDX-License-Identifier: Apache-2.0
+
+//! Chroma-keyed overlay.
+//!
+//! libg2d has no color-key registers, so the key is applied by a CPU pass
+//! that copies the source into an RGBA scratch buffer with the keyed pixels
+//! made fully transparent, followed by a source-over blended blit.
+
+use crate::{
+    dma::DmaBuffer, fill::CropLayout, g2d_format_G2D_RGBA8888, BlendFunc, Error, G2DFormat,
+    G2DSurface, Result, G2D,
+};
+
+impl G2D {
+    /// Blit `src` onto `dst`, leaving the destination untouched wherever
+    /// the source pixel matches the RGB color `key`.
+    ///
+    /// A source pixel is keyed out when each of its red, green and blue
+    /// channels is within `tolerance` of the key's, so a `tolerance` of 0
+    /// keys the exact color only and noisy camera backgrounds need a few
+    /// levels of slack. Other pixels keep their own alpha (opaque for
+    /// formats without one) and are blended source-over, scaled to the
+    /// destination crop as by [`blit()`](Self::blit); scaling softens the
+    /// key's edges.
+    ///
+    /// `src_buf` must hold `src`, whose format must be an 8-bit-per-channel
+    /// packed RGB format; others fail with [`Error::Unsupported`]. The
+    /// keyed copy of the source crop is a scratch buffer allocated from
+    /// `src_buf`'s heap, so this call waits for earlier queued work before
+    /// reading the source and for the blit before returning.
+    pub fn blit_chroma_key(
+        &self,
+        src: &G2DSurface,
+        src_buf: &DmaBuffer,
+        dst: &G2DSurface,
+        key: [u8; 3],
+        tolerance: u8,
+    ) -> Result<()> {
+        let format = src.format();
+        let (r, g, b, a) = channel_offsets(format).ok_or_else(|| {
+            Error::Unsupported(format!(
+                "cannot chroma-key g2d_format {}, which is not 8-bit packed RGB",
+                src.format
+            ))
+        })?;
+        let bpp = format.bytes_per_pixel().ok_or_else(|| {
+            Error::InvalidFormat(format!("g2d_format {} is not packed", src.format))
+        })?;
+        let Some(layout) = CropLayout::new(src, src_buf, bpp)? else {
+            return Ok(());
+        };
+
+        let rgba = G2DFormat(g2d_format_G2D_RGBA8888);
+        let rect = src.crop_rect();
+        let (width, height) = (rect.width() as usize, rect.height() as usize);
+        let mut scratch = DmaBuffer::new(src_buf.heap_type(), width * height * 4)?;
+        let mut keyed = scratch.reinterpret_surface(rgba, width, height, 0)?;
+
+        let within = |value: u8, key: u8| value.abs_diff(key) <= tolerance;
+        // Earlier queued work may still be writing the source.
+        self.finish()?;
+        src_buf.read_with(|data| {
+            scratch.write_with(|out| {
+                let pixels = layout.rows().flat_map(|row| data[row].chunks_exact(bpp));
+                for (px, out) in pixels.zip(out.chunks_exact_mut(4)) {
+                    let color = [px[r], px[g], px[b]];
+                    if within(color[0], key[0])
+                        && within(color[1], key[1])
+                        && within(color[2], key[2])
+                    {
+                        out.copy_from_slice(&[0; 4]);
+                    } else {
+                        let alpha = a.map_or(255, |a| px[a]);
+                        out.copy_from_slice(&[color[0], color[1], color[2], alpha]);
+                    }
+                }
+            })
+        });
+
+        keyed.global_alpha = src.global_alpha;
+        keyed.set_blend_func(BlendFunc::SrcAlpha);
+        let mut blend_dst = *dst;
+        blend_dst.set_blend_func(BlendFunc::OneMinusSrcAlpha);
+        self.blend(&keyed, &blend_dst)?;
+        // The scratch buffer is freed on return.
+        self.finish()
+    }
+}
+
+/// Byte offsets of the red, green, blue and (if present) alpha channels
+/// of an 8-bit-per-channel packed RGB format.
+fn channel_offsets(format: G2DFormat) -> Option<(usize, usize, usize, Option<usize>)> {
+    let (packed, len) = format.pack_rgba([1, 2, 3, 4])?;
+    if len < 3 {
+        return None;
+    }
+    let find = |channel: u8| packed[..len].iter().position(|&c| c == channel);
+    let alpha = format.alpha_offset();
+    Some((find(1)?, find(2)?, find(3)?, alpha))
+}
@@ -0,0 +1,273 @@
+// SPDX-FileCopyrightText: Copyright 2025 Au-Zone Technologies
+// SPDX-License-Identifier: Apache-2.0
+
+//! Plane offsets, row pitches and total buffer size of an image.
+//!
+//! [`G2DFormat::plane_layout`] is the one place that knows how each format
+//! splits into planes, how its chroma is subsampled and how row and height
+//! alignment pad it; buffer allocation and surface construction size
+//! images from it rather than from [`G2DFormat::bits_per_pixel`].
+
+use crate::{
+    g2d_format_G2D_I420, g2d_format_G2D_NV12, g2d_format_G2D_NV16, g2d_format_G2D_NV21,
+    g2d_format_G2D_NV61, g2d_format_G2D_UYVY, g2d_format_G2D_VYUY, g2d_format_G2D_YUYV,
+    g2d_format_G2D_YV12, g2d_format_G2D_YVYU, Error, G2DFormat, G2DSurface, Rect, Result,
+};
+
+/// Position and size of one plane within an image buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Plane {
+    /// Byte offset of the plane from the start of the buffer.
+    pub offset: usize,
+    /// Bytes from the start of one row to the next.
+    pub pitch: usize,
+    /// Number of rows, including height padding.
+    pub rows: usize,
+}
+
+impl Plane {
+    /// Size of the plane in bytes.
+    pub fn size(&self) -> usize {
+        self.pitch * self.rows
+    }
+}
+
+/// Planes of an image within one buffer, from [`G2DFormat::plane_layout`]
+/// or given explicitly with [`PlaneLayout::new`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlaneLayout {
+    planes: Vec<Plane>,
+    bytes_per_pixel: usize,
+}
+
+impl PlaneLayout {
+    /// Layout with explicit planes, such as the offsets and pitches a
+    /// video decoder reports for its output frames.
+    ///
+    /// `planes` must be in memory order with as many planes as `format`
+    /// has. libg2d takes a single stride and derives chroma pitches from
+    /// it, so each chroma pitch must be what it derives (the luma pitch for
+    /// NV12/NV16, half of it for I420/YV12) and chroma planes must cover
+    /// the subsampled luma rows; otherwise this fails with
+    /// [`Error::InvalidSurface`]. Offsets may leave gaps between planes.
+    pub fn new(format: G2DFormat, planes: Vec<Plane>) -> Result<Self> {
+        let shape = Shape::of(format)?;
+        let invalid = |why: String| {
+            Err(Error::InvalidSurface(format!(
+                "plane layout for g2d_format {}: {why}",
+                format.format()
+            )))
+        };
+        if planes.len() != 1 + shape.chroma.len() {
+            return invalid(format!(
+                "{} planes given, {} expected",
+                planes.len(),
+                1 + shape.chroma.len()
+            ));
+        }
+        let luma = planes[0];
+        if !luma.pitch.is_multiple_of(shape.bytes_per_pixel) {
+            return invalid(format!(
+                "pitch {} is not a whole number of {}-byte pixels",
+                luma.pitch, shape.bytes_per_pixel
+            ));
+        }
+        for (plane, &(pitch_div, row_div)) in planes[1..].iter().zip(shape.chroma) {
+            if !luma.pitch.is_multiple_of(pitch_div) || plane.pitch != luma.pitch / pitch_div {
+                return invalid(format!(
+                    "chroma pitch {} does not match luma pitch {}",
+                    plane.pitch, luma.pitch
+                ));
+            }
+            if plane.rows < luma.rows.div_ceil(row_div) {
+                return invalid(format!(
+                    "{} chroma rows cannot cover {} luma rows",
+                    plane.rows, luma.rows
+                ));
+            }
+        }
+        Ok(PlaneLayout {
+            planes,
+            bytes_per_pixel: shape.bytes_per_pixel,
+        })
+    }
+
+    /// Every plane in memory order: luma first for YUV formats, then
+    /// chroma (UV, or U then V for I420 and V then U for YV12).
+    pub fn planes(&self) -> &[Plane] {
+        &self.planes
+    }
+
+    /// Total buffer size in bytes: the end of the last plane.
+    pub fn size(&self) -> usize {
+        self.planes
+            .iter()
+            .map(|p| p.offset + p.size())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// `G2DSurface::stride` in pixels: the first plane's pitch over its
+    /// bytes per pixel.
+    pub fn stride(&self) -> usize {
+        self.planes[0].pitch / self.bytes_per_pixel
+    }
+}
+
+/// How a format splits into planes.
+struct Shape {
+    bytes_per_pixel: usize,
+    /// Horizontal granularity of a row in pixels (2 for chroma shared by
+    /// pixel pairs).
+    granule: usize,
+    /// Each chroma plane as (pitch divisor, row divisor) of the luma plane.
+    chroma: &'static [(usize, usize)],
+}
+
+impl Shape {
+    fn of(format: G2DFormat) -> Result<Self> {
+        let (bytes_per_pixel, granule, chroma): (usize, usize, &[(usize, usize)]) =
+            match format.format() {
+                g2d_format_G2D_NV12 | g2d_format_G2D_NV21 => (1, 2, &[(1, 2)]),
+                g2d_format_G2D_NV16 | g2d_format_G2D_NV61 => (1, 2, &[(1, 1)]),
+                g2d_format_G2D_I420 | g2d_format_G2D_YV12 => (1, 2, &[(2, 2), (2, 2)]),
+                g2d_format_G2D_YUYV | g2d_format_G2D_YVYU | g2d_format_G2D_UYVY
+                | g2d_format_G2D_VYUY => (2, 2, &[]),
+                _ => {
+                    let bits = format.bits_per_pixel().ok_or_else(|| {
+                        Error::InvalidFormat(format!(
+                            "cannot lay out an image of g2d_format {}",
+                            format.format()
+                        ))
+                    })?;
+                    (bits / 8, 1, &[])
+                }
+            };
+        Ok(Shape {
+            bytes_per_pixel,
+            granule,
+            chroma,
+        })
+    }
+}
+
+impl G2DFormat {
+    /// Lay out a `width`×`height` image of this format.
+    ///
+    /// The first plane's row pitch is padded to a multiple of
+    /// `stride_align` bytes, and its row count to a multiple of
+    /// `height_align`; `0` or `1` means no padding. Pass
+    /// [`required_stride_alignment()`](Self::required_stride_alignment) for
+    /// the pitch G2D expects. As with
+    /// [`aligned_stride()`](Self::aligned_stride), the pitch of a 3-byte
+    /// format stays a whole number of pixels.
+    ///
+    /// Chroma planes follow the luma plane with no gap. Their rows are the
+    /// padded luma rows divided by the vertical subsampling, rounded up, and
+    /// their pitch is the luma pitch (NV12/NV16 interleaved UV) or half of
+    /// it (I420/YV12). For formats with horizontal subsampling the luma
+    /// pitch is kept even so an odd width still has room for its last
+    /// chroma sample; packed 4:2:2 rows hold whole pixel pairs.
+    pub fn plane_layout(
+        &self,
+        width: usize,
+        height: usize,
+        stride_align: usize,
+        height_align: usize,
+    ) -> Result<PlaneLayout> {
+        let Shape {
+            bytes_per_pixel: bpp,
+            granule,
+            chroma,
+        } = Shape::of(*self)?;
+
+        let mut align = crate::stride::pixel_alignment(stride_align, bpp);
+        if granule > 1 && !align.is_multiple_of(2) {
+            align *= 2;
+        }
+        let too_large = || {
+            Error::InvalidSurface(format!(
+                "{width}x{height} g2d_format {} does not fit in memory",
+                self.0
+            ))
+        };
+        let pitch = width
+            .next_multiple_of(granule)
+            .checked_mul(bpp)
+            .and_then(|row| row.checked_next_multiple_of(align))
+            .ok_or_else(too_large)?;
+        let rows = height
+            .checked_next_multiple_of(height_align.max(1))
+            .ok_or_else(too_large)?;
+
+        let mut planes = vec![Plane {
+            offset: 0,
+            pitch,
+            rows,
+        }];
+        let mut offset = pitch.checked_mul(rows).ok_or_else(too_large)?;
+        for &(pitch_div, row_div) in chroma {
+            let plane = Plane {
+                offset,
+                pitch: pitch / pitch_div,
+                rows: rows.div_ceil(row_div),
+            };
+            offset = offset.checked_add(plane.size()).ok_or_else(too_large)?;
+            planes.push(plane);
+        }
+
+        Ok(PlaneLayout {
+            planes,
+            bytes_per_pixel: bpp,
+        })
+    }
+}
+
+impl G2DSurface {
+    /// Surface over an image of `format` whose planes are placed by `layout`
+    /// from `base_addr`, with `crop` as the region blits read or write.
+    ///
+    /// This is the way to import decoder output: build `layout` with
+    /// [`PlaneLayout::new`] from the offsets and pitches the decoder
+    /// reports, and pass the display rectangle as `crop`. The surface
+    /// covers the whole padded frame (`stride` pixels by the luma rows);
+    /// `crop` must lie inside it, or this fails with
+    /// [`Error::InvalidSurface`].
+    pub fn from_layout(
+        format: G2DFormat,
+        base_addr: u64,
+        layout: &PlaneLayout,
+        crop: Rect,
+    ) -> Result<Self> {
+        let layout = PlaneLayout::new(format, layout.planes.clone())?;
+        let width = layout.stride() as i32;
+        let height = layout.planes[0].rows as i32;
+        if crop.is_empty()
+            || crop.left < 0
+            || crop.top < 0
+            || crop.right > width
+            || crop.bottom > height
+        {
+            return Err(Error::InvalidSurface(format!(
+                "crop {crop:?} is outside the {width}x{height} frame"
+            )));
+        }
+
+        let mut planes = [0; 3];
+        for (address, plane) in planes.iter_mut().zip(layout.planes()) {
+            *address = base_addr + plane.offset as u64;
+        }
+        Ok(G2DSurface {
+            format: format.format(),
+            planes,
+            left: crop.left,
+            top: crop.top,
+            right: crop.right,
+            bottom: crop.bottom,
+            stride: width,
+            width,
+            height,
+            ..Default::default()
+        })
+    }
+}
@@ -12,27 +12,96 @@ include!("./ffi.rs");
 use four_char_code::{four_char_code, FourCharCode};
 use nix::ioctl_write_ptr;
 use std::{
+    cell::Cell,
     ffi::{c_char, CStr},
     fmt::Display,
     os::{
         fd::RawFd,
-        raw::{c_ulong, c_void},
+        raw::{c_int, c_ulong, c_void},
     },
     ptr::null_mut,
     rc::Rc,
+    time::{Duration, Instant},
 };
 
+pub mod batch;
+#[cfg(feature = "dma-heap")]
+pub mod bench;
+pub mod buffer;
+#[cfg(feature = "dma-heap")]
+pub mod buffer_pool;
+pub mod builder;
+mod clip;
+mod compose;
+pub mod conversion;
+mod csc;
+#[cfg(feature = "dma-heap")]
+pub mod display;
+#[cfg(feature = "dma-heap")]
+pub mod dma;
+pub mod fence;
+#[cfg(feature = "dma-heap")]
+mod fill;
+pub mod fourcc;
+#[cfg(feature = "dma-heap")]
+pub mod histogram;
+#[cfg(feature = "image")]
+pub mod io;
+#[cfg(feature = "dma-heap")]
+mod key;
+pub mod layout;
+pub mod metrics;
+#[cfg(feature = "mock")]
+pub mod mock;
+#[cfg(feature = "dma-heap")]
+pub mod multi;
+#[cfg(feature = "dma-heap")]
+pub mod options;
+pub mod pipeline;
+pub mod pixel;
+#[cfg(feature = "dma-heap")]
+pub mod plan;
+pub mod pool;
+#[cfg(feature = "dma-heap")]
+mod readback;
+#[cfg(feature = "dma-heap")]
+pub mod ring;
+#[cfg(feature = "dma-heap")]
+mod scale;
+#[cfg(feature = "dma-heap")]
+pub mod stream;
+mod stride;
+pub mod sync;
+#[cfg(feature = "dma-heap")]
+pub mod verify;
+#[cfg(feature = "dma-heap")]
+mod yuv;
+
+pub use sync::CacheSync;
+
 /// 8 bit grayscale, full range
-// pub const GREY: FourCharCode = four_char_code!("Y800");
+pub const GREY: FourCharCode = four_char_code!("Y800");
 pub const YUYV: FourCharCode = four_char_code!("YUYV");
 /// 8 bit interleaved YUV422 (V-Y-U-Y byte order)
 pub const VYUY: FourCharCode = four_char_code!("VYUY");
 pub const RGBA: FourCharCode = four_char_code!("RGBA");
 pub const RGB: FourCharCode = four_char_code!("RGB ");
 pub const NV12: FourCharCode = four_char_code!("NV12");
+/// 10 bit YUV 4:2:0 with 16-bit samples (Y plane, then interleaved UV).
+/// libg2d has no `g2d_format` for it, so [`G2DFormat::try_from`] rejects
+/// it with [`Error::Unsupported`]; reduce such frames to NV12 first.
+pub const P010: FourCharCode = four_char_code!("P010");
 
 const G2D_2_3_0: Version = Version::new(6, 4, 11, 1049711);
 
+/// The libg2d YUV matrix modes, of which at most one is in effect.
+const YUV_MATRIX_CAPS: [g2d_cap_mode; 4] = [
+    g2d_cap_mode_G2D_YUV_BT_601,
+    g2d_cap_mode_G2D_YUV_BT_709,
+    g2d_cap_mode_G2D_YUV_BT_601FR,
+    g2d_cap_mode_G2D_YUV_BT_709FR,
+];
+
 pub type Result<T, E = Error> = std::result::Result<T, E>;
 
 #[derive(Debug)]
@@ -40,6 +109,62 @@ pub enum Error {
     IoError(std::io::Error),
     LibraryError(libloading::Error),
     InvalidFormat(String),
+    InvalidSurface(String),
+    /// The format can only be read by G2D, not written (see
+    /// [`G2DFormat::is_valid_destination`]).
+    UnsupportedDestinationFormat(G2DFormat),
+    /// Mapping a freshly allocated DMA buffer into the process failed. The
+    /// buffer is released before this is returned. `source` keeps the OS
+    /// error: `ENOMEM` means the address space is exhausted (typical for
+    /// large buffers in 32-bit processes), `EACCES`/`EPERM` a permission
+    /// problem.
+    MmapFailed {
+        size: usize,
+        source: std::io::Error,
+    },
+    /// The DMA heap could not be opened. `source` is `NotFound` when the
+    /// heap does not exist on this system and `PermissionDenied` when the
+    /// device node is not accessible.
+    HeapUnavailable {
+        heap: String,
+        source: std::io::Error,
+    },
+    /// The DMA heap refused an allocation of `size` bytes. `ENOMEM` in
+    /// `source` means the heap is exhausted, so freeing buffers and
+    /// retrying can succeed.
+    AllocationFailed {
+        heap: String,
+        size: usize,
+        source: std::io::Error,
+    },
+    /// `DMA_BUF_IOCTL_PHYS` failed, typically because the file descriptor
+    /// is not a dma-buf or the buffer is not physically contiguous.
+    PhysicalAddress(std::io::Error),
+    /// The operation needs a hardware capability that libg2d does not
+    /// expose on this system.
+    Unsupported(String),
+    /// A [`FinishToken`] was used after more operations were queued on its
+    /// handle, so the buffer may still be being written.
+    NotFinished,
+    /// The [`G2D`] handle was closed with [`G2D::close`].
+    HandleClosed,
+    /// A blit between YUV and RGB was attempted under
+    /// [`ColorspacePolicy::Strict`] before a colorspace was configured.
+    ColorspaceNotSet,
+    /// A blit would scale an axis by more than [`MAX_SCALE_FACTOR`] up or
+    /// down, which G2D does not report but renders as garbage. `src` and
+    /// `dst` are the crops of the blit.
+    ScaleOutOfRange {
+        src: Rect,
+        dst: Rect,
+    },
+    /// A [`G2DPool`](pool::G2DPool) was built with a capacity of zero.
+    InvalidPoolCapacity,
+    /// All `capacity` contexts of a [`G2DPool`](pool::G2DPool) are checked
+    /// out; one must be dropped before another can be opened.
+    PoolExhausted {
+        capacity: usize,
+    },
 }
 
 impl std::fmt::Display for Error {
@@ -48,6 +173,45 @@ impl std::fmt::Display for Error {
             Error::IoError(e) => write!(f, "I/O error: {e}"),
             Error::LibraryError(e) => write!(f, "Library error: {e}"),
             Error::InvalidFormat(s) => write!(f, "Invalid format: {s}"),
+            Error::InvalidSurface(s) => write!(f, "Invalid surface: {s}"),
+            Error::UnsupportedDestinationFormat(fmt) => {
+                write!(f, "Unsupported destination format: {}", fmt.format())
+            }
+            Error::MmapFailed { size, source } => {
+                write!(f, "mmap of {size} byte DMA buffer failed: {source}")
+            }
+            Error::HeapUnavailable { heap, source } => {
+                write!(f, "Failed to open {heap} heap: {source}")
+            }
+            Error::AllocationFailed { heap, size, source } => {
+                write!(
+                    f,
+                    "Failed to allocate {size} bytes from {heap} heap: {source}"
+                )
+            }
+            Error::PhysicalAddress(e) => write!(f, "DMA_BUF_IOCTL_PHYS failed: {e}"),
+            Error::Unsupported(s) => write!(f, "Unsupported operation: {s}"),
+            Error::NotFinished => write!(
+                f,
+                "operations were queued after the finish token was issued"
+            ),
+            Error::HandleClosed => write!(f, "G2D handle is closed"),
+            Error::ColorspaceNotSet => write!(
+                f,
+                "YUV conversion attempted before a colorspace was configured"
+            ),
+            Error::ScaleOutOfRange { src, dst } => write!(
+                f,
+                "scaling a {}x{} crop to {}x{} exceeds the {MAX_SCALE_FACTOR}x G2D limit",
+                src.width(),
+                src.height(),
+                dst.width(),
+                dst.height()
+            ),
+            Error::InvalidPoolCapacity => write!(f, "G2D pool capacity must be at least 1"),
+            Error::PoolExhausted { capacity } => {
+                write!(f, "all {capacity} contexts of the G2D pool are in use")
+            }
         }
     }
 }
@@ -57,7 +221,20 @@ impl std::error::Error for Error {
         match self {
             Error::IoError(e) => Some(e),
             Error::LibraryError(e) => Some(e),
-            Error::InvalidFormat(_) => None,
+            Error::MmapFailed { source, .. } => Some(source),
+            Error::HeapUnavailable { source, .. } => Some(source),
+            Error::AllocationFailed { source, .. } => Some(source),
+            Error::PhysicalAddress(e) => Some(e),
+            Error::InvalidFormat(_)
+            | Error::InvalidSurface(_)
+            | Error::UnsupportedDestinationFormat(_)
+            | Error::Unsupported(_)
+            | Error::NotFinished
+            | Error::HandleClosed
+            | Error::ColorspaceNotSet
+            | Error::ScaleOutOfRange { .. }
+            | Error::InvalidPoolCapacity
+            | Error::PoolExhausted { .. } => None,
         }
     }
 }
@@ -79,7 +256,7 @@ pub struct G2DFormat(g2d_format);
 
 impl G2DFormat {
     /// Try to create a G2DFormat from a FourCharCode
-    /// Supported formats are RGB, RGBA, YUYV, NV12
+    /// Supported formats are RGB, RGBA, YUYV, VYUY, NV12, GREY
     pub fn try_from(fourcc: FourCharCode) -> Result<Self> {
         fourcc.try_into()
     }
@@ -88,6 +265,181 @@ impl G2DFormat {
     pub fn format(&self) -> g2d_format {
         self.0
     }
+
+    /// Bytes per pixel for single-plane packed formats, or `None` for
+    /// multi-plane YUV formats.
+    pub fn bytes_per_pixel(&self) -> Option<usize> {
+        match self.0 {
+            g2d_format_G2D_RGB565
+            | g2d_format_G2D_BGR565
+            | g2d_format_G2D_RGBA5551
+            | g2d_format_G2D_RGBX5551
+            | g2d_format_G2D_BGRA5551
+            | g2d_format_G2D_BGRX5551
+            | g2d_format_G2D_YUYV
+            | g2d_format_G2D_YVYU
+            | g2d_format_G2D_UYVY
+            | g2d_format_G2D_VYUY => Some(2),
+            g2d_format_G2D_RGB888 | g2d_format_G2D_BGR888 => Some(3),
+            g2d_format_G2D_RGBA8888
+            | g2d_format_G2D_RGBX8888
+            | g2d_format_G2D_BGRA8888
+            | g2d_format_G2D_BGRX8888
+            | g2d_format_G2D_ARGB8888
+            | g2d_format_G2D_ABGR8888
+            | g2d_format_G2D_XRGB8888
+            | g2d_format_G2D_XBGR8888
+            | g2d_format_G2D_RGBA1010102 => Some(4),
+            g2d_format_G2D_GRAY8 => Some(1),
+            _ => None,
+        }
+    }
+
+    /// Whether G2D can write this format as a `blit`/`clear` destination.
+    ///
+    /// Planar and semi-planar YUV, YVYU/VYUY, grayscale and 10-bit formats
+    /// are accepted as blit sources only. Note that `clear` is narrower
+    /// still; see [`is_clearable()`](Self::is_clearable).
+    pub fn is_valid_destination(&self) -> bool {
+        matches!(
+            self.0,
+            g2d_format_G2D_RGB565
+                | g2d_format_G2D_BGR565
+                | g2d_format_G2D_RGBA8888
+                | g2d_format_G2D_RGBX8888
+                | g2d_format_G2D_BGRA8888
+                | g2d_format_G2D_BGRX8888
+                | g2d_format_G2D_ARGB8888
+                | g2d_format_G2D_ABGR8888
+                | g2d_format_G2D_XRGB8888
+                | g2d_format_G2D_XBGR8888
+                | g2d_format_G2D_RGB888
+                | g2d_format_G2D_BGR888
+                | g2d_format_G2D_RGBA5551
+                | g2d_format_G2D_RGBX5551
+                | g2d_format_G2D_BGRA5551
+                | g2d_format_G2D_BGRX5551
+                | g2d_format_G2D_YUYV
+                | g2d_format_G2D_UYVY
+        )
+    }
+
+    /// Whether the driver's `g2d_clear` accepts this format: every valid
+    /// destination except the 3-byte RGB888/BGR888.
+    pub fn is_clearable(&self) -> bool {
+        self.is_valid_destination()
+            && !matches!(self.0, g2d_format_G2D_RGB888 | g2d_format_G2D_BGR888)
+    }
+
+    /// Whether pixels are stored as YUV, so a blit to or from an RGB format
+    /// goes through the YUV conversion matrix.
+    pub fn is_yuv(&self) -> bool {
+        matches!(
+            self.0,
+            g2d_format_G2D_YUYV
+                | g2d_format_G2D_YVYU
+                | g2d_format_G2D_UYVY
+                | g2d_format_G2D_VYUY
+                | g2d_format_G2D_NV12
+                | g2d_format_G2D_NV21
+                | g2d_format_G2D_NV16
+                | g2d_format_G2D_NV61
+                | g2d_format_G2D_I420
+                | g2d_format_G2D_YV12
+        )
+    }
+
+    /// Whether pixels carry an alpha channel. YUV, grayscale and the `X`
+    /// (padding) RGB formats do not, so converting into them discards alpha;
+    /// converting back yields opaque pixels.
+    pub fn has_alpha(&self) -> bool {
+        matches!(
+            self.0,
+            g2d_format_G2D_RGBA8888
+                | g2d_format_G2D_BGRA8888
+                | g2d_format_G2D_ARGB8888
+                | g2d_format_G2D_ABGR8888
+                | g2d_format_G2D_RGBA5551
+                | g2d_format_G2D_BGRA5551
+                | g2d_format_G2D_RGBA1010102
+        )
+    }
+
+    /// Byte offset of the 8-bit alpha channel within a packed pixel, or
+    /// `None` for formats without a byte-aligned alpha channel.
+    pub fn alpha_offset(&self) -> Option<usize> {
+        match self.0 {
+            g2d_format_G2D_RGBA8888 | g2d_format_G2D_BGRA8888 => Some(3),
+            g2d_format_G2D_ARGB8888 | g2d_format_G2D_ABGR8888 => Some(0),
+            _ => None,
+        }
+    }
+
+    /// Average bits per pixel across all planes, e.g. 12 for NV12 and 32
+    /// for RGBA8888.
+    pub fn bits_per_pixel(&self) -> Option<usize> {
+        if let Some(bpp) = self.bytes_per_pixel() {
+            return Some(bpp * 8);
+        }
+        match self.0 {
+            g2d_format_G2D_NV12 | g2d_format_G2D_NV21 | g2d_format_G2D_I420
+            | g2d_format_G2D_YV12 => Some(12),
+            g2d_format_G2D_NV16 | g2d_format_G2D_NV61 | g2d_format_G2D_GRAY10 => Some(16),
+            _ => None,
+        }
+    }
+
+    /// Pack an RGBA color into the in-memory byte order of this format.
+    ///
+    /// Returns the packed pixel and its length in bytes, or `None` for
+    /// formats that cannot be filled with a repeating RGB pixel (YUV, 5551,
+    /// 10-bit and grayscale formats).
+    pub fn pack_rgba(&self, color: [u8; 4]) -> Option<([u8; 4], usize)> {
+        let [r, g, b, a] = color;
+        let rgb565 = |hi: u8, lo: u8| {
+            let v = ((hi as u16 >> 3) << 11) | ((g as u16 >> 2) << 5) | (lo as u16 >> 3);
+            let [b0, b1] = v.to_le_bytes();
+            ([b0, b1, 0, 0], 2)
+        };
+        match self.0 {
+            g2d_format_G2D_RGBA8888 | g2d_format_G2D_RGBX8888 => Some(([r, g, b, a], 4)),
+            g2d_format_G2D_BGRA8888 | g2d_format_G2D_BGRX8888 => Some(([b, g, r, a], 4)),
+            g2d_format_G2D_ARGB8888 | g2d_format_G2D_XRGB8888 => Some(([a, r, g, b], 4)),
+            g2d_format_G2D_ABGR8888 | g2d_format_G2D_XBGR8888 => Some(([a, b, g, r], 4)),
+            g2d_format_G2D_RGB888 => Some(([r, g, b, 0], 3)),
+            g2d_format_G2D_BGR888 => Some(([b, g, r, 0], 3)),
+            g2d_format_G2D_RGB565 => Some(rgb565(r, b)),
+            g2d_format_G2D_BGR565 => Some(rgb565(b, r)),
+            _ => None,
+        }
+    }
+
+    /// The format whose in-memory byte order is this format's with the
+    /// bytes of each pixel reversed (each 16-bit unit for packed 4:2:2),
+    /// e.g. `ABGR8888` for `RGBA8888` and `UYVY` for `YUYV`.
+    ///
+    /// Returns `None` for formats whose swapped layout has no G2D format,
+    /// such as 565, 5551, 10-bit, grayscale and planar YUV.
+    pub fn byte_swapped(&self) -> Option<G2DFormat> {
+        let swapped = match self.0 {
+            g2d_format_G2D_RGBA8888 => g2d_format_G2D_ABGR8888,
+            g2d_format_G2D_ABGR8888 => g2d_format_G2D_RGBA8888,
+            g2d_format_G2D_BGRA8888 => g2d_format_G2D_ARGB8888,
+            g2d_format_G2D_ARGB8888 => g2d_format_G2D_BGRA8888,
+            g2d_format_G2D_RGBX8888 => g2d_format_G2D_XBGR8888,
+            g2d_format_G2D_XBGR8888 => g2d_format_G2D_RGBX8888,
+            g2d_format_G2D_BGRX8888 => g2d_format_G2D_XRGB8888,
+            g2d_format_G2D_XRGB8888 => g2d_format_G2D_BGRX8888,
+            g2d_format_G2D_RGB888 => g2d_format_G2D_BGR888,
+            g2d_format_G2D_BGR888 => g2d_format_G2D_RGB888,
+            g2d_format_G2D_YUYV => g2d_format_G2D_UYVY,
+            g2d_format_G2D_UYVY => g2d_format_G2D_YUYV,
+            g2d_format_G2D_YVYU => g2d_format_G2D_VYUY,
+            g2d_format_G2D_VYUY => g2d_format_G2D_YVYU,
+            _ => return None,
+        };
+        Some(G2DFormat(swapped))
+    }
 }
 
 impl TryFrom<FourCharCode> for G2DFormat {
@@ -100,7 +452,10 @@ impl TryFrom<FourCharCode> for G2DFormat {
             YUYV => Ok(G2DFormat(g2d_format_G2D_YUYV)),
             VYUY => Ok(G2DFormat(g2d_format_G2D_VYUY)),
             NV12 => Ok(G2DFormat(g2d_format_G2D_NV12)),
-            // GREY => Ok(G2DFormat(g2d_format_G2D_NV12)),
+            GREY => Ok(G2DFormat(g2d_format_G2D_GRAY8)),
+            P010 => Err(Error::Unsupported(
+                "libg2d has no 10-bit YUV format; P010 must be reduced to NV12 first".to_string(),
+            )),
             _ => Err(Error::InvalidFormat(format.to_string())),
         }
     }
@@ -110,7 +465,7 @@ impl TryFrom<G2DFormat> for FourCharCode {
     type Error = Error;
 
     /// Try to convert a G2DFormat to a FourCharCode
-    /// Supported formats are RGB, RGBA, YUYV, NV12
+    /// Supported formats are RGB, RGBA, YUYV, VYUY, NV12, GREY
     fn try_from(format: G2DFormat) -> Result<Self, Self::Error> {
         match format.0 {
             g2d_format_G2D_RGB888 => Ok(RGB),
@@ -118,6 +473,7 @@ impl TryFrom<G2DFormat> for FourCharCode {
             g2d_format_G2D_YUYV => Ok(YUYV),
             g2d_format_G2D_VYUY => Ok(VYUY),
             g2d_format_G2D_NV12 => Ok(NV12),
+            g2d_format_G2D_GRAY8 => Ok(GREY),
             _ => Err(Error::InvalidFormat(format!(
                 "Unsupported G2D format: {format:?}"
             ))),
@@ -129,12 +485,12 @@ impl TryFrom<G2DFormat> for FourCharCode {
 pub struct G2DPhysical(c_ulong);
 
 impl G2DPhysical {
+    /// Physical address of the dma-buf `fd`, from `DMA_BUF_IOCTL_PHYS`.
+    ///
+    /// Fails with [`Error::PhysicalAddress`] if the ioctl fails.
     pub fn new(fd: RawFd) -> Result<Self> {
         let phys = dma_buf_phys(0);
-        let err = unsafe { ioctl_dma_buf_phys(fd, &phys.0).unwrap_or(1) };
-        if err != 0 {
-            return Err(std::io::Error::last_os_error().into());
-        }
+        unsafe { ioctl_dma_buf_phys(fd, &phys.0) }.map_err(|e| Error::PhysicalAddress(e.into()))?;
 
         Ok(G2DPhysical(phys.0))
     }
@@ -192,7 +548,8 @@ impl Display for Version {
 }
 
 impl Version {
-    const fn new(major: i64, minor: i64, patch: i64, num: i64) -> Self {
+    /// Version `major.minor.patch` with build number `num`.
+    pub const fn new(major: i64, minor: i64, patch: i64, num: i64) -> Self {
         Version {
             major,
             minor,
@@ -200,6 +557,30 @@ impl Version {
             num,
         }
     }
+
+    /// Whether this is release `major.minor` or a later one, at any patch
+    /// level.
+    pub fn at_least(&self, major: i64, minor: i64) -> bool {
+        (self.major, self.minor) >= (major, minor)
+    }
+
+    /// Whether `g2d_clear` accepts the packed YUV 4:2:2 formats YUYV and
+    /// UYVY as targets.
+    ///
+    /// This is known for the 6.4 series (verified on 6.4.11, i.MX 8M Plus)
+    /// and later; older drivers are reported as lacking it since they have
+    /// not been tested.
+    pub fn supports_yuv_clear(&self) -> bool {
+        self.at_least(6, 4)
+    }
+
+    /// Whether `g2d_multi_blit` is known to work, for
+    /// [`G2D::multi_blit`]. As with
+    /// [`supports_yuv_clear()`](Self::supports_yuv_clear), this is the 6.4
+    /// series and later.
+    pub fn supports_multi_blit(&self) -> bool {
+        self.at_least(6, 4)
+    }
 }
 
 fn guess_version(g2d: &g2d) -> Option<Version> {
@@ -268,6 +649,718 @@ pub struct G2DSurface {
     pub rot: g2d_rotation,
 }
 
+/// Rectangle in surface pixel coordinates, using the same exclusive
+/// `right`/`bottom` convention as the `g2d_surface` crop fields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Rect {
+    pub left: i32,
+    pub top: i32,
+    pub right: i32,
+    pub bottom: i32,
+}
+
+impl Rect {
+    pub const fn new(left: i32, top: i32, right: i32, bottom: i32) -> Self {
+        Rect {
+            left,
+            top,
+            right,
+            bottom,
+        }
+    }
+
+    pub fn width(&self) -> i32 {
+        self.right - self.left
+    }
+
+    pub fn height(&self) -> i32 {
+        self.bottom - self.top
+    }
+
+    /// True if the rectangle covers no pixels.
+    pub fn is_empty(&self) -> bool {
+        self.right <= self.left || self.bottom <= self.top
+    }
+
+    /// Overlap of two rectangles, or `None` if they do not intersect.
+    pub fn intersect(&self, other: &Rect) -> Option<Rect> {
+        let rect = Rect::new(
+            self.left.max(other.left),
+            self.top.max(other.top),
+            self.right.min(other.right),
+            self.bottom.min(other.bottom),
+        );
+        (!rect.is_empty()).then_some(rect)
+    }
+
+    /// Largest rectangle with the aspect ratio `width`:`height` that fits
+    /// in this one, centered; the letterbox content area for a
+    /// `width`×`height` image.
+    ///
+    /// The scaled side is rounded to the nearest pixel. When the leftover
+    /// space is odd, the extra pixel goes to the right or bottom bar. A
+    /// matching aspect ratio returns this rectangle unchanged, and a
+    /// non-positive `width` or `height` an empty rectangle at its corner.
+    pub fn fit(&self, width: i32, height: i32) -> Rect {
+        if width <= 0 || height <= 0 {
+            return Rect::new(self.left, self.top, self.left, self.top);
+        }
+        let (outer_w, outer_h) = (self.width() as i64, self.height() as i64);
+        let (width, height) = (width as i64, height as i64);
+        let (fit_w, fit_h) = if width * outer_h > outer_w * height {
+            (outer_w, (outer_w * height + width / 2) / width)
+        } else {
+            ((outer_h * width + height / 2) / height, outer_h)
+        };
+        let left = self.left + ((outer_w - fit_w) / 2) as i32;
+        let top = self.top + ((outer_h - fit_h) / 2) as i32;
+        Rect::new(left, top, left + fit_w as i32, top + fit_h as i32)
+    }
+}
+
+/// Clockwise rotation applied by a blit, in 90° steps.
+///
+/// Converts into the raw `g2d_rotation` value for [`G2DSurface::rot`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Rotation {
+    #[default]
+    None,
+    Deg90,
+    Deg180,
+    Deg270,
+}
+
+impl Rotation {
+    /// Rotation from an angle in degrees; must be a multiple of 90.
+    pub fn from_degrees(degrees: i32) -> Option<Self> {
+        match degrees.rem_euclid(360) {
+            0 => Some(Rotation::None),
+            90 => Some(Rotation::Deg90),
+            180 => Some(Rotation::Deg180),
+            270 => Some(Rotation::Deg270),
+            _ => None,
+        }
+    }
+
+    /// Clockwise angle in degrees (0, 90, 180 or 270).
+    pub fn degrees(self) -> i32 {
+        match self {
+            Rotation::None => 0,
+            Rotation::Deg90 => 90,
+            Rotation::Deg180 => 180,
+            Rotation::Deg270 => 270,
+        }
+    }
+
+    /// Content rotation needed for a display physically mounted at `mount`.
+    ///
+    /// A panel mounted rotated 90° clockwise shows the framebuffer rotated
+    /// 90° clockwise, so the content must be rotated the opposite way to
+    /// appear upright: a 90° mount needs a 270° blit and vice versa, while
+    /// 0° and 180° map to themselves.
+    pub fn for_display_mount(mount: Rotation) -> Rotation {
+        match mount {
+            Rotation::None => Rotation::None,
+            Rotation::Deg90 => Rotation::Deg270,
+            Rotation::Deg180 => Rotation::Deg180,
+            Rotation::Deg270 => Rotation::Deg90,
+        }
+    }
+}
+
+impl From<Rotation> for g2d_rotation {
+    fn from(rotation: Rotation) -> Self {
+        match rotation {
+            Rotation::None => g2d_rotation_G2D_ROTATION_0,
+            Rotation::Deg90 => g2d_rotation_G2D_ROTATION_90,
+            Rotation::Deg180 => g2d_rotation_G2D_ROTATION_180,
+            Rotation::Deg270 => g2d_rotation_G2D_ROTATION_270,
+        }
+    }
+}
+
+impl TryFrom<g2d_rotation> for Rotation {
+    type Error = Error;
+
+    /// Fails for the flip values, which are not rotations.
+    fn try_from(rot: g2d_rotation) -> Result<Self> {
+        match Transform::try_from(rot)? {
+            Transform::Rotate(rotation) => Ok(rotation),
+            Transform::Flip(flip) => Err(Error::InvalidSurface(format!(
+                "g2d_rotation {rot} is a {flip:?} flip, not a rotation"
+            ))),
+        }
+    }
+}
+
+/// Mirror applied by a blit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flip {
+    /// Mirror left-right (`G2D_FLIP_H`).
+    Horizontal,
+    /// Mirror top-bottom (`G2D_FLIP_V`).
+    Vertical,
+    /// Mirror both ways. libg2d has no combined flip; this is the same
+    /// image as a 180° rotation and is sent, and read back from `rot`, as
+    /// [`Rotation::Deg180`].
+    Both,
+}
+
+impl From<Flip> for g2d_rotation {
+    fn from(flip: Flip) -> Self {
+        match flip {
+            Flip::Horizontal => g2d_rotation_G2D_FLIP_H,
+            Flip::Vertical => g2d_rotation_G2D_FLIP_V,
+            Flip::Both => g2d_rotation_G2D_ROTATION_180,
+        }
+    }
+}
+
+/// Any value of the destination `rot` field: a rotation or a flip.
+///
+/// libg2d takes a single `g2d_rotation` per surface, so a rotation and a
+/// flip cannot be combined in one blit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transform {
+    Rotate(Rotation),
+    Flip(Flip),
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Transform::Rotate(Rotation::None)
+    }
+}
+
+impl From<Rotation> for Transform {
+    fn from(rotation: Rotation) -> Self {
+        Transform::Rotate(rotation)
+    }
+}
+
+impl From<Flip> for Transform {
+    fn from(flip: Flip) -> Self {
+        Transform::Flip(flip)
+    }
+}
+
+impl From<Transform> for g2d_rotation {
+    fn from(transform: Transform) -> Self {
+        match transform {
+            Transform::Rotate(rotation) => rotation.into(),
+            Transform::Flip(flip) => flip.into(),
+        }
+    }
+}
+
+impl TryFrom<g2d_rotation> for Transform {
+    type Error = Error;
+
+    fn try_from(rot: g2d_rotation) -> Result<Self> {
+        match rot {
+            g2d_rotation_G2D_ROTATION_0 => Ok(Rotation::None.into()),
+            g2d_rotation_G2D_ROTATION_90 => Ok(Rotation::Deg90.into()),
+            g2d_rotation_G2D_ROTATION_180 => Ok(Rotation::Deg180.into()),
+            g2d_rotation_G2D_ROTATION_270 => Ok(Rotation::Deg270.into()),
+            g2d_rotation_G2D_FLIP_H => Ok(Flip::Horizontal.into()),
+            g2d_rotation_G2D_FLIP_V => Ok(Flip::Vertical.into()),
+            _ => Err(Error::InvalidSurface(format!("unknown g2d_rotation {rot}"))),
+        }
+    }
+}
+
+/// Alpha blend factor of a surface, set with [`G2DSurface::set_blend_func`]
+/// and applied by [`G2D::blend`].
+///
+/// The source surface's factor scales the source pixel and the
+/// destination surface's factor scales the pixel already there; source-over
+/// compositing is `SrcAlpha` on the source and `OneMinusSrcAlpha` on the
+/// destination. Converts into the raw `g2d_blend_func` value for
+/// [`G2DSurface::blendfunc`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BlendFunc {
+    #[default]
+    Zero,
+    One,
+    SrcAlpha,
+    OneMinusSrcAlpha,
+    DstAlpha,
+    OneMinusDstAlpha,
+}
+
+impl From<BlendFunc> for g2d_blend_func {
+    fn from(func: BlendFunc) -> Self {
+        match func {
+            BlendFunc::Zero => g2d_blend_func_G2D_ZERO,
+            BlendFunc::One => g2d_blend_func_G2D_ONE,
+            BlendFunc::SrcAlpha => g2d_blend_func_G2D_SRC_ALPHA,
+            BlendFunc::OneMinusSrcAlpha => g2d_blend_func_G2D_ONE_MINUS_SRC_ALPHA,
+            BlendFunc::DstAlpha => g2d_blend_func_G2D_DST_ALPHA,
+            BlendFunc::OneMinusDstAlpha => g2d_blend_func_G2D_ONE_MINUS_DST_ALPHA,
+        }
+    }
+}
+
+impl TryFrom<g2d_blend_func> for BlendFunc {
+    type Error = Error;
+
+    /// Fails for values with the `G2D_PRE_MULTIPLIED_ALPHA` or
+    /// `G2D_DEMULTIPLY_OUT_ALPHA` flags set, which are not plain factors.
+    fn try_from(func: g2d_blend_func) -> Result<Self> {
+        match func {
+            g2d_blend_func_G2D_ZERO => Ok(BlendFunc::Zero),
+            g2d_blend_func_G2D_ONE => Ok(BlendFunc::One),
+            g2d_blend_func_G2D_SRC_ALPHA => Ok(BlendFunc::SrcAlpha),
+            g2d_blend_func_G2D_ONE_MINUS_SRC_ALPHA => Ok(BlendFunc::OneMinusSrcAlpha),
+            g2d_blend_func_G2D_DST_ALPHA => Ok(BlendFunc::DstAlpha),
+            g2d_blend_func_G2D_ONE_MINUS_DST_ALPHA => Ok(BlendFunc::OneMinusDstAlpha),
+            _ => Err(Error::InvalidSurface(format!(
+                "unknown g2d_blend_func {func}"
+            ))),
+        }
+    }
+}
+
+/// YUV matrix used when a blit converts between YUV and RGB.
+///
+/// The matrix is applied with a [`Range`], limited unless set otherwise
+/// with [`G2D::set_colorspace`]; limited-range [`Colorspace::Bt601`] is
+/// what a freshly opened handle uses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Colorspace {
+    /// ITU-R BT.601, standard-definition video.
+    #[default]
+    Bt601,
+    /// ITU-R BT.709, high-definition video.
+    Bt709,
+    /// ITU-R BT.2020, UHD and HDR video. libg2d has no BT.2020 matrix, so
+    /// selecting it fails with [`Error::Unsupported`].
+    Bt2020,
+}
+
+/// Quantization range of the YUV side of a conversion.
+///
+/// The range is part of the YUV matrix, so it only affects blits between
+/// YUV and RGB; RGB to RGB blits copy values unchanged whatever the range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Range {
+    /// Video range: Y in 16–235, chroma in 16–240.
+    #[default]
+    Limited,
+    /// Full range: Y and chroma in 0–255, as from JPEG and many cameras.
+    Full,
+}
+
+/// Byte order of a surface's pixel data relative to its format, set with
+/// [`G2DSurface::with_byte_order`].
+///
+/// libg2d has no byte-swap control, so `Swapped` relabels the surface with
+/// the format whose byte order matches the swapped data (see
+/// [`G2DFormat::byte_swapped`]). Framegrabbers and network sources that
+/// deliver big-endian words produce such data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ByteOrder {
+    /// Pixels are stored in the format's own byte order.
+    #[default]
+    Native,
+    /// The bytes of each pixel (each 16-bit unit for packed 4:2:2) are
+    /// reversed.
+    Swapped,
+}
+
+/// How [`G2D::blit`] treats crop edges that fall between chroma samples of
+/// a subsampled YUV surface (odd x for 4:2:2 and 4:2:0, odd y for 4:2:0).
+///
+/// Such a crop starts or ends halfway through a chroma sample, which the
+/// hardware resolves by shifting chroma against luma.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CropAlignment {
+    /// Move each misaligned edge down to the previous chroma sample.
+    Floor,
+    /// Move each misaligned edge up to the next chroma sample, or down if
+    /// that would pass the surface edge.
+    Round,
+    /// Fail the blit with [`Error::InvalidSurface`].
+    #[default]
+    Reject,
+}
+
+/// What [`G2D::blit`] does with a conversion between YUV and RGB on a handle
+/// whose colorspace has not been configured, set with
+/// [`G2D::set_colorspace_policy`].
+///
+/// A colorspace counts as configured once it has been set through
+/// [`G2D::set_colorspace`] or its BT.601/BT.709 shorthands,
+/// [`G2D::convert_csc`], [`G2D::reset_state`], a
+/// [`G2DBuilder`](crate::builder::G2DBuilder) or by enabling a YUV matrix
+/// [`Feature`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorspacePolicy {
+    /// Fail the blit with [`Error::ColorspaceNotSet`], so a missing
+    /// colorspace shows up as an error instead of wrong colors.
+    Strict,
+    /// Apply this colorspace before the first such blit.
+    Lenient(Colorspace),
+}
+
+impl Default for ColorspacePolicy {
+    /// Lenient with BT.601, the matrix libg2d uses anyway, so blits behave
+    /// as on a fresh libg2d handle.
+    fn default() -> Self {
+        ColorspacePolicy::Lenient(Colorspace::Bt601)
+    }
+}
+
+/// Driver state toggled with [`G2D::enable`] and [`G2D::disable`], one per
+/// `g2d_cap_mode`.
+///
+/// The state belongs to the handle and applies to every later operation on
+/// it until changed; [`G2D::reset_state`] turns blending, global alpha and
+/// dithering back off. The YUV matrix modes are exclusive and better set
+/// through [`G2DBuilder::colorspace`](crate::builder::G2DBuilder::colorspace).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Feature {
+    /// Blend blits using the surfaces' `blendfunc` factors (`G2D_BLEND`).
+    Blend,
+    /// Dither when converting to lower bit-depth formats (`G2D_DITHER`).
+    Dither,
+    /// Scale source alpha by the source `global_alpha` (`G2D_GLOBAL_ALPHA`).
+    GlobalAlpha,
+    /// Dim the destination while blending (`G2D_BLEND_DIM`).
+    BlendDim,
+    /// `G2D_BLUR`.
+    Blur,
+    /// Limited-range BT.601 YUV matrix (`G2D_YUV_BT_601`).
+    YuvBt601,
+    /// Limited-range BT.709 YUV matrix (`G2D_YUV_BT_709`).
+    YuvBt709,
+    /// Full-range BT.601 YUV matrix (`G2D_YUV_BT_601FR`).
+    YuvBt601FullRange,
+    /// Full-range BT.709 YUV matrix (`G2D_YUV_BT_709FR`).
+    YuvBt709FullRange,
+    /// `G2D_WARPING`.
+    Warping,
+}
+
+impl From<Feature> for g2d_cap_mode {
+    fn from(feature: Feature) -> Self {
+        match feature {
+            Feature::Blend => g2d_cap_mode_G2D_BLEND,
+            Feature::Dither => g2d_cap_mode_G2D_DITHER,
+            Feature::GlobalAlpha => g2d_cap_mode_G2D_GLOBAL_ALPHA,
+            Feature::BlendDim => g2d_cap_mode_G2D_BLEND_DIM,
+            Feature::Blur => g2d_cap_mode_G2D_BLUR,
+            Feature::YuvBt601 => g2d_cap_mode_G2D_YUV_BT_601,
+            Feature::YuvBt709 => g2d_cap_mode_G2D_YUV_BT_709,
+            Feature::YuvBt601FullRange => g2d_cap_mode_G2D_YUV_BT_601FR,
+            Feature::YuvBt709FullRange => g2d_cap_mode_G2D_YUV_BT_709FR,
+            Feature::Warping => g2d_cap_mode_G2D_WARPING,
+        }
+    }
+}
+
+/// What a [`G2D`] handle can do, from [`G2D::capabilities`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Capabilities {
+    /// [`Feature::Blend`] is accepted (see [`G2D::query_cap`]).
+    pub blend: bool,
+    /// [`Feature::Dither`] is accepted.
+    pub dither: bool,
+    /// [`Feature::GlobalAlpha`] is accepted.
+    pub global_alpha: bool,
+    /// YUV sources can be read (the `G2D_SRC_YUV` hardware feature).
+    pub yuv_input: bool,
+    /// YUV destinations can be written (the `G2D_DST_YUV` hardware feature).
+    pub yuv_output: bool,
+    /// Blits can scale (the `G2D_SCALING` hardware feature), which
+    /// `Deinterlace::Bob` needs.
+    pub scaling: bool,
+}
+
+/// Scheduling priority hint for work submitted on a [`G2D`] handle, set
+/// with [`G2D::set_priority`].
+///
+/// libg2d exposes no scheduling priority: work from every context shares
+/// one GPU queue in submission order, and a queued blit cannot preempt one
+/// already running. The only effect available is when work is submitted,
+/// so `High` flushes each clear and blit to the GPU as soon as it is
+/// queued instead of at the next [`G2D::flush`] or [`G2D::finish`], and
+/// `Low` behaves like `Normal`. Keeping latency-critical display blits on
+/// their own handle at `High` still gets them to the GPU ahead of work that
+/// other handles have queued but not yet flushed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Priority {
+    Low,
+    #[default]
+    Normal,
+    High,
+}
+
+/// How a [`G2D`] handle waits for its work to complete, set with
+/// [`G2D::set_wait_mode`].
+///
+/// libg2d offers no way to query completion, so plain [`G2D::finish`]
+/// always blocks in `g2d_finish`. The mode applies where the crate has a
+/// fence to wait on: [`G2D::finish_for`] and [`fence::wait_with`].
+///
+/// `Block` sleeps in `poll` until the fence signals, freeing the core for
+/// other work at the cost of a wakeup (typically tens of microseconds, more
+/// under load). `Spin` polls the fence with a zero timeout in a loop, which
+/// notices completion within a poll call but keeps a core busy for the
+/// whole blit; use it only for short jobs on latency-critical paths.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WaitMode {
+    #[default]
+    Block,
+    Spin,
+}
+
+/// Engine a [`G2D`] handle submits to, selected with [`G2D::make_current`].
+///
+/// Which engines exist depends on the SoC; query them with
+/// [`G2D::core_available`]. A newly opened handle uses the GPU 2D core.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Core {
+    /// GPU 2D core.
+    #[default]
+    Gpu2d,
+    /// Display processing unit blit engine.
+    Dpu,
+    /// Pixel pipeline, a low-power engine for simple conversions and fills.
+    Pxp,
+}
+
+impl Core {
+    /// Every core, in declaration order.
+    pub const ALL: [Core; 3] = [Core::Gpu2d, Core::Dpu, Core::Pxp];
+
+    /// `g2d_hardware_type`s naming this core, newest revision first.
+    fn hardware_types(self) -> &'static [g2d_hardware_type] {
+        match self {
+            Core::Gpu2d => &[g2d_hardware_type_G2D_HARDWARE_2D],
+            Core::Dpu => &[
+                g2d_hardware_type_G2D_HARDWARE_DPU_V2,
+                g2d_hardware_type_G2D_HARDWARE_DPU_V1,
+            ],
+            Core::Pxp => &[
+                g2d_hardware_type_G2D_HARDWARE_PXP_V2,
+                g2d_hardware_type_G2D_HARDWARE_PXP_V1,
+            ],
+        }
+    }
+}
+
+impl G2DSurface {
+    /// Full-frame I420 surface over a contiguous buffer at `addr`: Y plane,
+    /// then the U plane, then the V plane, each chroma plane subsampled 2×2.
+    ///
+    /// Planes are placed as by [`G2DFormat::plane_layout`]: the luma stride
+    /// is `width` rounded up to even and the chroma stride half of it, and
+    /// an odd `height` rounds the chroma rows up, so odd dimensions keep
+    /// their last chroma sample.
+    pub fn i420(addr: c_ulong, width: i32, height: i32) -> Self {
+        Self::tri_planar(g2d_format_G2D_I420, addr, width, height)
+    }
+
+    /// Full-frame I420 surface with its Y, U and V planes at the given
+    /// addresses, for buffers whose planes are not contiguous or are padded.
+    ///
+    /// `stride` is the luma row pitch in bytes and should be even and at
+    /// least `width`; libg2d reads chroma rows at `stride / 2`.
+    pub fn i420_planes(
+        y_addr: c_ulong,
+        u_addr: c_ulong,
+        v_addr: c_ulong,
+        width: i32,
+        height: i32,
+        stride: i32,
+    ) -> Self {
+        Self::planar(
+            g2d_format_G2D_I420,
+            [y_addr, u_addr, v_addr],
+            width,
+            height,
+            stride,
+        )
+    }
+
+    /// Full-frame YV12 surface over a contiguous buffer at `addr`: Y plane,
+    /// then the **V** plane, then the U plane.
+    ///
+    /// The plane addresses are the same as [`i420()`](Self::i420) for the
+    /// same buffer: `planes[1]`/`planes[2]` are the second and third planes
+    /// in memory, and the `G2D_YV12` format tells libg2d they hold V and U.
+    /// Tagging a YV12 buffer as I420 (or vice versa) swaps the chroma.
+    pub fn yv12(addr: c_ulong, width: i32, height: i32) -> Self {
+        Self::tri_planar(g2d_format_G2D_YV12, addr, width, height)
+    }
+
+    /// Full-frame YV12 surface with its Y, U and V planes at the given
+    /// addresses, as for [`i420_planes()`](Self::i420_planes).
+    ///
+    /// The V address goes into `planes[1]` and the U address into
+    /// `planes[2]`, the order the `G2D_YV12` format tells libg2d to read.
+    pub fn yv12_planes(
+        y_addr: c_ulong,
+        u_addr: c_ulong,
+        v_addr: c_ulong,
+        width: i32,
+        height: i32,
+        stride: i32,
+    ) -> Self {
+        Self::planar(
+            g2d_format_G2D_YV12,
+            [y_addr, v_addr, u_addr],
+            width,
+            height,
+            stride,
+        )
+    }
+
+    /// Full-frame NV12 surface with the Y plane at `y_addr` and the
+    /// interleaved UV plane at `uv_addr`, both with rows `stride` bytes
+    /// apart.
+    ///
+    /// Decoders and cameras commonly pad rows past `width` and start the UV
+    /// plane at an aligned offset rather than right after the last Y row,
+    /// so both addresses are taken as given; `planes[1]` is `uv_addr`.
+    /// `width` and `height` should be even, and `stride` at least `width`.
+    ///
+    /// Both planes must be linear. libg2d has no tiled `g2d_format`: tiled
+    /// layouts such as the Amphion NV12 of the i.MX 8 video decoder are
+    /// described by a `g2d_surfaceEx` tiling field and blitted with
+    /// `g2d_blitEx`, neither of which is in the bindings this crate loads.
+    /// Have the decoder output linear frames instead.
+    pub fn nv12(y_addr: c_ulong, uv_addr: c_ulong, width: i32, height: i32, stride: i32) -> Self {
+        G2DSurface {
+            format: g2d_format_G2D_NV12,
+            planes: [y_addr, uv_addr, 0],
+            right: width,
+            bottom: height,
+            stride,
+            width,
+            height,
+            ..Default::default()
+        }
+    }
+
+    fn tri_planar(format: g2d_format, addr: c_ulong, width: i32, height: i32) -> Self {
+        let stride = width + width % 2;
+        let luma = stride as c_ulong * height as c_ulong;
+        let chroma = (stride / 2) as c_ulong * ((height + 1) / 2) as c_ulong;
+        Self::planar(
+            format,
+            [addr, addr + luma, addr + luma + chroma],
+            width,
+            height,
+            stride,
+        )
+    }
+
+    fn planar(
+        format: g2d_format,
+        planes: [c_ulong; 3],
+        width: i32,
+        height: i32,
+        stride: i32,
+    ) -> Self {
+        G2DSurface {
+            format,
+            planes,
+            right: width,
+            bottom: height,
+            stride,
+            width,
+            height,
+            ..Default::default()
+        }
+    }
+
+    /// Pixel format of the surface.
+    pub fn format(&self) -> G2DFormat {
+        G2DFormat(self.format)
+    }
+
+    /// Region of interest used by `blit`/`clear` (`left`/`top`/`right`/`bottom`).
+    pub fn crop_rect(&self) -> Rect {
+        Rect::new(self.left, self.top, self.right, self.bottom)
+    }
+
+    /// Surface `(width, height)` in pixels.
+    pub fn dimensions(&self) -> (i32, i32) {
+        (self.width, self.height)
+    }
+
+    /// Buffer stride in pixels.
+    pub fn stride(&self) -> i32 {
+        self.stride
+    }
+
+    /// Raw `g2d_rotation` value applied when this surface is a blit destination.
+    pub fn rotation(&self) -> g2d_rotation {
+        self.rot
+    }
+
+    /// The `rot` field as a [`Transform`], or `None` for an unknown value.
+    pub fn transform(&self) -> Option<Transform> {
+        Transform::try_from(self.rot).ok()
+    }
+
+    /// Set the rotation or flip applied when this surface is a blit
+    /// destination.
+    pub fn set_transform(&mut self, transform: impl Into<Transform>) {
+        self.rot = transform.into().into();
+    }
+
+    /// This surface with `rotation` applied when it is a blit destination.
+    ///
+    /// For [`Rotation::Deg90`] and [`Rotation::Deg270`] the surface and
+    /// crop are given in rotated orientation, with width and height swapped
+    /// relative to the source; [`G2D::blit`] rejects an unswapped
+    /// non-square crop.
+    pub fn with_rotation(mut self, rotation: Rotation) -> Self {
+        self.set_transform(rotation);
+        self
+    }
+
+    /// This surface with its data read or written in `order`.
+    ///
+    /// `Native` returns the surface unchanged. `Swapped` replaces the
+    /// format with its [`byte_swapped()`](G2DFormat::byte_swapped)
+    /// counterpart, failing with [`Error::Unsupported`] for formats that
+    /// have none. The order is relative to the current format, so swapping
+    /// twice restores it.
+    pub fn with_byte_order(mut self, order: ByteOrder) -> Result<Self> {
+        if order == ByteOrder::Swapped {
+            let swapped = self.format().byte_swapped().ok_or_else(|| {
+                Error::Unsupported(format!(
+                    "g2d_format {} has no byte-swapped equivalent",
+                    self.format
+                ))
+            })?;
+            self.format = swapped.format();
+        }
+        Ok(self)
+    }
+
+    /// The `blendfunc` field as a [`BlendFunc`], or `None` for a value
+    /// with alpha flags set or an unknown value.
+    pub fn blend_func(&self) -> Option<BlendFunc> {
+        BlendFunc::try_from(self.blendfunc).ok()
+    }
+
+    /// Set the factor this surface contributes when blended by
+    /// [`G2D::blend`].
+    pub fn set_blend_func(&mut self, func: BlendFunc) {
+        self.blendfunc = func.into();
+    }
+
+    /// Physical plane addresses.
+    pub fn planes(&self) -> [c_ulong; 3] {
+        self.planes
+    }
+}
+
 impl Default for G2DSurface {
     fn default() -> Self {
         G2DSurface {
@@ -355,11 +1448,161 @@ impl From<&G2DSurface> for G2DSurfaceLegacy {
     }
 }
 
+/// Most layers [`G2D::multi_blit`] submits in one call.
+pub const MAX_MULTI_BLIT_LAYERS: usize = 8;
+
+/// Largest factor a blit may scale an axis by, up or down; see
+/// [`Error::ScaleOutOfRange`].
+pub const MAX_SCALE_FACTOR: i32 = 16;
+
+/// `g2d_surface_pair` over either surface layout.
+#[repr(C)]
+struct SurfacePair<S> {
+    s: S,
+    d: S,
+}
+
+/// Call `g2d_multi_blit` on `pairs`, which must be in the layout the loaded
+/// library expects.
+unsafe fn multi_blit_pairs<S>(
+    lib: &g2d,
+    handle: *mut c_void,
+    pairs: &mut [SurfacePair<S>],
+) -> c_int {
+    let mut pointers: Vec<*mut g2d_surface_pair> = pairs
+        .iter_mut()
+        .map(|pair| pair as *mut SurfacePair<S> as *mut g2d_surface_pair)
+        .collect();
+    lib.g2d_multi_blit(handle, pointers.as_mut_ptr(), pointers.len() as c_int)
+}
+
+/// Time spent in each phase of opening a [`G2D`] handle, as measured by
+/// [`G2D::new_timed`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OpenTimings {
+    /// `dlopen` of the library, including its dependencies and constructors.
+    pub dlopen: Duration,
+    /// Resolving the `g2d_*` entry points with `dlsym`.
+    pub symbols: Duration,
+    /// `g2d_open` (GPU context creation) and version detection.
+    pub context: Duration,
+}
+
+impl OpenTimings {
+    /// Sum of all phases.
+    pub fn total(&self) -> Duration {
+        self.dlopen + self.symbols + self.context
+    }
+}
+
+/// Proof that every operation queued on a [`G2D`] handle up to some point
+/// has completed, returned by [`G2D::finish_token`].
+///
+/// The token goes stale as soon as another clear or blit is queued on the
+/// handle, whichever buffer it targets.
+#[derive(Debug, Clone, Copy)]
+pub struct FinishToken<'a> {
+    g2d: &'a G2D,
+    queued: u64,
+}
+
+impl FinishToken<'_> {
+    /// Whether no operation has been queued since the token was issued.
+    pub fn is_current(&self) -> bool {
+        self.g2d.queued.get() == self.queued
+    }
+}
+
+/// A blit submitted by [`G2D::blit_cancellable`].
+///
+/// libg2d cannot abort work once it has been submitted, so
+/// [`cancel()`](Self::cancel) is currently a no-op that reports `false`
+/// and the blit always runs to completion. Call [`wait()`](Self::wait)
+/// either way before reading the destination.
+#[derive(Debug)]
+pub struct BlitHandle<'a> {
+    g2d: &'a G2D,
+}
+
+impl<'a> BlitHandle<'a> {
+    /// Request that the blit be aborted. Returns whether it was; always
+    /// `false`, as libg2d has no submission cancellation.
+    pub fn cancel(&self) -> bool {
+        false
+    }
+
+    /// Wait for the blit, and every operation queued before it, to complete.
+    pub fn wait(self) -> Result<FinishToken<'a>> {
+        self.g2d.finish_token()
+    }
+}
+
+/// An open libg2d context.
+///
+/// # Shared state
+///
+/// Driver state set through a handle (the YUV colorspace, blending,
+/// dithering and the other `g2d_cap_mode`s) lives in the libg2d context,
+/// not in the Rust value. `G2D` is therefore not `Clone`: a clone would
+/// either alias the context, making state changes leak between supposedly
+/// separate handles, or open a fresh one that silently starts from driver
+/// defaults. Every reference to one `G2D` (`&G2D`, `Rc<G2D>`) observes
+/// changes made through any other, while separately opened handles,
+/// including the contexts of a [`G2DPool`](crate::pool::G2DPool), have
+/// independent state.
+///
+/// ```compile_fail
+/// fn assert_clone<T: Clone>() {}
+/// assert_clone::<g2d_sys::G2D>();
+/// ```
+///
+/// # Threads
+///
+/// A handle stays on the thread that opened it: `G2D` is neither `Send`
+/// nor `Sync`, because libg2d contexts are not safe to use from several
+/// threads and the handle's bookkeeping is unsynchronized. To blit from
+/// several threads, open one handle on each, or share a
+/// [`G2DPool`](crate::pool::G2DPool) and take one context from it on each;
+/// separately opened handles may submit work concurrently, each to its own
+/// buffers.
+///
+/// ```compile_fail
+/// fn assert_send<T: Send>() {}
+/// assert_send::<g2d_sys::G2D>();
+/// ```
+///
+/// ```compile_fail
+/// fn assert_sync<T: Sync>() {}
+/// assert_sync::<g2d_sys::G2D>();
+/// ```
 #[derive(Debug)]
 pub struct G2D {
     pub lib: Rc<g2d>,
     pub handle: *mut c_void,
     pub version: Version,
+    /// Pixel count below which `clear_auto` fills on the CPU; `None` until
+    /// set explicitly or calibrated on first use.
+    #[cfg(feature = "dma-heap")]
+    cpu_clear_threshold: Cell<Option<usize>>,
+    /// Memory bandwidth budget in bytes/sec, if configured.
+    bandwidth_budget: Cell<Option<u64>>,
+    /// Number of clears and blits queued on this handle so far.
+    queued: Cell<u64>,
+    /// Policy for crops misaligned with chroma subsampling.
+    crop_alignment: Cell<CropAlignment>,
+    /// Scheduling hint for clears and blits.
+    priority: Cell<Priority>,
+    /// How fence waits on this handle wait.
+    wait_mode: Cell<WaitMode>,
+    /// Whether a YUV matrix has been selected on this handle.
+    colorspace_set: Cell<bool>,
+    /// What a YUV conversion does before one has.
+    colorspace_policy: Cell<ColorspacePolicy>,
+    /// Blits that dropped the source alpha without acknowledgement.
+    alpha_loss_blits: Cell<u64>,
+    /// Destination buffer reused by `convert_into_vec`.
+    #[cfg(feature = "dma-heap")]
+    readback: std::cell::RefCell<Option<dma::DmaBuffer>>,
 }
 
 impl G2D {
@@ -368,6 +1611,56 @@ impl G2D {
         P: AsRef<::std::ffi::OsStr>,
     {
         let lib = unsafe { g2d::new(path)? };
+        Self::from_lib(lib)
+    }
+
+    /// Like [`new()`](Self::new), but also report how long each phase of
+    /// opening took, to tell library loading apart from GPU context
+    /// creation when diagnosing slow startup.
+    pub fn new_timed<P>(path: P) -> Result<(Self, OpenTimings)>
+    where
+        P: AsRef<::std::ffi::OsStr>,
+    {
+        let start = Instant::now();
+        let library = unsafe { libloading::Library::new(path.as_ref())? };
+        let dlopen = start.elapsed();
+
+        let start = Instant::now();
+        let lib = unsafe { g2d::from_library(library)? };
+        let symbols = start.elapsed();
+
+        let start = Instant::now();
+        let g2d = Self::from_lib(lib)?;
+        let context = start.elapsed();
+
+        Ok((
+            g2d,
+            OpenTimings {
+                dlopen,
+                symbols,
+                context,
+            },
+        ))
+    }
+
+    /// Open a G2D handle on a library that was already loaded elsewhere,
+    /// taking ownership of it instead of loading `libg2d` again by path.
+    ///
+    /// The library must export the `g2d_*` entry points with the libg2d 2.x
+    /// ABI; they are resolved with `dlsym` on `lib`, so a handle from
+    /// `dlopen` on libg2d itself, or on an object that links it with
+    /// `RTLD_GLOBAL` visibility, both work. `g2d_open`, `g2d_close`,
+    /// `g2d_clear`, `g2d_blit`, `g2d_finish`, `g2d_flush`, `g2d_enable` and
+    /// `g2d_disable` are required in practice: a method whose symbol failed to
+    /// resolve panics when called. The version is detected from the
+    /// `_G2D_VERSION` symbol as with [`new()`](Self::new).
+    pub fn from_loaded(lib: libloading::Library) -> Result<Self> {
+        let lib = unsafe { g2d::from_library(lib)? };
+        Self::from_lib(lib)
+    }
+
+    /// Open a handle on an already-resolved function table.
+    pub(crate) fn from_lib(lib: g2d) -> Result<Self> {
         let mut handle: *mut c_void = null_mut();
 
         if unsafe { lib.g2d_open(&mut handle) } != 0 {
@@ -380,6 +1673,18 @@ impl G2D {
             lib: Rc::new(lib),
             version,
             handle,
+            #[cfg(feature = "dma-heap")]
+            cpu_clear_threshold: Cell::new(None),
+            bandwidth_budget: Cell::new(None),
+            queued: Cell::new(0),
+            crop_alignment: Cell::new(CropAlignment::default()),
+            priority: Cell::new(Priority::default()),
+            wait_mode: Cell::new(WaitMode::default()),
+            colorspace_set: Cell::new(false),
+            colorspace_policy: Cell::new(ColorspacePolicy::default()),
+            alpha_loss_blits: Cell::new(0),
+            #[cfg(feature = "dma-heap")]
+            readback: std::cell::RefCell::new(None),
         })
     }
 
@@ -387,22 +1692,97 @@ impl G2D {
         self.version
     }
 
+    /// Scheduling [`Priority`] hint for clears and blits on this handle;
+    /// [`Priority::Normal`] unless changed.
+    pub fn priority(&self) -> Priority {
+        self.priority.get()
+    }
+
+    /// Set the [`Priority`] hint for subsequent clears and blits on this
+    /// handle.
+    pub fn set_priority(&mut self, priority: Priority) {
+        self.priority.set(priority);
+    }
+
+    /// [`WaitMode`] for fence waits on this handle; [`WaitMode::Block`]
+    /// unless changed.
+    pub fn wait_mode(&self) -> WaitMode {
+        self.wait_mode.get()
+    }
+
+    /// Set the [`WaitMode`] used by [`finish_for()`](Self::finish_for).
+    pub fn set_wait_mode(&mut self, mode: WaitMode) {
+        self.wait_mode.set(mode);
+    }
+
+    /// [`ColorspacePolicy`] for YUV conversions on this handle before a
+    /// colorspace is configured; lenient with BT.601 unless changed.
+    pub fn colorspace_policy(&self) -> ColorspacePolicy {
+        self.colorspace_policy.get()
+    }
+
+    /// Set the [`ColorspacePolicy`] for this handle.
+    pub fn set_colorspace_policy(&mut self, policy: ColorspacePolicy) {
+        self.colorspace_policy.set(policy);
+    }
+
+    /// Whether a colorspace has been configured on this handle (see
+    /// [`ColorspacePolicy`]).
+    pub fn is_colorspace_set(&self) -> bool {
+        self.colorspace_set.get()
+    }
+
+    /// Close the libg2d context now instead of on drop.
+    ///
+    /// Every later operation on this `G2D` fails with
+    /// [`Error::HandleClosed`] rather than reaching libg2d with a stale
+    /// context. Closing an already closed handle does nothing. Queued work
+    /// is not waited for; call [`finish()`](Self::finish) first if its
+    /// results are needed.
+    pub fn close(&mut self) -> Result<()> {
+        if self.handle.is_null() {
+            return Ok(());
+        }
+        let ret = unsafe { self.lib.g2d_close(self.handle) };
+        self.handle = null_mut();
+        if ret != 0 {
+            return Err(std::io::Error::last_os_error().into());
+        }
+        Ok(())
+    }
+
+    /// Whether [`close()`](Self::close) has been called.
+    pub fn is_closed(&self) -> bool {
+        self.handle.is_null()
+    }
+
+    /// The libg2d context, or [`Error::HandleClosed`] once closed.
+    fn open_handle(&self) -> Result<*mut c_void> {
+        if self.handle.is_null() {
+            return Err(Error::HandleClosed);
+        }
+        Ok(self.handle)
+    }
+
     /// Clear a surface to a solid color using the hardware `g2d_clear` operation.
     ///
     /// This queues the clear operation. Call [`finish()`](Self::finish) to wait
     /// for completion, or batch multiple operations before finishing.
     pub fn clear(&self, dst: &mut G2DSurface, color: [u8; 4]) -> Result<()> {
+        check_destination(dst)?;
+        let handle = self.open_handle()?;
+        self.queued.set(self.queued.get() + 1);
         dst.clrcolor = i32::from_le_bytes(color);
         let ret = if self.version >= G2D_2_3_0 {
             unsafe {
                 self.lib
-                    .g2d_clear(self.handle, dst as *const _ as *mut g2d_surface)
+                    .g2d_clear(handle, dst as *const _ as *mut g2d_surface)
             }
         } else {
             let dst: G2DSurfaceLegacy = (dst as &G2DSurface).into();
             unsafe {
                 self.lib
-                    .g2d_clear(self.handle, &dst as *const _ as *mut g2d_surface)
+                    .g2d_clear(handle, &dst as *const _ as *mut g2d_surface)
             }
         };
 
@@ -411,18 +1791,71 @@ impl G2D {
         }
         dst.clrcolor = 0;
 
-        Ok(())
+        self.submit_with_priority()
+    }
+
+    /// Clear several surfaces, each to its own color, and wait once for all
+    /// of them to complete.
+    ///
+    /// Equivalent to a [`clear()`](Self::clear) per entry followed by a
+    /// single [`finish()`](Self::finish). Stops at the first failing clear;
+    /// clears queued before it are still completed.
+    pub fn clear_many(&self, clears: &[(&G2DSurface, [u8; 4])]) -> Result<()> {
+        let queued = clears.iter().try_for_each(|&(surface, color)| {
+            let mut surface = *surface;
+            self.clear(&mut surface, color)
+        });
+        let finished = self.finish();
+        queued.and(finished)
     }
 
     /// Blit (copy/scale/convert) from source to destination surface.
     ///
     /// This queues the blit operation. Call [`finish()`](Self::finish) to wait
     /// for completion, or batch multiple operations before finishing.
+    ///
+    /// Crops of subsampled YUV surfaces that split a chroma sample are
+    /// handled per [`crop_alignment()`](Self::crop_alignment), rejecting
+    /// them by default.
+    ///
+    /// A rotation set on `dst` (see [`G2DSurface::with_rotation`]) is
+    /// applied to the source: for 90° and 270° the destination crop is
+    /// described in rotated orientation, so a 64×32 source crop maps to a
+    /// 32×64 destination crop. A non-square source crop rotated 90° or 270°
+    /// into a destination crop of the same, unswapped size is almost always
+    /// a missing swap and is rejected with [`Error::InvalidSurface`].
+    ///
+    /// G2D scales each axis by at most [`MAX_SCALE_FACTOR`] either way and
+    /// renders garbage beyond that without reporting an error, so such
+    /// blits are rejected with [`Error::ScaleOutOfRange`]. Larger
+    /// reductions need several passes; see [`plan()`](Self::plan).
+    ///
+    /// Converting a source with alpha into a destination without it drops
+    /// the alpha channel. Such blits are counted by
+    /// [`alpha_loss_blits()`](Self::alpha_loss_blits) and the first one on
+    /// a handle logs a warning; acknowledge the loss with
+    /// [`blit_with_options()`](Self::blit_with_options) and
+    /// `allow_alpha_loss` to skip both.
     pub fn blit(&self, src: &G2DSurface, dst: &G2DSurface) -> Result<()> {
+        self.blit_checked(src, dst, false)
+    }
+
+    /// [`blit()`](Self::blit), skipping the alpha loss check if
+    /// `allow_alpha_loss` is set.
+    pub(crate) fn blit_checked(
+        &self,
+        src: &G2DSurface,
+        dst: &G2DSurface,
+        allow_alpha_loss: bool,
+    ) -> Result<()> {
+        check_destination(dst)?;
+        let (src, dst) = &self.prepare_blit(src, dst, allow_alpha_loss)?;
+        let handle = self.open_handle()?;
+        self.queued.set(self.queued.get() + 1);
         let ret = if self.version >= G2D_2_3_0 {
             unsafe {
                 self.lib.g2d_blit(
-                    self.handle,
+                    handle,
                     src as *const _ as *mut g2d_surface,
                     dst as *const _ as *mut g2d_surface,
                 )
@@ -433,7 +1866,7 @@ impl G2D {
 
             unsafe {
                 self.lib.g2d_blit(
-                    self.handle,
+                    handle,
                     &src as *const _ as *mut g2d_surface,
                     &dst as *const _ as *mut g2d_surface,
                 )
@@ -444,7 +1877,156 @@ impl G2D {
             return Err(std::io::Error::last_os_error().into());
         }
 
-        Ok(())
+        self.submit_with_priority()
+    }
+
+    /// Copy the whole image of `src` into `dst` with `g2d_copy`, a plain
+    /// memory transfer that skips the blit engine setup.
+    ///
+    /// `src` and `dst` must have the same format, dimensions and stride;
+    /// this is checked in debug builds only. Crops, rotation and blending
+    /// are ignored: the copy covers `stride`×`height` pixels of every plane
+    /// as laid out by [`G2DFormat::plane_layout`], so multi-plane surfaces
+    /// must have their chroma planes right after the luma plane, or this
+    /// fails with [`Error::InvalidSurface`]. Like [`blit()`](Self::blit),
+    /// the copy is queued; call [`finish()`](Self::finish) to wait for it.
+    pub fn copy(&self, src: &G2DSurface, dst: &G2DSurface) -> Result<()> {
+        debug_assert_eq!(src.format, dst.format, "copy between formats");
+        debug_assert_eq!(
+            src.dimensions(),
+            dst.dimensions(),
+            "copy between dimensions"
+        );
+        debug_assert_eq!(src.stride(), dst.stride(), "copy between strides");
+
+        let size = copy_size(src)?;
+        copy_size(dst)?;
+        let size = c_int::try_from(size).map_err(|_| {
+            Error::InvalidSurface(format!("{size}-byte copy is too large for g2d_copy"))
+        })?;
+        let buf = |surface: &G2DSurface| g2d_buf {
+            buf_handle: null_mut(),
+            buf_vaddr: null_mut(),
+            buf_paddr: surface.planes[0] as g2d_phys_addr_t,
+            buf_size: size,
+        };
+        let (mut src_buf, mut dst_buf) = (buf(src), buf(dst));
+
+        let handle = self.open_handle()?;
+        self.queued.set(self.queued.get() + 1);
+        let ret = unsafe { self.lib.g2d_copy(handle, &mut dst_buf, &mut src_buf, size) };
+        if ret != 0 {
+            return Err(std::io::Error::last_os_error().into());
+        }
+
+        self.submit_with_priority()
+    }
+
+    /// Run the per-pair checks of [`blit()`](Self::blit) other than the
+    /// destination format, and snap both crops to the crop alignment.
+    fn prepare_blit(
+        &self,
+        src: &G2DSurface,
+        dst: &G2DSurface,
+        allow_alpha_loss: bool,
+    ) -> Result<(G2DSurface, G2DSurface)> {
+        check_rotation(src, dst)?;
+        check_scale(src, dst)?;
+        self.check_colorspace(src, dst)?;
+        if !allow_alpha_loss {
+            self.check_alpha_loss(src, dst);
+        }
+        let policy = self.crop_alignment.get();
+        Ok((
+            clip::align_crop(src, policy)?,
+            clip::align_crop(dst, policy)?,
+        ))
+    }
+
+    /// Count a blit that drops the source alpha channel, warning on the
+    /// first one on this handle.
+    fn check_alpha_loss(&self, src: &G2DSurface, dst: &G2DSurface) {
+        if !src.format().has_alpha() || dst.format().has_alpha() {
+            return;
+        }
+        let count = self.alpha_loss_blits.get() + 1;
+        self.alpha_loss_blits.set(count);
+        if count == 1 {
+            log::warn!(
+                "blit from g2d_format {} to {} discards the source alpha channel; \
+                 set BlitOptions::allow_alpha_loss to acknowledge",
+                src.format,
+                dst.format
+            );
+        }
+    }
+
+    /// Number of blits on this handle that converted a source with alpha
+    /// into a destination without it and did not set `allow_alpha_loss`
+    /// (see [`blit()`](Self::blit)).
+    pub fn alpha_loss_blits(&self) -> u64 {
+        self.alpha_loss_blits.get()
+    }
+
+    /// Queue a blit of each source into its rectangle of `dst` with one
+    /// `g2d_multi_blit` call, so the layers are submitted together.
+    ///
+    /// Each layer is a source surface, blitted from its crop, and the
+    /// rectangle of `dst` it is scaled into, which replaces the crop of
+    /// `dst` for that layer. Layers are drawn in order, later ones over
+    /// earlier ones. Every layer is checked as by [`blit()`](Self::blit)
+    /// before anything is queued. At most [`MAX_MULTI_BLIT_LAYERS`] layers
+    /// are accepted; more fail with [`Error::Unsupported`]. An empty slice
+    /// queues nothing.
+    pub fn multi_blit(&self, layers: &[(G2DSurface, Rect)], dst: &G2DSurface) -> Result<()> {
+        if layers.len() > MAX_MULTI_BLIT_LAYERS {
+            return Err(Error::Unsupported(format!(
+                "{} layers exceed the {MAX_MULTI_BLIT_LAYERS} g2d_multi_blit accepts",
+                layers.len()
+            )));
+        }
+        if layers.is_empty() {
+            return Ok(());
+        }
+        check_destination(dst)?;
+        let pairs = layers
+            .iter()
+            .map(|(src, rect)| {
+                let mut layer_dst = *dst;
+                layer_dst.left = rect.left;
+                layer_dst.top = rect.top;
+                layer_dst.right = rect.right;
+                layer_dst.bottom = rect.bottom;
+                self.prepare_blit(src, &layer_dst, false)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let handle = self.open_handle()?;
+        self.queued.set(self.queued.get() + 1);
+
+        // libg2d takes an array of pointers to pairs; both the pairs and the
+        // pointer array must outlive the call.
+        let ret = if self.version >= G2D_2_3_0 {
+            let mut pairs: Vec<SurfacePair<G2DSurface>> = pairs
+                .into_iter()
+                .map(|(s, d)| SurfacePair { s, d })
+                .collect();
+            unsafe { multi_blit_pairs(&self.lib, handle, &mut pairs) }
+        } else {
+            let mut pairs: Vec<SurfacePair<G2DSurfaceLegacy>> = pairs
+                .iter()
+                .map(|(s, d)| SurfacePair {
+                    s: s.into(),
+                    d: d.into(),
+                })
+                .collect();
+            unsafe { multi_blit_pairs(&self.lib, handle, &mut pairs) }
+        };
+
+        if ret != 0 {
+            return Err(std::io::Error::last_os_error().into());
+        }
+
+        self.submit_with_priority()
     }
 
     /// Wait for all queued G2D operations to complete.
@@ -452,12 +2034,25 @@ impl G2D {
     /// Must be called after [`clear()`](Self::clear) and/or
     /// [`blit()`](Self::blit) to ensure the hardware has finished writing.
     pub fn finish(&self) -> Result<()> {
-        if unsafe { self.lib.g2d_finish(self.handle) } != 0 {
+        let handle = self.open_handle()?;
+        if unsafe { self.lib.g2d_finish(handle) } != 0 {
             return Err(std::io::Error::last_os_error().into());
         }
         Ok(())
     }
 
+    /// [`finish()`](Self::finish), returning a token that proves the
+    /// operations queued so far have completed.
+    ///
+    /// Pass the token to
+    /// [`DmaBuffer::read_finished`](crate::dma::DmaBuffer::read_finished) to
+    /// read a destination buffer only once its writes are done.
+    pub fn finish_token(&self) -> Result<FinishToken<'_>> {
+        let queued = self.queued.get();
+        self.finish()?;
+        Ok(FinishToken { g2d: self, queued })
+    }
+
     /// Flush all queued G2D operations for asynchronous execution.
     ///
     /// Unlike [`finish()`](Self::finish), this does **not** wait for
@@ -468,63 +2063,411 @@ impl G2D {
     /// Useful in pipelines where the consumer of the result is not
     /// immediately ready, allowing GPU work to overlap with other CPU work.
     pub fn flush(&self) -> Result<()> {
-        if unsafe { self.lib.g2d_flush(self.handle) } != 0 {
+        let handle = self.open_handle()?;
+        if unsafe { self.lib.g2d_flush(handle) } != 0 {
             return Err(std::io::Error::last_os_error().into());
         }
         Ok(())
     }
 
-    pub fn set_bt601_colorspace(&mut self) -> Result<()> {
-        if unsafe {
-            self.lib
-                .g2d_enable(self.handle, g2d_cap_mode_G2D_YUV_BT_601)
-        } != 0
-        {
+    /// Queue a blit and flush it so the GPU starts on it immediately,
+    /// returning a handle to wait for (or request cancellation of) it.
+    ///
+    /// Intended for very large blits that a real-time consumer may want to
+    /// abandon. See [`BlitHandle`] for the current cancellation limits.
+    pub fn blit_cancellable(&self, src: &G2DSurface, dst: &G2DSurface) -> Result<BlitHandle<'_>> {
+        self.blit(src, dst)?;
+        self.flush()?;
+        Ok(BlitHandle { g2d: self })
+    }
+
+    /// Blit a camera frame from a sensor mounted at `rotation`, rotating it
+    /// upright and converting it to the format of `dst` in the same
+    /// operation.
+    ///
+    /// A sensor mounted rotated 90° clockwise captures the scene rotated
+    /// 90° counter-clockwise, so the frame is rotated by the mount rotation
+    /// itself.
+    ///
+    /// The crop of `src` is taken in sensor orientation, rotated, then
+    /// converted and scaled to the crop of `dst`, so `dst` is described in
+    /// upright orientation: a 90° or 270° mount turns a 640×480 frame into
+    /// a 480×640 one. Any transform already set on `dst` is replaced.
+    /// Rotating into a YUV destination is rejected with
+    /// [`Error::Unsupported`], as G2D writes YUV destinations unrotated
+    /// only.
+    pub fn ingest(&self, src: &G2DSurface, dst: &G2DSurface, rotation: Rotation) -> Result<()> {
+        if rotation != Rotation::None && dst.format().is_yuv() {
+            return Err(Error::Unsupported(format!(
+                "cannot rotate {}° into YUV g2d_format {}",
+                rotation.degrees(),
+                dst.format
+            )));
+        }
+        let mut dst = *dst;
+        dst.set_transform(rotation);
+        self.blit(src, &dst)
+    }
+
+    /// Blit `src` into `dst` mirrored by `flip`.
+    ///
+    /// The mirror is within the crops: the left column of the source crop
+    /// lands in the right column of the destination crop, and pixels
+    /// outside the destination crop are untouched. Any transform already
+    /// set on `dst` is replaced.
+    pub fn blit_with_flip(&self, src: &G2DSurface, dst: &G2DSurface, flip: Flip) -> Result<()> {
+        let mut dst = *dst;
+        dst.set_transform(flip);
+        self.blit(src, &dst)
+    }
+
+    /// Flush a just-queued operation when this handle is at
+    /// [`Priority::High`].
+    fn submit_with_priority(&self) -> Result<()> {
+        match self.priority.get() {
+            Priority::High => self.flush(),
+            Priority::Low | Priority::Normal => Ok(()),
+        }
+    }
+
+    /// Enable `feature` for subsequent operations on this handle.
+    ///
+    /// Fails with [`Error::IoError`] if libg2d rejects it, as it does for
+    /// modes the hardware lacks.
+    pub fn enable(&self, feature: Feature) -> Result<()> {
+        self.enable_cap(feature.into())?;
+        if matches!(
+            feature,
+            Feature::YuvBt601
+                | Feature::YuvBt709
+                | Feature::YuvBt601FullRange
+                | Feature::YuvBt709FullRange
+        ) {
+            self.colorspace_set.set(true);
+        }
+        Ok(())
+    }
+
+    /// Disable `feature` for subsequent operations on this handle.
+    pub fn disable(&self, feature: Feature) -> Result<()> {
+        self.disable_cap(feature.into())
+    }
+
+    /// Whether `feature` is currently enabled on this handle.
+    pub fn is_enabled(&self, feature: Feature) -> Result<bool> {
+        self.cap_enabled(feature.into())
+    }
+
+    /// Whether the driver supports `feature`, so a caller can pick another
+    /// path up front instead of failing at blit time.
+    ///
+    /// libg2d has no support query for these modes: `g2d_query_cap` only
+    /// reports whether a mode is enabled. A feature that is already enabled
+    /// is supported; otherwise it is enabled and disabled again, and
+    /// supported if libg2d accepts that. The handle's state is unchanged:
+    /// probing a YUV matrix, which replaces the selected one while
+    /// enabled, selects the previous matrix again afterwards.
+    pub fn query_cap(&self, feature: Feature) -> Result<bool> {
+        let cap = feature.into();
+        if self.cap_enabled(cap)? {
+            return Ok(true);
+        }
+        let mut selected = None;
+        if YUV_MATRIX_CAPS.contains(&cap) {
+            for matrix in YUV_MATRIX_CAPS {
+                if self.cap_enabled(matrix)? {
+                    selected = Some(matrix);
+                }
+            }
+        }
+        // The raw cap calls, so probing a YUV matrix does not count as
+        // configuring a colorspace under `ColorspacePolicy::Strict`.
+        let supported = match self.enable_cap(cap) {
+            Ok(()) => self.disable_cap(cap).map(|()| true),
+            Err(Error::IoError(_)) => Ok(false),
+            Err(e) => Err(e),
+        };
+        if let Some(matrix) = selected {
+            self.enable_cap(matrix)?;
+        }
+        supported
+    }
+
+    /// The common capability flags of this driver and hardware; see
+    /// [`Capabilities`].
+    pub fn capabilities(&self) -> Result<Capabilities> {
+        Ok(Capabilities {
+            blend: self.query_cap(Feature::Blend)?,
+            dither: self.query_cap(Feature::Dither)?,
+            global_alpha: self.query_cap(Feature::GlobalAlpha)?,
+            yuv_input: self.query_feature(g2d_feature_G2D_SRC_YUV)?,
+            yuv_output: self.query_feature(g2d_feature_G2D_DST_YUV)?,
+            scaling: self.query_feature(g2d_feature_G2D_SCALING)?,
+        })
+    }
+
+    /// Enable a `g2d_cap_mode` for subsequent operations on this handle.
+    pub(crate) fn enable_cap(&self, cap: g2d_cap_mode) -> Result<()> {
+        let handle = self.open_handle()?;
+        if unsafe { self.lib.g2d_enable(handle, cap) } != 0 {
             return Err(std::io::Error::last_os_error().into());
         }
-        if unsafe {
-            self.lib
-                .g2d_disable(self.handle, g2d_cap_mode_G2D_YUV_BT_709)
-        } != 0
-        {
+        Ok(())
+    }
+
+    /// Disable a `g2d_cap_mode` for subsequent operations on this handle.
+    pub(crate) fn disable_cap(&self, cap: g2d_cap_mode) -> Result<()> {
+        let handle = self.open_handle()?;
+        if unsafe { self.lib.g2d_disable(handle, cap) } != 0 {
             return Err(std::io::Error::last_os_error().into());
         }
         Ok(())
     }
 
-    pub fn set_bt709_colorspace(&mut self) -> Result<()> {
-        if unsafe {
-            self.lib
-                .g2d_disable(self.handle, g2d_cap_mode_G2D_YUV_BT_601)
-        } != 0
-        {
+    /// Whether the SoC has `core`, so that
+    /// [`make_current()`](Self::make_current) can select it.
+    pub fn core_available(&self, core: Core) -> Result<bool> {
+        Ok(self.hardware_type(core)?.is_some())
+    }
+
+    /// Route this handle's later clears and blits to `core`.
+    ///
+    /// Work already queued stays on the previous core, so
+    /// [`finish()`](Self::finish) it first if ordering matters. Fails with
+    /// [`Error::Unsupported`] when the SoC lacks `core`.
+    pub fn make_current(&mut self, core: Core) -> Result<()> {
+        let Some(hardware) = self.hardware_type(core)? else {
+            return Err(Error::Unsupported(format!(
+                "{core:?} core is not available on this SoC"
+            )));
+        };
+        let handle = self.open_handle()?;
+        if unsafe { self.lib.g2d_make_current(handle, hardware) } != 0 {
             return Err(std::io::Error::last_os_error().into());
         }
+        Ok(())
+    }
 
-        if unsafe {
-            self.lib
-                .g2d_disable(self.handle, g2d_cap_mode_G2D_YUV_BT_601FR)
-        } != 0
-        {
+    /// First `g2d_hardware_type` of `core` the SoC reports, if any.
+    fn hardware_type(&self, core: Core) -> Result<Option<g2d_hardware_type>> {
+        let handle = self.open_handle()?;
+        for &hardware in core.hardware_types() {
+            let mut available = 0;
+            if unsafe {
+                self.lib
+                    .g2d_query_hardware(handle, hardware, &mut available)
+            } != 0
+            {
+                return Err(std::io::Error::last_os_error().into());
+            }
+            if available != 0 {
+                return Ok(Some(hardware));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Whether a `g2d_cap_mode` is currently enabled on this handle.
+    pub(crate) fn cap_enabled(&self, cap: g2d_cap_mode) -> Result<bool> {
+        let handle = self.open_handle()?;
+        let mut enable = 0;
+        if unsafe { self.lib.g2d_query_cap(handle, cap, &mut enable) } != 0 {
             return Err(std::io::Error::last_os_error().into());
         }
+        Ok(enable != 0)
+    }
 
-        if unsafe {
-            self.lib
-                .g2d_disable(self.handle, g2d_cap_mode_G2D_YUV_BT_709FR)
-        } != 0
-        {
+    /// Whether the hardware reports support for a `g2d_feature`.
+    pub(crate) fn query_feature(&self, feature: g2d_feature) -> Result<bool> {
+        let handle = self.open_handle()?;
+        let mut available = 0;
+        if unsafe { self.lib.g2d_query_feature(handle, feature, &mut available) } != 0 {
             return Err(std::io::Error::last_os_error().into());
         }
+        Ok(available != 0)
+    }
+
+    /// YUV matrix the handle currently converts with, read back from the
+    /// context: [`Colorspace::Bt709`] if a BT.709 mode is enabled, otherwise
+    /// [`Colorspace::Bt601`], the libg2d default. See [`range()`](Self::range)
+    /// for the range.
+    pub fn colorspace(&self) -> Result<Colorspace> {
+        if self.cap_enabled(g2d_cap_mode_G2D_YUV_BT_709)?
+            || self.cap_enabled(g2d_cap_mode_G2D_YUV_BT_709FR)?
+        {
+            Ok(Colorspace::Bt709)
+        } else {
+            Ok(Colorspace::Bt601)
+        }
+    }
 
-        if unsafe {
-            self.lib
-                .g2d_enable(self.handle, g2d_cap_mode_G2D_YUV_BT_709)
-        } != 0
+    /// Range of the YUV matrix the handle currently converts with, read
+    /// back from the context: [`Range::Full`] if a full-range mode is
+    /// enabled, otherwise [`Range::Limited`].
+    pub fn range(&self) -> Result<Range> {
+        if self.cap_enabled(g2d_cap_mode_G2D_YUV_BT_601FR)?
+            || self.cap_enabled(g2d_cap_mode_G2D_YUV_BT_709FR)?
         {
-            return Err(std::io::Error::last_os_error().into());
+            Ok(Range::Full)
+        } else {
+            Ok(Range::Limited)
+        }
+    }
+
+    /// Select the YUV matrix for `colorspace` and `range`, used by the next
+    /// blits between YUV and RGB.
+    ///
+    /// The four libg2d matrix modes are exclusive, so the other three are
+    /// disabled. [`Colorspace::Bt2020`] fails with [`Error::Unsupported`]
+    /// and leaves the handle unchanged.
+    pub fn set_colorspace(&mut self, colorspace: Colorspace, range: Range) -> Result<()> {
+        self.set_yuv_matrix(colorspace, range)
+    }
+
+    /// [`set_colorspace()`](Self::set_colorspace) for the crate's own
+    /// callers, which hold the handle shared.
+    pub(crate) fn set_yuv_matrix(&self, colorspace: Colorspace, range: Range) -> Result<()> {
+        let enable = match (colorspace, range) {
+            (Colorspace::Bt601, Range::Limited) => g2d_cap_mode_G2D_YUV_BT_601,
+            (Colorspace::Bt709, Range::Limited) => g2d_cap_mode_G2D_YUV_BT_709,
+            (Colorspace::Bt601, Range::Full) => g2d_cap_mode_G2D_YUV_BT_601FR,
+            (Colorspace::Bt709, Range::Full) => g2d_cap_mode_G2D_YUV_BT_709FR,
+            (Colorspace::Bt2020, _) => {
+                return Err(Error::Unsupported(
+                    "libg2d has no BT.2020 YUV matrix".to_string(),
+                ))
+            }
+        };
+        for cap in YUV_MATRIX_CAPS {
+            if cap != enable {
+                self.disable_cap(cap)?;
+            }
+        }
+        self.enable_cap(enable)?;
+        self.colorspace_set.set(true);
+        Ok(())
+    }
+
+    /// Apply the [`ColorspacePolicy`] to a blit that converts between YUV
+    /// and RGB before a colorspace has been configured.
+    fn check_colorspace(&self, src: &G2DSurface, dst: &G2DSurface) -> Result<()> {
+        if self.colorspace_set.get() || src.format().is_yuv() == dst.format().is_yuv() {
+            return Ok(());
+        }
+        match self.colorspace_policy.get() {
+            ColorspacePolicy::Strict => Err(Error::ColorspaceNotSet),
+            ColorspacePolicy::Lenient(colorspace) => {
+                self.set_yuv_matrix(colorspace, Range::Limited)
+            }
+        }
+    }
+
+    /// Limited-range BT.601; shorthand for
+    /// [`set_colorspace()`](Self::set_colorspace).
+    pub fn set_bt601_colorspace(&mut self) -> Result<()> {
+        self.set_colorspace(Colorspace::Bt601, Range::Limited)
+    }
+
+    /// Limited-range BT.709; shorthand for
+    /// [`set_colorspace()`](Self::set_colorspace).
+    pub fn set_bt709_colorspace(&mut self) -> Result<()> {
+        self.set_colorspace(Colorspace::Bt709, Range::Limited)
+    }
+
+    /// Return the handle's driver state to a known baseline.
+    ///
+    /// The baseline matches a freshly opened libg2d handle:
+    ///
+    /// - `G2D_BLEND`, `G2D_GLOBAL_ALPHA` and `G2D_DITHER` disabled, so blits
+    ///   copy (and convert) pixels without blending or dithering.
+    /// - BT.601 limited-range YUV conversion, with `G2D_YUV_BT_709` and both
+    ///   full-range modes disabled.
+    ///
+    /// Use this between unrelated operations on a shared handle so capability
+    /// toggles from one stage do not leak into the next. Surface fields such
+    /// as `blendfunc` and `global_alpha` are per-call and are not affected.
+    pub fn reset_state(&self) -> Result<()> {
+        for cap in [
+            g2d_cap_mode_G2D_BLEND,
+            g2d_cap_mode_G2D_GLOBAL_ALPHA,
+            g2d_cap_mode_G2D_DITHER,
+        ] {
+            self.disable_cap(cap)?;
+        }
+        self.set_yuv_matrix(Colorspace::Bt601, Range::Limited)
+    }
+}
+
+fn check_destination(dst: &G2DSurface) -> Result<()> {
+    let format = dst.format();
+    if format.is_valid_destination() {
+        Ok(())
+    } else {
+        Err(Error::UnsupportedDestinationFormat(format))
+    }
+}
+
+/// Bytes [`G2D::copy`] transfers for `surface`, checking that its planes
+/// are contiguous.
+fn copy_size(surface: &G2DSurface) -> Result<usize> {
+    let (_, height) = surface.dimensions();
+    let layout = surface.format().plane_layout(
+        surface.stride().max(0) as usize,
+        height.max(0) as usize,
+        1,
+        1,
+    )?;
+    let base = surface.planes[0] as usize;
+    for (plane, address) in layout.planes().iter().zip(surface.planes).skip(1) {
+        if address as usize != base + plane.offset {
+            return Err(Error::InvalidSurface(format!(
+                "copy needs contiguous planes; a plane is at {address:#x}, expected {:#x}",
+                base + plane.offset
+            )));
         }
+    }
+    Ok(layout.size())
+}
+
+/// Reject a 90°/270° blit whose destination crop has the source crop's
+/// unswapped, non-square dimensions.
+fn check_rotation(src: &G2DSurface, dst: &G2DSurface) -> Result<()> {
+    let quarter_turn = matches!(
+        dst.transform(),
+        Some(Transform::Rotate(Rotation::Deg90 | Rotation::Deg270))
+    );
+    let (src_crop, dst_crop) = (src.crop_rect(), dst.crop_rect());
+    let (w, h) = (src_crop.width(), src_crop.height());
+    if quarter_turn && w != h && (dst_crop.width(), dst_crop.height()) == (w, h) {
+        return Err(Error::InvalidSurface(format!(
+            "{}° rotation of a {w}x{h} source crop needs a {h}x{w} destination crop, not {w}x{h}",
+            Rotation::try_from(dst.rot).map_or(0, Rotation::degrees)
+        )));
+    }
+    Ok(())
+}
+
+/// Reject a blit scaling either axis by more than [`MAX_SCALE_FACTOR`],
+/// comparing each destination axis with the source axis it is drawn from.
+fn check_scale(src: &G2DSurface, dst: &G2DSurface) -> Result<()> {
+    let quarter_turn = matches!(
+        dst.transform(),
+        Some(Transform::Rotate(Rotation::Deg90 | Rotation::Deg270))
+    );
+    let (src_crop, dst_crop) = (src.crop_rect(), dst.crop_rect());
+    let (w, h) = (src_crop.width() as i64, src_crop.height() as i64);
+    let (w, h) = if quarter_turn { (h, w) } else { (w, h) };
+    let within = |from: i64, to: i64| {
+        let limit = MAX_SCALE_FACTOR as i64;
+        from <= 0 || to <= 0 || (from <= to * limit && to <= from * limit)
+    };
+    if within(w, dst_crop.width() as i64) && within(h, dst_crop.height() as i64) {
         Ok(())
+    } else {
+        Err(Error::ScaleOutOfRange {
+            src: src_crop,
+            dst: dst_crop,
+        })
     }
 }
 
@@ -0,0 +1,65 @@
+// SPDX-FileCopyrightText: Copyright 2025 Au-Zone Technologies
+// SPDX-License-Identifier: Apache-2.0
+
+//! Memory traffic estimates for G2D operations.
+//!
+//! These are first-order figures: one read of the source crop and one write
+//! of the destination crop, ignoring cache effects and filter overfetch.
+//! They are meant for comparing operations against a bandwidth budget, not
+//! for predicting exact bus utilisation.
+
+use crate::{G2DSurface, G2D};
+
+/// Bytes covered by the crop rectangle of `surface`, or 0 if the format's
+/// size is unknown or the crop is empty.
+pub fn crop_bytes(surface: &G2DSurface) -> u64 {
+    let crop = surface.crop_rect();
+    if crop.is_empty() {
+        return 0;
+    }
+    let Some(bits) = surface.format().bits_per_pixel() else {
+        return 0;
+    };
+    crop.width() as u64 * crop.height() as u64 * bits as u64 / 8
+}
+
+/// Bytes read from `src` and written to `dst` by one blit.
+pub fn bytes_moved(src: &G2DSurface, dst: &G2DSurface) -> u64 {
+    crop_bytes(src) + crop_bytes(dst)
+}
+
+/// Bandwidth in bytes/sec of repeating the `src` → `dst` blit at `fps`.
+pub fn bandwidth(src: &G2DSurface, dst: &G2DSurface, fps: u32) -> u64 {
+    bytes_moved(src, dst) * fps as u64
+}
+
+impl G2D {
+    /// Memory bandwidth in bytes/sec needed to blit `src` to `dst` at `fps`.
+    ///
+    /// See [`metrics::bandwidth`](crate::metrics::bandwidth).
+    pub fn estimate_bandwidth(&self, src: &G2DSurface, dst: &G2DSurface, fps: u32) -> u64 {
+        bandwidth(src, dst, fps)
+    }
+
+    /// Configured bandwidth budget in bytes/sec, if any.
+    pub fn bandwidth_budget(&self) -> Option<u64> {
+        self.bandwidth_budget.get()
+    }
+
+    /// Set the bandwidth budget (bytes/sec) that
+    /// [`bandwidth_headroom()`](Self::bandwidth_headroom) compares against.
+    /// `None` removes the budget.
+    pub fn set_bandwidth_budget(&mut self, bytes_per_sec: Option<u64>) {
+        self.bandwidth_budget.set(bytes_per_sec);
+    }
+
+    /// Budget remaining after running `src` → `dst` at `fps`, in bytes/sec.
+    ///
+    /// Negative when the operation would exceed the budget; `None` if no
+    /// budget is configured.
+    pub fn bandwidth_headroom(&self, src: &G2DSurface, dst: &G2DSurface, fps: u32) -> Option<i64> {
+        self.bandwidth_budget
+            .get()
+            .map(|budget| budget as i64 - bandwidth(src, dst, fps) as i64)
+    }
+}
@@ -0,0 +1,713 @@
+// SPDX-FileCopyrightText: Copyright 2025 Au-Zone Technologies
+// SPDX-License-Identifier: Apache-2.0
+
+//! CPU implementation of the libg2d entry points for testing off-target.
+//!
+//! [`G2D::mock()`] returns a handle whose function table points at the Rust
+//! functions below instead of `libg2d.so.2`, so everything above the FFI
+//! boundary (validation, geometry, batching, state toggles) runs unchanged.
+//!
+//! The mock treats surface plane addresses as **CPU virtual addresses**, so
+//! surfaces must point at host memory, not physical DMA-buf addresses. To
+//! keep [`G2D::mock()`] safe, only memory the mock knows about is touched:
+//! every plane must lie inside a live [`MockBuffer`] that is not borrowed
+//! by [`MockBuffer::read_with`]/[`MockBuffer::write_with`], or inside a
+//! `g2d_alloc` allocation. A clear, blit or copy addressing anything else
+//! fails like a rejected hardware call. Operations complete synchronously;
+//! `flush`/`finish` are no-ops.
+//!
+//! Supported:
+//! - `clear` on 8888, RGB565/BGR565 and YUYV/UYVY destinations (RGB888 and
+//!   other formats are rejected, as on hardware)
+//! - `blit` with nearest-neighbour scaling, crop rectangles, destination
+//!   rotation/flip, `G2D_BLEND` with the basic blend factors and
+//!   `G2D_GLOBAL_ALPHA`, between 8888, RGB888/BGR888, RGB565/BGR565, packed
+//!   YUV 4:2:2, NV12/NV21 and (as sources) I420/YV12
+//! - YUV conversion following the BT.601/BT.709 (full range) caps
+//! - `g2d_copy`, `g2d_multi_blit`, `g2d_alloc`/`g2d_free` on host memory
+//! - a GPU 2D core only: `g2d_query_hardware` reports no DPU or PXP, and
+//!   `g2d_make_current` rejects them
+
+use crate::*;
+use std::{
+    os::raw::c_int,
+    sync::{Mutex, MutexGuard},
+};
+
+/// Host memory the mock may access.
+#[derive(Debug)]
+struct Region {
+    start: usize,
+    len: usize,
+    /// Open [`MockBuffer::read_with`]/[`MockBuffer::write_with`] calls.
+    borrows: usize,
+}
+
+static REGIONS: Mutex<Vec<Region>> = Mutex::new(Vec::new());
+
+/// Lock the region list. Mock operations hold the lock while they run, so
+/// a region cannot be borrowed or freed under them.
+fn regions() -> MutexGuard<'static, Vec<Region>> {
+    REGIONS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn register(start: usize, len: usize) {
+    if len > 0 {
+        regions().push(Region {
+            start,
+            len,
+            borrows: 0,
+        });
+    }
+}
+
+fn unregister(start: usize) {
+    let mut regions = regions();
+    if let Some(i) = regions.iter().position(|r| r.start == start) {
+        regions.swap_remove(i);
+    }
+}
+
+/// Whether `len` bytes from `addr` lie in one unborrowed region.
+fn accessible(regions: &[Region], addr: usize, len: usize) -> bool {
+    let Some(end) = addr.checked_add(len) else {
+        return false;
+    };
+    regions
+        .iter()
+        .any(|r| r.borrows == 0 && r.start <= addr && end <= r.start + r.len)
+}
+
+/// Whether every plane `s` addresses, `stride`×`height` pixels as laid out
+/// by [`G2DFormat::plane_layout`], is [`accessible`].
+fn surface_accessible(regions: &[Region], s: &G2DSurface) -> bool {
+    let (Ok(stride), Ok(height)) = (usize::try_from(s.stride), usize::try_from(s.height)) else {
+        return false;
+    };
+    let Ok(layout) = G2DFormat(s.format).plane_layout(stride, height, 1, 1) else {
+        return false;
+    };
+    layout.planes().iter().zip(s.planes).all(|(plane, addr)| {
+        usize::try_from(addr).is_ok_and(|addr| accessible(regions, addr, plane.size()))
+    })
+}
+
+/// Zeroed host memory that mock surfaces can address.
+///
+/// Use [`address()`](Self::address) as a surface plane address. The memory
+/// is only reachable through [`read_with`](Self::read_with) and
+/// [`write_with`](Self::write_with); while either runs, mock operations on
+/// the buffer fail instead of racing the closure.
+#[derive(Debug)]
+pub struct MockBuffer {
+    memory: *mut [u8],
+}
+
+impl MockBuffer {
+    /// Allocate `len` zeroed bytes and make them addressable by the mock.
+    pub fn new(len: usize) -> Self {
+        let memory = Box::into_raw(vec![0u8; len].into_boxed_slice());
+        register(memory as *mut u8 as usize, len);
+        MockBuffer { memory }
+    }
+
+    /// Address of the first byte, for `G2DSurface::planes`.
+    pub fn address(&self) -> u64 {
+        self.memory as *mut u8 as u64
+    }
+
+    pub fn len(&self) -> usize {
+        self.memory.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Run `f` on the contents.
+    pub fn read_with<R>(&self, f: impl FnOnce(&[u8]) -> R) -> R {
+        let _borrow = Borrow::new(self.address());
+        // SAFETY: the memory is owned by `self`, and mock operations skip it
+        // while it is borrowed.
+        f(unsafe { &*self.memory })
+    }
+
+    /// Run `f` on the contents, mutably.
+    pub fn write_with<R>(&mut self, f: impl FnOnce(&mut [u8]) -> R) -> R {
+        let _borrow = Borrow::new(self.address());
+        // SAFETY: as for `read_with`; `&mut self` excludes other borrows.
+        f(unsafe { &mut *self.memory })
+    }
+}
+
+impl Drop for MockBuffer {
+    fn drop(&mut self) {
+        unregister(self.address() as usize);
+        // SAFETY: allocated by `Box::into_raw` in `new`; no longer reachable
+        // by the mock once unregistered.
+        drop(unsafe { Box::from_raw(self.memory) });
+    }
+}
+
+/// Marks a [`MockBuffer`] as borrowed until dropped.
+struct Borrow(usize);
+
+impl Borrow {
+    fn new(address: u64) -> Self {
+        let start = address as usize;
+        if let Some(r) = regions().iter_mut().find(|r| r.start == start) {
+            r.borrows += 1;
+        }
+        Borrow(start)
+    }
+}
+
+impl Drop for Borrow {
+    fn drop(&mut self) {
+        if let Some(r) = regions().iter_mut().find(|r| r.start == self.0) {
+            r.borrows -= 1;
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct MockContext {
+    /// Enabled `g2d_cap_mode` bits.
+    caps: u32,
+}
+
+impl MockContext {
+    fn enabled(&self, cap: g2d_cap_mode) -> bool {
+        self.caps & (1 << cap) != 0
+    }
+
+    fn matrix(&self) -> YuvMatrix {
+        let (kr, kb) = if self.enabled(g2d_cap_mode_G2D_YUV_BT_709)
+            || self.enabled(g2d_cap_mode_G2D_YUV_BT_709FR)
+        {
+            (0.2126, 0.0722)
+        } else {
+            (0.299, 0.114)
+        };
+        let full = self.enabled(g2d_cap_mode_G2D_YUV_BT_601FR)
+            || self.enabled(g2d_cap_mode_G2D_YUV_BT_709FR);
+        YuvMatrix { kr, kb, full }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct YuvMatrix {
+    kr: f32,
+    kb: f32,
+    full: bool,
+}
+
+fn clamp_u8(v: f32) -> u8 {
+    v.round().clamp(0.0, 255.0) as u8
+}
+
+impl YuvMatrix {
+    fn to_rgb(self, y: u8, u: u8, v: u8) -> [u8; 3] {
+        let (y, u, v) = (y as f32, u as f32 - 128.0, v as f32 - 128.0);
+        let (y, u, v) = if self.full {
+            (y, u, v)
+        } else {
+            (
+                (y - 16.0) * 255.0 / 219.0,
+                u * 255.0 / 224.0,
+                v * 255.0 / 224.0,
+            )
+        };
+        let r = y + 2.0 * (1.0 - self.kr) * v;
+        let b = y + 2.0 * (1.0 - self.kb) * u;
+        let g = (y - self.kr * r - self.kb * b) / (1.0 - self.kr - self.kb);
+        [clamp_u8(r), clamp_u8(g), clamp_u8(b)]
+    }
+
+    fn to_yuv(self, rgb: [u8; 4]) -> [u8; 3] {
+        let [r, g, b] = [rgb[0] as f32, rgb[1] as f32, rgb[2] as f32];
+        let y = self.kr * r + (1.0 - self.kr - self.kb) * g + self.kb * b;
+        let u = (b - y) / (2.0 * (1.0 - self.kb));
+        let v = (r - y) / (2.0 * (1.0 - self.kr));
+        if self.full {
+            [clamp_u8(y), clamp_u8(u + 128.0), clamp_u8(v + 128.0)]
+        } else {
+            [
+                clamp_u8(16.0 + y * 219.0 / 255.0),
+                clamp_u8(128.0 + u * 224.0 / 255.0),
+                clamp_u8(128.0 + v * 224.0 / 255.0),
+            ]
+        }
+    }
+}
+
+/// Byte positions of `[Y0, U, Y1, V]` within a packed 4:2:2 macropixel.
+fn packed_yuv_layout(format: g2d_format) -> Option<[usize; 4]> {
+    match format {
+        g2d_format_G2D_YUYV => Some([0, 1, 2, 3]),
+        g2d_format_G2D_UYVY => Some([1, 0, 3, 2]),
+        g2d_format_G2D_YVYU => Some([0, 3, 2, 1]),
+        g2d_format_G2D_VYUY => Some([1, 2, 3, 0]),
+        _ => None,
+    }
+}
+
+/// Plane indices of U and V for three-plane 4:2:0 formats, whose planes
+/// are given in memory order.
+fn tri_planar_chroma(format: g2d_format) -> Option<(usize, usize)> {
+    match format {
+        g2d_format_G2D_I420 => Some((1, 2)),
+        g2d_format_G2D_YV12 => Some((2, 1)),
+        _ => None,
+    }
+}
+
+fn is_rgb(format: g2d_format) -> bool {
+    G2DFormat(format).pack_rgba([0; 4]).is_some()
+}
+
+fn is_supported(format: g2d_format) -> bool {
+    is_rgb(format)
+        || packed_yuv_layout(format).is_some()
+        || matches!(format, g2d_format_G2D_NV12 | g2d_format_G2D_NV21)
+        || tri_planar_chroma(format).is_some()
+}
+
+fn unpack_rgb(format: g2d_format, p: &[u8]) -> [u8; 4] {
+    let rgb565 = |p: &[u8]| {
+        let v = u16::from_le_bytes([p[0], p[1]]);
+        let expand5 = |c: u16| ((c << 3) | (c >> 2)) as u8;
+        let (hi, g, lo) = (v >> 11, (v >> 5) & 0x3f, v & 0x1f);
+        (expand5(hi), ((g << 2) | (g >> 4)) as u8, expand5(lo))
+    };
+    match format {
+        g2d_format_G2D_RGBA8888 => [p[0], p[1], p[2], p[3]],
+        g2d_format_G2D_RGBX8888 => [p[0], p[1], p[2], 255],
+        g2d_format_G2D_BGRA8888 => [p[2], p[1], p[0], p[3]],
+        g2d_format_G2D_BGRX8888 => [p[2], p[1], p[0], 255],
+        g2d_format_G2D_ARGB8888 => [p[1], p[2], p[3], p[0]],
+        g2d_format_G2D_XRGB8888 => [p[1], p[2], p[3], 255],
+        g2d_format_G2D_ABGR8888 => [p[3], p[2], p[1], p[0]],
+        g2d_format_G2D_XBGR8888 => [p[3], p[2], p[1], 255],
+        g2d_format_G2D_RGB888 => [p[0], p[1], p[2], 255],
+        g2d_format_G2D_BGR888 => [p[2], p[1], p[0], 255],
+        g2d_format_G2D_RGB565 => {
+            let (r, g, b) = rgb565(p);
+            [r, g, b, 255]
+        }
+        g2d_format_G2D_BGR565 => {
+            let (b, g, r) = rgb565(p);
+            [r, g, b, 255]
+        }
+        _ => unreachable!("unpack_rgb called with non-RGB format {format}"),
+    }
+}
+
+/// Pixel accessor over a surface whose planes are CPU addresses.
+struct Plane<'a> {
+    surface: &'a G2DSurface,
+}
+
+impl Plane<'_> {
+    fn ptr(&self, plane: usize, offset: usize) -> *mut u8 {
+        (self.surface.planes[plane] as usize + offset) as *mut u8
+    }
+
+    unsafe fn read(&self, x: i32, y: i32, m: YuvMatrix) -> [u8; 4] {
+        let s = self.surface;
+        let (x, y, stride) = (x as usize, y as usize, s.stride as usize);
+        if let Some((_, bpp)) = G2DFormat(s.format).pack_rgba([0; 4]) {
+            let p = std::slice::from_raw_parts(self.ptr(0, (y * stride + x) * bpp), bpp);
+            return unpack_rgb(s.format, p);
+        }
+        let (luma, u, v) = if let Some(layout) = packed_yuv_layout(s.format) {
+            let mp = std::slice::from_raw_parts(self.ptr(0, (y * stride + (x & !1)) * 2), 4);
+            let luma = if x & 1 == 0 {
+                mp[layout[0]]
+            } else {
+                mp[layout[2]]
+            };
+            (luma, mp[layout[1]], mp[layout[3]])
+        } else if let Some((u_plane, v_plane)) = tri_planar_chroma(s.format) {
+            let luma = *self.ptr(0, y * stride + x);
+            let offset = (y / 2) * (stride / 2) + x / 2;
+            (luma, *self.ptr(u_plane, offset), *self.ptr(v_plane, offset))
+        } else {
+            let luma = *self.ptr(0, y * stride + x);
+            let uv = std::slice::from_raw_parts(self.ptr(1, (y / 2) * stride + (x & !1)), 2);
+            match s.format {
+                g2d_format_G2D_NV21 => (luma, uv[1], uv[0]),
+                _ => (luma, uv[0], uv[1]),
+            }
+        };
+        let [r, g, b] = m.to_rgb(luma, u, v);
+        [r, g, b, 255]
+    }
+
+    unsafe fn write(&self, x: i32, y: i32, rgba: [u8; 4], m: YuvMatrix) {
+        let s = self.surface;
+        let (x, y, stride) = (x as usize, y as usize, s.stride as usize);
+        if let Some((packed, bpp)) = G2DFormat(s.format).pack_rgba(rgba) {
+            let p = std::slice::from_raw_parts_mut(self.ptr(0, (y * stride + x) * bpp), bpp);
+            p.copy_from_slice(&packed[..bpp]);
+            return;
+        }
+        // Chroma is taken from the even (top-left) pixel of each block.
+        let [luma, u, v] = m.to_yuv(rgba);
+        if let Some(layout) = packed_yuv_layout(s.format) {
+            let mp = std::slice::from_raw_parts_mut(self.ptr(0, (y * stride + (x & !1)) * 2), 4);
+            if x & 1 == 0 {
+                mp[layout[0]] = luma;
+                mp[layout[1]] = u;
+                mp[layout[3]] = v;
+            } else {
+                mp[layout[2]] = luma;
+            }
+        } else {
+            *self.ptr(0, y * stride + x) = luma;
+            if x & 1 == 0 && y & 1 == 0 {
+                let uv = std::slice::from_raw_parts_mut(self.ptr(1, (y / 2) * stride + x), 2);
+                match s.format {
+                    g2d_format_G2D_NV21 => uv.copy_from_slice(&[v, u]),
+                    _ => uv.copy_from_slice(&[u, v]),
+                }
+            }
+        }
+    }
+}
+
+fn crop_valid(s: &G2DSurface) -> bool {
+    s.left >= 0
+        && s.top >= 0
+        && s.right <= s.width
+        && s.bottom <= s.height
+        && s.left < s.right
+        && s.top < s.bottom
+        && s.width <= s.stride
+        && s.planes[0] != 0
+}
+
+fn blend_factor(func: g2d_blend_func, sa: f32, da: f32) -> f32 {
+    match func & 0xf {
+        g2d_blend_func_G2D_ONE => 1.0,
+        g2d_blend_func_G2D_SRC_ALPHA => sa,
+        g2d_blend_func_G2D_ONE_MINUS_SRC_ALPHA => 1.0 - sa,
+        g2d_blend_func_G2D_DST_ALPHA => da,
+        g2d_blend_func_G2D_ONE_MINUS_DST_ALPHA => 1.0 - da,
+        _ => 0.0,
+    }
+}
+
+/// Map a destination crop offset back to the unrotated source frame.
+fn unrotate(rot: g2d_rotation, dx: i32, dy: i32, dw: i32, dh: i32) -> (i32, i32, i32, i32) {
+    match rot {
+        g2d_rotation_G2D_ROTATION_90 => (dy, dw - 1 - dx, dh, dw),
+        g2d_rotation_G2D_ROTATION_180 => (dw - 1 - dx, dh - 1 - dy, dw, dh),
+        g2d_rotation_G2D_ROTATION_270 => (dh - 1 - dy, dx, dh, dw),
+        g2d_rotation_G2D_FLIP_H => (dw - 1 - dx, dy, dw, dh),
+        g2d_rotation_G2D_FLIP_V => (dx, dh - 1 - dy, dw, dh),
+        _ => (dx, dy, dw, dh),
+    }
+}
+
+unsafe fn context<'a>(handle: *mut c_void) -> Option<&'a mut MockContext> {
+    (handle as *mut MockContext).as_mut()
+}
+
+unsafe extern "C" fn mock_open(handle: *mut *mut c_void) -> c_int {
+    if handle.is_null() {
+        return -1;
+    }
+    *handle = Box::into_raw(Box::<MockContext>::default()) as *mut c_void;
+    0
+}
+
+unsafe extern "C" fn mock_close(handle: *mut c_void) -> c_int {
+    if !handle.is_null() {
+        drop(Box::from_raw(handle as *mut MockContext));
+    }
+    0
+}
+
+unsafe extern "C" fn mock_make_current(handle: *mut c_void, type_: g2d_hardware_type) -> c_int {
+    if context(handle).is_none() || type_ != g2d_hardware_type_G2D_HARDWARE_2D {
+        return -1;
+    }
+    0
+}
+
+unsafe extern "C" fn mock_clear(handle: *mut c_void, area: *mut g2d_surface) -> c_int {
+    let (Some(ctx), Some(area)) = (context(handle), (area as *const G2DSurface).as_ref()) else {
+        return -1;
+    };
+    let clearable = match area.format {
+        g2d_format_G2D_RGB888 | g2d_format_G2D_BGR888 => false,
+        format => is_rgb(format) || matches!(format, g2d_format_G2D_YUYV | g2d_format_G2D_UYVY),
+    };
+    let regions = regions();
+    if !clearable || !crop_valid(area) || !surface_accessible(&regions, area) {
+        return -1;
+    }
+
+    let color = area.clrcolor.to_le_bytes();
+    let plane = Plane { surface: area };
+    let m = ctx.matrix();
+    for y in area.top..area.bottom {
+        for x in area.left..area.right {
+            plane.write(x, y, color, m);
+        }
+    }
+    0
+}
+
+unsafe fn blit_one(ctx: &MockContext, src: &G2DSurface, dst: &G2DSurface) -> c_int {
+    if !is_supported(src.format)
+        || !is_supported(dst.format)
+        || tri_planar_chroma(dst.format).is_some()
+        || !crop_valid(src)
+        || !crop_valid(dst)
+    {
+        return -1;
+    }
+    let regions = regions();
+    if !surface_accessible(&regions, src) || !surface_accessible(&regions, dst) {
+        return -1;
+    }
+
+    let m = ctx.matrix();
+    let blend = ctx.enabled(g2d_cap_mode_G2D_BLEND);
+    let global_alpha = if ctx.enabled(g2d_cap_mode_G2D_GLOBAL_ALPHA) {
+        src.global_alpha.clamp(0, 255) as f32 / 255.0
+    } else {
+        1.0
+    };
+    let (sw, sh) = (src.right - src.left, src.bottom - src.top);
+    let (dw, dh) = (dst.right - dst.left, dst.bottom - dst.top);
+    let (src_plane, dst_plane) = (Plane { surface: src }, Plane { surface: dst });
+
+    for dy in 0..dh {
+        for dx in 0..dw {
+            let (ux, uy, uw, uh) = unrotate(dst.rot, dx, dy, dw, dh);
+            let sx = src.left + ((2 * ux + 1) * sw / (2 * uw)).min(sw - 1);
+            let sy = src.top + ((2 * uy + 1) * sh / (2 * uh)).min(sh - 1);
+            let mut px = src_plane.read(sx, sy, m);
+            let (x, y) = (dst.left + dx, dst.top + dy);
+
+            if blend {
+                let d = dst_plane.read(x, y, m);
+                let sa = px[3] as f32 / 255.0 * global_alpha;
+                let da = d[3] as f32 / 255.0;
+                let sf = blend_factor(src.blendfunc, sa, da);
+                let df = blend_factor(dst.blendfunc, sa, da);
+                for c in 0..4 {
+                    let s = if c == 3 { sa * 255.0 } else { px[c] as f32 };
+                    px[c] = clamp_u8(s * sf + d[c] as f32 * df);
+                }
+            }
+            dst_plane.write(x, y, px, m);
+        }
+    }
+    0
+}
+
+unsafe extern "C" fn mock_blit(
+    handle: *mut c_void,
+    src: *mut g2d_surface,
+    dst: *mut g2d_surface,
+) -> c_int {
+    match (
+        context(handle),
+        (src as *const G2DSurface).as_ref(),
+        (dst as *const G2DSurface).as_ref(),
+    ) {
+        (Some(ctx), Some(src), Some(dst)) => blit_one(ctx, src, dst),
+        _ => -1,
+    }
+}
+
+unsafe extern "C" fn mock_copy(
+    handle: *mut c_void,
+    d: *mut g2d_buf,
+    s: *mut g2d_buf,
+    size: c_int,
+) -> c_int {
+    let (Some(_), Some(d), Some(s)) = (context(handle), d.as_ref(), s.as_ref()) else {
+        return -1;
+    };
+    if size < 0 || size > d.buf_size || size > s.buf_size {
+        return -1;
+    }
+    let regions = regions();
+    if !accessible(&regions, s.buf_paddr as usize, size as usize)
+        || !accessible(&regions, d.buf_paddr as usize, size as usize)
+    {
+        return -1;
+    }
+    // Surfaces in the mock address host memory, so the physical address
+    // is the pointer.
+    std::ptr::copy(
+        s.buf_paddr as *const u8,
+        d.buf_paddr as *mut u8,
+        size as usize,
+    );
+    0
+}
+
+unsafe extern "C" fn mock_multi_blit(
+    handle: *mut c_void,
+    sp: *mut *mut g2d_surface_pair,
+    layers: c_int,
+) -> c_int {
+    let Some(ctx) = context(handle) else {
+        return -1;
+    };
+    if sp.is_null() || layers < 0 {
+        return -1;
+    }
+    for i in 0..layers as usize {
+        let Some(pair) = (*sp.add(i)).as_ref() else {
+            return -1;
+        };
+        let src = &*(&pair.s as *const g2d_surface as *const G2DSurface);
+        let dst = &*(&pair.d as *const g2d_surface as *const G2DSurface);
+        if blit_one(ctx, src, dst) != 0 {
+            return -1;
+        }
+    }
+    0
+}
+
+unsafe extern "C" fn mock_query_hardware(
+    _handle: *mut c_void,
+    type_: g2d_hardware_type,
+    available: *mut c_int,
+) -> c_int {
+    let Some(available) = available.as_mut() else {
+        return -1;
+    };
+    *available = (type_ == g2d_hardware_type_G2D_HARDWARE_2D) as c_int;
+    0
+}
+
+unsafe extern "C" fn mock_query_feature(
+    _handle: *mut c_void,
+    feature: g2d_feature,
+    available: *mut c_int,
+) -> c_int {
+    let Some(available) = available.as_mut() else {
+        return -1;
+    };
+    *available = matches!(
+        feature,
+        g2d_feature_G2D_SCALING
+            | g2d_feature_G2D_ROTATION
+            | g2d_feature_G2D_SRC_YUV
+            | g2d_feature_G2D_DST_YUV
+            | g2d_feature_G2D_MULTI_SOURCE_BLT
+    ) as c_int;
+    0
+}
+
+unsafe extern "C" fn mock_query_cap(
+    handle: *mut c_void,
+    cap: g2d_cap_mode,
+    enable: *mut c_int,
+) -> c_int {
+    let (Some(ctx), Some(enable)) = (context(handle), enable.as_mut()) else {
+        return -1;
+    };
+    *enable = ctx.enabled(cap) as c_int;
+    0
+}
+
+unsafe extern "C" fn mock_enable(handle: *mut c_void, cap: g2d_cap_mode) -> c_int {
+    let Some(ctx) = context(handle) else {
+        return -1;
+    };
+    // Like libg2d, selecting a YUV matrix replaces the previous one.
+    if YUV_MATRIX_CAPS.contains(&cap) {
+        for matrix in YUV_MATRIX_CAPS {
+            ctx.caps &= !(1 << matrix);
+        }
+    }
+    ctx.caps |= 1 << cap;
+    0
+}
+
+unsafe extern "C" fn mock_disable(handle: *mut c_void, cap: g2d_cap_mode) -> c_int {
+    let Some(ctx) = context(handle) else {
+        return -1;
+    };
+    ctx.caps &= !(1 << cap);
+    0
+}
+
+unsafe extern "C" fn mock_cache_op(_buf: *mut g2d_buf, _op: g2d_cache_mode) -> c_int {
+    0
+}
+
+unsafe extern "C" fn mock_alloc(size: c_int, _cacheable: c_int) -> *mut g2d_buf {
+    if size <= 0 {
+        return null_mut();
+    }
+    let memory = Box::into_raw(vec![0u8; size as usize].into_boxed_slice()) as *mut u8;
+    register(memory as usize, size as usize);
+    Box::into_raw(Box::new(g2d_buf {
+        buf_handle: memory as *mut c_void,
+        buf_vaddr: memory as *mut c_void,
+        buf_paddr: memory as g2d_phys_addr_t,
+        buf_size: size,
+    }))
+}
+
+unsafe extern "C" fn mock_free(buf: *mut g2d_buf) -> c_int {
+    if buf.is_null() {
+        return -1;
+    }
+    let buf = Box::from_raw(buf);
+    unregister(buf.buf_handle as usize);
+    let memory =
+        std::ptr::slice_from_raw_parts_mut(buf.buf_handle as *mut u8, buf.buf_size as usize);
+    drop(Box::from_raw(memory));
+    0
+}
+
+unsafe extern "C" fn mock_flush(_handle: *mut c_void) -> c_int {
+    0
+}
+
+unsafe extern "C" fn mock_finish(_handle: *mut c_void) -> c_int {
+    0
+}
+
+impl G2D {
+    /// Open a handle backed by the CPU mock instead of `libg2d.so.2`.
+    ///
+    /// Surface plane addresses must point into a [`MockBuffer`] (or a
+    /// `g2d_alloc` allocation); operations on other addresses fail. See
+    /// the [module documentation](crate::mock) for what the mock
+    /// implements.
+    pub fn mock() -> Result<Self> {
+        let lib = g2d {
+            __library: libloading::os::unix::Library::this().into(),
+            g2d_open: Ok(mock_open),
+            g2d_close: Ok(mock_close),
+            g2d_make_current: Ok(mock_make_current),
+            g2d_clear: Ok(mock_clear),
+            g2d_blit: Ok(mock_blit),
+            g2d_copy: Ok(mock_copy),
+            g2d_multi_blit: Ok(mock_multi_blit),
+            g2d_query_hardware: Ok(mock_query_hardware),
+            g2d_query_feature: Ok(mock_query_feature),
+            g2d_query_cap: Ok(mock_query_cap),
+            g2d_enable: Ok(mock_enable),
+            g2d_disable: Ok(mock_disable),
+            g2d_cache_op: Ok(mock_cache_op),
+            g2d_alloc: Ok(mock_alloc),
+            g2d_free: Ok(mock_free),
+            g2d_flush: Ok(mock_flush),
+            g2d_finish: Ok(mock_finish),
+        };
+        Self::from_lib(lib)
+    }
+}
@@ -0,0 +1,166 @@
+// SPDX-FileCopyrightText: Copyright 2025 Au-Zone Technologies
+// SPDX-License-Identifier: Apache-2.0
+
+//! One converted frame shared by consumers that need it in different
+//! formats, such as a display taking RGBA and an encoder taking YUYV.
+//!
+//! Every output is a G2D blit destination, so formats G2D only reads, such
+//! as NV12 and I420, cannot be outputs.
+
+use crate::{
+    dma::{DmaBuffer, HeapType},
+    metrics, Error, G2DFormat, G2DSurface, Result, G2D,
+};
+
+/// Per-frame conversions into a primary format and any number of secondary
+/// formats, each converted at most once per frame and only if requested.
+///
+/// [`set_frame()`](Self::set_frame) converts the source into the primary
+/// format straight away. A secondary format is converted from the primary
+/// output the first time [`get()`](Self::get) asks for it during that
+/// frame and served from its buffer after that, so a consumer running at a
+/// lower rate than the source costs no conversions for the frames it skips.
+#[derive(Debug)]
+pub struct MultiConsumer {
+    /// Primary output first, then the secondary outputs.
+    outputs: Vec<Output>,
+    frames: u64,
+    conversions: u64,
+    bytes_moved: u64,
+}
+
+#[derive(Debug)]
+struct Output {
+    buffer: DmaBuffer,
+    surface: G2DSurface,
+    /// Whether the output holds the current frame.
+    current: bool,
+}
+
+impl MultiConsumer {
+    /// Allocate one full-frame `width`×`height` buffer from `heap_type` for
+    /// each of `formats`, laid out as by
+    /// [`DmaBuffer::reinterpret_surface`]. The first format is the primary
+    /// one; every format must appear once and be a valid blit destination,
+    /// or [`Error::UnsupportedDestinationFormat`] is returned.
+    pub fn new(
+        width: usize,
+        height: usize,
+        formats: &[G2DFormat],
+        heap_type: HeapType,
+    ) -> Result<Self> {
+        if formats.is_empty() {
+            return Err(Error::InvalidFormat(
+                "a multi-consumer needs at least one output format".to_string(),
+            ));
+        }
+        for (i, format) in formats.iter().enumerate() {
+            if !format.is_valid_destination() {
+                return Err(Error::UnsupportedDestinationFormat(*format));
+            }
+            if formats[..i].iter().any(|f| f.format() == format.format()) {
+                return Err(Error::InvalidFormat(format!(
+                    "g2d_format {} is listed twice",
+                    format.format()
+                )));
+            }
+        }
+
+        let outputs = formats
+            .iter()
+            .map(|&format| {
+                let size = format.plane_layout(width, height, 1, 1)?.size();
+                let buffer = DmaBuffer::new(heap_type, size)?;
+                let surface = buffer.reinterpret_surface(format, width, height, 0)?;
+                Ok(Output {
+                    buffer,
+                    surface,
+                    current: false,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(MultiConsumer {
+            outputs,
+            frames: 0,
+            conversions: 0,
+            bytes_moved: 0,
+        })
+    }
+
+    /// Start a new frame: queue the conversion of `src` into the primary
+    /// format and drop every secondary output of the previous frame.
+    ///
+    /// The blit is only queued, as with [`G2D::blit`]. If it fails no
+    /// output holds a frame until the next successful call.
+    pub fn set_frame(&mut self, g2d: &G2D, src: &G2DSurface) -> Result<()> {
+        for output in &mut self.outputs {
+            output.current = false;
+        }
+        let primary = &mut self.outputs[0];
+        g2d.blit(src, &primary.surface)?;
+        primary.current = true;
+        self.frames += 1;
+        self.conversions += 1;
+        self.bytes_moved += metrics::bytes_moved(src, &primary.surface);
+        Ok(())
+    }
+
+    /// Buffer and surface holding the current frame in `format`.
+    ///
+    /// The first request for a secondary format during a frame queues its
+    /// conversion from the primary output; later requests return the same
+    /// buffer without converting again. Conversions are only queued; call
+    /// [`G2D::finish`] before reading the buffer. Fails with
+    /// [`Error::InvalidFormat`] for a format that is not an output, and
+    /// with [`Error::InvalidSurface`] before the first frame is set.
+    pub fn get(&mut self, g2d: &G2D, format: G2DFormat) -> Result<(&DmaBuffer, &G2DSurface)> {
+        let index = self
+            .outputs
+            .iter()
+            .position(|output| output.surface.format == format.format())
+            .ok_or_else(|| {
+                Error::InvalidFormat(format!(
+                    "g2d_format {} is not an output of this multi-consumer",
+                    format.format()
+                ))
+            })?;
+        if !self.outputs[0].current {
+            return Err(Error::InvalidSurface(
+                "no frame has been set on this multi-consumer".to_string(),
+            ));
+        }
+
+        if !self.outputs[index].current {
+            let primary = self.outputs[0].surface;
+            let output = &mut self.outputs[index];
+            g2d.blit(&primary, &output.surface)?;
+            output.current = true;
+            self.conversions += 1;
+            self.bytes_moved += metrics::bytes_moved(&primary, &output.surface);
+        }
+        let output = &self.outputs[index];
+        Ok((&output.buffer, &output.surface))
+    }
+
+    /// Output formats, primary first.
+    pub fn formats(&self) -> Vec<G2DFormat> {
+        self.outputs.iter().map(|o| o.surface.format()).collect()
+    }
+
+    /// Total frames set since the multi-consumer was created.
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+
+    /// Total conversions queued: one per frame for the primary format plus
+    /// one per secondary format requested during each frame.
+    pub fn conversions(&self) -> u64 {
+        self.conversions
+    }
+
+    /// Total bytes read and written by those conversions, as estimated by
+    /// [`metrics::bytes_moved`].
+    pub fn bytes_moved(&self) -> u64 {
+        self.bytes_moved
+    }
+}
@@ -0,0 +1,190 @@
+// SPDX-FileCopyrightText: Copyright 2025 Au-Zone Technologies
+// SPDX-License-Identifier: Apache-2.0
+
+//! Per-blit options, some of which need access to the destination buffer.
+//!
+//! libg2d has no per-channel write mask, so options such as preserving the
+//! destination alpha are emulated with a synced CPU pass around the blit.
+
+use crate::{
+    clip::clip_surfaces, dma::DmaBuffer, fill::CropLayout, Capabilities, Error, G2DSurface, Rect,
+    Result, G2D,
+};
+
+/// Options for [`G2D::blit_with_options`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BlitOptions {
+    /// Keep the destination's existing alpha channel instead of writing the
+    /// source (or opaque) alpha. Only honored for 8888 formats with alpha;
+    /// ignored for destinations without an alpha channel.
+    pub preserve_dst_alpha: bool,
+    /// Clip the destination crop to the destination surface bounds, and
+    /// the source crop in proportion, instead of rejecting a crop that
+    /// extends past the edge. A crop entirely outside writes nothing.
+    pub clip_to_bounds: bool,
+    /// Acknowledge that converting a source with alpha into a destination
+    /// without it (see [`G2DFormat::has_alpha`](crate::G2DFormat::has_alpha)) drops the alpha channel.
+    /// Without this, such a blit is counted by [`G2D::alpha_loss_blits`],
+    /// and the first one on a handle logs a warning.
+    pub allow_alpha_loss: bool,
+    /// How an interlaced source's two fields are combined.
+    pub deinterlace: Deinterlace,
+}
+
+/// Field handling for an interlaced source, whose even rows hold the top
+/// field and odd rows the bottom field.
+///
+/// libg2d has no field controls, so `Bob` reads the source through a view
+/// of its top field alone (every other row, at twice the stride) and has
+/// the hardware scaler stretch it back up to the destination height. It is
+/// only available where [`Capabilities::scaling`] is reported; see
+/// [`check()`](Self::check).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Deinterlace {
+    /// Treat the source as progressive.
+    #[default]
+    None,
+    /// Line-double the top field, halving vertical resolution but leaving
+    /// no combing on motion.
+    Bob,
+}
+
+impl Deinterlace {
+    /// Whether hardware with `caps` can apply this mode, failing with
+    /// [`Error::Unsupported`] if not.
+    pub fn check(self, caps: &Capabilities) -> Result<()> {
+        match self {
+            Deinterlace::None => Ok(()),
+            Deinterlace::Bob if caps.scaling => Ok(()),
+            Deinterlace::Bob => Err(Error::Unsupported(
+                "Deinterlace::Bob needs the G2D_SCALING hardware feature".to_string(),
+            )),
+        }
+    }
+}
+
+impl BlitOptions {
+    pub fn with_preserve_dst_alpha(mut self, preserve: bool) -> Self {
+        self.preserve_dst_alpha = preserve;
+        self
+    }
+
+    pub fn with_clip_to_bounds(mut self, clip: bool) -> Self {
+        self.clip_to_bounds = clip;
+        self
+    }
+
+    pub fn with_allow_alpha_loss(mut self, allow: bool) -> Self {
+        self.allow_alpha_loss = allow;
+        self
+    }
+
+    pub fn with_deinterlace(mut self, deinterlace: Deinterlace) -> Self {
+        self.deinterlace = deinterlace;
+        self
+    }
+}
+
+impl G2D {
+    /// Blit with [`BlitOptions`]. `dst_buf` must be the buffer backing
+    /// `dst.planes[0]`.
+    ///
+    /// With `preserve_dst_alpha`, the destination alpha in the crop region
+    /// is saved before the blit and written back after it, so this call
+    /// waits for completion (including any previously queued operations).
+    /// Without it, this is a queued [`blit()`](Self::blit).
+    ///
+    /// `deinterlace` is applied first and `clip_to_bounds` next; the other
+    /// options then act on the clipped region. [`Deinterlace::Bob`] needs
+    /// a source whose height and crop rows are even, and fails with
+    /// [`Error::Unsupported`] on hardware without scaling (see
+    /// [`Deinterlace::check`]) and for a source format whose rows cannot be
+    /// split into fields.
+    pub fn blit_with_options(
+        &self,
+        src: &G2DSurface,
+        dst: &G2DSurface,
+        dst_buf: &mut DmaBuffer,
+        options: &BlitOptions,
+    ) -> Result<()> {
+        let field;
+        let src = match options.deinterlace {
+            Deinterlace::None => src,
+            Deinterlace::Bob => {
+                options.deinterlace.check(&self.capabilities()?)?;
+                field = top_field(src)?;
+                &field
+            }
+        };
+
+        let clipped = if options.clip_to_bounds {
+            let (width, height) = dst.dimensions();
+            match clip_surfaces(src, dst, Rect::new(0, 0, width, height))? {
+                Some(pair) => Some(pair),
+                None => return Ok(()),
+            }
+        } else {
+            None
+        };
+        let (src, dst) = clipped.as_ref().map_or((src, dst), |(src, dst)| (src, dst));
+
+        let format = dst.format();
+        let alpha = match format.alpha_offset() {
+            Some(offset) if options.preserve_dst_alpha => offset,
+            _ => return self.blit_checked(src, dst, options.allow_alpha_loss),
+        };
+        let bpp = format.bytes_per_pixel().ok_or_else(|| {
+            Error::InvalidFormat(format!("g2d_format {} is not packed", dst.format))
+        })?;
+        let Some(layout) = CropLayout::new(dst, dst_buf, bpp)? else {
+            return self.blit_checked(src, dst, options.allow_alpha_loss);
+        };
+
+        // Earlier queued work may still be writing the destination.
+        self.finish()?;
+        let mut saved = Vec::with_capacity(layout.pixels());
+        dst_buf.read_with(|data| {
+            for row in layout.rows() {
+                saved.extend(data[row].chunks_exact(bpp).map(|px| px[alpha]));
+            }
+        });
+
+        self.blit_checked(src, dst, options.allow_alpha_loss)?;
+        self.finish()?;
+
+        dst_buf.write_with(|data| {
+            let mut saved = saved.iter();
+            for row in layout.rows() {
+                for (px, &a) in data[row].chunks_exact_mut(bpp).zip(&mut saved) {
+                    px[alpha] = a;
+                }
+            }
+        });
+        Ok(())
+    }
+}
+
+/// View of the top field of an interlaced `src`: its even rows, read at
+/// twice the stride, with the crop rows halved. Chroma planes of YUV
+/// formats interleave their fields the same way, so they follow the stride.
+fn top_field(src: &G2DSurface) -> Result<G2DSurface> {
+    if src.format().bits_per_pixel().is_none() {
+        return Err(Error::Unsupported(format!(
+            "cannot split g2d_format {} into fields",
+            src.format
+        )));
+    }
+    if src.height % 2 != 0 || src.top % 2 != 0 || src.bottom % 2 != 0 {
+        return Err(Error::InvalidSurface(format!(
+            "interlaced source needs even rows, got height {} and crop rows {}..{}",
+            src.height, src.top, src.bottom
+        )));
+    }
+    Ok(G2DSurface {
+        stride: src.stride * 2,
+        height: src.height / 2,
+        top: src.top / 2,
+        bottom: src.bottom / 2,
+        ..*src
+    })
+}
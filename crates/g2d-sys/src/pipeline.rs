@@ -0,0 +1,217 @@
+// SPDX-FileCopyrightText: Copyright 2025 Au-Zone Technologies
+// SPDX-License-Identifier: Apache-2.0
+
+//! Declarative description of a multi-stage blit pipeline that can be
+//! validated against the hardware before any buffer is allocated.
+//!
+//! [`Pipeline::validate`] is a dry run: it checks every stage's formats,
+//! geometry and hardware feature requirements and reports all problems at
+//! once, without submitting any work to the GPU.
+
+use crate::{
+    g2d_feature, g2d_feature_G2D_DST_YUV, g2d_feature_G2D_ROTATION, g2d_feature_G2D_SCALING,
+    g2d_feature_G2D_SRC_YUV, G2DFormat, Rotation, G2D,
+};
+use std::fmt;
+
+/// Largest surface width or height accepted by [`Pipeline::validate`].
+pub const MAX_DIMENSION: usize = 8192;
+
+/// Format and size of one side of a stage.
+#[derive(Debug, Clone, Copy)]
+pub struct SurfaceSpec {
+    pub format: G2DFormat,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl SurfaceSpec {
+    pub fn new(format: G2DFormat, width: usize, height: usize) -> Self {
+        SurfaceSpec {
+            format,
+            width,
+            height,
+        }
+    }
+}
+
+/// One blit of a pipeline.
+#[derive(Debug, Clone)]
+pub struct Stage {
+    pub name: String,
+    pub src: SurfaceSpec,
+    pub dst: SurfaceSpec,
+    pub rotation: Rotation,
+}
+
+impl Stage {
+    pub fn new(name: impl Into<String>, src: SurfaceSpec, dst: SurfaceSpec) -> Self {
+        Stage {
+            name: name.into(),
+            src,
+            dst,
+            rotation: Rotation::None,
+        }
+    }
+
+    pub fn with_rotation(mut self, rotation: Rotation) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    /// Whether the output size differs from the input size once rotation
+    /// is accounted for.
+    fn scales(&self) -> bool {
+        let (w, h) = match self.rotation {
+            Rotation::Deg90 | Rotation::Deg270 => (self.src.height, self.src.width),
+            Rotation::None | Rotation::Deg180 => (self.src.width, self.src.height),
+        };
+        (w, h) != (self.dst.width, self.dst.height)
+    }
+}
+
+/// Ordered list of stages.
+#[derive(Debug, Clone, Default)]
+pub struct Pipeline {
+    stages: Vec<Stage>,
+}
+
+/// Reason a stage cannot run on the current hardware.
+#[derive(Debug, Clone, Copy)]
+pub enum StageProblem {
+    /// Width or height is zero or above [`MAX_DIMENSION`].
+    InvalidDimensions { width: usize, height: usize },
+    /// The destination format can only be read (see
+    /// [`G2DFormat::is_valid_destination`]).
+    UnsupportedDestinationFormat(G2DFormat),
+    /// G2D writes YUV destinations unrotated only.
+    RotationUnsupportedForFormat(Rotation, G2DFormat),
+    /// The hardware does not report a `g2d_feature` the stage needs.
+    MissingFeature(g2d_feature),
+    /// Querying a feature failed, so the stage could not be checked.
+    QueryFailed(g2d_feature),
+}
+
+/// A problem found in one stage by [`Pipeline::validate`].
+#[derive(Debug, Clone)]
+pub struct StageError {
+    /// Position of the stage in the pipeline.
+    pub index: usize,
+    pub name: String,
+    pub problem: StageProblem,
+}
+
+impl fmt::Display for StageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "stage {} ({}): ", self.index, self.name)?;
+        match self.problem {
+            StageProblem::InvalidDimensions { width, height } => {
+                write!(f, "invalid dimensions {width}x{height}")
+            }
+            StageProblem::UnsupportedDestinationFormat(format) => {
+                write!(f, "unsupported destination format {}", format.format())
+            }
+            StageProblem::RotationUnsupportedForFormat(rotation, format) => write!(
+                f,
+                "{}° rotation unsupported into format {}",
+                rotation.degrees(),
+                format.format()
+            ),
+            StageProblem::MissingFeature(feature) => {
+                write!(f, "hardware lacks g2d_feature {feature}")
+            }
+            StageProblem::QueryFailed(feature) => {
+                write!(f, "failed to query g2d_feature {feature}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for StageError {}
+
+impl Pipeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a stage.
+    pub fn with_stage(mut self, stage: Stage) -> Self {
+        self.stages.push(stage);
+        self
+    }
+
+    pub fn stages(&self) -> &[Stage] {
+        &self.stages
+    }
+
+    /// Check every stage against `g2d` without submitting any work.
+    ///
+    /// Each stage is checked for valid dimensions, a writable destination
+    /// format, rotation into a supported format, and the `g2d_feature`s it
+    /// needs (scaling, rotation, YUV source/destination). All problems are
+    /// collected, so the error lists every misconfiguration at once.
+    pub fn validate(&self, g2d: &G2D) -> Result<(), Vec<StageError>> {
+        let mut errors = Vec::new();
+        for (index, stage) in self.stages.iter().enumerate() {
+            for problem in stage_problems(g2d, stage) {
+                errors.push(StageError {
+                    index,
+                    name: stage.name.clone(),
+                    problem,
+                });
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+fn stage_problems(g2d: &G2D, stage: &Stage) -> Vec<StageProblem> {
+    let mut problems = Vec::new();
+
+    for spec in [&stage.src, &stage.dst] {
+        let valid = |d: usize| (1..=MAX_DIMENSION).contains(&d);
+        if !valid(spec.width) || !valid(spec.height) {
+            problems.push(StageProblem::InvalidDimensions {
+                width: spec.width,
+                height: spec.height,
+            });
+        }
+    }
+
+    if !stage.dst.format.is_valid_destination() {
+        problems.push(StageProblem::UnsupportedDestinationFormat(stage.dst.format));
+    }
+    if stage.rotation != Rotation::None && stage.dst.format.is_yuv() {
+        problems.push(StageProblem::RotationUnsupportedForFormat(
+            stage.rotation,
+            stage.dst.format,
+        ));
+    }
+
+    let mut required = Vec::new();
+    if stage.scales() {
+        required.push(g2d_feature_G2D_SCALING);
+    }
+    if stage.rotation != Rotation::None {
+        required.push(g2d_feature_G2D_ROTATION);
+    }
+    if stage.src.format.is_yuv() {
+        required.push(g2d_feature_G2D_SRC_YUV);
+    }
+    if stage.dst.format.is_yuv() {
+        required.push(g2d_feature_G2D_DST_YUV);
+    }
+    for feature in required {
+        match g2d.query_feature(feature) {
+            Ok(true) => {}
+            Ok(false) => problems.push(StageProblem::MissingFeature(feature)),
+            Err(_) => problems.push(StageProblem::QueryFailed(feature)),
+        }
+    }
+
+    problems
+}
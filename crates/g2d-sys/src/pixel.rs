@@ -0,0 +1,104 @@
+// SPDX-FileCopyrightText: Copyright 2025 Au-Zone Technologies
+// SPDX-License-Identifier: Apache-2.0
+
+//! Typed pixels for reading packed-format buffers without manual offset
+//! arithmetic.
+//!
+//! Each type wraps the in-memory bytes of one pixel, so a byte slice of
+//! that format can be viewed as a slice of pixels in place (see
+//! [`DmaBuffer::as_pixels`](crate::dma::DmaBuffer::as_pixels)).
+
+use crate::{g2d_format, g2d_format_G2D_BGRA8888, g2d_format_G2D_RGB565, g2d_format_G2D_RGBA8888};
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// A pixel of a packed format, laid out exactly as its bytes in memory.
+///
+/// Sealed: implementors must be `#[repr(transparent)]` byte arrays for the
+/// in-place slice casts to be sound.
+pub trait Pixel: sealed::Sealed + Copy + 'static {
+    /// The `g2d_format` whose pixels this type describes.
+    const FORMAT: g2d_format;
+
+    /// The pixel as `[r, g, b, a]`, expanding channels narrower than 8 bits.
+    fn to_rgba(&self) -> [u8; 4];
+}
+
+/// `G2D_RGBA8888` pixel: bytes R, G, B, A.
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RGBA8(pub [u8; 4]);
+
+/// `G2D_BGRA8888` pixel: bytes B, G, R, A.
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BGRA8(pub [u8; 4]);
+
+/// `G2D_RGB565` pixel: a little-endian `u16` with red in the top 5 bits.
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RGB565(pub [u8; 2]);
+
+impl RGB565 {
+    /// The packed 16-bit value.
+    pub fn value(&self) -> u16 {
+        u16::from_le_bytes(self.0)
+    }
+}
+
+impl sealed::Sealed for RGBA8 {}
+impl sealed::Sealed for BGRA8 {}
+impl sealed::Sealed for RGB565 {}
+
+impl Pixel for RGBA8 {
+    const FORMAT: g2d_format = g2d_format_G2D_RGBA8888;
+
+    fn to_rgba(&self) -> [u8; 4] {
+        self.0
+    }
+}
+
+impl Pixel for BGRA8 {
+    const FORMAT: g2d_format = g2d_format_G2D_BGRA8888;
+
+    fn to_rgba(&self) -> [u8; 4] {
+        let [b, g, r, a] = self.0;
+        [r, g, b, a]
+    }
+}
+
+impl Pixel for RGB565 {
+    const FORMAT: g2d_format = g2d_format_G2D_RGB565;
+
+    fn to_rgba(&self) -> [u8; 4] {
+        let v = self.value();
+        let expand5 = |c: u16| ((c << 3) | (c >> 2)) as u8;
+        let expand6 = |c: u16| ((c << 2) | (c >> 4)) as u8;
+        [
+            expand5(v >> 11),
+            expand6((v >> 5) & 0x3f),
+            expand5(v & 0x1f),
+            255,
+        ]
+    }
+}
+
+impl PartialEq<[u8; 4]> for RGBA8 {
+    fn eq(&self, other: &[u8; 4]) -> bool {
+        self.0 == *other
+    }
+}
+
+impl PartialEq<[u8; 4]> for BGRA8 {
+    fn eq(&self, other: &[u8; 4]) -> bool {
+        self.0 == *other
+    }
+}
+
+impl PartialEq<[u8; 2]> for RGB565 {
+    fn eq(&self, other: &[u8; 2]) -> bool {
+        self.0 == *other
+    }
+}
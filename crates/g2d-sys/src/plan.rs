@@ -0,0 +1,209 @@
+// SPDX-FileCopyrightText: Copyright 2025 Au-Zone Technologies
+// SPDX-License-Identifier: Apache-2.0
+
+//! Planning a source-to-destination transform as a sequence of blits the
+//! hardware can run, and running it.
+//!
+//! [`G2D::plan`] turns a format, size and rotation change into the passes
+//! this board needs: a single blit when it can do the whole transform at
+//! once, a chain of passes when the scaling is too large for one, and a
+//! pass through RGBA when a rotated result must end up in a YUV format,
+//! which G2D only writes unrotated. The passes are ordinary [`Stage`]s, so
+//! a plan can be inspected or validated like a
+//! [`Pipeline`](crate::pipeline::Pipeline).
+
+use crate::{
+    dma::DmaBuffer,
+    g2d_feature, g2d_feature_G2D_DST_YUV, g2d_feature_G2D_ROTATION, g2d_feature_G2D_SCALING,
+    g2d_feature_G2D_SRC_YUV, g2d_format_G2D_RGBA8888,
+    pipeline::{Stage, SurfaceSpec, MAX_DIMENSION},
+    Error, G2DFormat, Result, Rotation, G2D, MAX_SCALE_FACTOR,
+};
+use std::cell::RefCell;
+
+/// Largest reduction per axis the planner gives a single pass.
+///
+/// Each output pixel of a G2D blit filters only a small neighborhood of
+/// the source, so a larger reduction in one pass skips source pixels and
+/// aliases. Bigger reductions are split into passes of at most this ratio.
+pub const MAX_PASS_DOWNSCALE: usize = 4;
+
+/// Blits planned by [`G2D::plan`] for one transform, run with
+/// [`run()`](Self::run).
+///
+/// Intermediate buffers are allocated on the first run, from the source
+/// buffer's heap, and reused by later runs.
+#[derive(Debug)]
+pub struct Plan<'a> {
+    g2d: &'a G2D,
+    steps: Vec<Stage>,
+    scratch: RefCell<Vec<DmaBuffer>>,
+}
+
+impl G2D {
+    /// Plan the blits that turn a `src` image into a `dst` image rotated
+    /// by `rotation`, on this board.
+    ///
+    /// `dst` is in output orientation, so a 90° rotation of a 1920×1080
+    /// source into a full-size output is planned with a 1080×1920 `dst`.
+    /// The plan is:
+    ///
+    /// - one blit, when no axis shrinks by more than
+    ///   [`MAX_PASS_DOWNSCALE`] or grows by more than [`MAX_SCALE_FACTOR`]
+    ///   and the rotation, if any, is into a format G2D can write rotated;
+    /// - a chain of passes otherwise, the first doing the rotation and
+    ///   format conversion and each shrinking by at most
+    ///   `MAX_PASS_DOWNSCALE` or growing by at most [`MAX_SCALE_FACTOR`],
+    ///   with intermediates in `dst`'s format, or RGBA8888 when that is
+    ///   YUV;
+    /// - an extra RGBA8888 pass before a rotated YUV output, which is
+    ///   rotated into RGBA and converted unrotated.
+    ///
+    /// Fails with [`Error::InvalidSurface`] for a dimension that is zero or
+    /// above [`MAX_DIMENSION`], with [`Error::UnsupportedDestinationFormat`]
+    /// when `dst` cannot be written, and with [`Error::Unsupported`] when
+    /// the hardware lacks a feature the plan needs (YUV input or output,
+    /// scaling or rotation).
+    pub fn plan(&self, src: SurfaceSpec, dst: SurfaceSpec, rotation: Rotation) -> Result<Plan<'_>> {
+        for spec in [&src, &dst] {
+            let valid = |d: usize| (1..=MAX_DIMENSION).contains(&d);
+            if !valid(spec.width) || !valid(spec.height) {
+                return Err(Error::InvalidSurface(format!(
+                    "cannot plan a {}x{} surface; dimensions must be 1 to {MAX_DIMENSION}",
+                    spec.width, spec.height
+                )));
+            }
+        }
+        if !dst.format.is_valid_destination() {
+            return Err(Error::UnsupportedDestinationFormat(dst.format));
+        }
+
+        let quarter_turn = matches!(rotation, Rotation::Deg90 | Rotation::Deg270);
+        let oriented = if quarter_turn {
+            (src.height, src.width)
+        } else {
+            (src.width, src.height)
+        };
+        let mut sizes = vec![oriented];
+        let mut size = oriented;
+        while size != (dst.width, dst.height) {
+            size = (next_size(size.0, dst.width), next_size(size.1, dst.height));
+            sizes.push(size);
+        }
+        // A pure format conversion or rotation is still one blit.
+        if sizes.len() == 1 {
+            sizes.push(oriented);
+        }
+
+        let intermediate = if dst.format.is_yuv() {
+            G2DFormat(g2d_format_G2D_RGBA8888)
+        } else {
+            dst.format
+        };
+        if rotation != Rotation::None && dst.format.is_yuv() && sizes.len() == 2 {
+            sizes.push(sizes[1]);
+        }
+
+        let passes = sizes.len() - 1;
+        let mut steps = Vec::with_capacity(passes);
+        let mut from = src;
+        for (i, &(width, height)) in sizes[1..].iter().enumerate() {
+            let format = if i + 1 == passes {
+                dst.format
+            } else {
+                intermediate
+            };
+            let to = SurfaceSpec::new(format, width, height);
+            let step = Stage::new(format!("pass {}", i + 1), from, to);
+            steps.push(if i == 0 {
+                step.with_rotation(rotation)
+            } else {
+                step
+            });
+            from = to;
+        }
+
+        let mut required = Vec::new();
+        if src.format.is_yuv() {
+            required.push((g2d_feature_G2D_SRC_YUV, "YUV input"));
+        }
+        if dst.format.is_yuv() {
+            required.push((g2d_feature_G2D_DST_YUV, "YUV output"));
+        }
+        if sizes.windows(2).any(|pair| pair[0] != pair[1]) {
+            required.push((g2d_feature_G2D_SCALING, "scaling"));
+        }
+        if rotation != Rotation::None {
+            required.push((g2d_feature_G2D_ROTATION, "rotation"));
+        }
+        for (feature, name) in required {
+            self.require(feature, name)?;
+        }
+
+        Ok(Plan {
+            g2d: self,
+            steps,
+            scratch: RefCell::new(Vec::new()),
+        })
+    }
+
+    fn require(&self, feature: g2d_feature, name: &str) -> Result<()> {
+        if self.query_feature(feature)? {
+            Ok(())
+        } else {
+            Err(Error::Unsupported(format!(
+                "planned transform needs {name}, which the hardware lacks"
+            )))
+        }
+    }
+}
+
+/// Next size along one axis from `from` towards `to`, shrinking by at most
+/// [`MAX_PASS_DOWNSCALE`] and growing by at most [`MAX_SCALE_FACTOR`].
+fn next_size(from: usize, to: usize) -> usize {
+    to.max(from.div_ceil(MAX_PASS_DOWNSCALE))
+        .min(from * MAX_SCALE_FACTOR as usize)
+}
+
+impl Plan<'_> {
+    /// The planned blits in order; the first reads the source and the last
+    /// writes the destination.
+    pub fn steps(&self) -> &[Stage] {
+        &self.steps
+    }
+
+    /// Run the plan from `src_buf` into `dst_buf` and wait for it.
+    ///
+    /// Both buffers hold full-frame images of the planned formats and sizes
+    /// laid out as by
+    /// [`DmaBuffer::reinterpret_surface`](crate::dma::DmaBuffer::reinterpret_surface)
+    /// at offset 0. All passes are queued before a single
+    /// [`finish()`](G2D::finish).
+    pub fn run(&self, src_buf: &DmaBuffer, dst_buf: &DmaBuffer) -> Result<()> {
+        let mut scratch = self.scratch.borrow_mut();
+        if scratch.is_empty() {
+            *scratch = self.steps[..self.steps.len() - 1]
+                .iter()
+                .map(|step| {
+                    let dst = step.dst;
+                    let size = dst.format.plane_layout(dst.width, dst.height, 1, 1)?.size();
+                    DmaBuffer::new(src_buf.heap_type(), size)
+                })
+                .collect::<Result<_>>()?;
+        }
+
+        let last = self.steps.len() - 1;
+        let queued = self.steps.iter().enumerate().try_for_each(|(i, step)| {
+            let from = if i == 0 { src_buf } else { &scratch[i - 1] };
+            let to = if i == last { dst_buf } else { &scratch[i] };
+            let src =
+                from.reinterpret_surface(step.src.format, step.src.width, step.src.height, 0)?;
+            let dst = to
+                .reinterpret_surface(step.dst.format, step.dst.width, step.dst.height, 0)?
+                .with_rotation(step.rotation);
+            self.g2d.blit(&src, &dst)
+        });
+        let finished = self.g2d.finish();
+        queued.and(finished)
+    }
+}
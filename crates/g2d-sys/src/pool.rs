@@ -0,0 +1,138 @@
+// SPDX-FileCopyrightText: Copyright 2025 Au-Zone Technologies
+// SPDX-License-Identifier: Apache-2.0
+
+//! A bounded number of G2D contexts for a fixed pool of worker threads.
+//!
+//! A [`G2D`] handle is neither `Send` nor `Sync`, so a context cannot be
+//! opened up front and handed to another thread. The pool is shared with
+//! the workers instead, and each worker opens its own context on its own
+//! thread with [`G2DPool::get()`]. At most the pool's capacity of contexts
+//! are open at once, so the driver resources held are fixed when the pool
+//! is built. Each context keeps its own capability state (blending, YUV
+//! matrix) and queue.
+
+use crate::{Error, Result, G2D};
+use std::{
+    ffi::OsStr,
+    ops::Deref,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+type Opener = dyn Fn() -> Result<G2D> + Send + Sync;
+
+/// At most `n` [`G2D`] contexts, each opened on the worker thread that uses
+/// it.
+///
+/// The pool is `Send` and `Sync`: share it with the workers (for example
+/// through an [`Arc`] or scoped threads) and have each one call
+/// [`get()`](Self::get) once and keep the returned [`PooledG2D`] for its
+/// lifetime. The context is used by that thread only, and its slot returns
+/// to the pool when it is dropped.
+pub struct G2DPool {
+    open: Box<Opener>,
+    capacity: usize,
+    /// Contexts currently checked out, shared with every [`PooledG2D`].
+    in_use: Arc<AtomicUsize>,
+}
+
+impl std::fmt::Debug for G2DPool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("G2DPool")
+            .field("capacity", &self.capacity)
+            .field("in_use", &self.in_use())
+            .finish_non_exhaustive()
+    }
+}
+
+impl G2DPool {
+    /// A pool of up to `n` contexts, each loading libg2d from `path`.
+    ///
+    /// Fails with [`Error::InvalidPoolCapacity`] if `n` is zero.
+    pub fn with_capacity<P>(path: P, n: usize) -> Result<Self>
+    where
+        P: AsRef<OsStr>,
+    {
+        let path = path.as_ref().to_owned();
+        Self::with_opener(n, move || G2D::new(&path))
+    }
+
+    /// A pool of up to `n` contexts, each opened by calling `open` on the
+    /// thread that asks for it, such as [`G2D::mock`] in tests.
+    ///
+    /// Fails with [`Error::InvalidPoolCapacity`] if `n` is zero.
+    pub fn with_opener<F>(n: usize, open: F) -> Result<Self>
+    where
+        F: Fn() -> Result<G2D> + Send + Sync + 'static,
+    {
+        if n == 0 {
+            return Err(Error::InvalidPoolCapacity);
+        }
+        Ok(G2DPool {
+            open: Box::new(open),
+            capacity: n,
+            in_use: Arc::new(AtomicUsize::new(0)),
+        })
+    }
+
+    /// Open a context for the calling thread.
+    ///
+    /// Fails with [`Error::PoolExhausted`] while all `capacity()` contexts
+    /// are checked out, and with the opener's error if the context cannot
+    /// be opened; neither takes a slot.
+    pub fn get(&self) -> Result<PooledG2D> {
+        self.in_use
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |n| {
+                (n < self.capacity).then_some(n + 1)
+            })
+            .map_err(|_| Error::PoolExhausted {
+                capacity: self.capacity,
+            })?;
+        match (self.open)() {
+            Ok(g2d) => Ok(PooledG2D {
+                g2d,
+                in_use: self.in_use.clone(),
+            }),
+            Err(e) => {
+                self.in_use.fetch_sub(1, Ordering::AcqRel);
+                Err(e)
+            }
+        }
+    }
+
+    /// Maximum number of contexts open at once.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of contexts currently checked out.
+    pub fn in_use(&self) -> usize {
+        self.in_use.load(Ordering::Acquire)
+    }
+}
+
+/// A context checked out of a [`G2DPool`], usable as a [`G2D`].
+///
+/// Like the handle it wraps, it stays on the thread that opened it. The
+/// context is closed and its slot returned to the pool on drop.
+#[derive(Debug)]
+pub struct PooledG2D {
+    g2d: G2D,
+    in_use: Arc<AtomicUsize>,
+}
+
+impl Deref for PooledG2D {
+    type Target = G2D;
+
+    fn deref(&self) -> &G2D {
+        &self.g2d
+    }
+}
+
+impl Drop for PooledG2D {
+    fn drop(&mut self) {
+        self.in_use.fetch_sub(1, Ordering::AcqRel);
+    }
+}
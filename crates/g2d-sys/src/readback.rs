@@ -0,0 +1,50 @@
+// SPDX-FileCopyrightText: Copyright 2025 Au-Zone Technologies
+// SPDX-License-Identifier: Apache-2.0
+
+//! Convert a frame and copy the result into caller-owned memory, for handing
+//! converted bytes to libraries that do not take DMA buffers.
+
+use crate::{
+    dma::{DmaBuffer, HeapType},
+    G2DFormat, G2DSurface, Result, G2D,
+};
+
+impl G2D {
+    /// Convert the crop of `src` to a tightly packed `dst_dims` (width,
+    /// height) image of `dst_format`, wait for it, and copy it into `out`.
+    ///
+    /// `out` is cleared and filled with exactly the image's bytes (its size
+    /// from [`G2DFormat::plane_layout`] with no alignment), so its
+    /// allocation is reused once it has grown to one frame. The blit goes
+    /// into a destination buffer kept on the handle and reallocated only
+    /// when a larger frame needs it; it comes from the cached CMA heap,
+    /// whose invalidate-then-copy reads are faster than CPU reads of
+    /// uncached memory, or the uncached heap if the cached one is missing
+    /// (see [`DmaBuffer::new_preferring`]). With neither heap, this fails
+    /// with [`Error::HeapUnavailable`](crate::Error::HeapUnavailable).
+    pub fn convert_into_vec(
+        &self,
+        src: &G2DSurface,
+        dst_format: G2DFormat,
+        dst_dims: (usize, usize),
+        out: &mut Vec<u8>,
+    ) -> Result<()> {
+        let (width, height) = dst_dims;
+        let size = dst_format.plane_layout(width, height, 1, 1)?.size();
+
+        let mut readback = self.readback.borrow_mut();
+        let buffer = match readback.take() {
+            Some(buffer) if buffer.size() >= size => buffer,
+            _ => DmaBuffer::new_preferring(HeapType::Cached, size)?,
+        };
+        let buffer = readback.insert(buffer);
+
+        let dst = buffer.reinterpret_surface(dst_format, width, height, 0)?;
+        self.blit(src, &dst)?;
+        self.finish()?;
+
+        out.clear();
+        buffer.read_with(|data| out.extend_from_slice(&data[..size]));
+        Ok(())
+    }
+}
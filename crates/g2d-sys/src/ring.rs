@@ -0,0 +1,103 @@
+// SPDX-FileCopyrightText: Copyright 2025 Au-Zone Technologies
+// SPDX-License-Identifier: Apache-2.0
+
+//! Fixed-size history of converted frames, for event capture that needs the
+//! last N frames before a trigger.
+
+use crate::{
+    dma::{DmaBuffer, HeapType},
+    Error, G2DFormat, G2DSurface, Result, G2D,
+};
+
+/// Ring of equally sized DMA buffers written round-robin by
+/// [`push_blit()`](Self::push_blit).
+///
+/// Once every slot has been written, each push overwrites the oldest frame.
+#[derive(Debug)]
+pub struct FrameRing {
+    slots: Vec<(DmaBuffer, G2DSurface)>,
+    /// Total frames pushed; the next frame goes to `pushed % capacity`.
+    pushed: u64,
+}
+
+impl FrameRing {
+    /// Allocate `capacity` full-frame `width`×`height` buffers of `format`
+    /// from `heap_type`, laid out as by
+    /// [`DmaBuffer::reinterpret_surface`].
+    pub fn new(
+        capacity: usize,
+        width: usize,
+        height: usize,
+        format: G2DFormat,
+        heap_type: HeapType,
+    ) -> Result<Self> {
+        if capacity == 0 {
+            return Err(Error::InvalidSurface(
+                "frame ring capacity must be at least 1".to_string(),
+            ));
+        }
+        let size = format.plane_layout(width, height, 1, 1)?.size();
+
+        let slots = (0..capacity)
+            .map(|_| {
+                let buffer = DmaBuffer::new(heap_type, size)?;
+                let surface = buffer.reinterpret_surface(format, width, height, 0)?;
+                Ok((buffer, surface))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(FrameRing { slots, pushed: 0 })
+    }
+
+    /// Queue a blit of `src` into the next slot and return its index.
+    ///
+    /// The blit is only queued, as with [`G2D::blit`]; call
+    /// [`G2D::finish`] before reading the slot. The slot is claimed even if
+    /// the blit fails, so a failed push leaves a stale frame behind rather
+    /// than breaking the ring order.
+    pub fn push_blit(&mut self, g2d: &G2D, src: &G2DSurface) -> Result<usize> {
+        let slot = (self.pushed % self.slots.len() as u64) as usize;
+        self.pushed += 1;
+        g2d.blit(src, &self.slots[slot].1)?;
+        Ok(slot)
+    }
+
+    /// Buffer and surface of `slot`, or `None` if the slot is out of range
+    /// or has not been written yet.
+    pub fn get(&self, slot: usize) -> Option<(&DmaBuffer, &G2DSurface)> {
+        if slot as u64 >= self.pushed {
+            return None;
+        }
+        self.slots
+            .get(slot)
+            .map(|(buffer, surface)| (buffer, surface))
+    }
+
+    /// Slot holding the frame pushed `age` frames ago (0 is the most recent),
+    /// or `None` if that frame has been overwritten or was never pushed.
+    pub fn slot_for_age(&self, age: usize) -> Option<usize> {
+        let age = age as u64;
+        if age >= self.pushed || age >= self.slots.len() as u64 {
+            return None;
+        }
+        Some(((self.pushed - 1 - age) % self.slots.len() as u64) as usize)
+    }
+
+    /// Number of slots.
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Number of slots holding a frame.
+    pub fn len(&self) -> usize {
+        self.pushed.min(self.slots.len() as u64) as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pushed == 0
+    }
+
+    /// Total frames pushed since the ring was created.
+    pub fn pushed(&self) -> u64 {
+        self.pushed
+    }
+}
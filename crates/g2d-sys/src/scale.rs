@@ -0,0 +1,57 @@
+// SPDX-FileCopyrightText: Copyright 2025 Au-Zone Technologies
+// SPDX-License-Identifier: Apache-2.0
+
+//! One source scaled to several output resolutions in a single batch.
+
+use crate::{dma::DmaBuffer, Error, G2DFormat, G2DSurface, Result, G2D};
+
+impl G2D {
+    /// Blit `src` scaled into each `(buffer, width, height)` output, in
+    /// `format`, and wait once for all of them.
+    ///
+    /// Every output is a full-frame packed surface with `stride == width`.
+    /// All blits are queued before a single [`finish()`](Self::finish), so
+    /// feeding several models from one decoded frame costs one wait instead
+    /// of one per resolution. Returns the output surfaces in input order.
+    pub fn multi_scale(
+        &self,
+        src: &G2DSurface,
+        format: G2DFormat,
+        outputs: &[(&DmaBuffer, usize, usize)],
+    ) -> Result<Vec<G2DSurface>> {
+        let bpp = format.bytes_per_pixel().ok_or_else(|| {
+            Error::InvalidFormat(format!(
+                "{:?} is not a packed destination format",
+                format.format()
+            ))
+        })?;
+
+        let surfaces = outputs
+            .iter()
+            .map(|&(buffer, width, height)| {
+                let size = width * height * bpp;
+                if size > buffer.size() {
+                    return Err(Error::InvalidSurface(format!(
+                        "{width}x{height} output needs {size} bytes, buffer has {}",
+                        buffer.size()
+                    )));
+                }
+                Ok(G2DSurface {
+                    format: format.format(),
+                    planes: [buffer.address(), 0, 0],
+                    right: width as i32,
+                    bottom: height as i32,
+                    stride: width as i32,
+                    width: width as i32,
+                    height: height as i32,
+                    ..Default::default()
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let queued = surfaces.iter().try_for_each(|dst| self.blit(src, dst));
+        let finished = self.finish();
+        queued.and(finished)?;
+        Ok(surfaces)
+    }
+}
@@ -0,0 +1,343 @@
+// SPDX-FileCopyrightText: Copyright 2025 Au-Zone Technologies
+// SPDX-License-Identifier: Apache-2.0
+
+//! Iterator adapter for converting a sequence of frames.
+//!
+//! [`G2D::process_stream`] blits each input frame into a pooled destination
+//! buffer, flushing after every submission so the GPU starts on frame N while
+//! frame N+1 is being queued. `g2d_finish` waits for all queued work, so
+//! frames are completed in batches of [`ConvertSpec::depth`] with a single
+//! wait per batch instead of one per frame.
+//!
+//! For live sources, [`ConvertSpec::drop_policy`] decides what happens when
+//! frames arrive faster than the GPU converts them (see [`DropPolicy`]).
+
+use crate::{
+    dma::{DmaBuffer, HeapType},
+    Error, G2DFormat, G2DSurface, Result, G2D,
+};
+use std::{cell::RefCell, collections::VecDeque, rc::Rc, time::Instant};
+
+/// Default number of frames in flight per batch.
+pub const DEFAULT_STREAM_DEPTH: usize = 4;
+
+/// Input frame for [`G2D::process_stream`].
+///
+/// Borrows the backing buffer so it stays alive until the GPU has finished
+/// reading it.
+#[derive(Debug, Clone, Copy)]
+pub struct FrameRef<'a> {
+    pub buffer: &'a DmaBuffer,
+    pub surface: G2DSurface,
+    /// Capture time; frames without one count as already arrived.
+    pub timestamp: Option<Instant>,
+}
+
+impl<'a> FrameRef<'a> {
+    pub fn new(buffer: &'a DmaBuffer, surface: G2DSurface) -> Self {
+        FrameRef {
+            buffer,
+            surface,
+            timestamp: None,
+        }
+    }
+
+    pub fn with_timestamp(mut self, timestamp: Instant) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+
+    fn arrived_by(&self, now: Instant) -> bool {
+        self.timestamp.is_none_or(|t| t <= now)
+    }
+}
+
+/// What a stream does with frames that arrive while its queue is full.
+///
+/// Each batch starts by queueing the frames that have already arrived (by
+/// [`FrameRef::timestamp`]), up to [`ConvertSpec::depth`]. Frames already
+/// submitted to the GPU are never dropped, and neither are frames without a
+/// timestamp: nothing shows they are late, so they wait as under `Block`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DropPolicy {
+    /// Convert every frame, pulling no more than a batch at a time
+    /// (backpressure on the source).
+    #[default]
+    Block,
+    /// Discard the oldest queued frame to make room, keeping latency low.
+    DropOldest,
+    /// Discard the arriving frame, keeping the queued ones.
+    DropNewest,
+}
+
+/// Fixed conversion applied to every frame of a stream.
+///
+/// The destination format must be a packed (single plane) format.
+#[derive(Debug, Clone, Copy)]
+pub struct ConvertSpec {
+    pub width: usize,
+    pub height: usize,
+    pub format: G2DFormat,
+    /// Heap used for the pooled destination buffers.
+    pub heap_type: HeapType,
+    /// Frames submitted before each `finish`; clamped to at least 1.
+    pub depth: usize,
+    /// Handling of frames that arrive faster than they are converted.
+    pub drop_policy: DropPolicy,
+}
+
+impl ConvertSpec {
+    pub fn new(width: usize, height: usize, format: G2DFormat, heap_type: HeapType) -> Self {
+        ConvertSpec {
+            width,
+            height,
+            format,
+            heap_type,
+            depth: DEFAULT_STREAM_DEPTH,
+            drop_policy: DropPolicy::Block,
+        }
+    }
+
+    pub fn with_depth(mut self, depth: usize) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    pub fn with_drop_policy(mut self, drop_policy: DropPolicy) -> Self {
+        self.drop_policy = drop_policy;
+        self
+    }
+
+    fn buffer_size(&self) -> Result<usize> {
+        let bpp = self.format.bytes_per_pixel().ok_or_else(|| {
+            Error::InvalidFormat(format!(
+                "{:?} is not a packed destination format",
+                self.format
+            ))
+        })?;
+        Ok(self.width * self.height * bpp)
+    }
+
+    fn surface(&self, buffer: &DmaBuffer) -> G2DSurface {
+        G2DSurface {
+            format: self.format.format(),
+            planes: [buffer.address(), 0, 0],
+            right: self.width as i32,
+            bottom: self.height as i32,
+            stride: self.width as i32,
+            width: self.width as i32,
+            height: self.height as i32,
+            ..Default::default()
+        }
+    }
+}
+
+type BufferPool = Rc<RefCell<Vec<DmaBuffer>>>;
+
+/// Converted frame yielded by [`FrameStream`].
+///
+/// The destination buffer returns to the stream's pool when dropped, so
+/// holding on to output frames makes the pool allocate more buffers.
+#[derive(Debug)]
+pub struct OutputFrame {
+    index: usize,
+    surface: G2DSurface,
+    buffer: Option<DmaBuffer>,
+    pool: BufferPool,
+}
+
+impl OutputFrame {
+    /// Position of the frame in the input sequence.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Destination surface describing the converted frame.
+    pub fn surface(&self) -> &G2DSurface {
+        &self.surface
+    }
+
+    /// Buffer holding the converted pixels.
+    pub fn buffer(&self) -> &DmaBuffer {
+        self.buffer.as_ref().expect("buffer present until drop")
+    }
+}
+
+impl Drop for OutputFrame {
+    fn drop(&mut self) {
+        if let Some(buffer) = self.buffer.take() {
+            self.pool.borrow_mut().push(buffer);
+        }
+    }
+}
+
+/// Iterator returned by [`G2D::process_stream`].
+pub struct FrameStream<'g, 'a, I>
+where
+    I: Iterator<Item = FrameRef<'a>>,
+{
+    g2d: &'g G2D,
+    frames: I,
+    spec: ConvertSpec,
+    pool: BufferPool,
+    next_index: usize,
+    /// Frames queued for the next batch, with their input index.
+    pending: VecDeque<(usize, FrameRef<'a>)>,
+    /// Frame pulled from the source before its timestamp was reached.
+    early: Option<(usize, FrameRef<'a>)>,
+    dropped: usize,
+    /// Frames submitted but not yet finished; the source borrow is held until
+    /// the batch completes.
+    in_flight: Vec<(FrameRef<'a>, OutputFrame)>,
+    ready: VecDeque<Result<OutputFrame>>,
+}
+
+impl<'a, I> FrameStream<'_, 'a, I>
+where
+    I: Iterator<Item = FrameRef<'a>>,
+{
+    fn acquire(&self) -> Result<DmaBuffer> {
+        if let Some(buffer) = self.pool.borrow_mut().pop() {
+            return Ok(buffer);
+        }
+        DmaBuffer::new(self.spec.heap_type, self.spec.buffer_size()?)
+    }
+
+    /// Number of frames discarded by the [`DropPolicy`] so far.
+    pub fn dropped(&self) -> usize {
+        self.dropped
+    }
+
+    fn pull(&mut self) -> Option<(usize, FrameRef<'a>)> {
+        let frame = self.frames.next()?;
+        let index = self.next_index;
+        self.next_index += 1;
+        Some((index, frame))
+    }
+
+    /// Queue the frames that have arrived, applying the drop policy once the
+    /// queue holds `depth` frames.
+    fn fill_pending(&mut self) {
+        let capacity = self.spec.depth.max(1);
+        let policy = self.spec.drop_policy;
+        let now = Instant::now();
+        loop {
+            if policy == DropPolicy::Block && self.pending.len() >= capacity {
+                break;
+            }
+            let Some((index, frame)) = self.early.take().or_else(|| self.pull()) else {
+                break;
+            };
+            // A live source would block for the first frame anyway, but later
+            // frames from the future have not arrived yet.
+            if policy != DropPolicy::Block && !self.pending.is_empty() && !frame.arrived_by(now) {
+                self.early = Some((index, frame));
+                break;
+            }
+            if self.pending.len() < capacity {
+                self.pending.push_back((index, frame));
+                continue;
+            }
+            // Dropping untimestamped frames would drain an unbounded source
+            // without ever finishing the batch.
+            if frame.timestamp.is_none() {
+                self.early = Some((index, frame));
+                break;
+            }
+            self.dropped += 1;
+            if policy == DropPolicy::DropOldest {
+                self.pending.pop_front();
+                self.pending.push_back((index, frame));
+            }
+        }
+    }
+
+    fn submit(&mut self, index: usize, frame: FrameRef<'a>) -> Result<()> {
+        let buffer = self.acquire()?;
+        let output = OutputFrame {
+            index,
+            surface: self.spec.surface(&buffer),
+            buffer: Some(buffer),
+            pool: self.pool.clone(),
+        };
+
+        self.g2d.blit(&frame.surface, &output.surface)?;
+        self.g2d.flush()?;
+        self.in_flight.push((frame, output));
+        Ok(())
+    }
+
+    /// Submit the queued frames, wait once, and move them to `ready`.
+    fn run_batch(&mut self) {
+        self.fill_pending();
+        let mut error = None;
+        while let Some((index, frame)) = self.pending.pop_front() {
+            if let Err(e) = self.submit(index, frame) {
+                error = Some(e);
+                break;
+            }
+        }
+
+        if !self.in_flight.is_empty() {
+            match self.g2d.finish() {
+                Ok(()) => self
+                    .ready
+                    .extend(self.in_flight.drain(..).map(|(_, output)| Ok(output))),
+                Err(e) => {
+                    self.in_flight.clear();
+                    self.ready.push_back(Err(e));
+                }
+            }
+        }
+        if let Some(e) = error {
+            self.ready.push_back(Err(e));
+        }
+    }
+}
+
+impl<'a, I> Iterator for FrameStream<'_, 'a, I>
+where
+    I: Iterator<Item = FrameRef<'a>>,
+{
+    type Item = Result<OutputFrame>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.ready.is_empty() {
+            self.run_batch();
+        }
+        self.ready.pop_front()
+    }
+}
+
+impl G2D {
+    /// Convert every frame from `frames` with a fixed `convert` spec.
+    ///
+    /// Frames are submitted in batches of `convert.depth` with a flush after
+    /// each blit and a single [`finish()`](Self::finish) per batch, so the
+    /// GPU overlaps with submission. Outputs are yielded in input order; a
+    /// failed submission or wait yields an `Err` for that batch and the
+    /// stream continues with the next frames. With a dropping
+    /// [`DropPolicy`], some inputs produce no output; the indices of the
+    /// remaining outputs still refer to input positions.
+    pub fn process_stream<'g, 'a, I>(
+        &'g self,
+        frames: I,
+        convert: ConvertSpec,
+    ) -> FrameStream<'g, 'a, I::IntoIter>
+    where
+        I: IntoIterator<Item = FrameRef<'a>>,
+    {
+        FrameStream {
+            g2d: self,
+            frames: frames.into_iter(),
+            spec: convert,
+            pool: Rc::new(RefCell::new(Vec::new())),
+            next_index: 0,
+            pending: VecDeque::new(),
+            early: None,
+            dropped: 0,
+            in_flight: Vec::new(),
+            ready: VecDeque::new(),
+        }
+    }
+}
@@ -0,0 +1,57 @@
+// SPDX-FileCopyrightText: Copyright 2025 Au-Zone Technologies
+// SPDX-License-Identifier: Apache-2.0
+
+//! Row stride alignment for G2D surfaces.
+//!
+//! libg2d has no call reporting the stride alignment it expects, so the
+//! values come from a table. They are conservative for every supported SoC
+//! (i.MX 8M Plus, i.MX 95): a 64-byte row pitch, which also matches the
+//! display scanout pitch ([`dma::SCANOUT_PITCH_ALIGN`](crate::dma), which
+//! is defined from it) and the
+//! cache line size, and 128 bytes for three-plane 4:2:0 formats so the
+//! half-width chroma rows stay 64-byte aligned too.
+
+use crate::{g2d_format_G2D_I420, g2d_format_G2D_YV12, G2DFormat};
+
+/// Row pitch alignment in bytes for most formats.
+pub(crate) const PITCH_ALIGN: usize = 64;
+
+impl G2DFormat {
+    /// Alignment in bytes that a surface's row pitch (`stride` times bytes
+    /// per pixel, or the luma row for YUV formats) should meet for this
+    /// format.
+    ///
+    /// Always a power of two of at least 16. Pass it as the `stride_align`
+    /// of [`plane_layout()`](Self::plane_layout) to lay out a G2D-aligned
+    /// image.
+    pub fn required_stride_alignment(&self) -> usize {
+        match self.format() {
+            g2d_format_G2D_I420 | g2d_format_G2D_YV12 => PITCH_ALIGN * 2,
+            _ => PITCH_ALIGN,
+        }
+    }
+
+    /// Smallest `G2DSurface::stride` (in pixels) of at least `width` whose
+    /// row pitch meets [`required_stride_alignment()`](Self::required_stride_alignment).
+    ///
+    /// Planar and semi-planar YUV formats are sized by their 1-byte luma
+    /// samples. For 3-byte formats the pitch is also a whole number of
+    /// pixels, so it is aligned to three times the alignment.
+    pub fn aligned_stride(&self, width: usize) -> usize {
+        let bpp = self.bytes_per_pixel().unwrap_or(1);
+        let align = pixel_alignment(self.required_stride_alignment(), bpp);
+        (width * bpp).next_multiple_of(align) / bpp
+    }
+}
+
+/// `align` (at least 1) raised so that a row pitch meeting it is also a
+/// whole number of `bpp`-byte pixels. `bpp` is 1..=4, so it either divides
+/// a power-of-two alignment or is coprime to it.
+pub(crate) fn pixel_alignment(align: usize, bpp: usize) -> usize {
+    let align = align.max(1);
+    if align.is_multiple_of(bpp) {
+        align
+    } else {
+        align * bpp
+    }
+}
@@ -0,0 +1,150 @@
+// SPDX-FileCopyrightText: Copyright 2025 Au-Zone Technologies
+// SPDX-License-Identifier: Apache-2.0
+
+//! CPU cache parameters and `DMA_BUF_IOCTL_SYNC` bracketing for CPU access
+//! to DMA-buf memory.
+//!
+//! See [ARCHITECTURE.md](https://github.com/EdgeFirstAI/g2d-rs/blob/main/ARCHITECTURE.md#cpu-cache-coherency)
+//! for the cache coherency protocol.
+
+use std::{
+    io,
+    os::fd::{AsFd, AsRawFd, BorrowedFd, OwnedFd},
+    sync::OnceLock,
+};
+
+// =============================================================================
+// DMA-buf synchronization constants (linux/dma-buf.h)
+// =============================================================================
+
+const DMA_BUF_BASE: u8 = b'b';
+const DMA_BUF_IOCTL_SYNC_NR: u8 = 0;
+
+pub(crate) const DMA_BUF_SYNC_READ: u64 = 1 << 0;
+pub(crate) const DMA_BUF_SYNC_WRITE: u64 = 1 << 1;
+pub(crate) const DMA_BUF_SYNC_START: u64 = 0 << 2;
+pub(crate) const DMA_BUF_SYNC_END: u64 = 1 << 2;
+
+/// `struct dma_buf_sync`.
+#[repr(C)]
+struct DmaBufSync {
+    flags: u64,
+}
+
+/// `DMA_BUF_IOCTL_SYNC`, `_IOW('b', 0, struct dma_buf_sync)`:
+/// direction=1 (write), size=8, type='b', nr=0.
+pub const DMA_BUF_IOCTL_SYNC: libc::c_ulong = (1 << 30)
+    | ((std::mem::size_of::<DmaBufSync>() as libc::c_ulong) << 16)
+    | ((DMA_BUF_BASE as libc::c_ulong) << 8)
+    | DMA_BUF_IOCTL_SYNC_NR as libc::c_ulong;
+
+/// `DMA_BUF_IOCTL_SYNC` bracketing for CPU access to a DMA-buf mapped by
+/// the caller, for buffers not allocated through
+/// [`DmaBuffer`](crate::dma::DmaBuffer), which does this itself.
+///
+/// Every CPU read of the mapping goes between
+/// [`begin_cpu_read()`](Self::begin_cpu_read) and
+/// [`end_cpu_read()`](Self::end_cpu_read), and every write between
+/// [`begin_cpu_write()`](Self::begin_cpu_write) and
+/// [`end_cpu_write()`](Self::end_cpu_write). The kernel decides what a sync
+/// costs: on an uncached heap the mapping bypasses the cache and the calls
+/// are cheap no-ops, while on a cached heap the start of a read invalidates
+/// stale lines and the end of a write cleans dirty ones.
+///
+/// The CMA heap only performs that maintenance for buffers with at least
+/// one `dma_buf_attach`. A buffer that has merely been allocated and mapped
+/// has none, so on a cached heap every sync silently does nothing until a
+/// device attaches; keep a persistent attachment for the buffer's lifetime,
+/// such as the DRM PRIME import that `DmaBuffer` makes for cached buffers.
+#[derive(Debug, Clone, Copy)]
+pub struct CacheSync<'a> {
+    fd: BorrowedFd<'a>,
+}
+
+impl<'a> CacheSync<'a> {
+    /// Sync helper for the DMA-buf `fd`.
+    pub fn new(fd: &'a OwnedFd) -> Self {
+        CacheSync { fd: fd.as_fd() }
+    }
+
+    /// Begin CPU reads: on a cached heap, invalidates lines so the CPU sees
+    /// device writes.
+    pub fn begin_cpu_read(&self) -> io::Result<()> {
+        self.sync(DMA_BUF_SYNC_START | DMA_BUF_SYNC_READ)
+    }
+
+    /// End CPU reads begun with [`begin_cpu_read()`](Self::begin_cpu_read).
+    pub fn end_cpu_read(&self) -> io::Result<()> {
+        self.sync(DMA_BUF_SYNC_END | DMA_BUF_SYNC_READ)
+    }
+
+    /// Begin CPU writes.
+    pub fn begin_cpu_write(&self) -> io::Result<()> {
+        self.sync(DMA_BUF_SYNC_START | DMA_BUF_SYNC_WRITE)
+    }
+
+    /// End CPU writes: on a cached heap, cleans lines so devices see them.
+    pub fn end_cpu_write(&self) -> io::Result<()> {
+        self.sync(DMA_BUF_SYNC_END | DMA_BUF_SYNC_WRITE)
+    }
+
+    /// Issue `DMA_BUF_IOCTL_SYNC` with `flags`, retrying when interrupted
+    /// (`EINTR`/`EAGAIN`) and otherwise returning the OS error.
+    pub(crate) fn sync(&self, flags: u64) -> io::Result<()> {
+        let sync = DmaBufSync { flags };
+        loop {
+            if unsafe { libc::ioctl(self.fd.as_raw_fd(), DMA_BUF_IOCTL_SYNC, &sync) } != -1 {
+                return Ok(());
+            }
+            let err = io::Error::last_os_error();
+            if !matches!(err.raw_os_error(), Some(libc::EINTR | libc::EAGAIN)) {
+                return Err(err);
+            }
+        }
+    }
+}
+
+/// Used when neither sysfs nor `sysconf` report a line size. 64 bytes is the
+/// data cache line on every Cortex-A core shipped in i.MX 8/9 parts.
+const DEFAULT_CACHE_LINE_SIZE: usize = 64;
+
+/// CPU data cache line size in bytes.
+///
+/// Sub-range syncs and CPU writes that share a cache line with GPU output
+/// must be aligned to this size, otherwise a clean or invalidate of the
+/// partial line can clobber the neighbouring bytes.
+///
+/// Read once from `/sys/devices/system/cpu/cpu0/cache` (the largest
+/// `coherency_line_size` across levels), then `sysconf`, falling back to
+/// 64. Always a power of two.
+pub fn cache_line_size() -> usize {
+    static SIZE: OnceLock<usize> = OnceLock::new();
+    *SIZE.get_or_init(|| {
+        sysfs_line_size()
+            .or_else(sysconf_line_size)
+            .filter(|size| size.is_power_of_two())
+            .unwrap_or(DEFAULT_CACHE_LINE_SIZE)
+    })
+}
+
+fn sysfs_line_size() -> Option<usize> {
+    let entries = std::fs::read_dir("/sys/devices/system/cpu/cpu0/cache").ok()?;
+    entries
+        .filter_map(|entry| {
+            let path = entry.ok()?.path().join("coherency_line_size");
+            std::fs::read_to_string(path).ok()?.trim().parse().ok()
+        })
+        .filter(|&size: &usize| size > 0)
+        .max()
+}
+
+#[cfg(target_env = "gnu")]
+fn sysconf_line_size() -> Option<usize> {
+    let size = unsafe { libc::sysconf(libc::_SC_LEVEL1_DCACHE_LINESIZE) };
+    (size > 0).then_some(size as usize)
+}
+
+#[cfg(not(target_env = "gnu"))]
+fn sysconf_line_size() -> Option<usize> {
+    None
+}
@@ -0,0 +1,173 @@
+// SPDX-FileCopyrightText: Copyright 2025 Au-Zone Technologies
+// SPDX-License-Identifier: Apache-2.0
+
+//! Helpers for validating GPU output in tests and tools.
+
+use crate::{dma::DmaBuffer, Error, G2DFormat, Rect, Result};
+
+/// Compare chunk size: large enough for `memcmp` to dominate, small enough
+/// that locating the mismatch within a chunk stays cheap.
+const COMPARE_CHUNK: usize = 4096;
+
+/// CRC-32 (IEEE 802.3, reflected) lookup table.
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+/// Compare the contents of two DMA buffers.
+///
+/// Returns the offset of the first differing byte, or `None` if the buffers
+/// are bit-identical. If the buffers differ in size but share a common
+/// prefix, the offset is the length of the shorter buffer.
+///
+/// Both buffers are read under `DMA_BUF_SYNC_READ` bracketing, so the result
+/// reflects completed GPU writes once [`G2D::finish`](crate::G2D::finish)
+/// has returned.
+pub fn buffers_equal(a: &DmaBuffer, b: &DmaBuffer) -> Option<usize> {
+    a.read_with(|a| b.read_with(|b| first_difference(a, b)))
+}
+
+fn first_difference(a: &[u8], b: &[u8]) -> Option<usize> {
+    let len = a.len().min(b.len());
+    for (index, (ca, cb)) in a[..len]
+        .chunks(COMPARE_CHUNK)
+        .zip(b[..len].chunks(COMPARE_CHUNK))
+        .enumerate()
+    {
+        if ca != cb {
+            let pos = ca.iter().zip(cb).position(|(x, y)| x != y).unwrap_or(0);
+            return Some(index * COMPARE_CHUNK + pos);
+        }
+    }
+
+    if a.len() != b.len() {
+        Some(len)
+    } else {
+        None
+    }
+}
+
+/// CRC-32 of `data`, the IEEE 802.3 checksum used by zlib and PNG.
+pub fn crc32(data: &[u8]) -> u32 {
+    !crc32_update(!0, data)
+}
+
+fn crc32_update(crc: u32, data: &[u8]) -> u32 {
+    data.iter().fold(crc, |crc, &b| {
+        CRC32_TABLE[((crc ^ b as u32) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
+
+impl DmaBuffer {
+    /// [`crc32`] of the `width`×`height` image of `format` at the start of
+    /// the buffer, read under a single `DMA_BUF_SYNC_READ` bracket.
+    ///
+    /// For packed formats only the pixels of each row are hashed, skipping
+    /// the padding of buffers with a [`pitch()`](Self::pitch); other
+    /// formats are hashed as their tightly packed planes. Cheaper than
+    /// copying the image out when only "did the output change" matters.
+    pub fn crc32(&self, format: G2DFormat, width: usize, height: usize) -> Result<u32> {
+        let (row_len, pitch, rows) = match format.bytes_per_pixel() {
+            Some(bpp) => {
+                let row_len = width * bpp;
+                let pitch = self.pitch().filter(|&p| p >= row_len).unwrap_or(row_len);
+                (row_len, pitch, height)
+            }
+            None => {
+                let bits = format.bits_per_pixel().ok_or_else(|| {
+                    Error::InvalidFormat(format!(
+                        "cannot size an image of g2d_format {}",
+                        format.format()
+                    ))
+                })?;
+                let len = (width * height * bits).div_ceil(8);
+                (len, len, 1)
+            }
+        };
+        let end = rows.saturating_sub(1) * pitch + row_len;
+        if end > self.size() {
+            return Err(Error::InvalidSurface(format!(
+                "{width}x{height} g2d_format {} ends at byte {end} of a {} byte buffer",
+                format.format(),
+                self.size()
+            )));
+        }
+
+        Ok(self.read_with(|data| {
+            let crc = (0..rows).fold(!0, |crc, row| {
+                crc32_update(crc, &data[row * pitch..row * pitch + row_len])
+            });
+            !crc
+        }))
+    }
+
+    /// [`crc32`] of the pixels inside `rect` of the `width`×`height` image
+    /// of packed `format` at the start of the buffer, row by row, read
+    /// under a single `DMA_BUF_SYNC_READ` bracket.
+    ///
+    /// Pixels outside `rect` do not affect the result, so comparing it
+    /// across frames detects changes in a region of interest only. Rows
+    /// are read at the buffer's [`pitch()`](Self::pitch) as for
+    /// [`crc32()`](Self::crc32). Fails with [`Error::InvalidFormat`] for
+    /// planar or sub-byte formats and with [`Error::InvalidSurface`] for a
+    /// `rect` that is empty or outside the image.
+    pub fn region_crc32(
+        &self,
+        rect: Rect,
+        format: G2DFormat,
+        width: usize,
+        height: usize,
+    ) -> Result<u32> {
+        let bpp = format.bytes_per_pixel().ok_or_else(|| {
+            Error::InvalidFormat(format!(
+                "region checksums need a packed format, not g2d_format {}",
+                format.format()
+            ))
+        })?;
+        if rect.is_empty()
+            || rect.left < 0
+            || rect.top < 0
+            || rect.right as usize > width
+            || rect.bottom as usize > height
+        {
+            return Err(Error::InvalidSurface(format!(
+                "region {rect:?} is empty or outside the {width}x{height} image"
+            )));
+        }
+        let row_len = width * bpp;
+        let pitch = self.pitch().filter(|&p| p >= row_len).unwrap_or(row_len);
+        let columns = rect.left as usize * bpp..rect.right as usize * bpp;
+        let rows = rect.top as usize..rect.bottom as usize;
+        let end = (rows.end - 1) * pitch + columns.end;
+        if end > self.size() {
+            return Err(Error::InvalidSurface(format!(
+                "region {rect:?} ends at byte {end} of a {} byte buffer",
+                self.size()
+            )));
+        }
+
+        Ok(self.read_with(|data| {
+            let crc = rows.fold(!0, |crc, row| {
+                let start = row * pitch;
+                crc32_update(crc, &data[start + columns.start..start + columns.end])
+            });
+            !crc
+        }))
+    }
+}
@@ -0,0 +1,345 @@
+// SPDX-FileCopyrightText: Copyright 2025 Au-Zone Technologies
+// SPDX-License-Identifier: Apache-2.0
+
+//! Lossless reordering between packed YUV 4:2:2 layouts.
+//!
+//! G2D converts packed YUV by way of RGB, which is lossy, and only YUYV and
+//! UYVY are writable destinations. Reordering YUYV/UYVY/YVYU/VYUY is a pure
+//! byte permutation of each 4-byte macropixel, so it is done on the CPU.
+//!
+//! The same goes for splitting packed 4:2:2 into planar 4:2:0: G2D reads
+//! I420/YV12 but cannot write them, so no GPU route (direct or by way of
+//! RGB) produces planar output.
+//!
+//! YUV black is Y=16 with U=V=128, not all-zero bytes, which decode as
+//! saturated green. [`G2D::clear_yuv_black`] fills a YUV crop correctly and
+//! [`DmaBuffer::check_yuv_source`] catches the all-zero mistake.
+
+use crate::{
+    clip::chroma_subsampling, dma::DmaBuffer, fill::CropLayout, g2d_format, g2d_format_G2D_I420,
+    g2d_format_G2D_NV12, g2d_format_G2D_NV16, g2d_format_G2D_NV21, g2d_format_G2D_NV61,
+    g2d_format_G2D_UYVY, g2d_format_G2D_VYUY, g2d_format_G2D_YUYV, g2d_format_G2D_YV12,
+    g2d_format_G2D_YVYU, Error, G2DSurface, Result, G2D,
+};
+
+/// Byte positions of `[Y0, U, Y1, V]` within a macropixel.
+fn macropixel_layout(format: g2d_format) -> Option<[usize; 4]> {
+    match format {
+        g2d_format_G2D_YUYV => Some([0, 1, 2, 3]),
+        g2d_format_G2D_UYVY => Some([1, 0, 3, 2]),
+        g2d_format_G2D_YVYU => Some([0, 3, 2, 1]),
+        g2d_format_G2D_VYUY => Some([1, 2, 3, 0]),
+        _ => None,
+    }
+}
+
+/// Luma and chroma of YUV black (limited range).
+const BLACK_Y: u8 = 16;
+const BLACK_UV: u8 = 128;
+
+/// The crop of each plane of a YUV `surface`, as a packed surface with its
+/// bytes per sample and the YUV black value of its even and odd bytes.
+fn yuv_planes(surface: &G2DSurface) -> Result<Vec<(G2DSurface, usize, [u8; 2])>> {
+    if let Some(layout) = macropixel_layout(surface.format) {
+        let black = if layout[0] == 0 {
+            [BLACK_Y, BLACK_UV]
+        } else {
+            [BLACK_UV, BLACK_Y]
+        };
+        return Ok(vec![(*surface, 2, black)]);
+    }
+
+    // Chroma planes of NV12/NV16 interleave U and V at the luma pitch;
+    // those of I420/YV12 hold one of them at half the luma pitch.
+    let chroma_planes: &[usize] = match surface.format {
+        g2d_format_G2D_NV12 | g2d_format_G2D_NV21 | g2d_format_G2D_NV16 | g2d_format_G2D_NV61 => {
+            &[1]
+        }
+        g2d_format_G2D_I420 | g2d_format_G2D_YV12 => &[1, 2],
+        format => {
+            return Err(Error::InvalidFormat(format!(
+                "g2d_format {format} is not YUV"
+            )))
+        }
+    };
+    let (sx, sy) = chroma_subsampling(surface);
+    let up = |v: i32, step: i32| (v + step - 1) / step;
+    let chroma_bpp = 2 / chroma_planes.len();
+
+    let mut planes = vec![(*surface, 1, [BLACK_Y; 2])];
+    for &index in chroma_planes {
+        let chroma = G2DSurface {
+            planes: [surface.planes[index], 0, 0],
+            left: surface.left / sx,
+            top: surface.top / sy,
+            right: up(surface.right, sx),
+            bottom: up(surface.bottom, sy),
+            stride: surface.stride / sx,
+            width: up(surface.width, sx),
+            height: up(surface.height, sy),
+            ..*surface
+        };
+        planes.push((chroma, chroma_bpp, [BLACK_UV; 2]));
+    }
+    Ok(planes)
+}
+
+impl DmaBuffer {
+    /// In debug builds, check whether the crop of the YUV `surface` backed
+    /// by this buffer is all zero bytes, and log a warning if so.
+    ///
+    /// All-zero YUV decodes as green rather than black, so a YUV source
+    /// filled with zeros is almost always a buffer meant to be black; use
+    /// [`G2D::clear_yuv_black`] instead. Returns whether the warning was
+    /// logged. Release builds skip the check and return `false`, as do
+    /// non-YUV surfaces and crops that do not fit the buffer.
+    pub fn check_yuv_source(&self, surface: &G2DSurface) -> bool {
+        if !cfg!(debug_assertions) {
+            return false;
+        }
+        let Ok(planes) = yuv_planes(surface) else {
+            return false;
+        };
+        let mut layouts = Vec::with_capacity(planes.len());
+        for (plane, bpp, _) in &planes {
+            match CropLayout::new(plane, self, *bpp) {
+                Ok(Some(layout)) => layouts.push(layout),
+                _ => return false,
+            }
+        }
+
+        let zeroed = self.read_with(|data| {
+            layouts
+                .iter()
+                .all(|layout| layout.rows().all(|row| data[row].iter().all(|&b| b == 0)))
+        });
+        if zeroed {
+            log::warn!(
+                "YUV source (g2d_format {}) is all zero bytes, which is green, not \
+                 black; fill it with G2D::clear_yuv_black",
+                surface.format
+            );
+        }
+        zeroed
+    }
+}
+
+impl G2D {
+    /// Fill the crop of a YUV surface with black: Y=16 and U=V=128.
+    ///
+    /// Zeroing a YUV buffer produces green, and `g2d_clear` cannot write
+    /// planar YUV, so this runs on the CPU after a
+    /// [`finish()`](Self::finish) and is complete on return. Chroma
+    /// samples partly covered by the crop are filled too. `buf` must back
+    /// every plane of `dst`; other formats fail with
+    /// [`Error::InvalidFormat`].
+    pub fn clear_yuv_black(&self, dst: &G2DSurface, buf: &mut DmaBuffer) -> Result<()> {
+        let mut fills = Vec::new();
+        for (plane, bpp, black) in yuv_planes(dst)? {
+            if let Some(layout) = CropLayout::new(&plane, buf, bpp)? {
+                fills.push((layout, black));
+            }
+        }
+
+        self.finish()?;
+        buf.write_with(|data| {
+            for (layout, black) in &fills {
+                for row in layout.rows() {
+                    for (i, byte) in data[row].iter_mut().enumerate() {
+                        *byte = black[i % 2];
+                    }
+                }
+            }
+        });
+        Ok(())
+    }
+
+    /// Copy the crop of a packed YUV 4:2:2 surface into another packed
+    /// 4:2:2 ordering (YUYV, UYVY, YVYU, VYUY) without changing any sample.
+    ///
+    /// Both crops must be the same size with an even left edge and width.
+    /// The copy runs on the CPU after a [`finish()`](Self::finish) and is
+    /// complete on return. `src_buf` and `dst_buf` must back the respective
+    /// `planes[0]`.
+    pub fn reorder_yuv(
+        &self,
+        src: &G2DSurface,
+        src_buf: &DmaBuffer,
+        dst: &G2DSurface,
+        dst_buf: &mut DmaBuffer,
+    ) -> Result<()> {
+        let layout_of = |surface: &G2DSurface| {
+            macropixel_layout(surface.format).ok_or_else(|| {
+                Error::InvalidFormat(format!(
+                    "g2d_format {} is not packed YUV 4:2:2",
+                    surface.format
+                ))
+            })
+        };
+        let from = layout_of(src)?;
+        let to = layout_of(dst)?;
+
+        let (src_crop, dst_crop) = (src.crop_rect(), dst.crop_rect());
+        if (src_crop.width(), src_crop.height()) != (dst_crop.width(), dst_crop.height()) {
+            return Err(Error::InvalidSurface(format!(
+                "crop size mismatch: src {src_crop:?}, dst {dst_crop:?}"
+            )));
+        }
+        for crop in [src_crop, dst_crop] {
+            if crop.left % 2 != 0 || crop.width() % 2 != 0 {
+                return Err(Error::InvalidSurface(format!(
+                    "crop {crop:?} splits a 4:2:2 macropixel"
+                )));
+            }
+        }
+
+        let (Some(src_layout), Some(dst_layout)) = (
+            CropLayout::new(src, src_buf, 2)?,
+            CropLayout::new(dst, dst_buf, 2)?,
+        ) else {
+            return Ok(());
+        };
+
+        self.finish()?;
+        src_buf.check_yuv_source(src);
+        src_buf.read_with(|src_data| {
+            dst_buf.write_with(|dst_data| {
+                for (src_row, dst_row) in src_layout.rows().zip(dst_layout.rows()) {
+                    for (s, d) in src_data[src_row]
+                        .chunks_exact(4)
+                        .zip(dst_data[dst_row].chunks_exact_mut(4))
+                    {
+                        for (&f, &t) in from.iter().zip(&to) {
+                            d[t] = s[f];
+                        }
+                    }
+                }
+            })
+        });
+        Ok(())
+    }
+
+    /// Split the crop of a packed YUV 4:2:2 surface (YUYV, UYVY, YVYU or
+    /// VYUY) into an I420 or YV12 surface of the same crop size, on the CPU.
+    ///
+    /// There is no GPU path: G2D cannot write planar YUV, directly or by
+    /// way of RGB, so the GPU is only waited for. Like
+    /// [`reorder_yuv()`](Self::reorder_yuv) the split runs after a
+    /// [`finish()`](Self::finish) and is complete on return.
+    ///
+    /// Luma is copied unchanged and each chroma sample is the rounded mean
+    /// of the two vertically adjacent 4:2:2 samples. Both crops must have
+    /// an even position and size; the destination chroma stride is half
+    /// its `stride`, as laid out by [`G2DSurface::i420`].
+    pub fn yuyv_to_i420(
+        &self,
+        src: &G2DSurface,
+        src_buf: &DmaBuffer,
+        dst: &G2DSurface,
+        dst_buf: &mut DmaBuffer,
+    ) -> Result<()> {
+        let from = macropixel_layout(src.format).ok_or_else(|| {
+            Error::InvalidFormat(format!("g2d_format {} is not packed YUV 4:2:2", src.format))
+        })?;
+        let (u_plane, v_plane) = match dst.format {
+            g2d_format_G2D_I420 => (1, 2),
+            g2d_format_G2D_YV12 => (2, 1),
+            f => {
+                return Err(Error::InvalidFormat(format!(
+                    "g2d_format {f} is not I420 or YV12"
+                )))
+            }
+        };
+
+        let (src_crop, dst_crop) = (src.crop_rect(), dst.crop_rect());
+        if (src_crop.width(), src_crop.height()) != (dst_crop.width(), dst_crop.height()) {
+            return Err(Error::InvalidSurface(format!(
+                "crop size mismatch: src {src_crop:?}, dst {dst_crop:?}"
+            )));
+        }
+        for crop in [src_crop, dst_crop] {
+            if [crop.left, crop.top, crop.right, crop.bottom]
+                .iter()
+                .any(|v| v % 2 != 0)
+            {
+                return Err(Error::InvalidSurface(format!(
+                    "crop {crop:?} splits a 4:2:0 chroma block"
+                )));
+            }
+        }
+
+        let Some(src_layout) = CropLayout::new(src, src_buf, 2)? else {
+            return Ok(());
+        };
+        if dst_crop.left < 0
+            || dst_crop.top < 0
+            || dst_crop.right > dst.width
+            || dst_crop.bottom > dst.height
+        {
+            return Err(Error::InvalidSurface(format!(
+                "crop {dst_crop:?} exceeds {}x{} surface",
+                dst.width, dst.height
+            )));
+        }
+
+        // Byte ranges of the crop rows of `plane`, subsampled 1:`sub`.
+        let luma_pitch = dst.stride as usize;
+        let plane_rows = |plane: usize, sub: usize| -> Result<Vec<std::ops::Range<usize>>> {
+            let base = dst_buf.offset_of(dst.planes[plane]).ok_or_else(|| {
+                Error::InvalidSurface(format!(
+                    "plane {plane} address 0x{:x} is outside the buffer",
+                    dst.planes[plane]
+                ))
+            })?;
+            let (pitch, left, width) = (
+                luma_pitch / sub,
+                dst_crop.left as usize / sub,
+                dst_crop.width() as usize / sub,
+            );
+            let rows: Vec<_> = (dst_crop.top as usize / sub..dst_crop.bottom as usize / sub)
+                .map(|row| {
+                    let start = base + row * pitch + left;
+                    start..start + width
+                })
+                .collect();
+            if let Some(last) = rows.last().filter(|r| r.end > dst_buf.size()) {
+                return Err(Error::InvalidSurface(format!(
+                    "plane {plane} ends at byte {} of a {} byte buffer",
+                    last.end,
+                    dst_buf.size()
+                )));
+            }
+            Ok(rows)
+        };
+        let y_rows = plane_rows(0, 1)?;
+        let u_rows = plane_rows(u_plane, 2)?;
+        let v_rows = plane_rows(v_plane, 2)?;
+        let src_rows: Vec<_> = src_layout.rows().collect();
+
+        self.finish()?;
+        src_buf.check_yuv_source(src);
+        src_buf.read_with(|src_data| {
+            dst_buf.write_with(|dst_data| {
+                for (src_row, y_row) in src_rows.iter().zip(&y_rows) {
+                    let luma = src_data[src_row.clone()]
+                        .chunks_exact(4)
+                        .flat_map(|m| [m[from[0]], m[from[2]]]);
+                    for (d, y) in dst_data[y_row.clone()].iter_mut().zip(luma) {
+                        *d = y;
+                    }
+                }
+                let pairs = src_rows.chunks_exact(2).zip(u_rows.iter().zip(&v_rows));
+                for (pair, (u_row, v_row)) in pairs {
+                    let (top, bottom) = (&src_data[pair[0].clone()], &src_data[pair[1].clone()]);
+                    let blocks = top.chunks_exact(4).zip(bottom.chunks_exact(4));
+                    for (i, (a, b)) in blocks.enumerate() {
+                        let mean = |k: usize| (a[k] as u16 + b[k] as u16).div_ceil(2) as u8;
+                        dst_data[u_row.start + i] = mean(from[1]);
+                        dst_data[v_row.start + i] = mean(from[3]);
+                    }
+                }
+            })
+        });
+        Ok(())
+    }
+}
@@ -16,186 +16,43 @@
 
 #![cfg(target_os = "linux")]
 
-use dma_heap::{Heap, HeapKind};
 use g2d_sys::{
-    g2d_format, g2d_format_G2D_ABGR8888, g2d_format_G2D_ARGB8888, g2d_format_G2D_BGR565,
-    g2d_format_G2D_BGR888, g2d_format_G2D_BGRA8888, g2d_format_G2D_BGRX8888, g2d_format_G2D_I420,
-    g2d_format_G2D_NV12, g2d_format_G2D_NV16, g2d_format_G2D_NV21, g2d_format_G2D_NV61,
-    g2d_format_G2D_RGB565, g2d_format_G2D_RGB888, g2d_format_G2D_RGBA8888, g2d_format_G2D_RGBX8888,
-    g2d_format_G2D_UYVY, g2d_format_G2D_VYUY, g2d_format_G2D_XBGR8888, g2d_format_G2D_XRGB8888,
-    g2d_format_G2D_YUYV, g2d_format_G2D_YV12, g2d_format_G2D_YVYU, g2d_rotation_G2D_ROTATION_0,
-    G2DFormat, G2DPhysical, G2DSurface, G2D, NV12, RGB, RGBA, YUYV,
+    bench,
+    buffer::G2DBuffer,
+    buffer_pool::BufferPool,
+    builder::{SurfaceBuilder, SurfaceError},
+    display::{DisplayTarget, DoubleBuffer},
+    dma::{DmaBuffer, HeapType, SCANOUT_PITCH_ALIGN, SCANOUT_SIZE_ALIGN},
+    fence, g2d_blend_func_G2D_ONE_MINUS_SRC_ALPHA, g2d_blend_func_G2D_SRC_ALPHA, g2d_cap_mode,
+    g2d_cap_mode_G2D_BLEND, g2d_cap_mode_G2D_DITHER, g2d_cap_mode_G2D_YUV_BT_601,
+    g2d_cap_mode_G2D_YUV_BT_709, g2d_format, g2d_format_G2D_ABGR8888, g2d_format_G2D_ARGB8888,
+    g2d_format_G2D_BGR565, g2d_format_G2D_BGR888, g2d_format_G2D_BGRA8888, g2d_format_G2D_BGRX8888,
+    g2d_format_G2D_GRAY8, g2d_format_G2D_I420, g2d_format_G2D_NV12, g2d_format_G2D_NV16,
+    g2d_format_G2D_NV21, g2d_format_G2D_NV61, g2d_format_G2D_RGB565, g2d_format_G2D_RGB888,
+    g2d_format_G2D_RGBA8888, g2d_format_G2D_RGBX8888, g2d_format_G2D_UYVY, g2d_format_G2D_VYUY,
+    g2d_format_G2D_XBGR8888, g2d_format_G2D_XRGB8888, g2d_format_G2D_YUYV, g2d_format_G2D_YV12,
+    g2d_format_G2D_YVYU, g2d_rotation, g2d_rotation_G2D_FLIP_H, g2d_rotation_G2D_FLIP_V,
+    g2d_rotation_G2D_ROTATION_0, g2d_rotation_G2D_ROTATION_180, g2d_rotation_G2D_ROTATION_270,
+    g2d_rotation_G2D_ROTATION_90,
+    layout::{Plane, PlaneLayout},
+    metrics,
+    multi::MultiConsumer,
+    options::{BlitOptions, Deinterlace},
+    pipeline::{Pipeline, Stage, StageProblem, SurfaceSpec},
+    pixel::{Pixel, BGRA8, RGB565, RGBA8},
+    pool::G2DPool,
+    ring::FrameRing,
+    stream::{ConvertSpec, DropPolicy, FrameRef},
+    sync, verify, BlendFunc, ByteOrder, Capabilities, Colorspace, ColorspacePolicy, Core,
+    CropAlignment, Error, Feature, Flip, G2DFormat, G2DPhysical, G2DSurface, Priority, Range, Rect,
+    Rotation, Transform, Version, WaitMode, G2D, GREY, NV12, P010, RGB, RGBA, YUYV,
 };
-use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
-use std::ptr;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 // =============================================================================
-// DMA-buf synchronization constants (linux/dma-buf.h)
+// Heap test harness
 // =============================================================================
 
-const DMA_BUF_BASE: u8 = b'b';
-const DMA_BUF_IOCTL_SYNC_NR: u8 = 0;
-
-const DMA_BUF_SYNC_READ: u64 = 1 << 0;
-const DMA_BUF_SYNC_WRITE: u64 = 1 << 1;
-const DMA_BUF_SYNC_START: u64 = 0 << 2;
-const DMA_BUF_SYNC_END: u64 = 1 << 2;
-
-#[repr(C)]
-struct DmaBufSync {
-    flags: u64,
-}
-
-// _IOW('b', 0, struct dma_buf_sync) = direction=1, size=8, type='b', nr=0
-const DMA_BUF_IOCTL_SYNC_CMD: libc::c_ulong = (1 << 30)
-    | ((std::mem::size_of::<DmaBufSync>() as libc::c_ulong) << 16)
-    | ((DMA_BUF_BASE as libc::c_ulong) << 8)
-    | DMA_BUF_IOCTL_SYNC_NR as libc::c_ulong;
-
-// =============================================================================
-// DRM PRIME import — creates persistent dma_buf_attach for cache maintenance
-// =============================================================================
-//
-// The CMA heap's begin_cpu_access iterates over buffer->attachments to perform
-// cache maintenance via dma_sync_sgtable_for_cpu(). Without any active
-// attachments, DMA_BUF_IOCTL_SYNC is a no-op.
-//
-// By importing the DMA-buf fd through the DRM/GPU driver (DRM_IOCTL_PRIME_FD_TO_HANDLE),
-// the GPU driver creates a persistent dma_buf_attach(). This makes
-// DMA_BUF_IOCTL_SYNC actually perform cache invalidation/flush.
-
-const DRM_IOCTL_BASE: u8 = b'd';
-
-// DRM_IOCTL_PRIME_FD_TO_HANDLE = _IOWR('d', 0x2e, struct drm_prime_handle)
-#[repr(C)]
-struct DrmPrimeHandle {
-    handle: u32,
-    flags: u32,
-    fd: i32,
-}
-
-const DRM_IOCTL_PRIME_FD_TO_HANDLE: libc::c_ulong = (3 << 30) // _IOWR
-    | ((std::mem::size_of::<DrmPrimeHandle>() as libc::c_ulong) << 16)
-    | ((DRM_IOCTL_BASE as libc::c_ulong) << 8)
-    | 0x2e;
-
-// DRM_IOCTL_GEM_CLOSE = _IOW('d', 0x09, struct drm_gem_close)
-#[repr(C)]
-struct DrmGemClose {
-    handle: u32,
-    pad: u32,
-}
-
-const DRM_IOCTL_GEM_CLOSE: libc::c_ulong = (1 << 30) // _IOW
-    | ((std::mem::size_of::<DrmGemClose>() as libc::c_ulong) << 16)
-    | ((DRM_IOCTL_BASE as libc::c_ulong) << 8)
-    | 0x09;
-
-/// Holds a DRM GEM handle that keeps a persistent dma_buf_attach alive.
-/// When dropped, closes the GEM handle (which detaches the DMA-buf).
-struct DrmAttachment {
-    drm_fd: OwnedFd,
-    gem_handle: u32,
-}
-
-impl DrmAttachment {
-    /// Import a DMA-buf fd through the GPU DRM driver to create a persistent
-    /// dma_buf_attach. Returns None if /dev/dri/renderD128 is not available.
-    fn new(dma_buf_fd: &OwnedFd) -> Option<Self> {
-        let path = b"/dev/dri/renderD128\0";
-        let raw_fd = unsafe {
-            libc::open(
-                path.as_ptr() as *const libc::c_char,
-                libc::O_RDWR | libc::O_CLOEXEC,
-            )
-        };
-        if raw_fd < 0 {
-            eprintln!(
-                "  DrmAttachment: /dev/dri/renderD128 not available: {}",
-                std::io::Error::last_os_error()
-            );
-            return None;
-        }
-        let drm_fd = unsafe { OwnedFd::from_raw_fd(raw_fd) };
-
-        let mut prime = DrmPrimeHandle {
-            handle: 0,
-            flags: 0,
-            fd: dma_buf_fd.as_raw_fd(),
-        };
-
-        let ret =
-            unsafe { libc::ioctl(drm_fd.as_raw_fd(), DRM_IOCTL_PRIME_FD_TO_HANDLE, &mut prime) };
-        if ret == -1 {
-            eprintln!(
-                "  DrmAttachment: PRIME_FD_TO_HANDLE failed: {}",
-                std::io::Error::last_os_error()
-            );
-            return None;
-        }
-
-        eprintln!("  DrmAttachment: imported as GEM handle {}", prime.handle);
-
-        Some(Self {
-            drm_fd,
-            gem_handle: prime.handle,
-        })
-    }
-}
-
-impl Drop for DrmAttachment {
-    fn drop(&mut self) {
-        let close = DrmGemClose {
-            handle: self.gem_handle,
-            pad: 0,
-        };
-        unsafe { libc::ioctl(self.drm_fd.as_raw_fd(), DRM_IOCTL_GEM_CLOSE, &close) };
-    }
-}
-
-// =============================================================================
-// Heap type abstraction
-// =============================================================================
-
-#[derive(Debug, Clone, Copy, PartialEq)]
-enum HeapType {
-    /// `/dev/dma_heap/linux,cma-uncached` — non-cacheable mapping, GPU writes
-    /// are immediately visible to CPU reads without cache maintenance.
-    Uncached,
-    /// `/dev/dma_heap/linux,cma` — cached mapping, requires DMA_BUF_IOCTL_SYNC
-    /// for CPU cache coherency after GPU DMA writes.
-    Cached,
-}
-
-impl HeapType {
-    fn name(&self) -> &str {
-        match self {
-            HeapType::Uncached => "linux,cma-uncached",
-            HeapType::Cached => "linux,cma",
-        }
-    }
-
-    fn heap_kind(&self) -> HeapKind {
-        match self {
-            HeapType::Uncached => {
-                HeapKind::Custom(std::path::PathBuf::from("/dev/dma_heap/linux,cma-uncached"))
-            }
-            HeapType::Cached => HeapKind::Cma,
-        }
-    }
-
-    fn is_available(&self) -> bool {
-        Heap::new(self.heap_kind()).is_ok()
-    }
-}
-
-impl std::fmt::Display for HeapType {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.name())
-    }
-}
-
 /// Run a test body with the given heap type, skipping if unavailable.
 fn with_heap<F>(heap_type: HeapType, test_name: &str, f: F)
 where
@@ -228,148 +85,6 @@ macro_rules! heap_tests {
     };
 }
 
-// =============================================================================
-// DMA Buffer with persistent mmap and proper DMA_BUF_IOCTL_SYNC
-// =============================================================================
-
-/// DMA buffer with persistent mmap and correct DMA_BUF_IOCTL_SYNC usage.
-///
-/// The buffer is mmapped once on creation and munmapped on drop. CPU access
-/// is bracketed by SYNC_START/SYNC_END ioctls with full return value checking.
-///
-/// This follows the Linux DMA-buf CPU access protocol:
-/// 1. `DMA_BUF_IOCTL_SYNC` with `SYNC_START` — begin CPU access
-/// 2. CPU reads/writes via the persistent mmap
-/// 3. `DMA_BUF_IOCTL_SYNC` with `SYNC_END` — end CPU access
-struct DmaBuffer {
-    fd: OwnedFd,
-    phys: G2DPhysical,
-    ptr: *mut u8,
-    size: usize,
-    heap_type: HeapType,
-    /// DRM PRIME import handle — keeps a persistent dma_buf_attach alive so that
-    /// DMA_BUF_IOCTL_SYNC actually performs cache maintenance on cached heaps.
-    _drm_attachment: Option<DrmAttachment>,
-}
-
-impl DmaBuffer {
-    fn new(heap_type: HeapType, size: usize) -> Result<Self, Box<dyn std::error::Error>> {
-        let heap = Heap::new(heap_type.heap_kind())
-            .map_err(|e| format!("Failed to open {heap_type} heap: {e}"))?;
-
-        let fd = heap
-            .allocate(size)
-            .map_err(|e| format!("Failed to allocate {size} bytes from {heap_type} heap: {e}"))?;
-
-        let phys = G2DPhysical::new(fd.as_raw_fd())?;
-
-        // Persistent mmap — mapped once for the buffer's lifetime
-        let ptr = unsafe {
-            libc::mmap(
-                ptr::null_mut(),
-                size,
-                libc::PROT_READ | libc::PROT_WRITE,
-                libc::MAP_SHARED,
-                fd.as_raw_fd(),
-                0,
-            )
-        };
-        if ptr == libc::MAP_FAILED {
-            return Err(format!(
-                "mmap failed for {heap_type} heap buffer ({size} bytes): {}",
-                std::io::Error::last_os_error()
-            )
-            .into());
-        }
-
-        // For cached heaps, create a persistent DRM PRIME import so that
-        // DMA_BUF_IOCTL_SYNC actually performs cache maintenance.
-        // Without this, begin_cpu_access iterates an empty attachment list.
-        let drm_attachment = if heap_type == HeapType::Cached {
-            DrmAttachment::new(&fd)
-        } else {
-            None
-        };
-
-        eprintln!(
-            "  DmaBuffer: {size} bytes from {heap_type} heap, phys=0x{:x}, drm_attach={}",
-            phys.address(),
-            drm_attachment.is_some()
-        );
-
-        Ok(Self {
-            fd,
-            phys,
-            ptr: ptr as *mut u8,
-            size,
-            heap_type,
-            _drm_attachment: drm_attachment,
-        })
-    }
-
-    fn address(&self) -> u64 {
-        self.phys.address()
-    }
-
-    /// Perform DMA_BUF_IOCTL_SYNC with full error checking.
-    fn dma_buf_sync(&self, flags: u64) {
-        let sync = DmaBufSync { flags };
-        let ret = unsafe { libc::ioctl(self.fd.as_raw_fd(), DMA_BUF_IOCTL_SYNC_CMD, &sync) };
-        assert_ne!(
-            ret,
-            -1,
-            "DMA_BUF_IOCTL_SYNC (flags=0x{:x}) failed on {heap} heap: {err}",
-            flags,
-            heap = self.heap_type,
-            err = std::io::Error::last_os_error()
-        );
-    }
-
-    /// Begin CPU access with the given direction flags.
-    fn sync_start(&self, flags: u64) {
-        self.dma_buf_sync(flags | DMA_BUF_SYNC_START);
-    }
-
-    /// End CPU access with the given direction flags.
-    fn sync_end(&self, flags: u64) {
-        self.dma_buf_sync(flags | DMA_BUF_SYNC_END);
-    }
-
-    /// Write to the buffer with proper sync bracketing.
-    ///
-    /// Uses `DMA_BUF_SYNC_WRITE` — tells the kernel the CPU will write,
-    /// so it can clean/flush caches on SYNC_END.
-    fn write_with<F: FnOnce(&mut [u8])>(&self, f: F) {
-        self.sync_start(DMA_BUF_SYNC_WRITE);
-        f(unsafe { std::slice::from_raw_parts_mut(self.ptr, self.size) });
-        self.sync_end(DMA_BUF_SYNC_WRITE);
-    }
-
-    /// Read from the buffer with proper sync bracketing.
-    ///
-    /// Uses `DMA_BUF_SYNC_READ` — tells the kernel the CPU will read,
-    /// so it can invalidate caches on SYNC_START to see GPU/DMA writes.
-    fn read_with<F: FnOnce(&[u8]) -> T, T>(&self, f: F) -> T {
-        self.sync_start(DMA_BUF_SYNC_READ);
-        let result = f(unsafe { std::slice::from_raw_parts(self.ptr, self.size) });
-        self.sync_end(DMA_BUF_SYNC_READ);
-        result
-    }
-}
-
-impl Drop for DmaBuffer {
-    fn drop(&mut self) {
-        let ret = unsafe { libc::munmap(self.ptr as *mut libc::c_void, self.size) };
-        if ret != 0 {
-            eprintln!(
-                "WARNING: munmap failed for {heap} heap buffer: {err}",
-                heap = self.heap_type,
-                err = std::io::Error::last_os_error()
-            );
-        }
-    }
-}
-
 // =============================================================================
 // Surface creation helpers
 // =============================================================================
@@ -395,22 +110,14 @@ fn create_surface(buf: &DmaBuffer, width: usize, height: usize, format: u32) ->
 
 /// Create a G2DSurface for NV12 (two-plane format).
 fn create_nv12_surface(buf: &DmaBuffer, width: usize, height: usize) -> G2DSurface {
-    let uv_offset = (width * height) as u64;
-    G2DSurface {
-        format: g2d_sys::g2d_format_G2D_NV12,
-        planes: [buf.address(), buf.address() + uv_offset, 0],
-        left: 0,
-        top: 0,
-        right: width as i32,
-        bottom: height as i32,
-        stride: width as i32,
-        width: width as i32,
-        height: height as i32,
-        blendfunc: 0,
-        global_alpha: 255,
-        clrcolor: 0,
-        rot: g2d_rotation_G2D_ROTATION_0,
-    }
+    let uv_addr = buf.address() + (width * height) as u64;
+    G2DSurface::nv12(
+        buf.address(),
+        uv_addr,
+        width as i32,
+        height as i32,
+        width as i32,
+    )
 }
 
 // =============================================================================
@@ -428,6 +135,188 @@ fn test_g2d_open_close() {
     eprintln!("G2D version: {}", g2d.version());
 }
 
+#[test]
+fn test_g2d_new_timed() {
+    let _ = env_logger::try_init();
+
+    let (g2d, timings) = G2D::new_timed("libg2d.so.2").expect("Failed to open G2D");
+    eprintln!("G2D open timings: {timings:?}");
+
+    assert!(timings.dlopen > Duration::ZERO, "dlopen not timed");
+    assert!(
+        timings.symbols > Duration::ZERO,
+        "symbol resolution not timed"
+    );
+    assert!(
+        timings.context > Duration::ZERO,
+        "context creation not timed"
+    );
+    assert_eq!(
+        timings.total(),
+        timings.dlopen + timings.symbols + timings.context
+    );
+    assert_eq!(
+        g2d.version(),
+        G2D::new("libg2d.so.2")
+            .expect("Failed to open G2D")
+            .version()
+    );
+}
+
+#[test]
+fn test_g2d_close_rejects_operations() {
+    let _ = env_logger::try_init();
+
+    let mut g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    assert!(!g2d.is_closed());
+    g2d.close().expect("close failed");
+    assert!(g2d.is_closed());
+
+    let mut dst = G2DSurface {
+        format: g2d_format_G2D_RGBA8888,
+        planes: [0x1000, 0, 0],
+        right: 16,
+        bottom: 16,
+        stride: 16,
+        width: 16,
+        height: 16,
+        ..Default::default()
+    };
+    assert!(matches!(
+        g2d.clear(&mut dst, [255, 0, 0, 255]),
+        Err(Error::HandleClosed)
+    ));
+    assert!(matches!(g2d.finish(), Err(Error::HandleClosed)));
+    // Closing again (and dropping) is harmless.
+    g2d.close().expect("second close failed");
+}
+
+#[test]
+fn test_g2d_shared_handle_state() {
+    let _ = env_logger::try_init();
+
+    // Two references to one handle share its driver state; a separately
+    // opened handle does not.
+    let shared = std::rc::Rc::new(G2D::new("libg2d.so.2").expect("Failed to open G2D"));
+    let other = std::rc::Rc::clone(&shared);
+    let separate = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+
+    G2D::builder()
+        .colorspace(Colorspace::Bt709)
+        .configure(&shared)
+        .unwrap();
+    assert_eq!(other.colorspace().unwrap(), Colorspace::Bt709);
+    assert_eq!(separate.colorspace().unwrap(), Colorspace::Bt601);
+
+    G2D::builder()
+        .colorspace(Colorspace::Bt601)
+        .configure(&other)
+        .unwrap();
+    assert_eq!(shared.colorspace().unwrap(), Colorspace::Bt601);
+}
+
+#[test]
+fn test_g2d_benchmark_convert() {
+    let _ = env_logger::try_init();
+
+    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    let nv12 = G2DFormat::try_from(NV12).unwrap();
+    let rgba = G2DFormat::try_from(RGBA).unwrap();
+    let result = g2d
+        .benchmark_convert(nv12, rgba, 640, 480, 5)
+        .expect("benchmark_convert failed");
+    eprintln!("NV12→RGBA 640x480: {result:?}");
+
+    assert_eq!(result.iterations, 5);
+    assert!(result.min > Duration::ZERO);
+    assert!(result.min <= result.mean, "min {result:?}");
+    assert!(result.mean <= result.max, "max {result:?}");
+    assert!(result.pixels_per_sec > 0.0);
+    assert!(result.bytes_per_sec > result.pixels_per_sec);
+}
+
+#[test]
+fn test_g2d_compare_heaps_clear_read() {
+    let _ = env_logger::try_init();
+
+    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    let (width, height) = (640usize, 480usize);
+    let comparison = bench::compare_heaps(5, |heap_type| {
+        let buf = DmaBuffer::new(heap_type, width * height * 4)?;
+        let mut surface = create_surface(&buf, width, height, g2d_format_G2D_RGBA8888);
+        let g2d = &g2d;
+        Ok(move || {
+            g2d.clear(&mut surface, [10, 20, 30, 255])?;
+            g2d.finish()?;
+            let sum = buf.read_with(|data| data.iter().map(|&b| b as u64).sum::<u64>());
+            assert_eq!(sum, (10 + 20 + 30 + 255) * (width * height) as u64);
+            Ok(())
+        })
+    })
+    .expect("compare_heaps failed");
+    eprintln!(
+        "clear+read 640x480: {comparison:?}, faster: {:?}",
+        comparison.faster()
+    );
+
+    for heap_type in [HeapType::Uncached, HeapType::Cached] {
+        assert_eq!(
+            comparison.get(heap_type).is_some(),
+            heap_type.is_available(),
+            "{heap_type:?}"
+        );
+        if let Some(timing) = comparison.get(heap_type) {
+            assert_eq!(timing.iterations, 5);
+            assert!(timing.min > Duration::ZERO);
+            assert!(timing.min <= timing.mean && timing.mean <= timing.max);
+        }
+    }
+    let faster = comparison.faster().expect("no heap was run");
+    assert!(comparison.get(faster).is_some());
+}
+
+#[test]
+fn test_g2d_enable_disable_dither() {
+    let _ = env_logger::try_init();
+
+    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    g2d.enable(Feature::Dither).expect("enable(Dither) failed");
+    assert!(g2d.is_enabled(Feature::Dither).unwrap());
+    assert_eq!(g2d_cap_mode::from(Feature::Dither), g2d_cap_mode_G2D_DITHER);
+    g2d.disable(Feature::Dither)
+        .expect("disable(Dither) failed");
+    assert!(!g2d.is_enabled(Feature::Dither).unwrap());
+}
+
+#[test]
+fn test_g2d_capabilities() {
+    let _ = env_logger::try_init();
+
+    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    g2d.reset_state().unwrap();
+    let caps = g2d.capabilities().expect("capabilities failed");
+    eprintln!("G2D capabilities: {caps:#?}");
+    for feature in [
+        Feature::Blend,
+        Feature::Dither,
+        Feature::GlobalAlpha,
+        Feature::BlendDim,
+        Feature::Blur,
+        Feature::YuvBt601,
+        Feature::YuvBt709,
+        Feature::YuvBt601FullRange,
+        Feature::YuvBt709FullRange,
+        Feature::Warping,
+    ] {
+        eprintln!("  {feature:?}: {:?}", g2d.query_cap(feature));
+    }
+
+    // Probing leaves the handle as it was.
+    assert!(!g2d.is_enabled(Feature::Blend).unwrap());
+    assert!(!g2d.is_enabled(Feature::Dither).unwrap());
+    assert_eq!(caps.blend, g2d.query_cap(Feature::Blend).unwrap());
+}
+
 #[test]
 fn test_g2d_version_detection() {
     let _ = env_logger::try_init();
@@ -449,6 +338,26 @@ fn test_g2d_version_detection() {
     eprintln!("Detected G2D version: {version}");
 }
 
+#[test]
+fn test_version_predicates() {
+    let version = Version::new(6, 4, 11, 1049711);
+    assert!(version.at_least(6, 4));
+    assert!(version.at_least(5, 9));
+    assert!(!version.at_least(6, 5));
+    assert!(!version.at_least(7, 0));
+    assert!(version.supports_yuv_clear());
+    assert!(version.supports_multi_blit());
+
+    // Patch level and build number do not matter.
+    assert!(Version::new(6, 4, 0, 0).supports_yuv_clear());
+    assert!(Version::new(7, 0, 0, 0).supports_multi_blit());
+
+    let old = Version::new(6, 2, 4, 0);
+    assert!(old.at_least(6, 2));
+    assert!(!old.supports_yuv_clear());
+    assert!(!old.supports_multi_blit());
+}
+
 #[test]
 fn test_g2d_colorspace_configuration() {
     let _ = env_logger::try_init();
@@ -462,6 +371,148 @@ fn test_g2d_colorspace_configuration() {
     assert!(result.is_ok(), "Failed to set BT.601: {:?}", result.err());
 }
 
+/// On a fresh handle, a strict policy rejects an NV12→RGBA blit until a
+/// colorspace is set, while the default lenient policy applies BT.601.
+fn colorspace_policy_test(heap_type: HeapType) {
+    let (width, height) = (32usize, 32usize);
+    let mut src_buf =
+        DmaBuffer::new(heap_type, width * height * 3 / 2).expect("Failed to allocate src buffer");
+    let dst_buf =
+        DmaBuffer::new(heap_type, width * height * 4).expect("Failed to allocate dst buffer");
+    src_buf.write_with(|data| data.fill(128));
+    let src = create_surface(&src_buf, width, height, g2d_format_G2D_NV12);
+    let dst = create_surface(&dst_buf, width, height, g2d_format_G2D_RGBA8888);
+
+    let mut strict = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    strict.set_colorspace_policy(ColorspacePolicy::Strict);
+    assert!(!strict.is_colorspace_set());
+    assert!(matches!(
+        strict.blit(&src, &dst),
+        Err(Error::ColorspaceNotSet)
+    ));
+    strict.set_bt709_colorspace().unwrap();
+    strict
+        .blit(&src, &dst)
+        .expect("blit after set_bt709_colorspace");
+    strict.finish().unwrap();
+
+    let lenient = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    assert_eq!(
+        lenient.colorspace_policy(),
+        ColorspacePolicy::Lenient(Colorspace::Bt601)
+    );
+    lenient.blit(&src, &dst).expect("lenient blit");
+    lenient.finish().unwrap();
+    assert!(lenient.is_colorspace_set());
+    assert_eq!(lenient.colorspace().unwrap(), Colorspace::Bt601);
+}
+heap_tests!(test_g2d_colorspace_policy, colorspace_policy_test);
+
+/// Query a capability through the raw FFI.
+fn cap_enabled(g2d: &G2D, cap: g2d_cap_mode) -> bool {
+    let mut enable = 0;
+    let ret = unsafe { g2d.lib.g2d_query_cap(g2d.handle, cap, &mut enable) };
+    assert_eq!(ret, 0, "g2d_query_cap({cap}) failed");
+    enable != 0
+}
+
+#[test]
+fn test_g2d_builder_configuration() {
+    let _ = env_logger::try_init();
+
+    let g2d = G2D::builder()
+        .library("libg2d.so.2")
+        .colorspace(Colorspace::Bt709)
+        .dither(true)
+        .build()
+        .expect("Failed to build G2D");
+
+    assert!(cap_enabled(&g2d, g2d_cap_mode_G2D_YUV_BT_709));
+    assert!(!cap_enabled(&g2d, g2d_cap_mode_G2D_YUV_BT_601));
+    assert!(cap_enabled(&g2d, g2d_cap_mode_G2D_DITHER));
+    assert!(!cap_enabled(&g2d, g2d_cap_mode_G2D_BLEND));
+}
+
+#[test]
+fn test_g2d_pipeline_validate() {
+    let _ = env_logger::try_init();
+
+    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    let format = |fourcc| G2DFormat::try_from(fourcc).unwrap();
+    let pipeline = Pipeline::new()
+        .with_stage(Stage::new(
+            "decode",
+            SurfaceSpec::new(format(NV12), 1920, 1080),
+            SurfaceSpec::new(format(RGBA), 640, 360),
+        ))
+        .with_stage(
+            Stage::new(
+                "rotate",
+                SurfaceSpec::new(format(RGBA), 640, 360),
+                SurfaceSpec::new(format(YUYV), 360, 640),
+            )
+            .with_rotation(Rotation::Deg90),
+        );
+
+    let errors = pipeline.validate(&g2d).unwrap_err();
+    for error in &errors {
+        eprintln!("  {error}");
+    }
+    assert!(errors.iter().all(|e| e.index == 1), "{errors:?}");
+    assert!(errors.iter().any(|e| matches!(
+        e.problem,
+        StageProblem::RotationUnsupportedForFormat(Rotation::Deg90, _)
+    )));
+}
+
+#[test]
+fn test_g2d_conversion_matrix() {
+    let _ = env_logger::try_init();
+
+    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    let matrix = g2d.conversion_matrix();
+    let format = |fourcc| G2DFormat::try_from(fourcc).unwrap();
+
+    assert!(matrix.supports(format(YUYV), format(RGBA)));
+    assert!(matrix.supports(format(RGBA), format(YUYV)));
+    assert!(matrix.supports(format(NV12), format(RGBA)));
+    assert!(!matrix.supports(format(RGBA), format(NV12)));
+
+    // Between writable formats, every conversion works both ways.
+    for a in matrix.formats().filter(G2DFormat::is_valid_destination) {
+        for b in matrix.formats().filter(G2DFormat::is_valid_destination) {
+            assert_eq!(
+                matrix.supports(a, b),
+                matrix.supports(b, a),
+                "g2d_format {} <-> {}",
+                a.format(),
+                b.format()
+            );
+        }
+    }
+    for dst in matrix.destinations(format(YUYV)) {
+        assert!(dst.is_valid_destination());
+    }
+}
+
+#[test]
+fn test_g2d_required_stride_alignment() {
+    for fourcc in [RGBA, RGB, YUYV, NV12] {
+        let format = G2DFormat::try_from(fourcc).unwrap();
+        let align = format.required_stride_alignment();
+        assert!(
+            align.is_power_of_two(),
+            "{fourcc}: {align} not a power of two"
+        );
+        assert!(align >= 16, "{fourcc}: {align} below 16");
+
+        let bpp = format.bytes_per_pixel().unwrap_or(1);
+        let stride = format.aligned_stride(1918);
+        assert!(stride >= 1918);
+        assert_eq!((stride * bpp) % align, 0, "{fourcc}: stride {stride}");
+    }
+}
+
 // =============================================================================
 // Format Conversion Tests
 // =============================================================================
@@ -482,865 +533,4674 @@ fn test_g2d_format_conversion() {
 
     let nv12 = G2DFormat::try_from(NV12);
     assert!(nv12.is_ok(), "NV12 format conversion failed");
-}
 
-// =============================================================================
-// Heap Availability Tests
-// =============================================================================
-
-#[test]
-fn test_heap_availability() {
-    let _ = env_logger::try_init();
+    let grey = G2DFormat::try_from(GREY).expect("GREY format conversion failed");
+    assert_eq!(grey.format(), g2d_format_G2D_GRAY8);
+    assert_eq!(four_char_code::FourCharCode::try_from(grey).unwrap(), GREY);
+}
 
-    for heap_type in [HeapType::Uncached, HeapType::Cached] {
-        if heap_type.is_available() {
-            eprintln!("  {heap_type}: AVAILABLE");
-        } else {
-            eprintln!("  {heap_type}: NOT AVAILABLE");
-        }
+/// `(offset, pitch, rows)` of each plane and the total size of the layout.
+fn plane_layout(
+    format: g2d_format,
+    width: usize,
+    height: usize,
+    stride_align: usize,
+    height_align: usize,
+) -> (Vec<(usize, usize, usize)>, usize) {
+    let layout = G2DSurface {
+        format,
+        ..Default::default()
     }
+    .format()
+    .plane_layout(width, height, stride_align, height_align)
+    .unwrap();
+    let planes = layout
+        .planes()
+        .iter()
+        .map(|p| (p.offset, p.pitch, p.rows))
+        .collect();
+    (planes, layout.size())
+}
 
-    // At least one heap must be available for the test suite to be useful
-    assert!(
-        HeapType::Uncached.is_available() || HeapType::Cached.is_available(),
-        "No DMA heap available — cannot run hardware tests"
+#[test]
+fn test_plane_layout_nv12() {
+    assert_eq!(
+        plane_layout(g2d_format_G2D_NV12, 640, 480, 1, 1),
+        (vec![(0, 640, 480), (307200, 640, 240)], 460800)
+    );
+    // 1080 rows pad to 1088; the UV plane starts after the padded luma.
+    assert_eq!(
+        plane_layout(g2d_format_G2D_NV12, 1920, 1080, 64, 16),
+        (vec![(0, 1920, 1088), (2088960, 1920, 544)], 3133440)
+    );
+    // An odd width keeps an even pitch so the last UV pair fits.
+    assert_eq!(
+        plane_layout(g2d_format_G2D_NV12, 5, 3, 1, 1),
+        (vec![(0, 6, 3), (18, 6, 2)], 30)
     );
 }
 
-// =============================================================================
-// Physical Address Tests
-// =============================================================================
-
-fn physical_address_test(heap_type: HeapType) {
-    let size = 4096;
-    let buf = DmaBuffer::new(heap_type, size).expect("Failed to allocate DMA buffer");
-
-    let phys_addr = buf.address();
-    assert!(phys_addr != 0, "Physical address should not be zero");
-    eprintln!("  Physical address: 0x{phys_addr:x}");
+#[test]
+fn test_plane_layout_i420() {
+    assert_eq!(
+        plane_layout(g2d_format_G2D_I420, 640, 480, 1, 1),
+        (
+            vec![(0, 640, 480), (307200, 320, 240), (384000, 320, 240)],
+            460800
+        )
+    );
+    // 100 pads to a 128-byte luma pitch, so each chroma pitch is 64.
+    assert_eq!(
+        plane_layout(g2d_format_G2D_I420, 100, 50, 128, 16),
+        (vec![(0, 128, 64), (8192, 64, 32), (10240, 64, 32)], 12288)
+    );
 }
-heap_tests!(test_g2d_physical_address, physical_address_test);
 
-// =============================================================================
-// Clear Operation Tests (DMA-buf buffers, uncached + cached)
-// =============================================================================
+#[test]
+fn test_plane_layout_yuyv() {
+    assert_eq!(
+        plane_layout(g2d_format_G2D_YUYV, 640, 480, 1, 1),
+        (vec![(0, 1280, 480)], 614400)
+    );
+    assert_eq!(
+        plane_layout(g2d_format_G2D_YUYV, 100, 10, 64, 8),
+        (vec![(0, 256, 16)], 4096)
+    );
+    // Packed 4:2:2 rows hold whole pixel pairs.
+    assert_eq!(
+        plane_layout(g2d_format_G2D_YUYV, 5, 2, 1, 1),
+        (vec![(0, 12, 2)], 24)
+    );
+}
 
-fn clear_rgba_test(heap_type: HeapType) {
-    let width = 64;
-    let height = 64;
-    let size = width * height * 4;
+#[test]
+fn test_plane_layout_rgba() {
+    assert_eq!(
+        plane_layout(g2d_format_G2D_RGBA8888, 640, 480, 1, 1),
+        (vec![(0, 2560, 480)], 1228800)
+    );
+    assert_eq!(
+        plane_layout(g2d_format_G2D_RGBA8888, 100, 10, 64, 4),
+        (vec![(0, 448, 12)], 5376)
+    );
+    // A 3-byte pitch stays a whole number of pixels.
+    let layout = G2DSurface {
+        format: g2d_format_G2D_RGB888,
+        ..Default::default()
+    }
+    .format()
+    .plane_layout(100, 10, 64, 1)
+    .unwrap();
+    assert_eq!(layout.planes()[0].pitch, 384);
+    assert_eq!(layout.stride(), 128);
+}
 
-    let buf = DmaBuffer::new(heap_type, size).expect("Failed to allocate DMA buffer");
+#[test]
+fn test_g2d_format_has_alpha() {
+    let has_alpha = |format| {
+        G2DSurface {
+            format,
+            ..Default::default()
+        }
+        .format()
+        .has_alpha()
+    };
+    for format in [
+        g2d_format_G2D_RGBA8888,
+        g2d_format_G2D_BGRA8888,
+        g2d_format_G2D_ARGB8888,
+        g2d_format_G2D_ABGR8888,
+    ] {
+        assert!(has_alpha(format), "g2d_format {format} has alpha");
+    }
+    for format in [
+        g2d_format_G2D_RGBX8888,
+        g2d_format_G2D_XRGB8888,
+        g2d_format_G2D_RGB888,
+        g2d_format_G2D_RGB565,
+        g2d_format_G2D_YUYV,
+        g2d_format_G2D_NV12,
+    ] {
+        assert!(!has_alpha(format), "g2d_format {format} has no alpha");
+    }
+}
+
+#[test]
+fn test_g2d_format_byte_swapped() {
+    let format = |format| {
+        G2DSurface {
+            format,
+            ..Default::default()
+        }
+        .format()
+    };
+    // Each RGB pair packs a pixel in reversed byte order, and swapping twice
+    // gives back the original format.
+    for rgb in [
+        g2d_format_G2D_RGBA8888,
+        g2d_format_G2D_BGRA8888,
+        g2d_format_G2D_ARGB8888,
+        g2d_format_G2D_ABGR8888,
+        g2d_format_G2D_RGBX8888,
+        g2d_format_G2D_XRGB8888,
+        g2d_format_G2D_RGB888,
+        g2d_format_G2D_BGR888,
+    ] {
+        let swapped = format(rgb).byte_swapped().expect("swappable");
+        let (native, len) = format(rgb).pack_rgba([1, 2, 3, 4]).unwrap();
+        let (reversed, _) = swapped.pack_rgba([1, 2, 3, 4]).unwrap();
+        let mut expected = native;
+        expected[..len].reverse();
+        assert_eq!(reversed[..len], expected[..len], "g2d_format {rgb}");
+        assert_eq!(swapped.byte_swapped().unwrap().format(), rgb);
+    }
+    assert_eq!(
+        format(g2d_format_G2D_YUYV).byte_swapped().unwrap().format(),
+        g2d_format_G2D_UYVY
+    );
+    for format in [
+        g2d_format_G2D_RGB565,
+        g2d_format_G2D_GRAY8,
+        g2d_format_G2D_NV12,
+    ] {
+        let surface = G2DSurface {
+            format,
+            ..Default::default()
+        };
+        assert!(surface.format().byte_swapped().is_none());
+        assert!(matches!(
+            surface.with_byte_order(ByteOrder::Swapped),
+            Err(Error::Unsupported(_))
+        ));
+    }
+}
+
+#[test]
+fn test_verify_crc32_check_value() {
+    assert_eq!(verify::crc32(b"123456789"), 0xCBF4_3926);
+    assert_eq!(verify::crc32(b""), 0);
+}
+
+#[test]
+fn test_pixel_to_rgba() {
+    assert_eq!(RGBA8([1, 2, 3, 4]).to_rgba(), [1, 2, 3, 4]);
+    assert_eq!(BGRA8([1, 2, 3, 4]).to_rgba(), [3, 2, 1, 4]);
+    assert_eq!(RGB565(0xf800u16.to_le_bytes()).to_rgba(), [255, 0, 0, 255]);
+    assert_eq!(RGB565(0x07e0u16.to_le_bytes()).to_rgba(), [0, 255, 0, 255]);
+    assert_eq!(RGB565(0x001fu16.to_le_bytes()).to_rgba(), [0, 0, 255, 255]);
+    assert_eq!(RGB565([0x34, 0x12]).value(), 0x1234);
+    assert_eq!(RGBA8::FORMAT, g2d_format_G2D_RGBA8888);
+    assert_eq!(BGRA8::FORMAT, g2d_format_G2D_BGRA8888);
+    assert_eq!(RGB565::FORMAT, g2d_format_G2D_RGB565);
+}
+
+// =============================================================================
+// Surface Accessor Tests
+// =============================================================================
+
+#[test]
+fn test_g2d_surface_accessors() {
+    let surface = G2DSurface {
+        format: g2d_format_G2D_NV12,
+        planes: [0x1000, 0x2000, 0],
+        left: 8,
+        top: 4,
+        right: 72,
+        bottom: 52,
+        stride: 128,
+        width: 100,
+        height: 60,
+        rot: g2d_rotation_G2D_ROTATION_90,
+        ..Default::default()
+    };
+
+    assert_eq!(surface.format().format(), g2d_format_G2D_NV12);
+    assert_eq!(surface.crop_rect(), Rect::new(8, 4, 72, 52));
+    assert_eq!(surface.crop_rect().width(), 64);
+    assert_eq!(surface.crop_rect().height(), 48);
+    assert_eq!(surface.dimensions(), (100, 60));
+    assert_eq!(surface.stride(), 128);
+    assert_eq!(surface.rotation(), g2d_rotation_G2D_ROTATION_90);
+    assert_eq!(surface.planes(), [0x1000, 0x2000, 0]);
+}
+
+/// A 100x60 RGBA builder with a 128-pixel stride and a valid crop.
+fn rgba_surface_builder() -> SurfaceBuilder {
+    SurfaceBuilder::new()
+        .format(G2DFormat::try_from(RGBA).unwrap())
+        .size(100, 60)
+        .stride(128)
+        .crop(8, 4, 72, 52)
+        .planes([0x1000, 0, 0])
+}
+
+#[test]
+fn test_surface_builder_valid() {
+    let surface = rgba_surface_builder().build().expect("valid geometry");
+    assert_eq!(surface.format, g2d_format_G2D_RGBA8888);
+    assert_eq!(surface.crop_rect(), Rect::new(8, 4, 72, 52));
+    assert_eq!(surface.dimensions(), (100, 60));
+    assert_eq!(surface.stride(), 128);
+    assert_eq!(surface.planes(), [0x1000, 0, 0]);
+    assert_eq!(surface.global_alpha, 255);
+
+    // Stride and crop default to the width and the whole surface.
+    let surface = SurfaceBuilder::new()
+        .format(G2DFormat::try_from(RGBA).unwrap())
+        .size(100, 60)
+        .planes([0x1000, 0, 0])
+        .build()
+        .unwrap();
+    assert_eq!(surface.stride(), 100);
+    assert_eq!(surface.crop_rect(), Rect::new(0, 0, 100, 60));
+}
+
+#[test]
+fn test_rect_fit() {
+    let square = Rect::new(0, 0, 640, 640);
+    assert_eq!(square.fit(1920, 1080), Rect::new(0, 140, 640, 500));
+    assert_eq!(square.fit(1080, 1920), Rect::new(140, 0, 500, 640));
+    // Matching aspect ratio: no bars.
+    assert_eq!(square.fit(100, 100), square);
+    assert_eq!(
+        Rect::new(10, 20, 330, 200).fit(1920, 1080),
+        Rect::new(10, 20, 330, 200)
+    );
+    // An odd leftover gives the bottom and right bars the extra pixel.
+    assert_eq!(
+        Rect::new(0, 0, 640, 641).fit(16, 9),
+        Rect::new(0, 140, 640, 500)
+    );
+    assert_eq!(Rect::new(0, 0, 33, 32).fit(1, 1), Rect::new(0, 0, 32, 32));
+    // The scaled side rounds to the nearest pixel.
+    assert_eq!(
+        Rect::new(0, 0, 100, 100).fit(3, 2),
+        Rect::new(0, 16, 100, 83)
+    );
+    assert!(square.fit(0, 1080).is_empty());
+}
+
+#[test]
+fn test_surface_builder_rejects_geometry() {
+    assert_eq!(
+        rgba_surface_builder()
+            .crop(40, 4, 40, 52)
+            .build()
+            .unwrap_err(),
+        SurfaceError::InvalidCrop {
+            crop: Rect::new(40, 4, 40, 52),
+            width: 100,
+            height: 60
+        },
+        "right <= left"
+    );
+    assert_eq!(
+        rgba_surface_builder()
+            .crop(0, 0, 100, 61)
+            .build()
+            .unwrap_err(),
+        SurfaceError::InvalidCrop {
+            crop: Rect::new(0, 0, 100, 61),
+            width: 100,
+            height: 60
+        },
+        "bottom > height"
+    );
+    assert!(matches!(
+        rgba_surface_builder().crop(-1, 0, 10, 10).build(),
+        Err(SurfaceError::InvalidCrop { .. })
+    ));
+    assert_eq!(
+        rgba_surface_builder().stride(99).build().unwrap_err(),
+        SurfaceError::StrideTooSmall {
+            stride: 99,
+            width: 100
+        }
+    );
+    assert_eq!(
+        rgba_surface_builder().stride(0).build().unwrap_err(),
+        SurfaceError::StrideTooSmall {
+            stride: 0,
+            width: 100
+        }
+    );
+    assert_eq!(
+        rgba_surface_builder().size(0, 60).build().unwrap_err(),
+        SurfaceError::InvalidSize {
+            width: 0,
+            height: 60
+        }
+    );
+    assert_eq!(
+        SurfaceBuilder::new().size(100, 60).build().unwrap_err(),
+        SurfaceError::MissingFormat
+    );
+
+    // NV12 needs its UV plane, and the error converts for `?` on `Result`.
+    let nv12 = SurfaceBuilder::new()
+        .format(G2DFormat::try_from(NV12).unwrap())
+        .size(64, 48)
+        .planes([0x1000, 0, 0])
+        .build()
+        .unwrap_err();
+    assert_eq!(nv12, SurfaceError::MissingPlane(1));
+    assert!(matches!(Error::from(nv12), Error::InvalidSurface(_)));
+}
+
+#[test]
+fn test_g2d_surface_tri_planar_offsets() {
+    let base = 0x1000_0000;
+    let (w, h) = (64, 48);
+    let luma = (w * h) as u64;
+    let chroma = (w / 2 * h / 2) as u64;
+
+    let i420 = G2DSurface::i420(base, w, h);
+    assert_eq!(i420.format, g2d_format_G2D_I420);
+    assert_eq!(i420.planes(), [base, base + luma, base + luma + chroma]);
+    assert_eq!(i420.crop_rect(), Rect::new(0, 0, w, h));
+    assert_eq!(i420.stride(), w);
+
+    // Same memory-order plane addresses; the format selects V-before-U.
+    let yv12 = G2DSurface::yv12(base, w, h);
+    assert_eq!(yv12.format, g2d_format_G2D_YV12);
+    assert_eq!(yv12.planes(), i420.planes());
+}
+
+#[test]
+fn test_g2d_surface_tri_planar_odd_dimensions() {
+    let base = 0x1000_0000;
+    // 63x47 keeps an even 64-byte luma stride and rounds the chroma rows
+    // up to 24, matching plane_layout.
+    let i420 = G2DSurface::i420(base, 63, 47);
+    assert_eq!(i420.stride(), 64);
+    assert_eq!(i420.crop_rect(), Rect::new(0, 0, 63, 47));
+    let (planes, _) = plane_layout(g2d_format_G2D_I420, 63, 47, 1, 1);
+    assert_eq!(
+        planes,
+        [(0, 64, 47), (64 * 47, 32, 24), (64 * 47 + 32 * 24, 32, 24)]
+    );
+    let offsets = planes.iter().map(|&(offset, _, _)| base + offset as u64);
+    assert!(i420.planes().into_iter().eq(offsets));
+}
+
+#[test]
+fn test_g2d_surface_tri_planar_explicit_planes() {
+    let (y, u, v) = (0x1000_0000, 0x1010_0000, 0x1020_0000);
+    let i420 = G2DSurface::i420_planes(y, u, v, 100, 64, 128);
+    assert_eq!(i420.format, g2d_format_G2D_I420);
+    assert_eq!(i420.planes(), [y, u, v]);
+    assert_eq!(i420.stride(), 128);
+    assert_eq!(i420.crop_rect(), Rect::new(0, 0, 100, 64));
+
+    // YV12 stores V before U, so the V address is the second plane.
+    let yv12 = G2DSurface::yv12_planes(y, u, v, 100, 64, 128);
+    assert_eq!(yv12.format, g2d_format_G2D_YV12);
+    assert_eq!(yv12.planes(), [y, v, u]);
+}
+
+// =============================================================================
+// Rotation Tests
+// =============================================================================
+
+#[test]
+fn test_rotation_for_display_mount() {
+    // A panel mounted 90° clockwise needs content counter-rotated by 270°.
+    assert_eq!(
+        Rotation::for_display_mount(Rotation::Deg90),
+        Rotation::Deg270
+    );
+    assert_eq!(
+        Rotation::for_display_mount(Rotation::Deg270),
+        Rotation::Deg90
+    );
+    assert_eq!(
+        Rotation::for_display_mount(Rotation::Deg180),
+        Rotation::Deg180
+    );
+    assert_eq!(Rotation::for_display_mount(Rotation::None), Rotation::None);
+
+    // Mount rotation followed by the content rotation is always upright.
+    for mount in [
+        Rotation::None,
+        Rotation::Deg90,
+        Rotation::Deg180,
+        Rotation::Deg270,
+    ] {
+        let content = Rotation::for_display_mount(mount);
+        assert_eq!((mount.degrees() + content.degrees()) % 360, 0);
+    }
+
+    assert_eq!(Rotation::from_degrees(-90), Some(Rotation::Deg270));
+    assert_eq!(Rotation::from_degrees(450), Some(Rotation::Deg90));
+    assert_eq!(Rotation::from_degrees(45), None);
+    assert_eq!(
+        g2d_sys::g2d_rotation::from(Rotation::Deg90),
+        g2d_rotation_G2D_ROTATION_90
+    );
+}
+
+/// Every `g2d_rotation_*` constant in the generated bindings maps to a
+/// `Transform` and back.
+#[test]
+fn test_rotation_constants_all_mapped() {
+    let bindings = include_str!("../src/ffi.rs");
+    let values: Vec<(&str, g2d_rotation)> = bindings
+        .lines()
+        .filter_map(|line| line.strip_prefix("pub const g2d_rotation_"))
+        .map(|rest| {
+            let (name, value) = rest.split_once(": g2d_rotation = ").expect("constant line");
+            (name, value.trim_end_matches(';').parse().expect("value"))
+        })
+        .collect();
+    assert_eq!(values.len(), 6, "expected 4 rotations and 2 flips");
+
+    for (name, value) in values {
+        let transform = Transform::try_from(value)
+            .unwrap_or_else(|_| panic!("g2d_rotation_{name} has no safe mapping"));
+        assert_eq!(g2d_rotation::from(transform), value, "{name} round trip");
+    }
+
+    assert_eq!(
+        Rotation::try_from(g2d_rotation_G2D_ROTATION_270).unwrap(),
+        Rotation::Deg270
+    );
+    assert!(Rotation::try_from(g2d_rotation_G2D_FLIP_H).is_err());
+    assert!(Transform::try_from(6).is_err());
+
+    let mut surface = G2DSurface::default();
+    surface.set_transform(Flip::Vertical);
+    assert_eq!(surface.rotation(), g2d_rotation_G2D_FLIP_V);
+    assert_eq!(surface.transform(), Some(Transform::Flip(Flip::Vertical)));
+
+    // A double flip is sent as, and reads back as, a 180° rotation.
+    surface.set_transform(Flip::Both);
+    assert_eq!(surface.rotation(), g2d_rotation_G2D_ROTATION_180);
+    assert_eq!(
+        surface.transform(),
+        Some(Transform::Rotate(Rotation::Deg180))
+    );
+}
+
+// =============================================================================
+// Metrics Tests
+// =============================================================================
+
+#[test]
+fn test_estimate_bandwidth_1080p_nv12_to_rgba() {
+    let surface = |format| G2DSurface {
+        format,
+        right: 1920,
+        bottom: 1080,
+        stride: 1920,
+        width: 1920,
+        height: 1080,
+        ..Default::default()
+    };
+    let src = surface(g2d_format_G2D_NV12);
+    let dst = surface(g2d_format_G2D_RGBA8888);
+
+    // 1.5 B/px NV12 read + 4 B/px RGBA write.
+    assert_eq!(
+        metrics::bytes_moved(&src, &dst),
+        1920 * 1080 * 3 / 2 + 1920 * 1080 * 4
+    );
+
+    let bw = metrics::bandwidth(&src, &dst, 30);
+    assert_eq!(bw, 342_144_000);
+    assert!(
+        (300_000_000..400_000_000).contains(&bw),
+        "1080p@30 NV12->RGBA should need ~340 MB/s, got {bw}"
+    );
+}
+
+// =============================================================================
+// Cache Line Tests
+// =============================================================================
+
+#[test]
+fn test_dma_buf_ioctl_sync_number() {
+    // _IOW('b', 0, struct dma_buf_sync): write direction, 8-byte argument.
+    let ioc_write = 1 << 30;
+    let size = (std::mem::size_of::<u64>() as libc::c_ulong) << 16;
+    let expected = ioc_write | size | ((b'b' as libc::c_ulong) << 8);
+    assert_eq!(sync::DMA_BUF_IOCTL_SYNC, expected);
+    assert_eq!(sync::DMA_BUF_IOCTL_SYNC, 0x4008_6200);
+}
+
+#[test]
+fn test_cache_line_size() {
+    let size = sync::cache_line_size();
+    eprintln!("  cache line size: {size} bytes");
+    assert!(size.is_power_of_two(), "{size} is not a power of two");
+    assert!(
+        size >= 32,
+        "{size} is smaller than any supported core's line"
+    );
+    assert_eq!(sync::cache_line_size(), size, "value must be stable");
+}
+
+// =============================================================================
+// Heap Availability Tests
+// =============================================================================
+
+#[test]
+fn test_heap_availability() {
+    let _ = env_logger::try_init();
+
+    for heap_type in [HeapType::Uncached, HeapType::Cached] {
+        if heap_type.is_available() {
+            eprintln!("  {heap_type}: AVAILABLE");
+        } else {
+            eprintln!("  {heap_type}: NOT AVAILABLE");
+        }
+    }
+
+    // At least one heap must be available for the test suite to be useful
+    assert!(
+        HeapType::Uncached.is_available() || HeapType::Cached.is_available(),
+        "No DMA heap available — cannot run hardware tests"
+    );
+}
+
+/// Uncached buffers are coherent and need no sync; cached buffers do.
+fn requires_sync_test(heap_type: HeapType) {
+    let buf = DmaBuffer::new(heap_type, 4096).expect("Failed to allocate DMA buffer");
+    assert_eq!(buf.requires_sync(), heap_type == HeapType::Cached);
+}
+heap_tests!(test_requires_sync, requires_sync_test);
+
+// =============================================================================
+// Physical Address Tests
+// =============================================================================
+
+fn physical_address_test(heap_type: HeapType) {
+    let size = 4096;
+    let buf = DmaBuffer::new(heap_type, size).expect("Failed to allocate DMA buffer");
+
+    let phys_addr = buf.address();
+    assert!(phys_addr != 0, "Physical address should not be zero");
+    eprintln!("  Physical address: 0x{phys_addr:x}");
+}
+heap_tests!(test_g2d_physical_address, physical_address_test);
+
+/// A file that is not a dma-buf has no physical address, reported as
+/// `Error::PhysicalAddress` with the ioctl's OS error.
+#[test]
+fn test_g2d_physical_address_not_dmabuf() {
+    use std::os::fd::AsRawFd;
+
+    let file = std::fs::File::open("/dev/null").expect("open /dev/null");
+    let err = G2DPhysical::new(file.as_raw_fd()).unwrap_err();
+    let Error::PhysicalAddress(source) = &err else {
+        panic!("expected Error::PhysicalAddress, got {err:?}");
+    };
+    assert!(source.raw_os_error().is_some(), "OS error kept: {source:?}");
+    assert!(err.to_string().contains("DMA_BUF_IOCTL_PHYS"), "{err}");
+    assert!(std::error::Error::source(&err).is_some());
+}
+
+/// An allocation larger than any heap can satisfy is reported as
+/// `Error::AllocationFailed` naming the heap and size, not as a generic
+/// I/O error.
+fn allocation_failure_test(heap_type: HeapType) {
+    let size = 1usize << 40;
+    match DmaBuffer::new(heap_type, size) {
+        Err(Error::AllocationFailed {
+            heap,
+            size: failed,
+            source,
+        }) => {
+            assert_eq!(heap, heap_type.to_string());
+            assert_eq!(failed, size);
+            assert!(source.raw_os_error().is_some(), "OS error kept: {source:?}");
+        }
+        other => panic!("expected Error::AllocationFailed, got {other:?}"),
+    }
+}
+heap_tests!(test_g2d_allocation_failure, allocation_failure_test);
+
+/// Current virtual memory size of this process in bytes.
+fn vm_size() -> usize {
+    let status = std::fs::read_to_string("/proc/self/status").expect("read /proc/self/status");
+    let kib: usize = status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmSize:"))
+        .and_then(|v| v.trim().trim_end_matches("kB").trim().parse().ok())
+        .expect("VmSize in /proc/self/status");
+    kib * 1024
+}
+
+/// A normal allocation maps fine; with the address space capped just above
+/// current usage (in a forked child), a 64 MiB allocation reports
+/// `Error::MmapFailed` with `ENOMEM` and the requested size.
+fn mmap_failure_test(heap_type: HeapType) {
+    DmaBuffer::new(heap_type, 1024 * 1024).expect("1 MiB allocation should succeed");
+
+    const SIZE: usize = 64 * 1024 * 1024;
+    let limit = (vm_size() + 8 * 1024 * 1024) as libc::rlim_t;
+
+    let pid = unsafe { libc::fork() };
+    assert!(pid >= 0, "fork failed");
+    if pid == 0 {
+        let rlimit = libc::rlimit {
+            rlim_cur: limit,
+            rlim_max: limit,
+        };
+        let code = if unsafe { libc::setrlimit(libc::RLIMIT_AS, &rlimit) } != 0 {
+            2
+        } else {
+            match DmaBuffer::new(heap_type, SIZE) {
+                Err(Error::MmapFailed { size, source })
+                    if size == SIZE && source.raw_os_error() == Some(libc::ENOMEM) =>
+                {
+                    0
+                }
+                _ => 1,
+            }
+        };
+        unsafe { libc::_exit(code) };
+    }
+
+    let mut status = 0;
+    assert_eq!(unsafe { libc::waitpid(pid, &mut status, 0) }, pid);
+    assert!(libc::WIFEXITED(status), "child did not exit cleanly");
+    match libc::WEXITSTATUS(status) {
+        0 => {}
+        2 => eprintln!("SKIPPED: setrlimit(RLIMIT_AS) failed"),
+        code => panic!("expected Error::MmapFailed {{ size: {SIZE}, ENOMEM }} (child exit {code})"),
+    }
+}
+heap_tests!(test_g2d_mmap_failure, mmap_failure_test);
+
+fn scanout_alignment_test(heap_type: HeapType) {
+    let (width, height) = (100, 50);
+
+    for (fourcc, bpp) in [(RGBA, 4), (RGB, 3)] {
+        let format = G2DFormat::try_from(fourcc).unwrap();
+        let buf = DmaBuffer::new_scanout(heap_type, format, width, height)
+            .expect("Failed to allocate scanout buffer");
+        let pitch = buf.pitch().expect("Scanout buffer has a pitch");
+        assert_eq!(pitch % SCANOUT_PITCH_ALIGN, 0, "pitch {pitch} not aligned");
+        assert_eq!(pitch, format.aligned_stride(width) * bpp);
+        assert_eq!(pitch % bpp, 0, "pitch {pitch} not a whole number of pixels");
+        assert!(pitch >= width * bpp);
+        assert_eq!(
+            buf.size() % SCANOUT_SIZE_ALIGN,
+            0,
+            "size {} not aligned",
+            buf.size()
+        );
+        assert!(buf.size() >= pitch * height);
+    }
+
+    // Blit an opaque white source into the padded RGBA scanout buffer.
+    let mut dst_buf =
+        DmaBuffer::new_scanout(heap_type, G2DFormat::try_from(RGBA).unwrap(), width, height)
+            .expect("Failed to allocate scanout buffer");
+    let pitch = dst_buf.pitch().unwrap();
+    dst_buf.write_with(|data| data.fill(0));
+    let mut dst_surface = create_surface(&dst_buf, width, height, g2d_format_G2D_RGBA8888);
+    dst_surface.stride = (pitch / 4) as i32;
+
+    let mut src_buf =
+        DmaBuffer::new(heap_type, width * height * 4).expect("Failed to allocate src buffer");
+    src_buf.write_with(|data| data.fill(255));
+    let src_surface = create_surface(&src_buf, width, height, g2d_format_G2D_RGBA8888);
+
+    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    g2d.blit(&src_surface, &dst_surface).expect("Blit failed");
+    g2d.finish().unwrap();
+
+    dst_buf.read_with(|data| {
+        for y in [0, height - 1] {
+            let row = &data[y * pitch..y * pitch + pitch];
+            assert!(
+                row[..width * 4].iter().all(|&b| b == 255),
+                "row {y} not written"
+            );
+            assert!(
+                row[width * 4..].iter().all(|&b| b == 0),
+                "row {y} padding written"
+            );
+        }
+    });
+}
+heap_tests!(test_g2d_scanout_alignment, scanout_alignment_test);
+
+/// Register a scanout buffer as a DRM framebuffer and present a solid frame
+/// into it. Skipped when there is no KMS device.
+fn display_target_present_test(heap_type: HeapType) {
+    let card = "/dev/dri/card0";
+    if !std::path::Path::new(card).exists() {
+        eprintln!("SKIP display_target_present: {card} not available");
+        return;
+    }
+    let (width, height) = (100usize, 20usize);
+    let format = G2DFormat::try_from(RGBA).unwrap();
+
+    let target = DisplayTarget::new(card, heap_type, format, width, height)
+        .expect("Failed to register framebuffer");
+    assert_ne!(target.gem_handle(), 0, "PRIME import returned no handle");
+    assert_ne!(target.framebuffer_id(), 0, "ADDFB2 returned no framebuffer");
+    let pitch = target.buffer().pitch().unwrap();
+    assert_eq!(target.surface().stride as usize * 4, pitch);
+
+    let mut src_buf =
+        DmaBuffer::new(heap_type, width * height * 4).expect("Failed to allocate src buffer");
+    src_buf.write_with(|data| {
+        for px in data.chunks_exact_mut(4) {
+            px.copy_from_slice(&[0, 128, 255, 255]);
+        }
+    });
+    let src = create_surface(&src_buf, width, height, g2d_format_G2D_RGBA8888);
+
+    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    g2d.present(&target, &src).expect("present failed");
+
+    target.buffer().read_with(|data| {
+        for y in 0..height {
+            let row = &data[y * pitch..y * pitch + width * 4];
+            assert!(
+                row.chunks_exact(4).all(|px| px == [0, 128, 255, 255]),
+                "row {y} not presented"
+            );
+        }
+    });
+}
+heap_tests!(test_g2d_display_target_present, display_target_present_test);
+
+/// Render red to the back buffer and swap, then blue and swap: after each
+/// swap the front buffer holds the frame just rendered, and the two frames
+/// were rendered into different buffers.
+fn double_buffer_swap_test(heap_type: HeapType) {
+    let (width, height) = (100usize, 20usize);
+    let format = G2DFormat::try_from(RGBA).unwrap();
+    let mut frames =
+        DoubleBuffer::new(heap_type, format, width, height).expect("Failed to allocate buffers");
+    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+
+    let front_is = |frames: &DoubleBuffer, color: [u8; 4]| {
+        let (buffer, surface) = frames.front();
+        let pitch = surface.stride as usize * 4;
+        buffer.read_with(|data| {
+            for y in 0..height {
+                let row = &data[y * pitch..y * pitch + width * 4];
+                assert!(
+                    row.chunks_exact(4).all(|px| px == color),
+                    "front row {y} is not {color:?}"
+                );
+            }
+        });
+    };
+
+    let mut fronts = Vec::new();
+    for color in [[255, 0, 0, 255], [0, 0, 255, 255]] {
+        frames
+            .render_to_back(|surface| g2d.clear(&mut { *surface }, color))
+            .expect("render failed");
+        frames.swap(&g2d).expect("swap failed");
+        front_is(&frames, color);
+        fronts.push(frames.front().0.address());
+    }
+    assert_ne!(fronts[0], fronts[1], "both frames went to one buffer");
+    assert_eq!(frames.back().0.address(), fronts[0]);
+    assert_eq!(frames.swaps(), 2);
+}
+heap_tests!(test_g2d_double_buffer_swap, double_buffer_swap_test);
+
+// =============================================================================
+// Clear Operation Tests (DMA-buf buffers, uncached + cached)
+// =============================================================================
+
+fn clear_rgba_test(heap_type: HeapType) {
+    let width = 64;
+    let height = 64;
+    let size = width * height * 4;
+
+    let mut buf = DmaBuffer::new(heap_type, size).expect("Failed to allocate DMA buffer");
+    buf.write_with(|data| data.fill(0));
+
+    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    let mut surface = create_surface(&buf, width, height, g2d_format_G2D_RGBA8888);
+
+    let color = [255u8, 0, 0, 255];
+    let result = g2d.clear(&mut surface, color);
+    assert!(result.is_ok(), "G2D clear failed: {:?}", result.err());
+    g2d.finish().unwrap();
+
+    buf.read_with(|data| {
+        for i in 0..10 {
+            let offset = i * 4;
+            assert_eq!(data[offset], 255, "Red channel mismatch at pixel {i}");
+            assert_eq!(data[offset + 1], 0, "Green channel mismatch at pixel {i}");
+            assert_eq!(data[offset + 2], 0, "Blue channel mismatch at pixel {i}");
+            assert_eq!(data[offset + 3], 255, "Alpha channel mismatch at pixel {i}");
+        }
+    });
+}
+heap_tests!(test_g2d_clear_rgba, clear_rgba_test);
+
+/// Read a cleared buffer back through the typed `as_pixels` view.
+fn clear_read_as_pixels_test(heap_type: HeapType) {
+    let width = 64;
+    let height = 32;
+    let size = width * height * 4;
+
+    let mut buf = DmaBuffer::new(heap_type, size).expect("Failed to allocate DMA buffer");
     buf.write_with(|data| data.fill(0));
 
     let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
-    let mut surface = create_surface(&buf, width, height, g2d_format_G2D_RGBA8888);
+    let mut surface = create_surface(&buf, width, height, g2d_format_G2D_RGBA8888);
+    g2d.clear(&mut surface, [255, 0, 0, 255]).unwrap();
+    g2d.finish().unwrap();
+
+    {
+        let pixels = buf.as_pixels::<RGBA8>(width, height).unwrap();
+        assert_eq!(pixels.len(), width * height);
+        assert_eq!(pixels[0], [255, 0, 0, 255]);
+        assert!(pixels.iter().all(|px| *px == [255, 0, 0, 255]));
+    }
+    {
+        let pixels = buf.as_pixels::<BGRA8>(width, height).unwrap();
+        assert_eq!(pixels[0].to_rgba(), [0, 0, 255, 255]);
+    }
+
+    assert!(matches!(
+        buf.as_pixels::<RGBA8>(width, height + 1),
+        Err(Error::InvalidSurface(_))
+    ));
+}
+heap_tests!(test_g2d_clear_read_as_pixels, clear_read_as_pixels_test);
+
+/// Read a cleared buffer through `read_finished`: the token from
+/// `finish_token` allows the read until another operation is queued, after
+/// which the stale token is refused and a fresh one is needed.
+fn clear_read_finished_test(heap_type: HeapType) {
+    let width = 32;
+    let height = 32;
+    let size = width * height * 4;
+
+    let buf = DmaBuffer::new(heap_type, size).expect("Failed to allocate DMA buffer");
+    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    let mut surface = create_surface(&buf, width, height, g2d_format_G2D_RGBA8888);
+
+    g2d.clear(&mut surface, [255, 0, 0, 255]).unwrap();
+    let token = g2d.finish_token().unwrap();
+    assert!(token.is_current());
+    let first = buf.read_finished(&token, |data| [data[0], data[1], data[2], data[3]]);
+    assert_eq!(first.unwrap(), [255, 0, 0, 255]);
+
+    // Reading on a token taken before this clear must be refused.
+    g2d.clear(&mut surface, [0, 255, 0, 255]).unwrap();
+    assert!(!token.is_current());
+    assert!(matches!(
+        buf.read_finished(&token, |_| ()),
+        Err(Error::NotFinished)
+    ));
+
+    let token = g2d.finish_token().unwrap();
+    let first = buf.read_finished(&token, |data| [data[0], data[1], data[2], data[3]]);
+    assert_eq!(first.unwrap(), [0, 255, 0, 255]);
+}
+heap_tests!(test_g2d_clear_read_finished, clear_read_finished_test);
+
+/// Four worker threads share a 4-context `G2DPool`, each opening its own
+/// context and running four clears: all 16 buffers hold their color.
+fn pool_clears_test(heap_type: HeapType) {
+    let (width, height) = (32usize, 32usize);
+    let pool = G2DPool::with_capacity("libg2d.so.2", 4).expect("Failed to create G2D pool");
+    assert_eq!(pool.capacity(), 4);
+    let color = |worker: usize, i: usize| [worker as u8 * 60, i as u8 * 16, 7, 255];
+
+    std::thread::scope(|scope| {
+        for worker in 0..4 {
+            let pool = &pool;
+            scope.spawn(move || {
+                let g2d = pool.get().expect("Failed to open pooled context");
+                let bufs: Vec<_> = (0..4)
+                    .map(|_| {
+                        DmaBuffer::new(heap_type, width * height * 4).expect("Failed to allocate")
+                    })
+                    .collect();
+                for (i, buf) in bufs.iter().enumerate() {
+                    let mut surface = create_surface(buf, width, height, g2d_format_G2D_RGBA8888);
+                    g2d.clear(&mut surface, color(worker, i))
+                        .unwrap_or_else(|e| panic!("worker {worker} clear {i} failed: {e}"));
+                }
+                g2d.finish().expect("finish failed");
+
+                for (i, buf) in bufs.iter().enumerate() {
+                    buf.read_with(|data| {
+                        assert!(
+                            data.chunks_exact(4).all(|px| px == color(worker, i)),
+                            "worker {worker} buffer {i} not cleared to {:?}",
+                            color(worker, i)
+                        );
+                    });
+                }
+            });
+        }
+    });
+    assert_eq!(pool.in_use(), 0);
+}
+heap_tests!(test_g2d_pool_clears, pool_clears_test);
+
+/// Four threads, each with its own handle and buffer, run 50 clears
+/// concurrently; every buffer must end up holding its thread's last color.
+fn threaded_clears_test(heap_type: HeapType) {
+    let (width, height) = (64usize, 64usize);
+    let color = |thread: usize, i: usize| [thread as u8 * 60, i as u8, 255 - i as u8, 255];
+
+    let workers: Vec<_> = (0..4)
+        .map(|thread| {
+            std::thread::spawn(move || {
+                let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+                let buf =
+                    DmaBuffer::new(heap_type, width * height * 4).expect("Failed to allocate");
+                let mut surface = create_surface(&buf, width, height, g2d_format_G2D_RGBA8888);
+                for i in 0..50 {
+                    g2d.clear(&mut surface, color(thread, i))
+                        .unwrap_or_else(|e| panic!("thread {thread} clear {i} failed: {e}"));
+                    g2d.finish().expect("finish failed");
+                }
+                buf.read_with(|data| data.chunks_exact(4).all(|px| px == color(thread, 49)))
+            })
+        })
+        .collect();
+
+    for (thread, worker) in workers.into_iter().enumerate() {
+        let cleared = worker.join().expect("worker panicked");
+        assert!(
+            cleared,
+            "thread {thread} buffer not cleared to its last color"
+        );
+    }
+}
+heap_tests!(test_g2d_threaded_clears, threaded_clears_test);
+
+/// Select each core the SoC has with `make_current` and clear on it;
+/// cores it lacks must be refused with `Error::Unsupported`.
+fn make_current_clear_test(heap_type: HeapType) {
+    let (width, height) = (64usize, 64usize);
+    let buf = DmaBuffer::new(heap_type, width * height * 4).expect("Failed to allocate");
+    let mut surface = create_surface(&buf, width, height, g2d_format_G2D_RGBA8888);
+    let mut g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    assert!(g2d.core_available(Core::Gpu2d).unwrap());
+
+    for (i, core) in Core::ALL.into_iter().enumerate() {
+        if !g2d.core_available(core).unwrap() {
+            assert!(matches!(g2d.make_current(core), Err(Error::Unsupported(_))));
+            eprintln!("{core:?} core not available, skipping");
+            continue;
+        }
+        g2d.make_current(core)
+            .unwrap_or_else(|e| panic!("make_current({core:?}) failed: {e}"));
+        let color = [i as u8 * 80, 255, 40, 255];
+        g2d.clear(&mut surface, color)
+            .unwrap_or_else(|e| panic!("clear on {core:?} failed: {e}"));
+        g2d.finish().unwrap();
+        buf.read_with(|data| {
+            assert!(
+                data.chunks_exact(4).all(|px| px == color),
+                "{core:?} clear did not fill the buffer"
+            );
+        });
+    }
+}
+heap_tests!(test_g2d_make_current_clear, make_current_clear_test);
+
+/// Clear a 1 MiB `g2d_alloc` buffer, cacheable and write-combined, and read
+/// the color back through its CPU mapping.
+#[test]
+fn test_g2d_buffer_clear() {
+    let (width, height) = (512usize, 512usize);
+    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+
+    for cacheable in [false, true] {
+        let mut buf =
+            G2DBuffer::new(&g2d, width * height * 4, cacheable).expect("g2d_alloc failed");
+        assert_eq!(buf.size(), 1 << 20);
+        buf.as_mut_slice().fill(0);
+        buf.clean().unwrap();
+
+        let mut surface = G2DSurface {
+            format: g2d_format_G2D_RGBA8888,
+            planes: [buf.address(), 0, 0],
+            right: width as i32,
+            bottom: height as i32,
+            stride: width as i32,
+            width: width as i32,
+            height: height as i32,
+            global_alpha: 255,
+            ..Default::default()
+        };
+        g2d.clear(&mut surface, [200, 100, 50, 255])
+            .expect("clear failed");
+        g2d.finish().unwrap();
+        buf.invalidate().unwrap();
+        assert!(
+            buf.as_slice()
+                .chunks_exact(4)
+                .all(|px| px == [200, 100, 50, 255]),
+            "cacheable={cacheable} buffer not cleared"
+        );
+    }
+}
+
+/// Submit a blit through `blit_cancellable` and wait for it, then do the
+/// same with a cancel request in between. Cancellation may or may not take
+/// effect, but `wait` must succeed and the handle must stay usable.
+/// A high-priority clear on a display handle and a normal-priority blit on
+/// a batch handle must both complete. libg2d has no preemption, so only
+/// completion is checked.
+fn priority_submissions_test(heap_type: HeapType) {
+    let (width, height) = (64usize, 64usize);
+    let frame_buf =
+        DmaBuffer::new(heap_type, width * height * 4).expect("Failed to allocate frame buffer");
+    let mut src_buf =
+        DmaBuffer::new(heap_type, width * height * 4).expect("Failed to allocate src buffer");
+    let dst_buf =
+        DmaBuffer::new(heap_type, width * height * 4).expect("Failed to allocate dst buffer");
+    src_buf.write_with(|data| {
+        for px in data.chunks_exact_mut(4) {
+            px.copy_from_slice(&[10, 20, 30, 255]);
+        }
+    });
+
+    let mut display = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    display.set_priority(Priority::High);
+    let batch = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    assert_eq!(batch.priority(), Priority::Normal);
+
+    let src = create_surface(&src_buf, width, height, g2d_format_G2D_RGBA8888);
+    let dst = create_surface(&dst_buf, width, height, g2d_format_G2D_RGBA8888);
+    batch.blit(&src, &dst).unwrap();
+    let mut frame = create_surface(&frame_buf, width, height, g2d_format_G2D_RGBA8888);
+    display.clear(&mut frame, [0, 0, 255, 255]).unwrap();
+
+    display.finish().unwrap();
+    batch.finish().unwrap();
+    frame_buf.read_with(|data| {
+        assert!(data.chunks_exact(4).all(|px| px == [0, 0, 255, 255]));
+    });
+    dst_buf.read_with(|data| {
+        assert!(data.chunks_exact(4).all(|px| px == [10, 20, 30, 255]));
+    });
+}
+heap_tests!(test_g2d_priority_submissions, priority_submissions_test);
+
+fn blit_cancellable_test(heap_type: HeapType) {
+    let (width, height) = (64usize, 64usize);
+    let mut src_buf =
+        DmaBuffer::new(heap_type, width * height * 4).expect("Failed to allocate src buffer");
+    let dst_buf =
+        DmaBuffer::new(heap_type, width * height * 4).expect("Failed to allocate dst buffer");
+    src_buf.write_with(|data| {
+        for px in data.chunks_exact_mut(4) {
+            px.copy_from_slice(&[10, 20, 30, 255]);
+        }
+    });
+
+    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    let src = create_surface(&src_buf, width, height, g2d_format_G2D_RGBA8888);
+    let mut dst = create_surface(&dst_buf, width, height, g2d_format_G2D_RGBA8888);
+
+    let token = g2d
+        .blit_cancellable(&src, &dst)
+        .expect("blit_cancellable failed")
+        .wait()
+        .expect("wait failed");
+    let copied = dst_buf
+        .read_finished(&token, |data| {
+            data.chunks_exact(4).all(|px| px == [10, 20, 30, 255])
+        })
+        .unwrap();
+    assert!(copied, "completed blit should copy the source");
+
+    g2d.clear(&mut dst, [0, 0, 0, 0]).unwrap();
+    let handle = g2d
+        .blit_cancellable(&src, &dst)
+        .expect("blit_cancellable failed");
+    let cancelled = handle.cancel();
+    handle.wait().expect("wait after cancel failed");
+    if !cancelled {
+        dst_buf.read_with(|data| {
+            assert!(data.chunks_exact(4).all(|px| px == [10, 20, 30, 255]));
+        });
+    }
+
+    // The G2D handle still works after a cancel request.
+    g2d.clear(&mut dst, [1, 2, 3, 4]).unwrap();
+    g2d.finish().unwrap();
+    dst_buf.read_with(|data| assert_eq!(&data[..4], [1, 2, 3, 4]));
+}
+heap_tests!(test_g2d_blit_cancellable, blit_cancellable_test);
+
+/// A 64x32 gradient rotated 90° clockwise into a 32x64 buffer: each corner
+/// of the destination comes from the matching source corner.
+fn blit_with_rotation_test(heap_type: HeapType) {
+    let (width, height) = (64usize, 32usize);
+    let mut src_buf =
+        DmaBuffer::new(heap_type, width * height * 4).expect("Failed to allocate src buffer");
+    let dst_buf =
+        DmaBuffer::new(heap_type, width * height * 4).expect("Failed to allocate dst buffer");
+    src_buf.write_with(|data| {
+        for (i, px) in data.chunks_exact_mut(4).enumerate() {
+            let (x, y) = (i % width, i / width);
+            px.copy_from_slice(&[(x * 4) as u8, (y * 8) as u8, 0, 255]);
+        }
+    });
+
+    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    let src = create_surface(&src_buf, width, height, g2d_format_G2D_RGBA8888);
+    let unswapped = create_surface(&dst_buf, width, height, g2d_format_G2D_RGBA8888)
+        .with_rotation(Rotation::Deg90);
+    assert!(matches!(
+        g2d.blit(&src, &unswapped),
+        Err(Error::InvalidSurface(_))
+    ));
+
+    let dst = create_surface(&dst_buf, height, width, g2d_format_G2D_RGBA8888)
+        .with_rotation(Rotation::Deg90);
+    g2d.blit(&src, &dst).unwrap();
+    g2d.finish().unwrap();
+
+    // Clockwise, source (x, y) lands at destination (31 - y, x).
+    dst_buf.read_with(|data| {
+        for ((dx, dy), expected) in [
+            ((0, 0), [0, 248]),
+            ((31, 0), [0, 0]),
+            ((0, 63), [252, 248]),
+            ((31, 63), [252, 0]),
+        ] {
+            let offset = (dy * height + dx) * 4;
+            assert_eq!(
+                &data[offset..offset + 2],
+                &expected,
+                "corner ({dx}, {dy}) mismatch"
+            );
+        }
+    });
+}
+heap_tests!(test_g2d_blit_with_rotation, blit_with_rotation_test);
+
+/// A 64x48 NV12 picture inside a 128x64 decoder frame, with a gap before
+/// the UV plane, converts to RGBA from the crop only.
+fn surface_from_decoder_layout_test(heap_type: HeapType) {
+    let (width, height) = (64usize, 48usize);
+    let luma = Plane {
+        offset: 0,
+        pitch: 128,
+        rows: 64,
+    };
+    let chroma = Plane {
+        offset: 128 * 64 + 4096,
+        pitch: 128,
+        rows: 32,
+    };
+    let nv12 = G2DFormat::try_from(NV12).unwrap();
+    let layout = PlaneLayout::new(nv12, vec![luma, chroma]).unwrap();
+
+    let mut src_buf =
+        DmaBuffer::new(heap_type, layout.size()).expect("Failed to allocate src buffer");
+    let dst_buf =
+        DmaBuffer::new(heap_type, width * height * 4).expect("Failed to allocate dst buffer");
+    src_buf.write_with(|data| {
+        // Bright picture, dark padding, neutral chroma everywhere.
+        data.fill(16);
+        for y in 0..height {
+            data[y * luma.pitch..y * luma.pitch + width].fill(235);
+        }
+        data[chroma.offset..].fill(128);
+    });
+
+    let src = G2DSurface::from_layout(
+        nv12,
+        src_buf.address(),
+        &layout,
+        Rect::new(0, 0, width as i32, height as i32),
+    )
+    .unwrap();
+    let dst = create_surface(&dst_buf, width, height, g2d_format_G2D_RGBA8888);
+    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    g2d.blit(&src, &dst).unwrap();
+    g2d.finish().unwrap();
+
+    dst_buf.read_with(|data| {
+        for (i, px) in data.chunks_exact(4).enumerate() {
+            assert!(
+                px[0] > 240 && px[1] > 240 && px[2] > 240,
+                "pixel ({}, {}) includes padding: {px:?}",
+                i % width,
+                i / width
+            );
+        }
+    });
+}
+heap_tests!(
+    test_g2d_surface_from_decoder_layout,
+    surface_from_decoder_layout_test
+);
+
+/// An all-zero NV12 source is flagged as green in debug builds; after
+/// `clear_yuv_black` it holds Y=16, UV=128 and is not flagged.
+fn clear_yuv_black_test(heap_type: HeapType) {
+    let (width, height) = (64usize, 48usize);
+    let mut buf =
+        DmaBuffer::new(heap_type, width * height * 3 / 2).expect("Failed to allocate buffer");
+    buf.write_with(|data| data.fill(0));
+    let nv12 = G2DFormat::try_from(NV12).unwrap();
+    let surface = buf.reinterpret_surface(nv12, width, height, 0).unwrap();
+
+    assert_eq!(buf.check_yuv_source(&surface), cfg!(debug_assertions));
+
+    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    g2d.clear_yuv_black(&surface, &mut buf).unwrap();
+    assert!(!buf.check_yuv_source(&surface));
+    buf.read_with(|data| {
+        let (luma, chroma) = data.split_at(width * height);
+        assert!(luma.iter().all(|&y| y == 16), "luma is not 16");
+        assert!(chroma.iter().all(|&c| c == 128), "chroma is not 128");
+    });
+
+    // Converted to RGBA, the cleared frame is black rather than green.
+    let dst_buf =
+        DmaBuffer::new(heap_type, width * height * 4).expect("Failed to allocate dst buffer");
+    let dst = create_surface(&dst_buf, width, height, g2d_format_G2D_RGBA8888);
+    g2d.blit(&surface, &dst).unwrap();
+    g2d.finish().unwrap();
+    dst_buf.read_with(|data| {
+        for px in data.chunks_exact(4) {
+            assert!(px[..3].iter().all(|&c| c < 8), "not black: {px:?}");
+        }
+    });
+}
+heap_tests!(test_g2d_clear_yuv_black, clear_yuv_black_test);
+
+/// Mirror an 8x4 pattern into an 8x4 ROI of a 16x8 buffer: the left
+/// source column lands in the right ROI column and nothing outside the ROI
+/// is written.
+fn blit_with_flip_test(heap_type: HeapType) {
+    let (src_w, src_h) = (8usize, 4usize);
+    let (dst_w, dst_h) = (16usize, 8usize);
+    let mut src_buf =
+        DmaBuffer::new(heap_type, src_w * src_h * 4).expect("Failed to allocate src buffer");
+    let mut dst_buf =
+        DmaBuffer::new(heap_type, dst_w * dst_h * 4).expect("Failed to allocate dst buffer");
+    src_buf.write_with(|data| {
+        for (i, px) in data.chunks_exact_mut(4).enumerate() {
+            let (x, y) = (i % src_w, i / src_w);
+            px.copy_from_slice(&[(x * 32) as u8, (y * 64) as u8, 0, 255]);
+        }
+    });
+    dst_buf.write_with(|data| data.fill(0));
+
+    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    let src = create_surface(&src_buf, src_w, src_h, g2d_format_G2D_RGBA8888);
+    let mut dst = create_surface(&dst_buf, dst_w, dst_h, g2d_format_G2D_RGBA8888);
+    dst.left = 4;
+    dst.top = 2;
+    dst.right = 12;
+    dst.bottom = 6;
+    g2d.blit_with_flip(&src, &dst, Flip::Horizontal).unwrap();
+    g2d.finish().unwrap();
+
+    dst_buf.read_with(|data| {
+        for y in 0..dst_h {
+            for x in 0..dst_w {
+                let offset = (y * dst_w + x) * 4;
+                let inside = (4..12).contains(&x) && (2..6).contains(&y);
+                let expected = if inside {
+                    let (sx, sy) = (11 - x, y - 2);
+                    [(sx * 32) as u8, (sy * 64) as u8, 0, 255]
+                } else {
+                    [0; 4]
+                };
+                assert_eq!(
+                    &data[offset..offset + 4],
+                    &expected,
+                    "pixel ({x}, {y}) mismatch"
+                );
+            }
+        }
+    });
+}
+heap_tests!(test_g2d_blit_with_flip, blit_with_flip_test);
+
+/// A 64x48 NV12 frame from a sensor mounted at 90° lands upright in a 48x64
+/// RGBA destination: its bright left half becomes the top half.
+fn ingest_rotated_sensor_test(heap_type: HeapType) {
+    let (width, height) = (64usize, 48usize);
+    let mut src_buf =
+        DmaBuffer::new(heap_type, width * height * 3 / 2).expect("Failed to allocate src buffer");
+    let mut dst_buf =
+        DmaBuffer::new(heap_type, width * height * 4).expect("Failed to allocate dst buffer");
+    src_buf.write_with(|data| {
+        let (luma, chroma) = data.split_at_mut(width * height);
+        for (i, y) in luma.iter_mut().enumerate() {
+            *y = if i % width < width / 2 { 235 } else { 16 };
+        }
+        chroma.fill(128);
+    });
+    dst_buf.write_with(|data| data.fill(0));
+
+    let nv12 = G2DFormat::try_from(NV12).unwrap();
+    let rgba = G2DFormat::try_from(RGBA).unwrap();
+    let src = src_buf.reinterpret_surface(nv12, width, height, 0).unwrap();
+    let dst = dst_buf.reinterpret_surface(rgba, height, width, 0).unwrap();
+    assert_eq!(dst.dimensions(), (height as i32, width as i32));
+
+    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    g2d.ingest(&src, &dst, Rotation::Deg90).unwrap();
+    g2d.finish().unwrap();
+
+    // Sample away from the bright/dark edge to allow for chroma filtering.
+    dst_buf.read_with(|data| {
+        for (y, bright) in [
+            (4, true),
+            (width / 2 - 4, true),
+            (width / 2 + 4, false),
+            (width - 4, false),
+        ] {
+            for x in [2, height / 2, height - 2] {
+                let offset = (y * height + x) * 4;
+                let px = &data[offset..offset + 4];
+                assert_eq!(px[3], 255, "pixel ({x}, {y}) not written");
+                if bright {
+                    assert!(px[0] > 200, "pixel ({x}, {y}) should be bright: {px:?}");
+                } else {
+                    assert!(px[0] < 50, "pixel ({x}, {y}) should be dark: {px:?}");
+                }
+            }
+        }
+    });
+}
+heap_tests!(test_g2d_ingest_rotated_sensor, ingest_rotated_sensor_test);
+
+/// Create a solid blue RGBA source with `DmaBuffer::solid` and blit it over
+/// a red destination: the result must be blue. RGB888 goes through the CPU
+/// fill path, and formats that cannot be filled are rejected.
+fn solid_source_test(heap_type: HeapType) {
+    let width = 48;
+    let height = 32;
+    let blue = [0u8, 0, 255, 255];
+
+    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    let rgba = G2DFormat::try_from(RGBA).unwrap();
+    let (_src_buf, src) =
+        DmaBuffer::solid(&g2d, heap_type, rgba, width, height, blue).expect("solid RGBA");
+
+    let dst_buf =
+        DmaBuffer::new(heap_type, width * height * 4).expect("Failed to allocate dst buffer");
+    let mut dst = create_surface(&dst_buf, width, height, g2d_format_G2D_RGBA8888);
+    g2d.clear(&mut dst, [255, 0, 0, 255]).unwrap();
+    g2d.blit(&src, &dst).unwrap();
+    g2d.finish().unwrap();
+    dst_buf.read_with(|data| {
+        for (i, px) in data.chunks_exact(4).enumerate() {
+            assert_eq!(px, blue, "pixel {i}");
+        }
+    });
+
+    let rgb = G2DFormat::try_from(RGB).unwrap();
+    let (rgb_buf, _) =
+        DmaBuffer::solid(&g2d, heap_type, rgb, width, height, blue).expect("solid RGB888");
+    rgb_buf.read_with(|data| {
+        for px in data[..width * height * 3].chunks_exact(3) {
+            assert_eq!(px, [0, 0, 255]);
+        }
+    });
+
+    let nv12 = G2DFormat::try_from(NV12).unwrap();
+    assert!(matches!(
+        DmaBuffer::solid(&g2d, heap_type, nv12, width, height, blue),
+        Err(Error::InvalidFormat(_))
+    ));
+}
+heap_tests!(test_g2d_solid_source, solid_source_test);
+
+fn clear_multiple_colors_test(heap_type: HeapType) {
+    let width = 32;
+    let height = 32;
+    let size = width * height * 4;
+
+    let buf = DmaBuffer::new(heap_type, size).expect("Failed to allocate DMA buffer");
+    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    let mut surface = create_surface(&buf, width, height, g2d_format_G2D_RGBA8888);
+
+    let colors = [
+        [255, 0, 0, 255],     // Red
+        [0, 255, 0, 255],     // Green
+        [0, 0, 255, 255],     // Blue
+        [128, 128, 128, 255], // Gray
+        [0, 0, 0, 255],       // Black
+        [255, 255, 255, 255], // White
+    ];
+
+    for color in colors {
+        let result = g2d.clear(&mut surface, color);
+        assert!(
+            result.is_ok(),
+            "Clear with color {color:?} failed: {:?}",
+            result.err()
+        );
+        g2d.finish().unwrap();
+
+        buf.read_with(|data| {
+            for pixel in [0, 10, 100, width * height - 1] {
+                let offset = pixel * 4;
+                assert_eq!(
+                    &data[offset..offset + 4],
+                    &color,
+                    "Color mismatch at pixel {pixel} for {color:?}"
+                );
+            }
+        });
+    }
+}
+heap_tests!(test_g2d_clear_multiple_colors, clear_multiple_colors_test);
+
+fn clear_large_surface_test(heap_type: HeapType) {
+    let width = 1920;
+    let height = 1080;
+    let size = width * height * 4;
+
+    let buf = DmaBuffer::new(heap_type, size).expect("Failed to allocate DMA buffer");
+    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    let mut surface = create_surface(&buf, width, height, g2d_format_G2D_RGBA8888);
+
+    let color = [0u8, 128, 255, 255]; // Blue-ish
+    let result = g2d.clear(&mut surface, color);
+    assert!(result.is_ok(), "G2D clear 1080p failed: {:?}", result.err());
+    g2d.finish().unwrap();
+
+    buf.read_with(|data| {
+        let pixels_to_check = [
+            0,                                // top-left
+            width - 1,                        // top-right
+            (height / 2) * width + width / 2, // center
+            (height - 1) * width,             // bottom-left
+            (height - 1) * width + width - 1, // bottom-right
+        ];
+        for pixel in pixels_to_check {
+            let offset = pixel * 4;
+            assert_eq!(
+                &data[offset..offset + 4],
+                &color,
+                "Color mismatch at pixel {pixel}"
+            );
+        }
+    });
+}
+heap_tests!(test_g2d_clear_large_surface, clear_large_surface_test);
+
+// =============================================================================
+// Clear Format Tests — g2d_clear with various destination formats
+// =============================================================================
+//
+// The clrcolor field is always in RGBA8888 format. The GPU converts it to the
+// destination surface format. These tests verify g2d_clear works correctly
+// with each supported RGB destination format.
+
+/// Verify that g2d_clear rejects formats not supported as clear destinations.
+///
+/// As of G2D v6.4.11 (i.MX 8M Plus), g2d_clear only supports 2-byte (565)
+/// and 4-byte (8888) RGB formats. 3-byte RGB and all YUV formats are rejected.
+///
+/// Formats that fail `G2DFormat::is_valid_destination()` are now rejected by
+/// the crate before reaching the driver; RGB888/BGR888 still exercise the
+/// driver's own rejection.
+///
+/// **NOTE FOR FUTURE DEVELOPERS:** If this test starts FAILING, it means
+/// g2d_clear now SUCCEEDS on a format that previously returned an error.
+/// This is GOOD NEWS — the GPU driver has gained new clear capabilities!
+/// Update the test: move the newly-supported format from this list into
+/// `clear_all_rgb_formats_test` (or add a new byte-level verification test),
+/// and remove it from the unsupported list here.
+fn clear_unsupported_formats_test(heap_type: HeapType) {
+    let width = 64;
+    let height = 64;
+
+    // Allocate a buffer large enough for any format (4 bpp * 64 * 64 = 16 KiB)
+    let size = width * height * 4;
+    let buf = DmaBuffer::new(heap_type, size).expect("Failed to allocate DMA buffer");
+    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+
+    // Formats that g2d_clear does NOT support as of G2D v6.4.11.
+    // Each entry: (format constant, human-readable name).
+    //
+    // Note: YUYV and UYVY ARE supported from 6.4 on
+    // (`Version::supports_yuv_clear`, tested in clear_all_formats_test).
+    // Only YVYU/VYUY are rejected among packed YUV 4:2:2 formats.
+    let unsupported: &[(g2d_format, &str)] = &[
+        // 3-byte RGB — hardware only supports 2-byte and 4-byte clear targets
+        (g2d_format_G2D_RGB888, "RGB888"),
+        (g2d_format_G2D_BGR888, "BGR888"),
+        // Packed YUV 4:2:2 (only YVYU/VYUY; YUYV/UYVY are supported)
+        (g2d_format_G2D_YVYU, "YVYU"),
+        (g2d_format_G2D_VYUY, "VYUY"),
+        // Semi-planar YUV 4:2:0
+        (g2d_format_G2D_NV12, "NV12"),
+        (g2d_format_G2D_NV21, "NV21"),
+        // Planar YUV 4:2:0
+        (g2d_format_G2D_I420, "I420"),
+        (g2d_format_G2D_YV12, "YV12"),
+        // Semi-planar YUV 4:2:2
+        (g2d_format_G2D_NV16, "NV16"),
+        (g2d_format_G2D_NV61, "NV61"),
+    ];
+
+    let mut newly_supported = Vec::new();
+    for &(format, name) in unsupported {
+        let mut surface = create_surface(&buf, width, height, format);
+        let result = g2d.clear(&mut surface, [255, 0, 0, 255]);
+        if result.is_ok() {
+            eprintln!("  {name}: UNEXPECTEDLY SUCCEEDED — driver now supports this format!");
+            newly_supported.push(name);
+        } else {
+            eprintln!("  {name}: correctly rejected");
+        }
+    }
+    assert!(
+        newly_supported.is_empty(),
+        "g2d_clear now SUCCEEDS for previously unsupported formats: {newly_supported:?}. \
+         This is GOOD NEWS — the GPU driver has gained new clear capabilities! \
+         Move these formats from clear_unsupported_formats_test into \
+         clear_all_rgb_formats_test (or add dedicated byte-verification tests)."
+    );
+}
+heap_tests!(
+    test_g2d_clear_unsupported_formats,
+    clear_unsupported_formats_test
+);
+
+/// Every format `clearable_formats()` reports must actually clear, and the
+/// rejected formats above must not be listed.
+fn clearable_formats_test(heap_type: HeapType) {
+    let width = 64;
+    let height = 64;
+    let buf = DmaBuffer::new(heap_type, width * height * 4).expect("Failed to allocate DMA buffer");
+    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+
+    let clearable: Vec<_> = g2d.clearable_formats().iter().map(|f| f.format()).collect();
+    assert!(clearable.contains(&g2d_format_G2D_RGBA8888));
+    assert!(clearable.contains(&g2d_format_G2D_RGB565));
+    assert!(!clearable.contains(&g2d_format_G2D_RGB888));
+    assert!(!clearable.contains(&g2d_format_G2D_NV12));
+    assert_eq!(
+        clearable.contains(&g2d_format_G2D_YUYV),
+        g2d.version().supports_yuv_clear()
+    );
+
+    for format in clearable {
+        let mut surface = create_surface(&buf, width, height, format);
+        g2d.clear(&mut surface, [255, 0, 0, 255])
+            .unwrap_or_else(|e| panic!("clear of g2d_format {format} failed: {e}"));
+    }
+    g2d.finish().unwrap();
+}
+heap_tests!(test_g2d_clearable_formats, clearable_formats_test);
+
+fn clear_bgra8888_test(heap_type: HeapType) {
+    let width = 64;
+    let height = 64;
+    let bpp = 4;
+    let size = width * height * bpp;
+
+    let mut buf = DmaBuffer::new(heap_type, size).expect("Failed to allocate DMA buffer");
+    buf.write_with(|data| data.fill(0));
+
+    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    let mut surface = create_surface(&buf, width, height, g2d_format_G2D_BGRA8888);
+
+    // Clear with red (clrcolor is RGBA8888)
+    let color = [255u8, 0, 0, 255];
+    let result = g2d.clear(&mut surface, color);
+    assert!(
+        result.is_ok(),
+        "G2D clear BGRA8888 failed: {:?}",
+        result.err()
+    );
+    g2d.finish().unwrap();
+
+    // BGRA8888 memory layout: [B, G, R, A] per pixel
+    buf.read_with(|data| {
+        for i in 0..10 {
+            let off = i * bpp;
+            assert_eq!(data[off], 0, "B mismatch at pixel {i}");
+            assert_eq!(data[off + 1], 0, "G mismatch at pixel {i}");
+            assert_eq!(data[off + 2], 255, "R mismatch at pixel {i}");
+            assert_eq!(data[off + 3], 255, "A mismatch at pixel {i}");
+        }
+    });
+}
+heap_tests!(test_g2d_clear_bgra8888, clear_bgra8888_test);
+
+fn clear_argb8888_test(heap_type: HeapType) {
+    let width = 64;
+    let height = 64;
+    let bpp = 4;
+    let size = width * height * bpp;
+
+    let mut buf = DmaBuffer::new(heap_type, size).expect("Failed to allocate DMA buffer");
+    buf.write_with(|data| data.fill(0));
+
+    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    let mut surface = create_surface(&buf, width, height, g2d_format_G2D_ARGB8888);
+
+    // Clear with red (clrcolor is RGBA8888)
+    let color = [255u8, 0, 0, 255];
+    let result = g2d.clear(&mut surface, color);
+    assert!(
+        result.is_ok(),
+        "G2D clear ARGB8888 failed: {:?}",
+        result.err()
+    );
+    g2d.finish().unwrap();
+
+    // ARGB8888 memory layout: [A, R, G, B] per pixel
+    buf.read_with(|data| {
+        for i in 0..10 {
+            let off = i * bpp;
+            assert_eq!(data[off], 255, "A mismatch at pixel {i}");
+            assert_eq!(data[off + 1], 255, "R mismatch at pixel {i}");
+            assert_eq!(data[off + 2], 0, "G mismatch at pixel {i}");
+            assert_eq!(data[off + 3], 0, "B mismatch at pixel {i}");
+        }
+    });
+}
+heap_tests!(test_g2d_clear_argb8888, clear_argb8888_test);
+
+fn clear_rgb565_test(heap_type: HeapType) {
+    let width = 64;
+    let height = 64;
+    let bpp = 2;
+    let size = width * height * bpp;
+
+    let mut buf = DmaBuffer::new(heap_type, size).expect("Failed to allocate DMA buffer");
+    buf.write_with(|data| data.fill(0));
+
+    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    let mut surface = create_surface(&buf, width, height, g2d_format_G2D_RGB565);
+
+    // RGB565 LE layout: R(15:11) G(10:5) B(4:0)
+    // Pure red   → R=31 G=0 B=0  → 0xF800
+    // Pure green → R=0  G=63 B=0 → 0x07E0
+    // Pure blue  → R=0  G=0 B=31 → 0x001F
+    // White      → all-ones       → 0xFFFF
+    let test_cases: [([u8; 4], u16, &str); 4] = [
+        ([255, 0, 0, 255], 0xF800, "red"),
+        ([0, 255, 0, 255], 0x07E0, "green"),
+        ([0, 0, 255, 255], 0x001F, "blue"),
+        ([255, 255, 255, 255], 0xFFFF, "white"),
+    ];
+
+    for (color, expected, name) in &test_cases {
+        let result = g2d.clear(&mut surface, *color);
+        assert!(
+            result.is_ok(),
+            "G2D clear RGB565 {name} failed: {:?}",
+            result.err()
+        );
+        g2d.finish().unwrap();
+
+        buf.read_with(|data| {
+            for i in 0..10 {
+                let off = i * bpp;
+                let pixel = u16::from_le_bytes([data[off], data[off + 1]]);
+                assert_eq!(
+                    pixel, *expected,
+                    "RGB565 {name} mismatch at pixel {i}: got 0x{pixel:04X}, expected 0x{expected:04X}"
+                );
+            }
+        });
+    }
+}
+heap_tests!(test_g2d_clear_rgb565, clear_rgb565_test);
+
+/// Bytes per pixel for a g2d_format, or None for multi-plane/unsupported formats.
+#[allow(non_upper_case_globals)]
+fn format_bpp(format: g2d_format) -> Option<usize> {
+    match format {
+        g2d_format_G2D_RGB565 | g2d_format_G2D_BGR565 => Some(2),
+        g2d_format_G2D_YUYV | g2d_format_G2D_UYVY => Some(2),
+        g2d_format_G2D_RGB888 | g2d_format_G2D_BGR888 => Some(3),
+        g2d_format_G2D_RGBA8888
+        | g2d_format_G2D_RGBX8888
+        | g2d_format_G2D_BGRA8888
+        | g2d_format_G2D_BGRX8888
+        | g2d_format_G2D_ARGB8888
+        | g2d_format_G2D_ABGR8888
+        | g2d_format_G2D_XRGB8888
+        | g2d_format_G2D_XBGR8888 => Some(4),
+        _ => None,
+    }
+}
+
+/// Format name for diagnostic output.
+#[allow(non_upper_case_globals)]
+fn format_name(format: g2d_format) -> &'static str {
+    match format {
+        g2d_format_G2D_RGB565 => "RGB565",
+        g2d_format_G2D_RGBA8888 => "RGBA8888",
+        g2d_format_G2D_RGBX8888 => "RGBX8888",
+        g2d_format_G2D_BGRA8888 => "BGRA8888",
+        g2d_format_G2D_BGRX8888 => "BGRX8888",
+        g2d_format_G2D_BGR565 => "BGR565",
+        g2d_format_G2D_ARGB8888 => "ARGB8888",
+        g2d_format_G2D_ABGR8888 => "ABGR8888",
+        g2d_format_G2D_XRGB8888 => "XRGB8888",
+        g2d_format_G2D_XBGR8888 => "XBGR8888",
+        g2d_format_G2D_RGB888 => "RGB888",
+        g2d_format_G2D_BGR888 => "BGR888",
+        g2d_format_G2D_YUYV => "YUYV",
+        g2d_format_G2D_UYVY => "UYVY",
+        _ => "unknown",
+    }
+}
+
+/// Comprehensive clear test across all supported destination formats.
+///
+/// For each format: clear with two different colors and verify the buffer
+/// contents change between clears (not stale). This validates that g2d_clear
+/// correctly handles the format without requiring exact byte-order knowledge
+/// for every variant.
+///
+/// Unsupported formats are tested separately in `clear_unsupported_formats_test`.
+fn clear_all_formats_test(heap_type: HeapType) {
+    let width = 64;
+    let height = 64;
+
+    let formats = [
+        // 16-bit RGB
+        g2d_format_G2D_RGB565,
+        g2d_format_G2D_BGR565,
+        // 32-bit RGB variants
+        g2d_format_G2D_RGBA8888,
+        g2d_format_G2D_RGBX8888,
+        g2d_format_G2D_BGRA8888,
+        g2d_format_G2D_BGRX8888,
+        g2d_format_G2D_ARGB8888,
+        g2d_format_G2D_ABGR8888,
+        g2d_format_G2D_XRGB8888,
+        g2d_format_G2D_XBGR8888,
+        // Packed YUV 4:2:2 (YUYV/UYVY supported; YVYU/VYUY are not)
+        g2d_format_G2D_YUYV,
+        g2d_format_G2D_UYVY,
+    ];
+
+    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+
+    for format in formats {
+        let name = format_name(format);
+        let bpp = format_bpp(format).expect("unknown bpp");
+        let size = width * height * bpp;
+
+        let mut buf = DmaBuffer::new(heap_type, size).expect("Failed to allocate DMA buffer");
+        buf.write_with(|data| data.fill(0));
+
+        let mut surface = create_surface(&buf, width, height, format);
+
+        // Clear with red
+        let red = [255u8, 0, 0, 255];
+        let result = g2d.clear(&mut surface, red);
+        assert!(
+            result.is_ok(),
+            "{name}: clear with red failed: {:?}",
+            result.err()
+        );
+        g2d.finish().unwrap();
+
+        let red_snapshot = buf.read_with(|data| data[..bpp * 10].to_vec());
+
+        // Buffer must not be all zeros after clear
+        assert!(
+            red_snapshot.iter().any(|&b| b != 0),
+            "{name}: buffer still all zeros after red clear"
+        );
+
+        // Clear with blue
+        let blue = [0u8, 0, 255, 255];
+        let result = g2d.clear(&mut surface, blue);
+        assert!(
+            result.is_ok(),
+            "{name}: clear with blue failed: {:?}",
+            result.err()
+        );
+        g2d.finish().unwrap();
+
+        let blue_snapshot = buf.read_with(|data| data[..bpp * 10].to_vec());
+
+        // Blue clear must produce different bytes than red clear
+        assert_ne!(
+            red_snapshot, blue_snapshot,
+            "{name}: buffer unchanged between red and blue clears (stale data?)"
+        );
+
+        eprintln!("  {name} ({bpp} bpp): OK");
+    }
+}
+heap_tests!(test_g2d_clear_all_formats, clear_all_formats_test);
+
+// =============================================================================
+// Partial Clear Tests — sub-region clearing for letterbox
+// =============================================================================
+
+/// Test that g2d_clear respects the left/top/right/bottom region of interest.
+///
+/// Clears only the top and bottom bars of an RGBA surface (simulating
+/// letterbox borders) and verifies that the content area is untouched.
+fn clear_partial_region_test(heap_type: HeapType) {
+    let width = 128;
+    let height = 128;
+    let bpp = 4;
+    let size = width * height * bpp;
+
+    let mut buf = DmaBuffer::new(heap_type, size).expect("Failed to allocate DMA buffer");
+
+    // Fill entire buffer with a known pattern (green)
+    let green = [0u8, 255, 0, 255];
+    buf.write_with(|data| {
+        for chunk in data.chunks_exact_mut(4) {
+            chunk.copy_from_slice(&green);
+        }
+    });
+
+    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+
+    // Clear only the top 32 rows with red
+    let red = [255u8, 0, 0, 255];
+    let mut top_surface = create_surface(&buf, width, height, g2d_format_G2D_RGBA8888);
+    top_surface.left = 0;
+    top_surface.top = 0;
+    top_surface.right = width as i32;
+    top_surface.bottom = 32;
+    g2d.clear(&mut top_surface, red).unwrap();
+
+    // Clear only the bottom 32 rows with blue
+    let blue = [0u8, 0, 255, 255];
+    let mut bottom_surface = create_surface(&buf, width, height, g2d_format_G2D_RGBA8888);
+    bottom_surface.left = 0;
+    bottom_surface.top = 96;
+    bottom_surface.right = width as i32;
+    bottom_surface.bottom = 128;
+    g2d.clear(&mut bottom_surface, blue).unwrap();
+
+    // Single finish for both clears
+    g2d.finish().unwrap();
+
+    buf.read_with(|data| {
+        // Top bar (rows 0-31): should be red
+        for row in 0..32 {
+            let offset = row * width * bpp;
+            assert_eq!(
+                &data[offset..offset + 4],
+                &red,
+                "Top bar pixel at row {row} should be red"
+            );
+        }
+
+        // Content area (rows 32-95): should still be green (untouched)
+        for row in 32..96 {
+            let offset = row * width * bpp;
+            assert_eq!(
+                &data[offset..offset + 4],
+                &green,
+                "Content area pixel at row {row} should be green (untouched)"
+            );
+        }
+
+        // Bottom bar (rows 96-127): should be blue
+        for row in 96..128 {
+            let offset = row * width * bpp;
+            assert_eq!(
+                &data[offset..offset + 4],
+                &blue,
+                "Bottom bar pixel at row {row} should be blue"
+            );
+        }
+    });
+}
+heap_tests!(test_g2d_clear_partial_region, clear_partial_region_test);
+
+/// Test `clear_rect` on a pattern-filled buffer: every byte outside the
+/// rect must be identical before and after, and the rect must hold the
+/// clear color. `clear_rect_checked` also self-verifies in debug builds.
+fn clear_rect_test(heap_type: HeapType) {
+    let width = 96;
+    let height = 64;
+    let bpp = 4;
+    let size = width * height * bpp;
+
+    let mut buf = DmaBuffer::new(heap_type, size).expect("Failed to allocate DMA buffer");
+    buf.write_with(|data| {
+        for (i, byte) in data.iter_mut().enumerate() {
+            *byte = (i * 7 + i / 251) as u8;
+        }
+    });
+    let before = buf.read_with(|data| data.to_vec());
+
+    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    let surface = create_surface(&buf, width, height, g2d_format_G2D_RGBA8888);
+    let rect = Rect::new(13, 7, 61, 45);
+    let color = [12u8, 34, 56, 255];
+    g2d.clear_rect_checked(&surface, &buf, rect, color).unwrap();
+
+    buf.read_with(|data| {
+        for y in 0..height {
+            for x in 0..width {
+                let offset = (y * width + x) * bpp;
+                let inside = (13..61).contains(&x) && (7..45).contains(&y);
+                let expected = if inside {
+                    &color[..]
+                } else {
+                    &before[offset..offset + bpp]
+                };
+                assert_eq!(
+                    &data[offset..offset + bpp],
+                    expected,
+                    "pixel ({x},{y}) inside={inside}"
+                );
+            }
+        }
+    });
+}
+heap_tests!(test_g2d_clear_rect, clear_rect_test);
+
+/// Test partial clear with left/right vertical bars (portrait letterbox).
+fn clear_partial_left_right_test(heap_type: HeapType) {
+    let width = 128;
+    let height = 64;
+    let bpp = 4;
+    let size = width * height * bpp;
+
+    let mut buf = DmaBuffer::new(heap_type, size).expect("Failed to allocate DMA buffer");
+
+    // Fill with green
+    let green = [0u8, 255, 0, 255];
+    buf.write_with(|data| {
+        for chunk in data.chunks_exact_mut(4) {
+            chunk.copy_from_slice(&green);
+        }
+    });
+
+    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    let gray = [114u8, 114, 114, 255];
+
+    // Clear left 16 columns
+    let mut left_surface = create_surface(&buf, width, height, g2d_format_G2D_RGBA8888);
+    left_surface.left = 0;
+    left_surface.top = 0;
+    left_surface.right = 16;
+    left_surface.bottom = height as i32;
+    g2d.clear(&mut left_surface, gray).unwrap();
+
+    // Clear right 16 columns
+    let mut right_surface = create_surface(&buf, width, height, g2d_format_G2D_RGBA8888);
+    right_surface.left = 112;
+    right_surface.top = 0;
+    right_surface.right = 128;
+    right_surface.bottom = height as i32;
+    g2d.clear(&mut right_surface, gray).unwrap();
+
+    g2d.finish().unwrap();
+
+    buf.read_with(|data| {
+        for row in 0..height {
+            let row_offset = row * width * bpp;
+
+            // Left bar (cols 0-15): gray
+            assert_eq!(
+                &data[row_offset..row_offset + 4],
+                &gray,
+                "Left bar at row {row} col 0 should be gray"
+            );
+
+            // Content (col 16): green
+            let mid_offset = row_offset + 16 * bpp;
+            assert_eq!(
+                &data[mid_offset..mid_offset + 4],
+                &green,
+                "Content at row {row} col 16 should be green"
+            );
+
+            // Content (col 111): green
+            let mid_offset = row_offset + 111 * bpp;
+            assert_eq!(
+                &data[mid_offset..mid_offset + 4],
+                &green,
+                "Content at row {row} col 111 should be green"
+            );
+
+            // Right bar (col 112): gray
+            let right_offset = row_offset + 112 * bpp;
+            assert_eq!(
+                &data[right_offset..right_offset + 4],
+                &gray,
+                "Right bar at row {row} col 112 should be gray"
+            );
+        }
+    });
+}
+heap_tests!(
+    test_g2d_clear_partial_left_right,
+    clear_partial_left_right_test
+);
+
+/// A tiny clear must produce identical output whether `clear_auto` picks the
+/// GPU, the CPU, or whatever the calibrated threshold selects.
+fn clear_auto_tiny_region_test(heap_type: HeapType) {
+    let width = 64;
+    let height = 64;
+    let bpp = 4;
+    let size = width * height * bpp;
+    let green = [0u8, 255, 0, 255];
+    let red = [255u8, 0, 0, 255];
+
+    let mut buf = DmaBuffer::new(heap_type, size).expect("Failed to allocate DMA buffer");
+
+    for (label, threshold) in [("gpu", Some(0)), ("cpu", Some(usize::MAX)), ("auto", None)] {
+        buf.write_with(|data| {
+            for chunk in data.chunks_exact_mut(4) {
+                chunk.copy_from_slice(&green);
+            }
+        });
+
+        let mut g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+        if let Some(threshold) = threshold {
+            g2d.set_cpu_clear_threshold(threshold);
+        }
+
+        // 4x4 region at (8, 8)
+        let mut surface = create_surface(&buf, width, height, g2d_format_G2D_RGBA8888);
+        surface.left = 8;
+        surface.top = 8;
+        surface.right = 12;
+        surface.bottom = 12;
+        g2d.clear_auto(&mut surface, &mut buf, red).unwrap();
+        g2d.finish().unwrap();
+        eprintln!("  {label}: threshold={:?} px", g2d.cpu_clear_threshold());
+
+        buf.read_with(|data| {
+            for y in 0..height {
+                for x in 0..width {
+                    let offset = (y * width + x) * bpp;
+                    let inside = (8..12).contains(&x) && (8..12).contains(&y);
+                    let expected = if inside { red } else { green };
+                    assert_eq!(
+                        &data[offset..offset + 4],
+                        &expected,
+                        "{label}: pixel ({x}, {y}) mismatch"
+                    );
+                }
+            }
+        });
+    }
+}
+heap_tests!(test_g2d_clear_auto_tiny_region, clear_auto_tiny_region_test);
+
+/// RGB888 is not clearable by `g2d_clear`, so `clear_auto` must fill it on
+/// the CPU even when the region is above the threshold.
+fn clear_auto_rgb888_test(heap_type: HeapType) {
+    let width = 64;
+    let height = 64;
+    let red = [255u8, 0, 0, 255];
+
+    let mut buf =
+        DmaBuffer::new(heap_type, width * height * 3).expect("Failed to allocate DMA buffer");
+    let mut g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    g2d.set_cpu_clear_threshold(0);
+
+    let mut surface = create_surface(&buf, width, height, g2d_format_G2D_RGB888);
+    g2d.clear_auto(&mut surface, &mut buf, red).unwrap();
+    g2d.finish().unwrap();
+
+    buf.read_with(|data| {
+        for (i, px) in data.chunks_exact(3).enumerate() {
+            assert_eq!(px, [255, 0, 0], "pixel {i}");
+        }
+    });
+}
+heap_tests!(test_g2d_clear_auto_rgb888, clear_auto_rgb888_test);
+
+/// `clear_black` and `clear_white` must write exactly 0x00 and 0xFF bytes on
+/// both the CPU and GPU paths, for full-width and partial crops.
+fn clear_black_white_test(heap_type: HeapType) {
+    let width = 64;
+    let height = 64;
+    let bpp = 4;
+    let mut buf =
+        DmaBuffer::new(heap_type, width * height * bpp).expect("Failed to allocate DMA buffer");
+
+    for (label, threshold) in [("gpu", 0), ("cpu", usize::MAX)] {
+        let mut g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+        g2d.set_cpu_clear_threshold(threshold);
+
+        // Whole surface, which the CPU path fills as one span.
+        let mut surface = create_surface(&buf, width, height, g2d_format_G2D_RGBA8888);
+        g2d.clear_white(&mut surface, &mut buf).unwrap();
+        g2d.finish().unwrap();
+        buf.read_with(|data| {
+            assert!(
+                data.iter().all(|&b| b == 0xFF),
+                "{label}: clear_white left a byte other than 0xFF"
+            );
+        });
+
+        g2d.clear_black(&mut surface, &mut buf).unwrap();
+        g2d.finish().unwrap();
+        buf.read_with(|data| {
+            assert!(
+                data.iter().all(|&b| b == 0x00),
+                "{label}: clear_black left a byte other than 0x00"
+            );
+        });
+
+        // Partial crop, filled row by row.
+        surface.left = 8;
+        surface.top = 4;
+        surface.right = 24;
+        surface.bottom = 12;
+        g2d.clear_white(&mut surface, &mut buf).unwrap();
+        g2d.finish().unwrap();
+        buf.read_with(|data| {
+            for y in 0..height {
+                for x in 0..width {
+                    let offset = (y * width + x) * bpp;
+                    let inside = (8..24).contains(&x) && (4..12).contains(&y);
+                    let expected = if inside { [0xFF; 4] } else { [0x00; 4] };
+                    assert_eq!(
+                        &data[offset..offset + 4],
+                        &expected,
+                        "{label}: pixel ({x}, {y}) mismatch"
+                    );
+                }
+            }
+        });
+    }
+}
+heap_tests!(test_g2d_clear_black_white, clear_black_white_test);
+
+fn clear_many_test(heap_type: HeapType) {
+    let width = 32;
+    let height = 32;
+    let colors = [[255, 0, 0, 255], [0, 255, 0, 255], [0, 0, 255, 255]];
+
+    let bufs: Vec<DmaBuffer> = colors
+        .iter()
+        .map(|_| {
+            let mut buf = DmaBuffer::new(heap_type, width * height * 4)
+                .expect("Failed to allocate DMA buffer");
+            buf.write_with(|data| data.fill(0));
+            buf
+        })
+        .collect();
+    let surfaces: Vec<G2DSurface> = bufs
+        .iter()
+        .map(|buf| create_surface(buf, width, height, g2d_format_G2D_RGBA8888))
+        .collect();
+
+    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    let clears: Vec<(&G2DSurface, [u8; 4])> = surfaces.iter().zip(colors).collect();
+    g2d.clear_many(&clears).expect("clear_many failed");
+
+    for (buf, color) in bufs.iter().zip(colors) {
+        buf.read_with(|data| {
+            for (i, px) in data.chunks_exact(4).enumerate() {
+                assert_eq!(px, color, "pixel {i} mismatch for {color:?}");
+            }
+        });
+    }
+}
+heap_tests!(test_g2d_clear_many, clear_many_test);
+
+fn clear_from_loaded_library_test(heap_type: HeapType) {
+    let width = 32;
+    let height = 32;
+
+    let mut buf =
+        DmaBuffer::new(heap_type, width * height * 4).expect("Failed to allocate DMA buffer");
+    buf.write_with(|data| data.fill(0));
+
+    let lib = unsafe { libloading::Library::new("libg2d.so.2") }.expect("Failed to load libg2d");
+    let g2d = G2D::from_loaded(lib).expect("Failed to open G2D from loaded library");
+    let mut surface = create_surface(&buf, width, height, g2d_format_G2D_RGBA8888);
+
+    g2d.clear(&mut surface, [10, 20, 30, 255])
+        .expect("clear failed");
+    g2d.finish().unwrap();
+
+    buf.read_with(|data| {
+        for (i, px) in data.chunks_exact(4).enumerate() {
+            assert_eq!(px, [10, 20, 30, 255], "pixel {i} mismatch");
+        }
+    });
+}
+heap_tests!(
+    test_g2d_clear_from_loaded_library,
+    clear_from_loaded_library_test
+);
+
+fn clear_with_fence_test(heap_type: HeapType) {
+    let width = 64;
+    let height = 64;
+
+    let mut buf = DmaBuffer::new(heap_type, width * height * 4).expect("Failed to allocate buffer");
+    buf.write_with(|data| data.fill(0));
+
+    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    let mut surface = create_surface(&buf, width, height, g2d_format_G2D_RGBA8888);
+    g2d.clear(&mut surface, [0, 0, 255, 255]).unwrap();
+
+    let fence = match g2d.submit_with_fence(&buf) {
+        Ok(fence) => fence,
+        Err(e) => {
+            eprintln!("SKIPPED: fence export unavailable ({e}), falling back to finish");
+            g2d.finish().unwrap();
+            return;
+        }
+    };
+    let signaled = fence::wait(&fence, Some(Duration::from_secs(1))).expect("Fence poll failed");
+    assert!(signaled, "Fence did not signal within 1s");
+
+    g2d.finish().unwrap();
+    buf.read_with(|data| {
+        assert_eq!(&data[..4], &[0, 0, 255, 255]);
+    });
+}
+heap_tests!(test_g2d_clear_with_fence, clear_with_fence_test);
+
+/// Submit a clear, wait for its fence with `poll`, and read the pixels with
+/// no `finish()` in between, as an asynchronous pipeline would. Failing
+/// here while `test_g2d_clear_with_fence` passes means the GPU driver does
+/// not attach G2D jobs to the dma-buf reservation, so the fence cannot
+/// stand in for `finish()` on this kernel.
+fn clear_fence_poll_read_test(heap_type: HeapType) {
+    let (width, height) = (64usize, 64usize);
+    let color = [0, 200, 100, 255];
+
+    let mut buf = DmaBuffer::new(heap_type, width * height * 4).expect("Failed to allocate buffer");
+    buf.write_with(|data| data.fill(0));
+
+    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    let mut surface = create_surface(&buf, width, height, g2d_format_G2D_RGBA8888);
+    g2d.clear(&mut surface, color).unwrap();
+
+    let fence = match g2d.submit_with_fence(&buf) {
+        Ok(fence) => fence,
+        Err(e) => {
+            eprintln!("SKIPPED: fence export unavailable ({e})");
+            g2d.finish().unwrap();
+            return;
+        }
+    };
+    let signaled = fence::wait(&fence, Some(Duration::from_secs(1))).expect("Fence poll failed");
+    assert!(signaled, "Fence did not signal within 1s");
+
+    buf.read_with(|data| {
+        for (i, px) in data.chunks_exact(4).enumerate() {
+            assert_eq!(px, color, "pixel {i} read after the fence signaled");
+        }
+    });
+    g2d.finish().unwrap();
+}
+heap_tests!(test_g2d_clear_fence_poll_read, clear_fence_poll_read_test);
+
+/// Clear and wait with `finish_for` under both wait modes; each must see
+/// the full clear. Prints the wait time of each mode for comparison.
+fn clear_wait_modes_test(heap_type: HeapType) {
+    let (width, height) = (256usize, 256usize);
+    let mut buf = DmaBuffer::new(heap_type, width * height * 4).expect("Failed to allocate buffer");
+
+    let mut g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    assert_eq!(g2d.wait_mode(), WaitMode::Block);
+    let mut surface = create_surface(&buf, width, height, g2d_format_G2D_RGBA8888);
+
+    for (mode, color) in [
+        (WaitMode::Block, [255, 0, 0, 255]),
+        (WaitMode::Spin, [0, 0, 255, 255]),
+    ] {
+        g2d.set_wait_mode(mode);
+        assert_eq!(g2d.wait_mode(), mode);
+        buf.write_with(|data| data.fill(0));
+
+        let start = Instant::now();
+        g2d.clear(&mut surface, color).unwrap();
+        g2d.finish_for(&buf).expect("finish_for failed");
+        println!("{mode:?}: clear + wait {:?}", start.elapsed());
+
+        buf.read_with(|data| {
+            for (i, px) in data.chunks_exact(4).enumerate() {
+                assert_eq!(px, color, "{mode:?}: pixel {i}");
+            }
+        });
+    }
+}
+heap_tests!(test_g2d_clear_wait_modes, clear_wait_modes_test);
+
+// =============================================================================
+// Blit Operation Tests
+// =============================================================================
+
+fn blit_rgba_to_rgba_test(heap_type: HeapType) {
+    let width = 64;
+    let height = 64;
+    let size = width * height * 4;
+
+    let mut src_buf = DmaBuffer::new(heap_type, size).expect("Failed to allocate src buffer");
+    let mut dst_buf = DmaBuffer::new(heap_type, size).expect("Failed to allocate dst buffer");
+
+    src_buf.write_with(|data| {
+        for (i, byte) in data.iter_mut().enumerate() {
+            *byte = (i % 256) as u8;
+        }
+    });
+    dst_buf.write_with(|data| data.fill(0));
+
+    let mut g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    g2d.set_bt709_colorspace()
+        .expect("Failed to set colorspace");
+
+    let src_surface = create_surface(&src_buf, width, height, g2d_format_G2D_RGBA8888);
+    let dst_surface = create_surface(&dst_buf, width, height, g2d_format_G2D_RGBA8888);
+
+    let result = g2d.blit(&src_surface, &dst_surface);
+    assert!(result.is_ok(), "G2D blit failed: {:?}", result.err());
+    g2d.finish().unwrap();
+
+    let src_snapshot = src_buf.read_with(|data| data[..100].to_vec());
+    dst_buf.read_with(|data| {
+        for i in 0..100 {
+            assert_eq!(
+                src_snapshot[i], data[i],
+                "Data mismatch at byte {i}: src={} dst={}",
+                src_snapshot[i], data[i]
+            );
+        }
+    });
+}
+heap_tests!(test_g2d_blit_rgba_to_rgba, blit_rgba_to_rgba_test);
+
+fn blit_with_scaling_test(heap_type: HeapType) {
+    let src_width = 128;
+    let src_height = 128;
+    let dst_width = 64;
+    let dst_height = 64;
+
+    let src_size = src_width * src_height * 4;
+    let dst_size = dst_width * dst_height * 4;
+
+    let mut src_buf = DmaBuffer::new(heap_type, src_size).expect("Failed to allocate src buffer");
+    let mut dst_buf = DmaBuffer::new(heap_type, dst_size).expect("Failed to allocate dst buffer");
+
+    src_buf.write_with(|slice| {
+        for y in 0..src_height {
+            for x in 0..src_width {
+                let offset = (y * src_width + x) * 4;
+                slice[offset] = (x * 2) as u8;
+                slice[offset + 1] = (y * 2) as u8;
+                slice[offset + 2] = 128;
+                slice[offset + 3] = 255;
+            }
+        }
+    });
+    dst_buf.write_with(|data| data.fill(0));
+
+    let mut g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    g2d.set_bt709_colorspace()
+        .expect("Failed to set colorspace");
+
+    let src_surface = create_surface(&src_buf, src_width, src_height, g2d_format_G2D_RGBA8888);
+    let dst_surface = create_surface(&dst_buf, dst_width, dst_height, g2d_format_G2D_RGBA8888);
+
+    let result = g2d.blit(&src_surface, &dst_surface);
+    assert!(
+        result.is_ok(),
+        "G2D blit with scaling failed: {:?}",
+        result.err()
+    );
+    g2d.finish().unwrap();
+
+    dst_buf.read_with(|dst_data| {
+        let non_zero_count = dst_data.iter().filter(|&&b| b != 0).count();
+        assert!(
+            non_zero_count > dst_size / 2,
+            "Destination buffer appears empty after scaling"
+        );
+    });
+}
+heap_tests!(test_g2d_blit_with_scaling, blit_with_scaling_test);
+
+/// A 2000x2000 to 100x100 blit (20x) is rejected with `ScaleOutOfRange`
+/// before reaching the GPU; 16x still blits.
+fn blit_scale_out_of_range_test(heap_type: HeapType) {
+    let src_buf =
+        DmaBuffer::new(heap_type, 2000 * 2000 * 4).expect("Failed to allocate src buffer");
+    let dst_buf = DmaBuffer::new(heap_type, 100 * 100 * 4).expect("Failed to allocate dst buffer");
+
+    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    let src = create_surface(&src_buf, 2000, 2000, g2d_format_G2D_RGBA8888);
+    let dst = create_surface(&dst_buf, 100, 100, g2d_format_G2D_RGBA8888);
+
+    match g2d.blit(&src, &dst) {
+        Err(Error::ScaleOutOfRange { src, dst }) => {
+            assert_eq!(src, Rect::new(0, 0, 2000, 2000));
+            assert_eq!(dst, Rect::new(0, 0, 100, 100));
+        }
+        other => panic!("expected ScaleOutOfRange, got {other:?}"),
+    }
+
+    let mut src_16x = src;
+    src_16x.right = 1600;
+    src_16x.bottom = 1600;
+    g2d.blit(&src_16x, &dst).expect("16x downscale rejected");
+    g2d.finish().unwrap();
+}
+heap_tests!(
+    test_g2d_blit_scale_out_of_range,
+    blit_scale_out_of_range_test
+);
+
+fn multi_scale_test(heap_type: HeapType) {
+    let (src_w, src_h) = (1280, 720);
+    let mut src_buf =
+        DmaBuffer::new(heap_type, src_w * src_h * 4).expect("Failed to allocate src buffer");
+    src_buf.write_with(|data| {
+        for px in data.chunks_exact_mut(4) {
+            px.copy_from_slice(&[40, 160, 220, 255]);
+        }
+    });
+    let src_surface = create_surface(&src_buf, src_w, src_h, g2d_format_G2D_RGBA8888);
+
+    let sizes = [640, 320, 160];
+    let buffers: Vec<DmaBuffer> = sizes
+        .iter()
+        .map(|&s| {
+            let mut buf =
+                DmaBuffer::new(heap_type, s * s * 4).expect("Failed to allocate dst buffer");
+            buf.write_with(|data| data.fill(0));
+            buf
+        })
+        .collect();
+    let outputs: Vec<_> = buffers.iter().zip(sizes).map(|(b, s)| (b, s, s)).collect();
+
+    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    let surfaces = g2d
+        .multi_scale(&src_surface, G2DFormat::try_from(RGBA).unwrap(), &outputs)
+        .expect("multi_scale failed");
+
+    for ((buf, size), surface) in buffers.iter().zip(sizes).zip(&surfaces) {
+        assert_eq!(surface.dimensions(), (size as i32, size as i32));
+        buf.read_with(|data| {
+            assert_eq!(data.len(), size * size * 4);
+            for (p, px) in data.chunks_exact(4).enumerate() {
+                assert_eq!(px, [40, 160, 220, 255], "{size}x{size} pixel {p}");
+            }
+        });
+    }
+}
+heap_tests!(test_g2d_multi_scale, multi_scale_test);
+
+/// Test that a batch drops a full-surface clear followed by a full-surface
+/// blit into the same buffer, and that the output is the blit result.
+fn batch_elides_redundant_clear_test(heap_type: HeapType) {
+    let width = 320;
+    let height = 240;
+    let size = width * height * 4;
+
+    let mut src_buf = DmaBuffer::new(heap_type, size).expect("Failed to allocate src buffer");
+    let dst_buf = DmaBuffer::new(heap_type, size).expect("Failed to allocate dst buffer");
+    src_buf.write_with(|data| {
+        for px in data.chunks_exact_mut(4) {
+            px.copy_from_slice(&[0, 0, 255, 255]);
+        }
+    });
+
+    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    g2d.reset_state().unwrap();
+    let src = create_surface(&src_buf, width, height, g2d_format_G2D_RGBA8888);
+    let dst = create_surface(&dst_buf, width, height, g2d_format_G2D_RGBA8888);
+
+    let stats = g2d
+        .scope(|batch| {
+            batch.clear(&dst, [255, 0, 0, 255]).blit(&src, &dst);
+        })
+        .unwrap();
+    assert_eq!(stats.elided_clears, 1, "redundant clear not elided");
+    assert_eq!((stats.clears, stats.blits), (0, 1));
+
+    dst_buf.read_with(|data| {
+        for (i, px) in data.chunks_exact(4).enumerate() {
+            assert_eq!(px, [0, 0, 255, 255], "pixel {i}");
+        }
+    });
+}
+heap_tests!(
+    test_g2d_batch_elides_redundant_clear,
+    batch_elides_redundant_clear_test
+);
+
+fn blit_rgba_to_rgb_test(heap_type: HeapType) {
+    let width = 64;
+    let height = 64;
+    let src_size = width * height * 4; // RGBA
+    let dst_size = width * height * 3; // RGB
+
+    let mut src_buf = DmaBuffer::new(heap_type, src_size).expect("Failed to allocate src buffer");
+    let mut dst_buf = DmaBuffer::new(heap_type, dst_size).expect("Failed to allocate dst buffer");
+
+    src_buf.write_with(|slice| {
+        for i in 0..(width * height) {
+            let offset = i * 4;
+            slice[offset] = 255;
+            slice[offset + 1] = 0;
+            slice[offset + 2] = 0;
+            slice[offset + 3] = 255;
+        }
+    });
+    dst_buf.write_with(|data| data.fill(0));
+
+    let mut g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    g2d.set_bt709_colorspace()
+        .expect("Failed to set colorspace");
+
+    let src_surface = create_surface(&src_buf, width, height, g2d_format_G2D_RGBA8888);
+    let dst_surface = create_surface(&dst_buf, width, height, g2d_format_G2D_RGB888);
+
+    let result = g2d.blit(&src_surface, &dst_surface);
+    assert!(
+        result.is_ok(),
+        "G2D RGBA to RGB blit failed: {:?}",
+        result.err()
+    );
+    g2d.finish().unwrap();
+
+    dst_buf.read_with(|dst_data| {
+        for i in 0..10 {
+            let offset = i * 3;
+            assert_eq!(dst_data[offset], 255, "Red channel mismatch at pixel {i}");
+            assert_eq!(
+                dst_data[offset + 1],
+                0,
+                "Green channel mismatch at pixel {i}"
+            );
+            assert_eq!(
+                dst_data[offset + 2],
+                0,
+                "Blue channel mismatch at pixel {i}"
+            );
+        }
+    });
+}
+heap_tests!(test_g2d_blit_rgba_to_rgb, blit_rgba_to_rgb_test);
+
+fn blit_clipped_quarter_test(heap_type: HeapType) {
+    let width = 64;
+    let height = 64;
+    let size = width * height * 4;
+    let background = [1u8, 2, 3, 4];
+    let pattern = |x: usize, y: usize| [(x * 4) as u8, (y * 4) as u8, 77, 255];
+
+    let mut src_buf = DmaBuffer::new(heap_type, size).expect("Failed to allocate src buffer");
+    let mut dst_buf = DmaBuffer::new(heap_type, size).expect("Failed to allocate dst buffer");
+
+    src_buf.write_with(|data| {
+        for y in 0..height {
+            for x in 0..width {
+                let offset = (y * width + x) * 4;
+                data[offset..offset + 4].copy_from_slice(&pattern(x, y));
+            }
+        }
+    });
+    dst_buf.write_with(|data| {
+        for px in data.chunks_exact_mut(4) {
+            px.copy_from_slice(&background);
+        }
+    });
+
+    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    let src_surface = create_surface(&src_buf, width, height, g2d_format_G2D_RGBA8888);
+    let dst_surface = create_surface(&dst_buf, width, height, g2d_format_G2D_RGBA8888);
+
+    let clip = Rect::new(0, 0, width as i32 / 2, height as i32 / 2);
+    g2d.blit_clipped(&src_surface, &dst_surface, clip)
+        .expect("blit_clipped failed");
+    g2d.finish().unwrap();
+
+    dst_buf.read_with(|data| {
+        for y in 0..height {
+            for x in 0..width {
+                let offset = (y * width + x) * 4;
+                let expected = if x < width / 2 && y < height / 2 {
+                    pattern(x, y)
+                } else {
+                    background
+                };
+                assert_eq!(
+                    &data[offset..offset + 4],
+                    expected,
+                    "pixel ({x},{y}) mismatch"
+                );
+            }
+        }
+    });
+}
+heap_tests!(test_g2d_blit_clipped_quarter, blit_clipped_quarter_test);
+
+/// Test `multi_blit` compositing four solid 32x32 tiles into the quadrants
+/// of a 64x64 destination in one submission.
+fn multi_blit_quadrants_test(heap_type: HeapType) {
+    let colors = [
+        [255u8, 0, 0, 255],
+        [0, 255, 0, 255],
+        [0, 0, 255, 255],
+        [255, 255, 0, 255],
+    ];
+    let tiles: Vec<DmaBuffer> = colors
+        .iter()
+        .map(|color| {
+            let mut buf = DmaBuffer::new(heap_type, 32 * 32 * 4).expect("Failed to allocate tile");
+            buf.write_with(|data| {
+                for px in data.chunks_exact_mut(4) {
+                    px.copy_from_slice(color);
+                }
+            });
+            buf
+        })
+        .collect();
+    let dst_buf = DmaBuffer::new(heap_type, 64 * 64 * 4).expect("Failed to allocate dst buffer");
+
+    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    let layers: Vec<(G2DSurface, Rect)> = tiles
+        .iter()
+        .enumerate()
+        .map(|(i, tile)| {
+            let (x, y) = ((i % 2) as i32 * 32, (i / 2) as i32 * 32);
+            (
+                create_surface(tile, 32, 32, g2d_format_G2D_RGBA8888),
+                Rect::new(x, y, x + 32, y + 32),
+            )
+        })
+        .collect();
+    let dst_surface = create_surface(&dst_buf, 64, 64, g2d_format_G2D_RGBA8888);
+
+    g2d.multi_blit(&layers, &dst_surface)
+        .expect("multi_blit failed");
+    g2d.finish().unwrap();
+
+    dst_buf.read_with(|data| {
+        for y in 0..64 {
+            for x in 0..64 {
+                let offset = (y * 64 + x) * 4;
+                let quadrant = (y / 32) * 2 + x / 32;
+                assert_eq!(
+                    &data[offset..offset + 4],
+                    colors[quadrant],
+                    "pixel ({x},{y}) mismatch in quadrant {quadrant}"
+                );
+            }
+        }
+    });
+}
+heap_tests!(test_g2d_multi_blit_quadrants, multi_blit_quadrants_test);
+
+/// Test `place` with a 32x32 source positioned half off the right edge of a
+/// 64x64 destination: only the visible 16 columns are written, unscaled. A
+/// plain blit with the same out-of-bounds crop and `clip_to_bounds` must
+/// write the same pixels.
+fn place_off_right_edge_test(heap_type: HeapType) {
+    let (width, height) = (64usize, 64usize);
+    let (sw, sh) = (32usize, 32usize);
+    let (x0, y0) = (48usize, 8usize);
+    let background = [1u8, 2, 3, 4];
+    let pattern = |x: usize, y: usize| [(x * 8) as u8, (y * 8) as u8, 77, 255];
+
+    let mut src_buf =
+        DmaBuffer::new(heap_type, sw * sh * 4).expect("Failed to allocate src buffer");
+    let mut dst_buf =
+        DmaBuffer::new(heap_type, width * height * 4).expect("Failed to allocate dst buffer");
+    src_buf.write_with(|data| {
+        for y in 0..sh {
+            for x in 0..sw {
+                let offset = (y * sw + x) * 4;
+                data[offset..offset + 4].copy_from_slice(&pattern(x, y));
+            }
+        }
+    });
+
+    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    let src = create_surface(&src_buf, sw, sh, g2d_format_G2D_RGBA8888);
+    let dst = create_surface(&dst_buf, width, height, g2d_format_G2D_RGBA8888);
+
+    let check = |dst_buf: &DmaBuffer, what: &str| {
+        dst_buf.read_with(|data| {
+            for y in 0..height {
+                for x in 0..width {
+                    let offset = (y * width + x) * 4;
+                    let inside = x >= x0 && (y0..y0 + sh).contains(&y);
+                    let expected = if inside {
+                        pattern(x - x0, y - y0)
+                    } else {
+                        background
+                    };
+                    assert_eq!(
+                        &data[offset..offset + 4],
+                        expected,
+                        "{what}: pixel ({x},{y})"
+                    );
+                }
+            }
+        });
+    };
+    let reset = |dst_buf: &mut DmaBuffer| {
+        dst_buf.write_with(|data| {
+            for px in data.chunks_exact_mut(4) {
+                px.copy_from_slice(&background);
+            }
+        })
+    };
+
+    reset(&mut dst_buf);
+    g2d.place(&src, &dst, x0 as i32, y0 as i32)
+        .expect("place failed");
+    g2d.finish().unwrap();
+    check(&dst_buf, "place");
+
+    reset(&mut dst_buf);
+    let mut off_edge = dst;
+    off_edge.left = x0 as i32;
+    off_edge.top = y0 as i32;
+    off_edge.right = (x0 + sw) as i32;
+    off_edge.bottom = (y0 + sh) as i32;
+    let options = BlitOptions::default().with_clip_to_bounds(true);
+    g2d.blit_with_options(&src, &off_edge, &mut dst_buf, &options)
+        .expect("clipped blit failed");
+    g2d.finish().unwrap();
+    check(&dst_buf, "clip_to_bounds");
+}
+heap_tests!(test_g2d_place_off_right_edge, place_off_right_edge_test);
+
+/// Load a 4x4 PNG (red/green/blue/white quadrants) and blit it 1:1.
+#[cfg(feature = "image")]
+fn blit_loaded_image_test(heap_type: HeapType) {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/quadrants_4x4.png");
+    let (src_buf, src_surface) =
+        g2d_sys::io::load_to_dma(path, heap_type).expect("Failed to load image");
+    assert_eq!(src_surface.dimensions(), (4, 4));
+
+    let mut dst_buf = DmaBuffer::new(heap_type, 4 * 4 * 4).expect("Failed to allocate dst buffer");
+    dst_buf.write_with(|data| data.fill(0));
+    let dst_surface = create_surface(&dst_buf, 4, 4, g2d_format_G2D_RGBA8888);
+
+    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    g2d.blit(&src_surface, &dst_surface).expect("Blit failed");
+    g2d.finish().unwrap();
+    drop(src_buf);
+
+    let expected = [
+        ((0, 0), [255, 0, 0, 255]),
+        ((3, 0), [0, 255, 0, 255]),
+        ((0, 3), [0, 0, 255, 255]),
+        ((3, 3), [255, 255, 255, 255]),
+    ];
+    dst_buf.read_with(|data| {
+        for ((x, y), rgba) in expected {
+            let offset = (y * 4 + x) * 4;
+            assert_eq!(
+                &data[offset..offset + 4],
+                &rgba,
+                "Pixel ({x}, {y}) mismatch"
+            );
+        }
+    });
+}
+#[cfg(feature = "image")]
+heap_tests!(test_g2d_blit_loaded_image, blit_loaded_image_test);
+
+/// Round-trip a gradient through `image`: read it out of a packed buffer,
+/// upload it into a stride-padded surface, blit that into a surface with a
+/// different padding, and read the result back.
+#[cfg(feature = "image")]
+fn rgba_image_round_trip_test(heap_type: HeapType) {
+    use g2d_sys::io::{rgba_image_to_surface, surface_to_rgba_image};
+
+    let (width, height) = (100usize, 50usize);
+    let mut packed_buf =
+        DmaBuffer::new(heap_type, width * height * 4).expect("Failed to allocate packed buffer");
+    packed_buf.write_with(|data| {
+        for (i, px) in data.chunks_exact_mut(4).enumerate() {
+            let (x, y) = (i % width, i / width);
+            px.copy_from_slice(&[(x * 255 / width) as u8, (y * 255 / height) as u8, 128, 255]);
+        }
+    });
+    let packed = create_surface(&packed_buf, width, height, g2d_format_G2D_RGBA8888);
+    let gradient = surface_to_rgba_image(&packed_buf, &packed).expect("read gradient failed");
+    assert_eq!(gradient.dimensions(), (width as u32, height as u32));
+
+    let surface_with_stride = |buf: &DmaBuffer, stride: usize| {
+        let mut surface = create_surface(buf, width, height, g2d_format_G2D_RGBA8888);
+        surface.stride = stride as i32;
+        surface
+    };
+    let mut src_buf = DmaBuffer::new(heap_type, 128 * height * 4).expect("Failed to allocate src");
+    let mut dst_buf = DmaBuffer::new(heap_type, 112 * height * 4).expect("Failed to allocate dst");
+    src_buf.write_with(|data| data.fill(0xee));
+    dst_buf.write_with(|data| data.fill(0));
+    let src = surface_with_stride(&src_buf, 128);
+    let dst = surface_with_stride(&dst_buf, 112);
+
+    rgba_image_to_surface(&gradient, &mut src_buf, &src).expect("upload failed");
+    src_buf.read_with(|data| {
+        assert_eq!(
+            &data[width * 4..128 * 4],
+            &[0xee; 28 * 4][..],
+            "row padding overwritten"
+        );
+    });
+
+    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    g2d.blit(&src, &dst).expect("Blit failed");
+    g2d.finish().unwrap();
+
+    let round_trip = surface_to_rgba_image(&dst_buf, &dst).expect("read back failed");
+    assert!(round_trip == gradient, "round-tripped image differs");
+}
+#[cfg(feature = "image")]
+heap_tests!(test_g2d_rgba_image_round_trip, rgba_image_round_trip_test);
+
+/// Solid mid-gray YUYV converted to RGBA samples into a single luma bin.
+fn convert_and_sample_gray_test(heap_type: HeapType) {
+    let width = 64;
+    let height = 64;
+
+    let mut src_buf =
+        DmaBuffer::new(heap_type, width * height * 2).expect("Failed to allocate src buffer");
+    let dst_buf =
+        DmaBuffer::new(heap_type, width * height * 4).expect("Failed to allocate dst buffer");
+    // Limited-range Y=126 with neutral chroma decodes to R=G=B=128.
+    src_buf.write_with(|data| {
+        for mp in data.chunks_exact_mut(4) {
+            mp.copy_from_slice(&[126, 128, 126, 128]);
+        }
+    });
+
+    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    let src_surface = create_surface(&src_buf, width, height, g2d_format_G2D_YUYV);
+    let dst_surface = create_surface(&dst_buf, width, height, g2d_format_G2D_RGBA8888);
+
+    let histogram = g2d
+        .convert_and_sample(&src_surface, &dst_surface, &dst_buf, 4)
+        .expect("convert_and_sample failed");
+
+    assert_eq!(histogram.samples, 16 * 16);
+    let peak = histogram.luma_peak();
+    assert!(
+        peak.abs_diff(128) <= 2,
+        "luma peak at {peak}, expected ~128"
+    );
+    assert_eq!(histogram.luma[peak as usize], histogram.samples);
+}
+heap_tests!(
+    test_g2d_convert_and_sample_gray,
+    convert_and_sample_gray_test
+);
+
+fn blit_preserve_dst_alpha_test(heap_type: HeapType) {
+    let width = 32;
+    let height = 32;
+
+    let mut src_buf =
+        DmaBuffer::new(heap_type, width * height * 3).expect("Failed to allocate src buffer");
+    let mut dst_buf =
+        DmaBuffer::new(heap_type, width * height * 4).expect("Failed to allocate dst buffer");
+
+    src_buf.write_with(|data| {
+        for px in data.chunks_exact_mut(3) {
+            px.copy_from_slice(&[200, 100, 50]);
+        }
+    });
+    dst_buf.write_with(|data| {
+        for px in data.chunks_exact_mut(4) {
+            px.copy_from_slice(&[10, 20, 30, 0x80]);
+        }
+    });
+
+    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    let src_surface = create_surface(&src_buf, width, height, g2d_format_G2D_RGB888);
+    let dst_surface = create_surface(&dst_buf, width, height, g2d_format_G2D_RGBA8888);
+
+    let options = BlitOptions::default().with_preserve_dst_alpha(true);
+    g2d.blit_with_options(&src_surface, &dst_surface, &mut dst_buf, &options)
+        .expect("blit_with_options failed");
+
+    dst_buf.read_with(|data| {
+        for (i, px) in data.chunks_exact(4).enumerate() {
+            assert_eq!(
+                px,
+                [200, 100, 50, 0x80],
+                "pixel {i}: RGB not updated or alpha lost"
+            );
+        }
+    });
+}
+heap_tests!(
+    test_g2d_blit_preserve_dst_alpha,
+    blit_preserve_dst_alpha_test
+);
+
+/// Convert RGBA with alpha 128 to YUYV and back. The RGBA→YUYV conversion
+/// drops alpha and is counted unless `allow_alpha_loss` is set; the way back
+/// yields alpha 255.
+fn blit_alpha_loss_roundtrip_test(heap_type: HeapType) {
+    let width = 32;
+    let height = 32;
+
+    let mut src_buf =
+        DmaBuffer::new(heap_type, width * height * 4).expect("Failed to allocate src buffer");
+    let mut yuyv_buf =
+        DmaBuffer::new(heap_type, width * height * 2).expect("Failed to allocate YUYV buffer");
+    let back_buf =
+        DmaBuffer::new(heap_type, width * height * 4).expect("Failed to allocate dst buffer");
+    src_buf.write_with(|data| {
+        for px in data.chunks_exact_mut(4) {
+            px.copy_from_slice(&[200, 60, 30, 128]);
+        }
+    });
+
+    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    g2d.reset_state().unwrap();
+    let src = create_surface(&src_buf, width, height, g2d_format_G2D_RGBA8888);
+    let yuyv = create_surface(&yuyv_buf, width, height, g2d_format_G2D_YUYV);
+    let back = create_surface(&back_buf, width, height, g2d_format_G2D_RGBA8888);
+    assert!(src.format().has_alpha() && !yuyv.format().has_alpha());
+
+    // Unacknowledged: counted (and warned about once), but still converts.
+    g2d.blit(&src, &yuyv).unwrap();
+    g2d.blit_with_options(&src, &yuyv, &mut yuyv_buf, &BlitOptions::default())
+        .unwrap();
+    assert_eq!(g2d.alpha_loss_blits(), 2);
+    // Acknowledged: not counted.
+    let options = BlitOptions::default().with_allow_alpha_loss(true);
+    g2d.blit_with_options(&src, &yuyv, &mut yuyv_buf, &options)
+        .unwrap();
+    assert_eq!(g2d.alpha_loss_blits(), 2);
+    g2d.blit(&yuyv, &back).unwrap();
+    g2d.finish().unwrap();
+
+    back_buf.read_with(|data| {
+        for (i, px) in data.chunks_exact(4).enumerate() {
+            assert_eq!(px[3], 255, "pixel {i}: alpha should be opaque after YUYV");
+            assert!(px[0].abs_diff(200) <= 4, "pixel {i}: red {px:?}");
+        }
+    });
+}
+heap_tests!(
+    test_g2d_blit_alpha_loss_roundtrip,
+    blit_alpha_loss_roundtrip_test
+);
+
+/// Bob-deinterlace a source whose fields differ (red top field on even
+/// rows, blue bottom field on odd rows): every output row comes from the
+/// top field, where a weave would keep the blue rows.
+fn blit_deinterlace_bob_test(heap_type: HeapType) {
+    let (width, height) = (32usize, 32usize);
+    let size = width * height * 4;
+
+    let mut src_buf = DmaBuffer::new(heap_type, size).expect("Failed to allocate src buffer");
+    let mut dst_buf = DmaBuffer::new(heap_type, size).expect("Failed to allocate dst buffer");
+    src_buf.write_with(|data| {
+        for (y, row) in data.chunks_exact_mut(width * 4).enumerate() {
+            let color = if y % 2 == 0 {
+                [255, 0, 0, 255]
+            } else {
+                [0, 0, 255, 255]
+            };
+            for px in row.chunks_exact_mut(4) {
+                px.copy_from_slice(&color);
+            }
+        }
+    });
+    dst_buf.write_with(|data| data.fill(0));
+
+    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    if !g2d.capabilities().unwrap().scaling {
+        eprintln!("SKIPPED: no G2D_SCALING");
+        return;
+    }
+    let src = create_surface(&src_buf, width, height, g2d_format_G2D_RGBA8888);
+    let dst = create_surface(&dst_buf, width, height, g2d_format_G2D_RGBA8888);
+    let options = BlitOptions::default().with_deinterlace(Deinterlace::Bob);
+    g2d.blit_with_options(&src, &dst, &mut dst_buf, &options)
+        .expect("Bob blit failed");
+    g2d.finish().unwrap();
+
+    dst_buf.read_with(|data| {
+        assert!(data.iter().any(|&b| b != 0), "output is empty");
+        for (i, px) in data.chunks_exact(4).enumerate() {
+            assert!(
+                px[0] >= 250 && px[2] <= 5,
+                "pixel {i} should be top-field red, got {px:?}"
+            );
+        }
+    });
+
+    let mut odd = src;
+    odd.bottom = height as i32 - 1;
+    assert!(matches!(
+        g2d.blit_with_options(&odd, &dst, &mut dst_buf, &options),
+        Err(Error::InvalidSurface(_))
+    ));
+}
+heap_tests!(test_g2d_blit_deinterlace_bob, blit_deinterlace_bob_test);
+
+#[test]
+fn test_deinterlace_needs_scaling() {
+    let scaling = Capabilities {
+        scaling: true,
+        ..Default::default()
+    };
+    let fixed = Capabilities::default();
+
+    assert!(Deinterlace::None.check(&fixed).is_ok());
+    assert!(Deinterlace::Bob.check(&scaling).is_ok());
+    assert!(matches!(
+        Deinterlace::Bob.check(&fixed),
+        Err(Error::Unsupported(_))
+    ));
+}
+
+fn blit_over_color_test(heap_type: HeapType) {
+    let width = 32;
+    let height = 32;
+    let size = width * height * 4;
+    let hole = 12..20;
+    let in_hole = |x: usize, y: usize| hole.contains(&x) && hole.contains(&y);
+
+    let mut src_buf = DmaBuffer::new(heap_type, size).expect("Failed to allocate src buffer");
+    let mut dst_buf = DmaBuffer::new(heap_type, size).expect("Failed to allocate dst buffer");
+
+    // Opaque green with a fully transparent 8x8 hole in the middle.
+    src_buf.write_with(|data| {
+        for y in 0..height {
+            for x in 0..width {
+                let offset = (y * width + x) * 4;
+                let px = if in_hole(x, y) {
+                    [0, 0, 0, 0]
+                } else {
+                    [0, 255, 0, 255]
+                };
+                data[offset..offset + 4].copy_from_slice(&px);
+            }
+        }
+    });
+    dst_buf.write_with(|data| data.fill(0));
+
+    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    let src_surface = create_surface(&src_buf, width, height, g2d_format_G2D_RGBA8888);
+    let mut dst_surface = create_surface(&dst_buf, width, height, g2d_format_G2D_RGBA8888);
 
-    let color = [255u8, 0, 0, 255];
-    let result = g2d.clear(&mut surface, color);
-    assert!(result.is_ok(), "G2D clear failed: {:?}", result.err());
+    g2d.blit_over_color(&src_surface, &mut dst_surface, [255, 0, 0, 255])
+        .expect("blit_over_color failed");
     g2d.finish().unwrap();
 
-    buf.read_with(|data| {
-        for i in 0..10 {
-            let offset = i * 4;
-            assert_eq!(data[offset], 255, "Red channel mismatch at pixel {i}");
-            assert_eq!(data[offset + 1], 0, "Green channel mismatch at pixel {i}");
-            assert_eq!(data[offset + 2], 0, "Blue channel mismatch at pixel {i}");
-            assert_eq!(data[offset + 3], 255, "Alpha channel mismatch at pixel {i}");
+    dst_buf.read_with(|data| {
+        for y in 0..height {
+            for x in 0..width {
+                let offset = (y * width + x) * 4;
+                let expected: [u8; 4] = if in_hole(x, y) {
+                    [255, 0, 0, 255]
+                } else {
+                    [0, 255, 0, 255]
+                };
+                for c in 0..4 {
+                    let diff = (data[offset + c] as i32 - expected[c] as i32).abs();
+                    assert!(
+                        diff <= 2,
+                        "pixel ({x},{y}) = {:?}, expected {expected:?}",
+                        &data[offset..offset + 4]
+                    );
+                }
+            }
         }
     });
 }
-heap_tests!(test_g2d_clear_rgba, clear_rgba_test);
-
-fn clear_multiple_colors_test(heap_type: HeapType) {
-    let width = 32;
-    let height = 32;
-    let size = width * height * 4;
+heap_tests!(test_g2d_blit_over_color, blit_over_color_test);
+
+/// Letterbox a solid 1920x1080 frame into a 640x640 model input: the
+/// 640x360 content band is the frame's color and the 140-row bars above
+/// and below it are the fill.
+fn letterbox_test(heap_type: HeapType) {
+    let (src_w, src_h) = (1920usize, 1080usize);
+    let (dst_w, dst_h) = (640usize, 640usize);
+    let color = [20, 200, 60, 255];
+    let gray = [114, 114, 114, 255];
+
+    let src_buf =
+        DmaBuffer::new(heap_type, src_w * src_h * 4).expect("Failed to allocate src buffer");
+    let mut dst_buf =
+        DmaBuffer::new(heap_type, dst_w * dst_h * 4).expect("Failed to allocate dst buffer");
+    dst_buf.write_with(|data| data.fill(0));
 
-    let buf = DmaBuffer::new(heap_type, size).expect("Failed to allocate DMA buffer");
     let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
-    let mut surface = create_surface(&buf, width, height, g2d_format_G2D_RGBA8888);
-
-    let colors = [
-        [255, 0, 0, 255],     // Red
-        [0, 255, 0, 255],     // Green
-        [0, 0, 255, 255],     // Blue
-        [128, 128, 128, 255], // Gray
-        [0, 0, 0, 255],       // Black
-        [255, 255, 255, 255], // White
-    ];
+    let mut src = create_surface(&src_buf, src_w, src_h, g2d_format_G2D_RGBA8888);
+    let dst = create_surface(&dst_buf, dst_w, dst_h, g2d_format_G2D_RGBA8888);
+    g2d.clear(&mut src, color).unwrap();
 
-    for color in colors {
-        let result = g2d.clear(&mut surface, color);
-        assert!(
-            result.is_ok(),
-            "Clear with color {color:?} failed: {:?}",
-            result.err()
-        );
-        g2d.finish().unwrap();
+    g2d.letterbox(&src, &dst, gray).expect("letterbox failed");
+    g2d.finish().unwrap();
 
-        buf.read_with(|data| {
-            for pixel in [0, 10, 100, width * height - 1] {
-                let offset = pixel * 4;
+    dst_buf.read_with(|data| {
+        for y in 0..dst_h {
+            let expected = if (140..500).contains(&y) { color } else { gray };
+            for x in 0..dst_w {
+                let offset = (y * dst_w + x) * 4;
                 assert_eq!(
                     &data[offset..offset + 4],
-                    &color,
-                    "Color mismatch at pixel {pixel} for {color:?}"
+                    expected,
+                    "pixel ({x},{y}) mismatch"
                 );
             }
-        });
-    }
+        }
+    });
 }
-heap_tests!(test_g2d_clear_multiple_colors, clear_multiple_colors_test);
+heap_tests!(test_g2d_letterbox, letterbox_test);
+
+/// Blend a semi-transparent white 16x8 overlay at (10,10) onto a black
+/// frame, recorded in the same batch as the frame clear.
+fn overlay_test(heap_type: HeapType) {
+    let (width, height) = (64usize, 48usize);
+    let (ow, oh) = (16usize, 8usize);
+    let (x0, y0) = (10usize, 10usize);
+
+    let mut overlay_buf =
+        DmaBuffer::new(heap_type, ow * oh * 4).expect("Failed to allocate overlay buffer");
+    let frame_buf =
+        DmaBuffer::new(heap_type, width * height * 4).expect("Failed to allocate frame buffer");
+    overlay_buf.write_with(|data| {
+        for px in data.chunks_exact_mut(4) {
+            px.copy_from_slice(&[255, 255, 255, 128]);
+        }
+    });
 
-fn clear_large_surface_test(heap_type: HeapType) {
-    let width = 1920;
-    let height = 1080;
+    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    g2d.reset_state().unwrap();
+    let overlay = create_surface(&overlay_buf, ow, oh, g2d_format_G2D_RGBA8888);
+    let frame = create_surface(&frame_buf, width, height, g2d_format_G2D_RGBA8888);
+
+    let stats = g2d
+        .scope(|batch| {
+            batch
+                .clear(&frame, [0, 0, 0, 255])
+                .overlay(&frame, &overlay, x0 as i32, y0 as i32);
+        })
+        .expect("overlay batch failed");
+    assert_eq!((stats.clears, stats.overlays), (1, 1));
+
+    frame_buf.read_with(|data| {
+        for y in 0..height {
+            for x in 0..width {
+                let offset = (y * width + x) * 4;
+                let rgb = &data[offset..offset + 3];
+                if (x0..x0 + ow).contains(&x) && (y0..y0 + oh).contains(&y) {
+                    assert!(
+                        rgb.iter().all(|&c| c.abs_diff(128) <= 3),
+                        "pixel ({x},{y}) should be ~50% grey, got {rgb:?}"
+                    );
+                } else {
+                    assert_eq!(rgb, [0, 0, 0], "pixel ({x},{y}) should stay black");
+                }
+            }
+        }
+    });
+}
+heap_tests!(test_g2d_overlay, overlay_test);
+
+/// Blend 50%-alpha red over opaque blue with source-over factors: the
+/// result is an even purple.
+fn blend_test(heap_type: HeapType) {
+    let (width, height) = (16usize, 16usize);
     let size = width * height * 4;
 
-    let buf = DmaBuffer::new(heap_type, size).expect("Failed to allocate DMA buffer");
-    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
-    let mut surface = create_surface(&buf, width, height, g2d_format_G2D_RGBA8888);
+    let mut src_buf = DmaBuffer::new(heap_type, size).expect("Failed to allocate src buffer");
+    let mut dst_buf = DmaBuffer::new(heap_type, size).expect("Failed to allocate dst buffer");
+    src_buf.write_with(|data| {
+        for px in data.chunks_exact_mut(4) {
+            px.copy_from_slice(&[255, 0, 0, 128]);
+        }
+    });
+    dst_buf.write_with(|data| {
+        for px in data.chunks_exact_mut(4) {
+            px.copy_from_slice(&[0, 0, 255, 255]);
+        }
+    });
 
-    let color = [0u8, 128, 255, 255]; // Blue-ish
-    let result = g2d.clear(&mut surface, color);
-    assert!(result.is_ok(), "G2D clear 1080p failed: {:?}", result.err());
+    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    let mut src = create_surface(&src_buf, width, height, g2d_format_G2D_RGBA8888);
+    let mut dst = create_surface(&dst_buf, width, height, g2d_format_G2D_RGBA8888);
+    src.set_blend_func(BlendFunc::SrcAlpha);
+    dst.set_blend_func(BlendFunc::OneMinusSrcAlpha);
+    assert_eq!(src.blendfunc, g2d_blend_func_G2D_SRC_ALPHA);
+    assert_eq!(dst.blend_func(), Some(BlendFunc::OneMinusSrcAlpha));
+
+    g2d.blend(&src, &dst).expect("blend failed");
     g2d.finish().unwrap();
 
-    buf.read_with(|data| {
-        let pixels_to_check = [
-            0,                                // top-left
-            width - 1,                        // top-right
-            (height / 2) * width + width / 2, // center
-            (height - 1) * width,             // bottom-left
-            (height - 1) * width + width - 1, // bottom-right
-        ];
-        for pixel in pixels_to_check {
-            let offset = pixel * 4;
-            assert_eq!(
-                &data[offset..offset + 4],
-                &color,
-                "Color mismatch at pixel {pixel}"
+    dst_buf.read_with(|data| {
+        for (i, px) in data.chunks_exact(4).enumerate() {
+            let rgb = &px[..3];
+            assert!(
+                rgb[0].abs_diff(128) <= 3 && rgb[1] <= 3 && rgb[2].abs_diff(127) <= 3,
+                "pixel {i} should be purple, got {rgb:?}"
             );
         }
     });
 }
-heap_tests!(test_g2d_clear_large_surface, clear_large_surface_test);
+heap_tests!(test_g2d_blend, blend_test);
 
-// =============================================================================
-// Clear Format Tests — g2d_clear with various destination formats
-// =============================================================================
-//
-// The clrcolor field is always in RGBA8888 format. The GPU converts it to the
-// destination surface format. These tests verify g2d_clear works correctly
-// with each supported RGB destination format.
+/// An RGBA source whose pixels arrive byte-reversed decodes to the wrong
+/// colors as native RGBA, and to the right ones with `ByteOrder::Swapped`.
+fn byte_order_swapped_test(heap_type: HeapType) {
+    let (width, height) = (16usize, 16usize);
+    let size = width * height * 4;
+    let color = [200, 100, 50, 255];
 
-/// Verify that g2d_clear rejects formats not supported as clear destinations.
-///
-/// As of G2D v6.4.11 (i.MX 8M Plus), g2d_clear only supports 2-byte (565)
-/// and 4-byte (8888) RGB formats. 3-byte RGB and all YUV formats are rejected.
-///
-/// **NOTE FOR FUTURE DEVELOPERS:** If this test starts FAILING, it means
-/// g2d_clear now SUCCEEDS on a format that previously returned an error.
-/// This is GOOD NEWS — the GPU driver has gained new clear capabilities!
-/// Update the test: move the newly-supported format from this list into
-/// `clear_all_rgb_formats_test` (or add a new byte-level verification test),
-/// and remove it from the unsupported list here.
-fn clear_unsupported_formats_test(heap_type: HeapType) {
-    let width = 64;
-    let height = 64;
+    let mut src_buf = DmaBuffer::new(heap_type, size).expect("Failed to allocate src buffer");
+    let mut dst_buf = DmaBuffer::new(heap_type, size).expect("Failed to allocate dst buffer");
+    src_buf.write_with(|data| {
+        for px in data.chunks_exact_mut(4) {
+            let [r, g, b, a] = color;
+            px.copy_from_slice(&[a, b, g, r]);
+        }
+    });
 
-    // Allocate a buffer large enough for any format (4 bpp * 64 * 64 = 16 KiB)
-    let size = width * height * 4;
-    let buf = DmaBuffer::new(heap_type, size).expect("Failed to allocate DMA buffer");
     let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    let src = create_surface(&src_buf, width, height, g2d_format_G2D_RGBA8888);
+    let dst = create_surface(&dst_buf, width, height, g2d_format_G2D_RGBA8888);
+
+    for (order, expected) in [
+        (ByteOrder::Native, [255, 50, 100, 200]),
+        (ByteOrder::Swapped, color),
+    ] {
+        dst_buf.write_with(|data| data.fill(0));
+        let src = src.with_byte_order(order).expect("RGBA can be swapped");
+        g2d.blit(&src, &dst).expect("Blit failed");
+        g2d.finish().unwrap();
+        dst_buf.read_with(|data| {
+            for (i, px) in data.chunks_exact(4).enumerate() {
+                assert_eq!(px, expected, "{order:?} pixel {i}");
+            }
+        });
+    }
+}
+heap_tests!(test_g2d_byte_order_swapped, byte_order_swapped_test);
 
-    // Formats that g2d_clear does NOT support as of G2D v6.4.11.
-    // Each entry: (format constant, human-readable name).
-    //
-    // Note: YUYV and UYVY ARE supported (tested in clear_all_formats_test).
-    // Only YVYU/VYUY are rejected among packed YUV 4:2:2 formats.
-    let unsupported: &[(g2d_format, &str)] = &[
-        // 3-byte RGB — hardware only supports 2-byte and 4-byte clear targets
-        (g2d_format_G2D_RGB888, "RGB888"),
-        (g2d_format_G2D_BGR888, "BGR888"),
-        // Packed YUV 4:2:2 (only YVYU/VYUY; YUYV/UYVY are supported)
-        (g2d_format_G2D_YVYU, "YVYU"),
-        (g2d_format_G2D_VYUY, "VYUY"),
-        // Semi-planar YUV 4:2:0
-        (g2d_format_G2D_NV12, "NV12"),
-        (g2d_format_G2D_NV21, "NV21"),
-        // Planar YUV 4:2:0
-        (g2d_format_G2D_I420, "I420"),
-        (g2d_format_G2D_YV12, "YV12"),
-        // Semi-planar YUV 4:2:2
-        (g2d_format_G2D_NV16, "NV16"),
-        (g2d_format_G2D_NV61, "NV61"),
-    ];
+/// A noisy green background within the tolerance of the key is left out,
+/// so the destination keeps its blue there and only the red square lands.
+fn blit_chroma_key_test(heap_type: HeapType) {
+    let (width, height) = (32usize, 32usize);
+    let size = width * height * 4;
 
-    let mut newly_supported = Vec::new();
-    for &(format, name) in unsupported {
-        let mut surface = create_surface(&buf, width, height, format);
-        let result = g2d.clear(&mut surface, [255, 0, 0, 255]);
-        if result.is_ok() {
-            eprintln!("  {name}: UNEXPECTEDLY SUCCEEDED — driver now supports this format!");
-            newly_supported.push(name);
-        } else {
-            eprintln!("  {name}: correctly rejected");
+    let mut src_buf = DmaBuffer::new(heap_type, size).expect("Failed to allocate src buffer");
+    let mut dst_buf = DmaBuffer::new(heap_type, size).expect("Failed to allocate dst buffer");
+    let in_square = |x: usize, y: usize| (8..24).contains(&x) && (8..24).contains(&y);
+    src_buf.write_with(|data| {
+        for (i, px) in data.chunks_exact_mut(4).enumerate() {
+            let (x, y) = (i % width, i / width);
+            if in_square(x, y) {
+                px.copy_from_slice(&[255, 0, 0, 255]);
+            } else {
+                // Green channel noise of 250±5.
+                let green = 245 + (i * 7 % 11) as u8;
+                px.copy_from_slice(&[0, green, 0, 255]);
+            }
         }
-    }
-    assert!(
-        newly_supported.is_empty(),
-        "g2d_clear now SUCCEEDS for previously unsupported formats: {newly_supported:?}. \
-         This is GOOD NEWS — the GPU driver has gained new clear capabilities! \
-         Move these formats from clear_unsupported_formats_test into \
-         clear_all_rgb_formats_test (or add dedicated byte-verification tests)."
-    );
+    });
+    dst_buf.write_with(|data| {
+        for px in data.chunks_exact_mut(4) {
+            px.copy_from_slice(&[0, 0, 255, 255]);
+        }
+    });
+
+    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    let src = create_surface(&src_buf, width, height, g2d_format_G2D_RGBA8888);
+    let dst = create_surface(&dst_buf, width, height, g2d_format_G2D_RGBA8888);
+    g2d.blit_chroma_key(&src, &src_buf, &dst, [0, 250, 0], 8)
+        .expect("blit_chroma_key failed");
+
+    dst_buf.read_with(|data| {
+        for (i, px) in data.chunks_exact(4).enumerate() {
+            let (x, y) = (i % width, i / width);
+            let expected = if in_square(x, y) {
+                [255, 0, 0, 255]
+            } else {
+                [0, 0, 255, 255]
+            };
+            assert_eq!(px, expected, "pixel ({x}, {y})");
+        }
+    });
 }
-heap_tests!(
-    test_g2d_clear_unsupported_formats,
-    clear_unsupported_formats_test
-);
+heap_tests!(test_g2d_blit_chroma_key, blit_chroma_key_test);
 
-fn clear_bgra8888_test(heap_type: HeapType) {
-    let width = 64;
-    let height = 64;
-    let bpp = 4;
-    let size = width * height * bpp;
+/// Blend enabled through the raw FFI is cleared by `reset_state`, so a
+/// half-transparent source with source-over blend factors copies verbatim.
+fn reset_state_disables_blend_test(heap_type: HeapType) {
+    let width = 16;
+    let height = 16;
+    let size = width * height * 4;
 
-    let buf = DmaBuffer::new(heap_type, size).expect("Failed to allocate DMA buffer");
-    buf.write_with(|data| data.fill(0));
+    let mut src_buf = DmaBuffer::new(heap_type, size).expect("Failed to allocate src buffer");
+    let mut dst_buf = DmaBuffer::new(heap_type, size).expect("Failed to allocate dst buffer");
+    src_buf.write_with(|data| {
+        for px in data.chunks_exact_mut(4) {
+            px.copy_from_slice(&[255, 0, 0, 128]);
+        }
+    });
+    dst_buf.write_with(|data| {
+        for px in data.chunks_exact_mut(4) {
+            px.copy_from_slice(&[0, 0, 255, 255]);
+        }
+    });
 
     let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
-    let mut surface = create_surface(&buf, width, height, g2d_format_G2D_BGRA8888);
-
-    // Clear with red (clrcolor is RGBA8888)
-    let color = [255u8, 0, 0, 255];
-    let result = g2d.clear(&mut surface, color);
-    assert!(
-        result.is_ok(),
-        "G2D clear BGRA8888 failed: {:?}",
-        result.err()
+    let mut src_surface = create_surface(&src_buf, width, height, g2d_format_G2D_RGBA8888);
+    let mut dst_surface = create_surface(&dst_buf, width, height, g2d_format_G2D_RGBA8888);
+    src_surface.blendfunc = g2d_blend_func_G2D_SRC_ALPHA;
+    dst_surface.blendfunc = g2d_blend_func_G2D_ONE_MINUS_SRC_ALPHA;
+
+    assert_eq!(
+        unsafe { g2d.lib.g2d_enable(g2d.handle, g2d_cap_mode_G2D_BLEND) },
+        0,
+        "g2d_enable(G2D_BLEND) failed"
     );
+    g2d.reset_state().expect("reset_state failed");
+
+    g2d.blit(&src_surface, &dst_surface).expect("Blit failed");
     g2d.finish().unwrap();
 
-    // BGRA8888 memory layout: [B, G, R, A] per pixel
-    buf.read_with(|data| {
-        for i in 0..10 {
-            let off = i * bpp;
-            assert_eq!(data[off], 0, "B mismatch at pixel {i}");
-            assert_eq!(data[off + 1], 0, "G mismatch at pixel {i}");
-            assert_eq!(data[off + 2], 255, "R mismatch at pixel {i}");
-            assert_eq!(data[off + 3], 255, "A mismatch at pixel {i}");
+    dst_buf.read_with(|data| {
+        for (i, px) in data.chunks_exact(4).enumerate() {
+            assert_eq!(px, [255, 0, 0, 128], "pixel {i} was blended");
         }
     });
 }
-heap_tests!(test_g2d_clear_bgra8888, clear_bgra8888_test);
+heap_tests!(
+    test_g2d_reset_state_disables_blend,
+    reset_state_disables_blend_test
+);
 
-fn clear_argb8888_test(heap_type: HeapType) {
+// =============================================================================
+// YUV Format Tests
+// =============================================================================
+
+fn blit_yuyv_to_rgba_test(heap_type: HeapType) {
     let width = 64;
     let height = 64;
-    let bpp = 4;
-    let size = width * height * bpp;
+    let src_size = width * height * 2; // YUYV = 2 bytes per pixel
+    let dst_size = width * height * 4; // RGBA
 
-    let buf = DmaBuffer::new(heap_type, size).expect("Failed to allocate DMA buffer");
-    buf.write_with(|data| data.fill(0));
+    let mut src_buf = DmaBuffer::new(heap_type, src_size).expect("Failed to allocate src buffer");
+    let mut dst_buf = DmaBuffer::new(heap_type, dst_size).expect("Failed to allocate dst buffer");
 
-    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
-    let mut surface = create_surface(&buf, width, height, g2d_format_G2D_ARGB8888);
+    src_buf.write_with(|slice| {
+        for i in 0..(src_size / 4) {
+            let offset = i * 4;
+            slice[offset] = 128; // Y0
+            slice[offset + 1] = 128; // U
+            slice[offset + 2] = 128; // Y1
+            slice[offset + 3] = 128; // V
+        }
+    });
+    dst_buf.write_with(|data| data.fill(0));
 
-    // Clear with red (clrcolor is RGBA8888)
-    let color = [255u8, 0, 0, 255];
-    let result = g2d.clear(&mut surface, color);
+    let mut g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    g2d.set_bt709_colorspace()
+        .expect("Failed to set colorspace");
+
+    let src_surface = create_surface(&src_buf, width, height, g2d_format_G2D_YUYV);
+    let dst_surface = create_surface(&dst_buf, width, height, g2d_format_G2D_RGBA8888);
+
+    let result = g2d.blit(&src_surface, &dst_surface);
     assert!(
         result.is_ok(),
-        "G2D clear ARGB8888 failed: {:?}",
+        "G2D YUYV to RGBA blit failed: {:?}",
         result.err()
     );
     g2d.finish().unwrap();
 
-    // ARGB8888 memory layout: [A, R, G, B] per pixel
-    buf.read_with(|data| {
-        for i in 0..10 {
-            let off = i * bpp;
-            assert_eq!(data[off], 255, "A mismatch at pixel {i}");
-            assert_eq!(data[off + 1], 255, "R mismatch at pixel {i}");
-            assert_eq!(data[off + 2], 0, "G mismatch at pixel {i}");
-            assert_eq!(data[off + 3], 0, "B mismatch at pixel {i}");
-        }
+    dst_buf.read_with(|dst_data| {
+        let non_zero = dst_data.iter().filter(|&&b| b != 0).count();
+        assert!(
+            non_zero > dst_size / 4,
+            "Destination appears empty after YUV conversion"
+        );
     });
 }
-heap_tests!(test_g2d_clear_argb8888, clear_argb8888_test);
+heap_tests!(test_g2d_blit_yuyv_to_rgba, blit_yuyv_to_rgba_test);
 
-fn clear_rgb565_test(heap_type: HeapType) {
-    let width = 64;
-    let height = 64;
-    let bpp = 2;
-    let size = width * height * bpp;
+/// The same saturated YUYV frame converts to visibly different RGBA under
+/// `set_colorspace` BT.601 and BT.709, and under limited and full range;
+/// the selection reads back through `colorspace()` and `range()`.
+fn set_colorspace_matrices_differ_test(heap_type: HeapType) {
+    let (width, height) = (64usize, 64usize);
+    let mut src_buf =
+        DmaBuffer::new(heap_type, width * height * 2).expect("Failed to allocate src buffer");
+    src_buf.write_with(|data| {
+        for px in data.chunks_exact_mut(4) {
+            px.copy_from_slice(&[110, 80, 110, 200]); // Y0 U Y1 V: orange
+        }
+    });
+    let dst_buf =
+        DmaBuffer::new(heap_type, width * height * 4).expect("Failed to allocate dst buffer");
 
-    let buf = DmaBuffer::new(heap_type, size).expect("Failed to allocate DMA buffer");
-    buf.write_with(|data| data.fill(0));
+    let mut g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    let src = create_surface(&src_buf, width, height, g2d_format_G2D_YUYV);
+    let dst = create_surface(&dst_buf, width, height, g2d_format_G2D_RGBA8888);
+
+    let mut convert = |colorspace, range| {
+        g2d.set_colorspace(colorspace, range)
+            .expect("set_colorspace failed");
+        assert_eq!(g2d.colorspace().unwrap(), colorspace);
+        assert_eq!(g2d.range().unwrap(), range);
+        g2d.blit(&src, &dst).unwrap();
+        g2d.finish().unwrap();
+        dst_buf.read_with(|data| [data[0], data[1], data[2]])
+    };
+    let bt601 = convert(Colorspace::Bt601, Range::Limited);
+    let bt709 = convert(Colorspace::Bt709, Range::Limited);
+    let bt601_full = convert(Colorspace::Bt601, Range::Full);
+
+    let distance = |a: [u8; 3], b: [u8; 3]| {
+        a.iter()
+            .zip(b)
+            .map(|(&a, b)| a.abs_diff(b) as u32)
+            .sum::<u32>()
+    };
+    assert!(
+        distance(bt601, bt709) >= 20,
+        "BT.601 {bt601:?} and BT.709 {bt709:?} barely differ"
+    );
+    assert!(
+        distance(bt601, bt601_full) >= 20,
+        "limited {bt601:?} and full {bt601_full:?} range barely differ"
+    );
 
-    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
-    let mut surface = create_surface(&buf, width, height, g2d_format_G2D_RGB565);
+    assert!(matches!(
+        g2d.set_colorspace(Colorspace::Bt2020, Range::Limited),
+        Err(Error::Unsupported(_))
+    ));
+    assert_eq!(g2d.colorspace().unwrap(), Colorspace::Bt601);
+}
+heap_tests!(
+    test_g2d_set_colorspace_matrices_differ,
+    set_colorspace_matrices_differ_test
+);
 
-    // RGB565 LE layout: R(15:11) G(10:5) B(4:0)
-    // Pure red   → R=31 G=0 B=0  → 0xF800
-    // Pure green → R=0  G=63 B=0 → 0x07E0
-    // Pure blue  → R=0  G=0 B=31 → 0x001F
-    // White      → all-ones       → 0xFFFF
-    let test_cases: [([u8; 4], u16, &str); 4] = [
-        ([255, 0, 0, 255], 0xF800, "red"),
-        ([0, 255, 0, 255], 0x07E0, "green"),
-        ([0, 0, 255, 255], 0x001F, "blue"),
-        ([255, 255, 255, 255], 0xFFFF, "white"),
-    ];
+/// Convert Y=16 with neutral chroma, which is black in limited range but a
+/// dark gray in full range, and check that an RGB to RGB copy ignores the
+/// range.
+fn yuv_range_black_level_test(heap_type: HeapType) {
+    let (width, height) = (64usize, 64usize);
+    let mut src_buf =
+        DmaBuffer::new(heap_type, width * height * 2).expect("Failed to allocate src buffer");
+    src_buf.write_with(|data| {
+        for px in data.chunks_exact_mut(4) {
+            px.copy_from_slice(&[16, 128, 16, 128]);
+        }
+    });
+    let dst_buf =
+        DmaBuffer::new(heap_type, width * height * 4).expect("Failed to allocate dst buffer");
+    let copy_buf =
+        DmaBuffer::new(heap_type, width * height * 4).expect("Failed to allocate copy buffer");
 
-    for (color, expected, name) in &test_cases {
-        let result = g2d.clear(&mut surface, *color);
-        assert!(
-            result.is_ok(),
-            "G2D clear RGB565 {name} failed: {:?}",
-            result.err()
-        );
-        g2d.finish().unwrap();
+    let mut g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    let src = create_surface(&src_buf, width, height, g2d_format_G2D_YUYV);
+    let dst = create_surface(&dst_buf, width, height, g2d_format_G2D_RGBA8888);
+    let copy = create_surface(&copy_buf, width, height, g2d_format_G2D_RGBA8888);
 
-        buf.read_with(|data| {
-            for i in 0..10 {
-                let off = i * bpp;
-                let pixel = u16::from_le_bytes([data[off], data[off + 1]]);
-                assert_eq!(
-                    pixel, *expected,
-                    "RGB565 {name} mismatch at pixel {i}: got 0x{pixel:04X}, expected 0x{expected:04X}"
-                );
-            }
-        });
-    }
-}
-heap_tests!(test_g2d_clear_rgb565, clear_rgb565_test);
+    g2d.set_colorspace(Colorspace::Bt601, Range::Limited)
+        .unwrap();
+    g2d.blit(&src, &dst).unwrap();
+    g2d.finish().unwrap();
+    let limited = dst_buf.read_with(|data| [data[0], data[1], data[2]]);
+    assert!(
+        limited.iter().all(|&c| c <= 4),
+        "limited-range Y=16 should be black, got {limited:?}"
+    );
 
-/// Bytes per pixel for a g2d_format, or None for multi-plane/unsupported formats.
-#[allow(non_upper_case_globals)]
-fn format_bpp(format: g2d_format) -> Option<usize> {
-    match format {
-        g2d_format_G2D_RGB565 | g2d_format_G2D_BGR565 => Some(2),
-        g2d_format_G2D_YUYV | g2d_format_G2D_UYVY => Some(2),
-        g2d_format_G2D_RGB888 | g2d_format_G2D_BGR888 => Some(3),
-        g2d_format_G2D_RGBA8888
-        | g2d_format_G2D_RGBX8888
-        | g2d_format_G2D_BGRA8888
-        | g2d_format_G2D_BGRX8888
-        | g2d_format_G2D_ARGB8888
-        | g2d_format_G2D_ABGR8888
-        | g2d_format_G2D_XRGB8888
-        | g2d_format_G2D_XBGR8888 => Some(4),
-        _ => None,
-    }
-}
+    g2d.set_colorspace(Colorspace::Bt601, Range::Full).unwrap();
+    g2d.blit(&src, &dst).unwrap();
+    g2d.finish().unwrap();
+    let full = dst_buf.read_with(|data| [data[0], data[1], data[2]]);
+    assert!(
+        full.iter().all(|&c| (10..=22).contains(&c)),
+        "full-range Y=16 should be dark gray, got {full:?}"
+    );
 
-/// Format name for diagnostic output.
-#[allow(non_upper_case_globals)]
-fn format_name(format: g2d_format) -> &'static str {
-    match format {
-        g2d_format_G2D_RGB565 => "RGB565",
-        g2d_format_G2D_RGBA8888 => "RGBA8888",
-        g2d_format_G2D_RGBX8888 => "RGBX8888",
-        g2d_format_G2D_BGRA8888 => "BGRA8888",
-        g2d_format_G2D_BGRX8888 => "BGRX8888",
-        g2d_format_G2D_BGR565 => "BGR565",
-        g2d_format_G2D_ARGB8888 => "ARGB8888",
-        g2d_format_G2D_ABGR8888 => "ABGR8888",
-        g2d_format_G2D_XRGB8888 => "XRGB8888",
-        g2d_format_G2D_XBGR8888 => "XBGR8888",
-        g2d_format_G2D_RGB888 => "RGB888",
-        g2d_format_G2D_BGR888 => "BGR888",
-        g2d_format_G2D_YUYV => "YUYV",
-        g2d_format_G2D_UYVY => "UYVY",
-        _ => "unknown",
-    }
+    // The range belongs to the YUV matrix; an RGB copy is unchanged.
+    g2d.blit(&dst, &copy).unwrap();
+    g2d.finish().unwrap();
+    let copied = copy_buf.read_with(|data| [data[0], data[1], data[2]]);
+    assert_eq!(copied, full, "RGB to RGB copy must ignore the range");
 }
 
-/// Comprehensive clear test across all supported destination formats.
-///
-/// For each format: clear with two different colors and verify the buffer
-/// contents change between clears (not stale). This validates that g2d_clear
-/// correctly handles the format without requiring exact byte-order knowledge
-/// for every variant.
-///
-/// Unsupported formats are tested separately in `clear_unsupported_formats_test`.
-fn clear_all_formats_test(heap_type: HeapType) {
-    let width = 64;
-    let height = 64;
-
-    let formats = [
-        // 16-bit RGB
-        g2d_format_G2D_RGB565,
-        g2d_format_G2D_BGR565,
-        // 32-bit RGB variants
-        g2d_format_G2D_RGBA8888,
-        g2d_format_G2D_RGBX8888,
-        g2d_format_G2D_BGRA8888,
-        g2d_format_G2D_BGRX8888,
-        g2d_format_G2D_ARGB8888,
-        g2d_format_G2D_ABGR8888,
-        g2d_format_G2D_XRGB8888,
-        g2d_format_G2D_XBGR8888,
-        // Packed YUV 4:2:2 (YUYV/UYVY supported; YVYU/VYUY are not)
-        g2d_format_G2D_YUYV,
-        g2d_format_G2D_UYVY,
-    ];
+heap_tests!(test_g2d_yuv_range_black_level, yuv_range_black_level_test);
 
+/// Copy a patterned RGBA frame and a contiguous NV12 frame with
+/// `G2D::copy` and check the destinations match the sources byte for byte.
+fn copy_exact_test(heap_type: HeapType) {
+    let (width, height) = (640usize, 480usize);
     let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
 
-    for format in formats {
-        let name = format_name(format);
-        let bpp = format_bpp(format).expect("unknown bpp");
-        let size = width * height * bpp;
-
-        let buf = DmaBuffer::new(heap_type, size).expect("Failed to allocate DMA buffer");
-        buf.write_with(|data| data.fill(0));
+    for (format, size) in [
+        (g2d_format_G2D_RGBA8888, width * height * 4),
+        (g2d_format_G2D_NV12, width * height * 3 / 2),
+    ] {
+        let mut src_buf = DmaBuffer::new(heap_type, size).expect("Failed to allocate src buffer");
+        let mut dst_buf = DmaBuffer::new(heap_type, size).expect("Failed to allocate dst buffer");
+        src_buf.write_with(|data| {
+            for (i, byte) in data.iter_mut().enumerate() {
+                *byte = (i * 31 % 251) as u8;
+            }
+        });
+        dst_buf.write_with(|data| data.fill(0));
 
-        let mut surface = create_surface(&buf, width, height, format);
+        let mut src = create_surface(&src_buf, width, height, format);
+        let mut dst = create_surface(&dst_buf, width, height, format);
+        if format == g2d_format_G2D_NV12 {
+            src.planes[1] = src.planes[0] + (width * height) as u64;
+            dst.planes[1] = dst.planes[0] + (width * height) as u64;
+        }
+        g2d.copy(&src, &dst).expect("copy failed");
+        g2d.finish().unwrap();
 
-        // Clear with red
-        let red = [255u8, 0, 0, 255];
-        let result = g2d.clear(&mut surface, red);
+        let expected = src_buf.read_with(|data| data.to_vec());
+        let copied = dst_buf.read_with(|data| data.to_vec());
         assert!(
-            result.is_ok(),
-            "{name}: clear with red failed: {:?}",
-            result.err()
+            expected == copied,
+            "g2d_format {format} copy differs from its source"
         );
-        g2d.finish().unwrap();
+    }
+}
 
-        let red_snapshot = buf.read_with(|data| data[..bpp * 10].to_vec());
+heap_tests!(test_g2d_copy_exact, copy_exact_test);
 
-        // Buffer must not be all zeros after clear
-        assert!(
-            red_snapshot.iter().any(|&b| b != 0),
-            "{name}: buffer still all zeros after red clear"
-        );
+/// Convert out-of-gamut YUYV to RGBA and check that the overflowing channel
+/// saturates instead of wrapping. With BT.601 limited range, Y=235 V=240
+/// puts red near 433 (wraps to ~177) and Y=16 U=16 puts blue near -226
+/// (wraps to ~30). The "non-empty" YUV tests would pass either way.
+fn yuv_output_clamp_test(heap_type: HeapType) {
+    let width = 16;
+    let height = 16;
+    let src_size = width * height * 2;
 
-        // Clear with blue
-        let blue = [0u8, 0, 255, 255];
-        let result = g2d.clear(&mut surface, blue);
-        assert!(
-            result.is_ok(),
-            "{name}: clear with blue failed: {:?}",
-            result.err()
-        );
-        g2d.finish().unwrap();
+    let mut src_buf = DmaBuffer::new(heap_type, src_size).expect("Failed to allocate src buffer");
+    let dst_buf =
+        DmaBuffer::new(heap_type, width * height * 4).expect("Failed to allocate dst buffer");
+    src_buf.write_with(|data| {
+        let (top, bottom) = data.split_at_mut(src_size / 2);
+        for macropixel in top.chunks_exact_mut(4) {
+            macropixel.copy_from_slice(&[235, 128, 235, 240]);
+        }
+        for macropixel in bottom.chunks_exact_mut(4) {
+            macropixel.copy_from_slice(&[16, 16, 16, 128]);
+        }
+    });
 
-        let blue_snapshot = buf.read_with(|data| data[..bpp * 10].to_vec());
+    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    g2d.reset_state().unwrap();
 
-        // Blue clear must produce different bytes than red clear
-        assert_ne!(
-            red_snapshot, blue_snapshot,
-            "{name}: buffer unchanged between red and blue clears (stale data?)"
-        );
+    let src = create_surface(&src_buf, width, height, g2d_format_G2D_YUYV);
+    let dst = create_surface(&dst_buf, width, height, g2d_format_G2D_RGBA8888);
+    g2d.blit(&src, &dst).unwrap();
+    g2d.finish().unwrap();
 
-        eprintln!("  {name} ({bpp} bpp): OK");
-    }
+    dst_buf.read_with(|data| {
+        let (top, bottom) = data.split_at(data.len() / 2);
+        for px in top.chunks_exact(4) {
+            assert!(px[0] >= 250, "red wrapped instead of clamping high: {px:?}");
+        }
+        for px in bottom.chunks_exact(4) {
+            assert!(px[2] <= 5, "blue wrapped instead of clamping low: {px:?}");
+        }
+    });
 }
-heap_tests!(test_g2d_clear_all_formats, clear_all_formats_test);
+heap_tests!(test_g2d_yuv_output_clamp, yuv_output_clamp_test);
 
-// =============================================================================
-// Partial Clear Tests — sub-region clearing for letterbox
-// =============================================================================
+fn blit_nv12_to_rgba_test(heap_type: HeapType) {
+    let width = 64;
+    let height = 64;
+    let src_size = width * height + width * height / 2; // Y + UV
+    let dst_size = width * height * 4;
 
-/// Test that g2d_clear respects the left/top/right/bottom region of interest.
-///
-/// Clears only the top and bottom bars of an RGBA surface (simulating
-/// letterbox borders) and verifies that the content area is untouched.
-fn clear_partial_region_test(heap_type: HeapType) {
-    let width = 128;
-    let height = 128;
-    let bpp = 4;
-    let size = width * height * bpp;
+    let mut src_buf = DmaBuffer::new(heap_type, src_size).expect("Failed to allocate src buffer");
+    let mut dst_buf = DmaBuffer::new(heap_type, dst_size).expect("Failed to allocate dst buffer");
 
-    let buf = DmaBuffer::new(heap_type, size).expect("Failed to allocate DMA buffer");
+    let y_size = width * height;
+    src_buf.write_with(|data| {
+        data[..y_size].fill(128);
+        data[y_size..].fill(128);
+    });
+    dst_buf.write_with(|data| data.fill(0));
 
-    // Fill entire buffer with a known pattern (green)
-    let green = [0u8, 255, 0, 255];
-    buf.write_with(|data| {
-        for chunk in data.chunks_exact_mut(4) {
-            chunk.copy_from_slice(&green);
+    let mut g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    g2d.set_bt709_colorspace()
+        .expect("Failed to set colorspace");
+
+    let src_surface = create_nv12_surface(&src_buf, width, height);
+    let dst_surface = create_surface(&dst_buf, width, height, g2d_format_G2D_RGBA8888);
+
+    let result = g2d.blit(&src_surface, &dst_surface);
+    assert!(
+        result.is_ok(),
+        "G2D NV12 to RGBA blit failed: {:?}",
+        result.err()
+    );
+    g2d.finish().unwrap();
+
+    dst_buf.read_with(|dst_data| {
+        let non_zero = dst_data.iter().filter(|&&b| b != 0).count();
+        assert!(
+            non_zero > dst_size / 4,
+            "Destination appears empty after NV12 conversion"
+        );
+    });
+}
+heap_tests!(test_g2d_blit_nv12_to_rgba, blit_nv12_to_rgba_test);
+
+/// Plan and run 4K NV12 to 160x160 RGBA: three passes through RGBA, with a
+/// frame that is dark grey on the left and light grey on the right keeping
+/// its halves apart in the output.
+fn plan_4k_nv12_to_rgba_test(heap_type: HeapType) {
+    let (width, height) = (3840usize, 2160usize);
+    let nv12 = G2DFormat::try_from(NV12).unwrap();
+    let rgba = G2DFormat::try_from(RGBA).unwrap();
+
+    let mut src_buf =
+        DmaBuffer::new(heap_type, width * height * 3 / 2).expect("Failed to allocate src buffer");
+    src_buf.write_with(|data| {
+        let (luma, chroma) = data.split_at_mut(width * height);
+        for row in luma.chunks_exact_mut(width) {
+            row[..width / 2].fill(40);
+            row[width / 2..].fill(200);
         }
+        chroma.fill(128);
     });
+    let mut dst_buf =
+        DmaBuffer::new(heap_type, 160 * 160 * 4).expect("Failed to allocate dst buffer");
+    dst_buf.write_with(|data| data.fill(0));
 
     let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    let plan = g2d
+        .plan(
+            SurfaceSpec::new(nv12, width, height),
+            SurfaceSpec::new(rgba, 160, 160),
+            Rotation::None,
+        )
+        .expect("plan failed");
+    let sizes: Vec<_> = plan
+        .steps()
+        .iter()
+        .map(|step| (step.dst.width, step.dst.height))
+        .collect();
+    assert_eq!(sizes, [(960, 540), (240, 160), (160, 160)]);
+
+    plan.run(&src_buf, &dst_buf).expect("plan run failed");
 
-    // Clear only the top 32 rows with red
-    let red = [255u8, 0, 0, 255];
-    let mut top_surface = create_surface(&buf, width, height, g2d_format_G2D_RGBA8888);
-    top_surface.left = 0;
-    top_surface.top = 0;
-    top_surface.right = width as i32;
-    top_surface.bottom = 32;
-    g2d.clear(&mut top_surface, red).unwrap();
+    dst_buf.read_with(|data| {
+        for y in 0..160 {
+            for x in (0..70).chain(90..160) {
+                let offset = (y * 160 + x) * 4;
+                let px = &data[offset..offset + 4];
+                let (lo, hi) = if x < 80 { (10, 60) } else { (180, 240) };
+                assert!(
+                    px[..3].iter().all(|c| (lo..=hi).contains(c)) && px[3] == 255,
+                    "pixel ({x},{y}) = {px:?}, expected grey in {lo}..={hi}"
+                );
+            }
+        }
+    });
+}
+heap_tests!(test_g2d_plan_4k_nv12_to_rgba, plan_4k_nv12_to_rgba_test);
+
+/// A 100-wide NV12 frame with 128-byte rows and its UV plane at a
+/// page-aligned offset past the Y plane, as a decoder lays out a padded
+/// frame. The row padding and the gap before the UV plane hold saturated
+/// chroma, so reading either as image data tints the grey output green or
+/// pink.
+fn nv12_padded_stride_test(heap_type: HeapType) {
+    let (width, height, stride) = (100usize, 64usize, 128usize);
+    let uv_offset = (stride * height).next_multiple_of(4096) + 4096;
+    let size = uv_offset + stride * height / 2;
+
+    let mut src_buf = DmaBuffer::new(heap_type, size).expect("Failed to allocate src buffer");
+    let mut dst_buf =
+        DmaBuffer::new(heap_type, width * height * 4).expect("Failed to allocate dst buffer");
+    src_buf.write_with(|data| {
+        data.fill(255);
+        for row in 0..height {
+            data[row * stride..row * stride + width].fill(128);
+        }
+        for row in 0..height / 2 {
+            let start = uv_offset + row * stride;
+            data[start..start + width].fill(128);
+        }
+    });
+    dst_buf.write_with(|data| data.fill(0));
 
-    // Clear only the bottom 32 rows with blue
-    let blue = [0u8, 0, 255, 255];
-    let mut bottom_surface = create_surface(&buf, width, height, g2d_format_G2D_RGBA8888);
-    bottom_surface.left = 0;
-    bottom_surface.top = 96;
-    bottom_surface.right = width as i32;
-    bottom_surface.bottom = 128;
-    g2d.clear(&mut bottom_surface, blue).unwrap();
+    let mut g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    g2d.set_bt709_colorspace()
+        .expect("Failed to set colorspace");
+    let src = G2DSurface::nv12(
+        src_buf.address(),
+        src_buf.address() + uv_offset as u64,
+        width as i32,
+        height as i32,
+        stride as i32,
+    );
+    assert_eq!(src.planes[1], src_buf.address() + uv_offset as u64);
+    let dst = create_surface(&dst_buf, width, height, g2d_format_G2D_RGBA8888);
 
-    // Single finish for both clears
+    g2d.blit(&src, &dst).expect("NV12 blit failed");
     g2d.finish().unwrap();
 
-    buf.read_with(|data| {
-        // Top bar (rows 0-31): should be red
-        for row in 0..32 {
-            let offset = row * width * bpp;
-            assert_eq!(
-                &data[offset..offset + 4],
-                &red,
-                "Top bar pixel at row {row} should be red"
-            );
-        }
-
-        // Content area (rows 32-95): should still be green (untouched)
-        for row in 32..96 {
-            let offset = row * width * bpp;
-            assert_eq!(
-                &data[offset..offset + 4],
-                &green,
-                "Content area pixel at row {row} should be green (untouched)"
-            );
-        }
-
-        // Bottom bar (rows 96-127): should be blue
-        for row in 96..128 {
-            let offset = row * width * bpp;
-            assert_eq!(
-                &data[offset..offset + 4],
-                &blue,
-                "Bottom bar pixel at row {row} should be blue"
+    dst_buf.read_with(|data| {
+        for (i, px) in data.chunks_exact(4).enumerate() {
+            let (r, g, b) = (px[0], px[1], px[2]);
+            assert!(
+                r.abs_diff(g) <= 4 && g.abs_diff(b) <= 4 && r.abs_diff(128) <= 16,
+                "pixel ({}, {}) should be grey, got {:?}",
+                i % width,
+                i / width,
+                &px[..3]
             );
         }
     });
 }
-heap_tests!(test_g2d_clear_partial_region, clear_partial_region_test);
-
-/// Test partial clear with left/right vertical bars (portrait letterbox).
-fn clear_partial_left_right_test(heap_type: HeapType) {
-    let width = 128;
-    let height = 64;
-    let bpp = 4;
-    let size = width * height * bpp;
+heap_tests!(test_g2d_nv12_padded_stride, nv12_padded_stride_test);
 
-    let buf = DmaBuffer::new(heap_type, size).expect("Failed to allocate DMA buffer");
-
-    // Fill with green
-    let green = [0u8, 255, 0, 255];
-    buf.write_with(|data| {
-        for chunk in data.chunks_exact_mut(4) {
-            chunk.copy_from_slice(&green);
+/// Test `reinterpret_surface` by blitting the Y plane of an NV12 buffer as
+/// a GRAY8 source: each RGBA output pixel must carry the luma value in R, G
+/// and B. Out-of-bounds reinterpretations must be rejected.
+fn blit_nv12_luma_as_gray8_test(heap_type: HeapType) {
+    let width = 64;
+    let height = 32;
+    let nv12_size = width * height * 3 / 2;
+
+    let mut nv12_buf =
+        DmaBuffer::new(heap_type, nv12_size).expect("Failed to allocate NV12 buffer");
+    nv12_buf.write_with(|data| {
+        let (luma, chroma) = data.split_at_mut(width * height);
+        for (i, y) in luma.iter_mut().enumerate() {
+            *y = (16 + (i % width) * 3) as u8;
         }
+        // Saturated chroma, to catch the Y plane being read as color.
+        chroma.fill(240);
     });
 
-    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
-    let gray = [114u8, 114, 114, 255];
+    let gray = nv12_buf
+        .reinterpret_surface(G2DFormat::try_from(GREY).unwrap(), width, height, 0)
+        .expect("Y plane should fit");
+    assert_eq!(gray.planes[0], nv12_buf.address());
 
-    // Clear left 16 columns
-    let mut left_surface = create_surface(&buf, width, height, g2d_format_G2D_RGBA8888);
-    left_surface.left = 0;
-    left_surface.top = 0;
-    left_surface.right = 16;
-    left_surface.bottom = height as i32;
-    g2d.clear(&mut left_surface, gray).unwrap();
+    let nv12 = G2DFormat::try_from(NV12).unwrap();
+    assert!(nv12_buf.reinterpret_surface(nv12, width, height, 0).is_ok());
+    assert!(matches!(
+        nv12_buf.reinterpret_surface(nv12, width, height, 1),
+        Err(Error::InvalidSurface(_))
+    ));
 
-    // Clear right 16 columns
-    let mut right_surface = create_surface(&buf, width, height, g2d_format_G2D_RGBA8888);
-    right_surface.left = 112;
-    right_surface.top = 0;
-    right_surface.right = 128;
-    right_surface.bottom = height as i32;
-    g2d.clear(&mut right_surface, gray).unwrap();
+    let dst_buf =
+        DmaBuffer::new(heap_type, width * height * 4).expect("Failed to allocate RGBA buffer");
+    let dst = create_surface(&dst_buf, width, height, g2d_format_G2D_RGBA8888);
 
+    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    g2d.blit(&gray, &dst).unwrap();
     g2d.finish().unwrap();
 
-    buf.read_with(|data| {
-        for row in 0..height {
-            let row_offset = row * width * bpp;
-
-            // Left bar (cols 0-15): gray
-            assert_eq!(
-                &data[row_offset..row_offset + 4],
-                &gray,
-                "Left bar at row {row} col 0 should be gray"
-            );
-
-            // Content (col 16): green
-            let mid_offset = row_offset + 16 * bpp;
-            assert_eq!(
-                &data[mid_offset..mid_offset + 4],
-                &green,
-                "Content at row {row} col 16 should be green"
-            );
-
-            // Content (col 111): green
-            let mid_offset = row_offset + 111 * bpp;
-            assert_eq!(
-                &data[mid_offset..mid_offset + 4],
-                &green,
-                "Content at row {row} col 111 should be green"
-            );
-
-            // Right bar (col 112): gray
-            let right_offset = row_offset + 112 * bpp;
-            assert_eq!(
-                &data[right_offset..right_offset + 4],
-                &gray,
-                "Right bar at row {row} col 112 should be gray"
-            );
+    dst_buf.read_with(|data| {
+        for (i, px) in data.chunks_exact(4).enumerate() {
+            let y = (16 + (i % width) * 3) as u8;
+            for (c, &value) in px[..3].iter().enumerate() {
+                assert!(
+                    value.abs_diff(y) <= 2,
+                    "pixel {i} channel {c}: got {value}, expected luma {y}"
+                );
+            }
         }
     });
 }
 heap_tests!(
-    test_g2d_clear_partial_left_right,
-    clear_partial_left_right_test
+    test_g2d_blit_nv12_luma_as_gray8,
+    blit_nv12_luma_as_gray8_test
 );
 
-// =============================================================================
-// Blit Operation Tests
-// =============================================================================
-
-fn blit_rgba_to_rgba_test(heap_type: HeapType) {
-    let width = 64;
-    let height = 64;
-    let size = width * height * 4;
+/// Crop an NV12 source at odd x under each `CropAlignment` policy: `Reject`
+/// fails, `Floor` and `Round` match blits of the even crops either side.
+fn crop_alignment_nv12_test(heap_type: HeapType) {
+    let (width, height) = (64usize, 16usize);
+    let mut src_buf =
+        DmaBuffer::new(heap_type, width * height * 3 / 2).expect("Failed to allocate src buffer");
+    src_buf.write_with(|data| {
+        let (luma, chroma) = data.split_at_mut(width * height);
+        for (i, y) in luma.iter_mut().enumerate() {
+            *y = (16 + 3 * (i % width)) as u8;
+        }
+        for (i, c) in chroma.iter_mut().enumerate() {
+            *c = (64 + 2 * (i % width)) as u8;
+        }
+    });
+    let src = src_buf
+        .reinterpret_surface(G2DFormat::try_from(NV12).unwrap(), width, height, 0)
+        .unwrap();
+    let crop = |left: i32| {
+        let mut surface = src;
+        surface.left = left;
+        surface.right = left + 32;
+        surface
+    };
+    let dst_a = DmaBuffer::new(heap_type, 32 * height * 4).expect("Failed to allocate dst buffer");
+    let dst_b = DmaBuffer::new(heap_type, 32 * height * 4).expect("Failed to allocate dst buffer");
+    let surface_a = create_surface(&dst_a, 32, height, g2d_format_G2D_RGBA8888);
+    let surface_b = create_surface(&dst_b, 32, height, g2d_format_G2D_RGBA8888);
 
-    let src_buf = DmaBuffer::new(heap_type, size).expect("Failed to allocate src buffer");
-    let dst_buf = DmaBuffer::new(heap_type, size).expect("Failed to allocate dst buffer");
+    let mut g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    assert_eq!(g2d.crop_alignment(), CropAlignment::Reject);
+    assert!(matches!(
+        g2d.blit(&crop(3), &surface_a),
+        Err(Error::InvalidSurface(_))
+    ));
+
+    for (policy, aligned) in [(CropAlignment::Floor, 2), (CropAlignment::Round, 4)] {
+        g2d.set_crop_alignment(policy);
+        g2d.blit(&crop(3), &surface_a).unwrap();
+        g2d.blit(&crop(aligned), &surface_b).unwrap();
+        g2d.finish().unwrap();
+        assert_eq!(
+            verify::buffers_equal(&dst_a, &dst_b),
+            None,
+            "{policy:?}: odd crop should match the crop at x={aligned}"
+        );
+    }
+}
+heap_tests!(test_g2d_crop_alignment_nv12, crop_alignment_nv12_test);
 
-    src_buf.write_with(|data| {
-        for (i, byte) in data.iter_mut().enumerate() {
-            *byte = (i % 256) as u8;
+fn reorder_yuyv_to_uyvy_test(heap_type: HeapType) {
+    let width = 64;
+    let height = 16;
+    let yuv_size = width * height * 2;
+    let rgba_size = width * height * 4;
+
+    let mut yuyv_buf = DmaBuffer::new(heap_type, yuv_size).expect("Failed to allocate YUYV buffer");
+    let mut uyvy_buf = DmaBuffer::new(heap_type, yuv_size).expect("Failed to allocate UYVY buffer");
+
+    // Distinct samples per macropixel so any misplaced byte is detected.
+    yuyv_buf.write_with(|data| {
+        for (i, mp) in data.chunks_exact_mut(4).enumerate() {
+            let y = (16 + i % 200) as u8;
+            mp.copy_from_slice(&[y, (64 + i % 128) as u8, y + 10, (192 - i % 128) as u8]);
         }
     });
-    dst_buf.write_with(|data| data.fill(0));
+    uyvy_buf.write_with(|data| data.fill(0));
 
     let mut g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
     g2d.set_bt709_colorspace()
         .expect("Failed to set colorspace");
 
-    let src_surface = create_surface(&src_buf, width, height, g2d_format_G2D_RGBA8888);
-    let dst_surface = create_surface(&dst_buf, width, height, g2d_format_G2D_RGBA8888);
+    let yuyv = create_surface(&yuyv_buf, width, height, g2d_format_G2D_YUYV);
+    let uyvy = create_surface(&uyvy_buf, width, height, g2d_format_G2D_UYVY);
+    g2d.reorder_yuv(&yuyv, &yuyv_buf, &uyvy, &mut uyvy_buf)
+        .expect("reorder_yuv failed");
 
-    let result = g2d.blit(&src_surface, &dst_surface);
-    assert!(result.is_ok(), "G2D blit failed: {:?}", result.err());
-    g2d.finish().unwrap();
+    yuyv_buf.read_with(|src| {
+        uyvy_buf.read_with(|dst| {
+            for (i, (s, d)) in src.chunks_exact(4).zip(dst.chunks_exact(4)).enumerate() {
+                assert_eq!(d, [s[1], s[0], s[3], s[2]], "macropixel {i} not swapped");
+            }
+        })
+    });
 
-    let src_snapshot = src_buf.read_with(|data| data[..100].to_vec());
-    dst_buf.read_with(|data| {
-        for i in 0..100 {
-            assert_eq!(
-                src_snapshot[i], data[i],
-                "Data mismatch at byte {i}: src={} dst={}",
-                src_snapshot[i], data[i]
-            );
+    // Both layouts must decode to the same colors.
+    let rgba_a = DmaBuffer::new(heap_type, rgba_size).expect("Failed to allocate RGBA buffer");
+    let rgba_b = DmaBuffer::new(heap_type, rgba_size).expect("Failed to allocate RGBA buffer");
+    g2d.blit(
+        &yuyv,
+        &create_surface(&rgba_a, width, height, g2d_format_G2D_RGBA8888),
+    )
+    .unwrap();
+    g2d.blit(
+        &uyvy,
+        &create_surface(&rgba_b, width, height, g2d_format_G2D_RGBA8888),
+    )
+    .unwrap();
+    g2d.finish().unwrap();
+    assert_eq!(
+        verify::buffers_equal(&rgba_a, &rgba_b),
+        None,
+        "YUYV and reordered UYVY decode differently"
+    );
+}
+heap_tests!(test_reorder_yuyv_to_uyvy, reorder_yuyv_to_uyvy_test);
+
+fn yuyv_to_i420_test(heap_type: HeapType) {
+    let (width, height) = (64usize, 16usize);
+    let (cw, ch) = (width / 2, height / 2);
+    let luma = width * height;
+
+    let mut yuyv_buf =
+        DmaBuffer::new(heap_type, width * height * 2).expect("Failed to allocate YUYV buffer");
+    let mut i420_buf =
+        DmaBuffer::new(heap_type, luma + 2 * cw * ch).expect("Failed to allocate I420 buffer");
+
+    // Luma ramps along x; chroma alternates by row so the 4:2:0 average is
+    // distinguishable from either source row.
+    yuyv_buf.write_with(|data| {
+        for y in 0..height {
+            let (u, v) = if y.is_multiple_of(2) {
+                (100, 180)
+            } else {
+                (111, 151)
+            };
+            for mx in 0..cw {
+                let offset = (y * cw + mx) * 4;
+                let y0 = (16 + 6 * mx) as u8;
+                data[offset..offset + 4].copy_from_slice(&[y0, u + mx as u8, y0 + 3, v - mx as u8]);
+            }
         }
     });
-}
-heap_tests!(test_g2d_blit_rgba_to_rgba, blit_rgba_to_rgba_test);
+    i420_buf.write_with(|data| data.fill(0));
 
-fn blit_with_scaling_test(heap_type: HeapType) {
-    let src_width = 128;
-    let src_height = 128;
-    let dst_width = 64;
-    let dst_height = 64;
+    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    let yuyv = create_surface(&yuyv_buf, width, height, g2d_format_G2D_YUYV);
+    let i420 = G2DSurface::i420(i420_buf.address(), width as i32, height as i32);
+    g2d.yuyv_to_i420(&yuyv, &yuyv_buf, &i420, &mut i420_buf)
+        .expect("yuyv_to_i420 failed");
+
+    let (src, dst) = (
+        yuyv_buf.read_with(|d| d.to_vec()),
+        i420_buf.read_with(|d| d.to_vec()),
+    );
+    let (y_plane, chroma) = dst.split_at(luma);
+    let (u_plane, v_plane) = chroma.split_at(cw * ch);
+    assert_eq!((u_plane.len(), v_plane.len()), (cw * ch, cw * ch));
+    for y in 0..height {
+        for x in 0..width {
+            let mp = &src[(y * cw + x / 2) * 4..][..4];
+            let expected = if x % 2 == 0 { mp[0] } else { mp[2] };
+            assert_eq!(y_plane[y * width + x], expected, "Y ({x},{y})");
+        }
+    }
+    for cy in 0..ch {
+        for cx in 0..cw {
+            let top = &src[(2 * cy * cw + cx) * 4..][..4];
+            let bottom = &src[((2 * cy + 1) * cw + cx) * 4..][..4];
+            let mean = |k: usize| (top[k] as u16 + bottom[k] as u16).div_ceil(2) as u8;
+            assert_eq!(u_plane[cy * cw + cx], mean(1), "U ({cx},{cy})");
+            assert_eq!(v_plane[cy * cw + cx], mean(3), "V ({cx},{cy})");
+        }
+    }
 
-    let src_size = src_width * src_height * 4;
-    let dst_size = dst_width * dst_height * 4;
+    // The planar result decodes close to the packed source.
+    let rgba_a =
+        DmaBuffer::new(heap_type, width * height * 4).expect("Failed to allocate RGBA buffer");
+    let rgba_b =
+        DmaBuffer::new(heap_type, width * height * 4).expect("Failed to allocate RGBA buffer");
+    g2d.blit(
+        &yuyv,
+        &create_surface(&rgba_a, width, height, g2d_format_G2D_RGBA8888),
+    )
+    .unwrap();
+    g2d.blit(
+        &i420,
+        &create_surface(&rgba_b, width, height, g2d_format_G2D_RGBA8888),
+    )
+    .unwrap();
+    g2d.finish().unwrap();
+    rgba_a.read_with(|a| {
+        rgba_b.read_with(|b| {
+            for (i, (pa, pb)) in a.chunks_exact(4).zip(b.chunks_exact(4)).enumerate() {
+                for c in 0..3 {
+                    assert!(
+                        pa[c].abs_diff(pb[c]) <= 40,
+                        "pixel {i}: YUYV {pa:?} vs I420 {pb:?}"
+                    );
+                }
+            }
+        })
+    });
+}
+heap_tests!(test_yuyv_to_i420, yuyv_to_i420_test);
 
-    let src_buf = DmaBuffer::new(heap_type, src_size).expect("Failed to allocate src buffer");
-    let dst_buf = DmaBuffer::new(heap_type, dst_size).expect("Failed to allocate dst buffer");
+fn i420_vs_yv12_chroma_test(heap_type: HeapType) {
+    let (width, height) = (64usize, 64usize);
+    let luma = width * height;
+    let chroma = luma / 4;
 
-    src_buf.write_with(|slice| {
-        for y in 0..src_height {
-            for x in 0..src_width {
-                let offset = (y * src_width + x) * 4;
-                slice[offset] = (x * 2) as u8;
-                slice[offset + 1] = (y * 2) as u8;
-                slice[offset + 2] = 128;
-                slice[offset + 3] = 255;
-            }
-        }
+    // Second plane low, third plane high: reddish as I420 (U low, V high),
+    // bluish as YV12 (V low, U high).
+    let mut src_buf = DmaBuffer::new(heap_type, luma + 2 * chroma).expect("Failed to allocate src");
+    src_buf.write_with(|data| {
+        data[..luma].fill(128);
+        data[luma..luma + chroma].fill(90);
+        data[luma + chroma..].fill(200);
     });
-    dst_buf.write_with(|data| data.fill(0));
+    let dst_i420 =
+        DmaBuffer::new(heap_type, width * height * 4).expect("Failed to allocate dst buffer");
+    let dst_yv12 =
+        DmaBuffer::new(heap_type, width * height * 4).expect("Failed to allocate dst buffer");
 
     let mut g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
     g2d.set_bt709_colorspace()
         .expect("Failed to set colorspace");
 
-    let src_surface = create_surface(&src_buf, src_width, src_height, g2d_format_G2D_RGBA8888);
-    let dst_surface = create_surface(&dst_buf, dst_width, dst_height, g2d_format_G2D_RGBA8888);
+    let (w, h) = (width as i32, height as i32);
+    g2d.blit(
+        &G2DSurface::i420(src_buf.address(), w, h),
+        &create_surface(&dst_i420, width, height, g2d_format_G2D_RGBA8888),
+    )
+    .expect("I420 blit failed");
+    g2d.blit(
+        &G2DSurface::yv12(src_buf.address(), w, h),
+        &create_surface(&dst_yv12, width, height, g2d_format_G2D_RGBA8888),
+    )
+    .expect("YV12 blit failed");
+    g2d.finish().unwrap();
 
-    let result = g2d.blit(&src_surface, &dst_surface);
-    assert!(
-        result.is_ok(),
-        "G2D blit with scaling failed: {:?}",
-        result.err()
+    let center = |buf: &DmaBuffer| {
+        buf.read_with(|data| {
+            let offset = ((height / 2) * width + width / 2) * 4;
+            [data[offset], data[offset + 1], data[offset + 2]]
+        })
+    };
+    let [r, _, b] = center(&dst_i420);
+    assert!(r > b, "I420 should decode reddish, got r={r} b={b}");
+    let [r, _, b] = center(&dst_yv12);
+    assert!(b > r, "YV12 should decode bluish, got r={r} b={b}");
+}
+heap_tests!(test_g2d_blit_i420_vs_yv12, i420_vs_yv12_chroma_test);
+
+/// I420 with padded rows and each plane on its own page, as a decoder may
+/// lay it out, converts to RGBA through `G2DSurface::i420_planes`.
+fn blit_i420_planes_to_rgba_test(heap_type: HeapType) {
+    let (width, height, stride) = (100usize, 64usize, 128usize);
+    let u_offset = (stride * height).next_multiple_of(4096);
+    let v_offset = u_offset + (stride / 2 * height / 2).next_multiple_of(4096);
+    let size = v_offset + stride / 2 * height / 2;
+
+    let mut src_buf = DmaBuffer::new(heap_type, size).expect("Failed to allocate src buffer");
+    let mut dst_buf =
+        DmaBuffer::new(heap_type, width * height * 4).expect("Failed to allocate dst buffer");
+    src_buf.write_with(|data| data.fill(128));
+    dst_buf.write_with(|data| data.fill(0));
+
+    let mut g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    g2d.set_bt709_colorspace()
+        .expect("Failed to set colorspace");
+    let base = src_buf.address();
+    let src = G2DSurface::i420_planes(
+        base,
+        base + u_offset as u64,
+        base + v_offset as u64,
+        width as i32,
+        height as i32,
+        stride as i32,
     );
+    let dst = create_surface(&dst_buf, width, height, g2d_format_G2D_RGBA8888);
+
+    g2d.blit(&src, &dst).expect("I420 blit failed");
     g2d.finish().unwrap();
 
-    dst_buf.read_with(|dst_data| {
-        let non_zero_count = dst_data.iter().filter(|&&b| b != 0).count();
+    dst_buf.read_with(|data| {
+        let non_zero = data.iter().filter(|&&b| b != 0).count();
         assert!(
-            non_zero_count > dst_size / 2,
-            "Destination buffer appears empty after scaling"
+            non_zero > data.len() / 4,
+            "Destination appears empty after I420 conversion"
         );
     });
 }
-heap_tests!(test_g2d_blit_with_scaling, blit_with_scaling_test);
+heap_tests!(
+    test_g2d_blit_i420_planes_to_rgba,
+    blit_i420_planes_to_rgba_test
+);
 
-fn blit_rgba_to_rgb_test(heap_type: HeapType) {
-    let width = 64;
-    let height = 64;
-    let src_size = width * height * 4; // RGBA
-    let dst_size = width * height * 3; // RGB
+/// Reference limited-range Y'CbCr to R'G'B' decode for luma weights `kr`/`kb`.
+fn reference_yuv_to_rgb(kr: f32, kb: f32, [y, u, v]: [u8; 3]) -> [u8; 3] {
+    let y = (y as f32 - 16.0) * 255.0 / 219.0;
+    let u = (u as f32 - 128.0) * 255.0 / 224.0;
+    let v = (v as f32 - 128.0) * 255.0 / 224.0;
+    let r = y + 2.0 * (1.0 - kr) * v;
+    let b = y + 2.0 * (1.0 - kb) * u;
+    let g = (y - kr * r - kb * b) / (1.0 - kr - kb);
+    [r, g, b].map(|c| c.round().clamp(0.0, 255.0) as u8)
+}
 
-    let src_buf = DmaBuffer::new(heap_type, src_size).expect("Failed to allocate src buffer");
-    let dst_buf = DmaBuffer::new(heap_type, dst_size).expect("Failed to allocate dst buffer");
+/// Reference R'G'B' to limited-range Y'CbCr encode for luma weights `kr`/`kb`.
+fn reference_rgb_to_yuv(kr: f32, kb: f32, [r, g, b]: [u8; 3]) -> [u8; 3] {
+    let (r, g, b) = (r as f32, g as f32, b as f32);
+    let y = kr * r + (1.0 - kr - kb) * g + kb * b;
+    let u = (b - y) / (2.0 * (1.0 - kb));
+    let v = (r - y) / (2.0 * (1.0 - kr));
+    [
+        16.0 + y * 219.0 / 255.0,
+        128.0 + u * 224.0 / 255.0,
+        128.0 + v * 224.0 / 255.0,
+    ]
+    .map(|c| c.round().clamp(0.0, 255.0) as u8)
+}
+
+const BT601: (f32, f32) = (0.299, 0.114);
+const BT709: (f32, f32) = (0.2126, 0.0722);
+
+/// BT.601 YUYV red declared as BT.601 and converted to an RGB target declared
+/// BT.709 decodes with the BT.601 matrix; re-encoding that RGB as BT.709 YUYV
+/// matches the reference cross-conversion.
+fn convert_csc_bt601_to_bt709_test(heap_type: HeapType) {
+    let width = 32;
+    let height = 32;
+    let yuv = [81, 90, 240];
 
-    src_buf.write_with(|slice| {
-        for i in 0..(width * height) {
-            let offset = i * 4;
-            slice[offset] = 255;
-            slice[offset + 1] = 0;
-            slice[offset + 2] = 0;
-            slice[offset + 3] = 255;
+    let mut src_buf =
+        DmaBuffer::new(heap_type, width * height * 2).expect("Failed to allocate src buffer");
+    let mut dst_buf =
+        DmaBuffer::new(heap_type, width * height * 4).expect("Failed to allocate dst buffer");
+    src_buf.write_with(|data| {
+        for mp in data.chunks_exact_mut(4) {
+            mp.copy_from_slice(&[yuv[0], yuv[1], yuv[0], yuv[2]]);
         }
     });
     dst_buf.write_with(|data| data.fill(0));
 
-    let mut g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
-    g2d.set_bt709_colorspace()
-        .expect("Failed to set colorspace");
-
-    let src_surface = create_surface(&src_buf, width, height, g2d_format_G2D_RGBA8888);
-    let dst_surface = create_surface(&dst_buf, width, height, g2d_format_G2D_RGB888);
+    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    let src_surface = create_surface(&src_buf, width, height, g2d_format_G2D_YUYV);
+    let dst_surface = create_surface(&dst_buf, width, height, g2d_format_G2D_RGBA8888);
 
-    let result = g2d.blit(&src_surface, &dst_surface);
-    assert!(
-        result.is_ok(),
-        "G2D RGBA to RGB blit failed: {:?}",
-        result.err()
-    );
+    g2d.convert_csc(
+        &src_surface,
+        &dst_surface,
+        Colorspace::Bt601,
+        Colorspace::Bt709,
+    )
+    .expect("convert_csc failed");
     g2d.finish().unwrap();
 
-    dst_buf.read_with(|dst_data| {
-        for i in 0..10 {
-            let offset = i * 3;
-            assert_eq!(dst_data[offset], 255, "Red channel mismatch at pixel {i}");
-            assert_eq!(
-                dst_data[offset + 1],
-                0,
-                "Green channel mismatch at pixel {i}"
+    let [r, g, b] = reference_yuv_to_rgb(BT601.0, BT601.1, yuv);
+    dst_buf.read_with(|data| {
+        let offset = (height / 2 * width + width / 2) * 4;
+        let px = &data[offset..offset + 3];
+        for (c, (&actual, expected)) in px.iter().zip([r, g, b]).enumerate() {
+            assert!(
+                actual.abs_diff(expected) <= 4,
+                "channel {c}: got {px:?}, expected {:?}",
+                [r, g, b]
             );
-            assert_eq!(
-                dst_data[offset + 2],
-                0,
-                "Blue channel mismatch at pixel {i}"
+        }
+    });
+
+    // Re-encode the RGB result as BT.709 YUYV.
+    let out_buf =
+        DmaBuffer::new(heap_type, width * height * 2).expect("Failed to allocate out buffer");
+    let out_surface = create_surface(&out_buf, width, height, g2d_format_G2D_YUYV);
+    g2d.convert_csc(
+        &dst_surface,
+        &out_surface,
+        Colorspace::Bt709,
+        Colorspace::Bt709,
+    )
+    .expect("convert_csc failed");
+    g2d.finish().unwrap();
+
+    let [y, u, v] = reference_rgb_to_yuv(BT709.0, BT709.1, [r, g, b]);
+    out_buf.read_with(|data| {
+        let offset = (height / 2 * width + width / 2) * 2;
+        let mp = &data[offset..offset + 4];
+        for (&actual, expected) in mp.iter().zip([y, u, y, v]) {
+            assert!(
+                actual.abs_diff(expected) <= 4,
+                "got {mp:?}, expected {:?}",
+                [y, u, y, v]
             );
         }
     });
 }
-heap_tests!(test_g2d_blit_rgba_to_rgb, blit_rgba_to_rgb_test);
+heap_tests!(
+    test_g2d_convert_csc_bt601_to_bt709,
+    convert_csc_bt601_to_bt709_test
+);
 
 // =============================================================================
-// YUV Format Tests
+// Streaming Tests
 // =============================================================================
 
-fn blit_yuyv_to_rgba_test(heap_type: HeapType) {
-    let width = 64;
-    let height = 64;
-    let src_size = width * height * 2; // YUYV = 2 bytes per pixel
-    let dst_size = width * height * 4; // RGBA
+fn process_stream_test(heap_type: HeapType) {
+    const FRAMES: usize = 20;
+    let (src_w, src_h) = (64, 64);
+    let (dst_w, dst_h) = (32, 32);
+    let color = |i: usize| [(i * 12) as u8, 255 - (i * 12) as u8, (i * 5) as u8, 255];
+
+    let sources: Vec<DmaBuffer> = (0..FRAMES)
+        .map(|i| {
+            let mut buf = DmaBuffer::new(heap_type, src_w * src_h * 4)
+                .expect("Failed to allocate src buffer");
+            buf.write_with(|data| {
+                for px in data.chunks_exact_mut(4) {
+                    px.copy_from_slice(&color(i));
+                }
+            });
+            buf
+        })
+        .collect();
+    let frames = || {
+        sources.iter().map(|buf| {
+            FrameRef::new(
+                buf,
+                create_surface(buf, src_w, src_h, g2d_format_G2D_RGBA8888),
+            )
+        })
+    };
 
-    let src_buf = DmaBuffer::new(heap_type, src_size).expect("Failed to allocate src buffer");
-    let dst_buf = DmaBuffer::new(heap_type, dst_size).expect("Failed to allocate dst buffer");
+    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    let spec = ConvertSpec::new(dst_w, dst_h, G2DFormat::try_from(RGBA).unwrap(), heap_type);
 
-    src_buf.write_with(|slice| {
-        for i in 0..(src_size / 4) {
-            let offset = i * 4;
-            slice[offset] = 128; // Y0
-            slice[offset + 1] = 128; // U
-            slice[offset + 2] = 128; // Y1
-            slice[offset + 3] = 128; // V
+    let start = Instant::now();
+    let mut count = 0;
+    for (i, output) in g2d.process_stream(frames(), spec).enumerate() {
+        let output = output.expect("stream frame failed");
+        assert_eq!(output.index(), i);
+        assert_eq!(output.surface().dimensions(), (dst_w as i32, dst_h as i32));
+        output.buffer().read_with(|data| {
+            for (p, px) in data.chunks_exact(4).enumerate() {
+                assert_eq!(px, color(i), "frame {i} pixel {p} mismatch");
+            }
+        });
+        count += 1;
+    }
+    let streamed = start.elapsed();
+    assert_eq!(count, FRAMES);
+
+    // Baseline: one destination, finish after every frame.
+    let dst_buf =
+        DmaBuffer::new(heap_type, dst_w * dst_h * 4).expect("Failed to allocate dst buffer");
+    let dst_surface = create_surface(&dst_buf, dst_w, dst_h, g2d_format_G2D_RGBA8888);
+    let start = Instant::now();
+    for frame in frames() {
+        g2d.blit(&frame.surface, &dst_surface).unwrap();
+        g2d.finish().unwrap();
+    }
+    let serial = start.elapsed();
+
+    eprintln!("  {FRAMES} frames: stream={streamed:?} serial={serial:?}");
+    // The stream also pays for pool allocation on its first batch, so only
+    // guard against it being dramatically slower.
+    assert!(
+        streamed < serial * 3,
+        "stream ({streamed:?}) much slower than serial finish-per-frame ({serial:?})"
+    );
+}
+heap_tests!(test_g2d_process_stream, process_stream_test);
+
+/// A burst of 20 frames that all arrived before the first batch (a source
+/// outpacing the GPU) with a queue depth of 4: `Block` converts all of them,
+/// `DropNewest` keeps the first four and `DropOldest` the last four.
+fn process_stream_drop_policy_test(heap_type: HeapType) {
+    const FRAMES: usize = 20;
+    const DEPTH: usize = 4;
+    let (w, h) = (16, 16);
+
+    let sources: Vec<DmaBuffer> = (0..FRAMES)
+        .map(|i| {
+            let mut buf =
+                DmaBuffer::new(heap_type, w * h * 4).expect("Failed to allocate src buffer");
+            buf.write_with(|data| data.fill(i as u8));
+            buf
+        })
+        .collect();
+    let arrived = Instant::now();
+    let frames = || {
+        sources.iter().map(|buf| {
+            FrameRef::new(buf, create_surface(buf, w, h, g2d_format_G2D_RGBA8888))
+                .with_timestamp(arrived)
+        })
+    };
+
+    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    let spec =
+        ConvertSpec::new(w, h, G2DFormat::try_from(RGBA).unwrap(), heap_type).with_depth(DEPTH);
+
+    for (policy, expected) in [
+        (DropPolicy::Block, (0..FRAMES).collect::<Vec<_>>()),
+        (DropPolicy::DropNewest, (0..DEPTH).collect()),
+        (DropPolicy::DropOldest, (FRAMES - DEPTH..FRAMES).collect()),
+    ] {
+        let mut stream = g2d.process_stream(frames(), spec.with_drop_policy(policy));
+        let mut indices = Vec::new();
+        for output in stream.by_ref() {
+            let output = output.expect("stream frame failed");
+            let i = output.index();
+            output.buffer().read_with(|data| {
+                assert!(
+                    data.iter().all(|&b| b == i as u8),
+                    "{policy:?} frame {i} mismatch"
+                );
+            });
+            indices.push(i);
         }
-    });
-    dst_buf.write_with(|data| data.fill(0));
+        assert_eq!(indices, expected, "{policy:?} outputs");
+        assert_eq!(
+            stream.dropped(),
+            FRAMES - expected.len(),
+            "{policy:?} drops"
+        );
+    }
+}
+heap_tests!(
+    test_g2d_process_stream_drop_policy,
+    process_stream_drop_policy_test
+);
 
-    let mut g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
-    g2d.set_bt709_colorspace()
-        .expect("Failed to set colorspace");
+/// An endless source of untimestamped frames under the dropping policies:
+/// nothing marks them late, so each batch stops at the queue depth and the
+/// stream yields every frame in order instead of draining the source.
+fn process_stream_untimestamped_test(heap_type: HeapType) {
+    const DEPTH: usize = 4;
+    let (w, h) = (16, 16);
 
-    let src_surface = create_surface(&src_buf, width, height, g2d_format_G2D_YUYV);
-    let dst_surface = create_surface(&dst_buf, width, height, g2d_format_G2D_RGBA8888);
+    let src = DmaBuffer::new(heap_type, w * h * 4).expect("Failed to allocate src buffer");
+    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    let spec =
+        ConvertSpec::new(w, h, G2DFormat::try_from(RGBA).unwrap(), heap_type).with_depth(DEPTH);
 
-    let result = g2d.blit(&src_surface, &dst_surface);
-    assert!(
-        result.is_ok(),
-        "G2D YUYV to RGBA blit failed: {:?}",
-        result.err()
+    for policy in [DropPolicy::DropOldest, DropPolicy::DropNewest] {
+        let frames = std::iter::repeat_with(|| {
+            FrameRef::new(&src, create_surface(&src, w, h, g2d_format_G2D_RGBA8888))
+        });
+        let mut stream = g2d.process_stream(frames, spec.with_drop_policy(policy));
+        let indices: Vec<usize> = stream
+            .by_ref()
+            .take(3 * DEPTH)
+            .map(|output| output.expect("stream frame failed").index())
+            .collect();
+        assert_eq!(indices, (0..3 * DEPTH).collect::<Vec<_>>(), "{policy:?}");
+        assert_eq!(stream.dropped(), 0, "{policy:?} drops");
+    }
+}
+heap_tests!(
+    test_g2d_process_stream_untimestamped,
+    process_stream_untimestamped_test
+);
+
+/// Push 5 solid-color frames into a 3-slot `FrameRing`: frames 3 and 4 must
+/// have overwritten slots 0 and 1, and frame 2 must still be in slot 2.
+fn frame_ring_overwrite_test(heap_type: HeapType) {
+    let width = 32;
+    let height = 16;
+    let color = |frame: usize| [(frame * 40) as u8, 255 - (frame * 40) as u8, 7, 255];
+
+    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    let rgba = G2DFormat::try_from(RGBA).unwrap();
+    let mut ring = FrameRing::new(3, width, height, rgba, heap_type).expect("FrameRing::new");
+    assert!(ring.is_empty());
+    assert!(ring.get(0).is_none());
+
+    let src_buf =
+        DmaBuffer::new(heap_type, width * height * 4).expect("Failed to allocate src buffer");
+    let mut src = create_surface(&src_buf, width, height, g2d_format_G2D_RGBA8888);
+    for frame in 0..5 {
+        g2d.clear(&mut src, color(frame)).unwrap();
+        let slot = ring.push_blit(&g2d, &src).unwrap();
+        assert_eq!(slot, frame % 3);
+        // The source is reused, so each frame must land before the next clear.
+        g2d.finish().unwrap();
+    }
+
+    assert_eq!((ring.len(), ring.capacity(), ring.pushed()), (3, 3, 5));
+    assert_eq!(ring.slot_for_age(0), Some(1));
+    assert_eq!(ring.slot_for_age(2), Some(2));
+    assert_eq!(ring.slot_for_age(3), None);
+
+    for (slot, frame) in [(0, 3), (1, 4), (2, 2)] {
+        let (buffer, _) = ring.get(slot).expect("slot written");
+        buffer.read_with(|data| {
+            for px in data.chunks_exact(4) {
+                assert_eq!(px, color(frame), "slot {slot} should hold frame {frame}");
+            }
+        });
+    }
+}
+heap_tests!(test_g2d_frame_ring_overwrite, frame_ring_overwrite_test);
+
+/// A display reads RGBA twice and an encoder reads YUYV once from the same
+/// frame: the RGBA primary is converted once and YUYV only on request.
+fn multi_consumer_test(heap_type: HeapType) {
+    let (width, height) = (64usize, 48usize);
+    let rgba = G2DFormat::try_from(RGBA).unwrap();
+    let yuyv = G2DFormat::try_from(YUYV).unwrap();
+
+    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    let src_buf =
+        DmaBuffer::new(heap_type, width * height * 4).expect("Failed to allocate src buffer");
+    let mut src = create_surface(&src_buf, width, height, g2d_format_G2D_RGBA8888);
+    g2d.clear(&mut src, [200, 100, 50, 255]).unwrap();
+
+    let mut consumer =
+        MultiConsumer::new(width, height, &[rgba, yuyv], heap_type).expect("MultiConsumer::new");
+    assert!(consumer.get(&g2d, rgba).is_err(), "no frame set yet");
+    consumer.set_frame(&g2d, &src).unwrap();
+    let primary_bytes = metrics::bytes_moved(&src, &src);
+    assert_eq!(
+        (consumer.conversions(), consumer.bytes_moved()),
+        (1, primary_bytes)
     );
+
+    for _ in 0..2 {
+        consumer.get(&g2d, rgba).unwrap();
+    }
+    assert_eq!(consumer.conversions(), 1, "RGBA must come from the cache");
+
+    consumer.get(&g2d, yuyv).unwrap();
+    consumer.get(&g2d, yuyv).unwrap();
+    assert_eq!(consumer.conversions(), 2, "YUYV converted once on demand");
+    assert!(consumer.bytes_moved() > primary_bytes);
     g2d.finish().unwrap();
 
-    dst_buf.read_with(|dst_data| {
-        let non_zero = dst_data.iter().filter(|&&b| b != 0).count();
-        assert!(
-            non_zero > dst_size / 4,
-            "Destination appears empty after YUV conversion"
-        );
+    let (buffer, _) = consumer.get(&g2d, rgba).unwrap();
+    buffer.read_with(|data| {
+        for px in data.chunks_exact(4) {
+            assert_eq!(px, [200, 100, 50, 255]);
+        }
+    });
+    let (buffer, _) = consumer.get(&g2d, yuyv).unwrap();
+    buffer.read_with(|data| {
+        let luma = data[0] as i32;
+        assert!(luma.abs_diff(124) <= 6, "Y = {luma}");
     });
+
+    // A new frame drops the secondary output until it is asked for again.
+    consumer.set_frame(&g2d, &src).unwrap();
+    assert_eq!((consumer.frames(), consumer.conversions()), (2, 3));
+    consumer.get(&g2d, yuyv).unwrap();
+    assert_eq!(consumer.conversions(), 4);
 }
-heap_tests!(test_g2d_blit_yuyv_to_rgba, blit_yuyv_to_rgba_test);
+heap_tests!(test_g2d_multi_consumer, multi_consumer_test);
 
-fn blit_nv12_to_rgba_test(heap_type: HeapType) {
-    let width = 64;
-    let height = 64;
-    let src_size = width * height + width * height / 2; // Y + UV
-    let dst_size = width * height * 4;
+#[test]
+fn test_multi_consumer_rejects_unwritable_format() {
+    // G2D only reads NV12, so it cannot be an output; the check comes
+    // before any buffer is allocated.
+    let rgba = G2DFormat::try_from(RGBA).unwrap();
+    let nv12 = G2DFormat::try_from(NV12).unwrap();
+    assert!(matches!(
+        MultiConsumer::new(64, 48, &[rgba, nv12], HeapType::Uncached),
+        Err(Error::UnsupportedDestinationFormat(_))
+    ));
+}
 
-    let src_buf = DmaBuffer::new(heap_type, src_size).expect("Failed to allocate src buffer");
-    let dst_buf = DmaBuffer::new(heap_type, dst_size).expect("Failed to allocate dst buffer");
+/// Convert ten solid RGBA frames to RGB888 into one reused `Vec`: each
+/// readback holds the frame's color, and the capacity never grows after
+/// the first frame.
+fn convert_into_vec_test(heap_type: HeapType) {
+    let (width, height) = (64usize, 48usize);
+    let rgb = G2DFormat::try_from(RGB).unwrap();
 
-    let y_size = width * height;
-    src_buf.write_with(|data| {
-        data[..y_size].fill(128);
-        data[y_size..].fill(128);
+    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    let src_buf =
+        DmaBuffer::new(heap_type, width * height * 4).expect("Failed to allocate src buffer");
+    let mut src = create_surface(&src_buf, width, height, g2d_format_G2D_RGBA8888);
+
+    let mut out = Vec::new();
+    let mut capacity = None;
+    for frame in 0..10u8 {
+        let color = [frame * 25, 255 - frame * 25, 60, 255];
+        g2d.clear(&mut src, color).unwrap();
+        g2d.convert_into_vec(&src, rgb, (width, height), &mut out)
+            .expect("convert_into_vec failed");
+
+        assert_eq!(out.len(), width * height * 3);
+        for (i, px) in out.chunks_exact(3).enumerate() {
+            assert_eq!(px, &color[..3], "frame {frame} pixel {i}");
+        }
+        match capacity {
+            None => capacity = Some(out.capacity()),
+            Some(first) => assert_eq!(out.capacity(), first, "frame {frame} grew the Vec"),
+        }
+    }
+}
+heap_tests!(test_g2d_convert_into_vec, convert_into_vec_test);
+
+/// A buffer built by `from_slice` from a known RGBA gradient blits to
+/// RGB888 with every pixel intact.
+fn from_slice_blit_test(heap_type: HeapType) {
+    let (width, height) = (64usize, 32usize);
+    let rgba = G2DFormat::try_from(RGBA).unwrap();
+    let pattern = |x: usize, y: usize| [(x * 4) as u8, (y * 8) as u8, 90, 255];
+    let pixels: Vec<u8> = (0..height)
+        .flat_map(|y| (0..width).flat_map(move |x| pattern(x, y)))
+        .collect();
+
+    let src_buf =
+        DmaBuffer::from_slice(heap_type, rgba, width, height, &pixels).expect("from_slice failed");
+    assert_eq!(src_buf.size(), pixels.len());
+    let src = src_buf.reinterpret_surface(rgba, width, height, 0).unwrap();
+
+    let dst_buf =
+        DmaBuffer::new(heap_type, width * height * 3).expect("Failed to allocate dst buffer");
+    let dst = create_surface(&dst_buf, width, height, g2d_format_G2D_RGB888);
+
+    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    g2d.blit(&src, &dst).unwrap();
+    g2d.finish().unwrap();
+
+    dst_buf.read_with(|data| {
+        for y in 0..height {
+            for x in 0..width {
+                let offset = (y * width + x) * 3;
+                assert_eq!(
+                    &data[offset..offset + 3],
+                    &pattern(x, y)[..3],
+                    "pixel ({x},{y}) mismatch"
+                );
+            }
+        }
     });
-    dst_buf.write_with(|data| data.fill(0));
+}
+heap_tests!(test_g2d_dma_from_slice_blit, from_slice_blit_test);
 
-    let mut g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
-    g2d.set_bt709_colorspace()
-        .expect("Failed to set colorspace");
+/// `from_slice` rejects slices that are not exactly one image, before
+/// allocating anything, so no heap is needed.
+/// `new_auto` prefers the uncached heap and falls back to the cached one,
+/// so it succeeds whenever either exists.
+#[test]
+fn test_dma_new_auto() {
+    let available: Vec<_> = [HeapType::Uncached, HeapType::Cached]
+        .into_iter()
+        .filter(HeapType::is_available)
+        .collect();
+    let result = DmaBuffer::new_auto(4096);
+    let Some(&preferred) = available.first() else {
+        assert!(matches!(result, Err(Error::HeapUnavailable { .. })));
+        return;
+    };
+    let mut buf = result.expect("new_auto failed with a heap available");
+    assert_eq!(buf.heap_type(), preferred);
+    assert_eq!(buf.requires_sync(), preferred == HeapType::Cached);
 
-    let src_surface = create_nv12_surface(&src_buf, width, height);
-    let dst_surface = create_surface(&dst_buf, width, height, g2d_format_G2D_RGBA8888);
+    buf.write_with(|data| data.fill(0x5a));
+    assert!(buf.read_with(|data| data.iter().all(|&b| b == 0x5a)));
+}
 
-    let result = g2d.blit(&src_surface, &dst_surface);
-    assert!(
-        result.is_ok(),
-        "G2D NV12 to RGBA blit failed: {:?}",
-        result.err()
-    );
-    g2d.finish().unwrap();
+/// `new_preferring(Cached, …)` is the same fallback in the other order.
+#[test]
+fn test_dma_new_preferring_cached() {
+    let available: Vec<_> = [HeapType::Cached, HeapType::Uncached]
+        .into_iter()
+        .filter(HeapType::is_available)
+        .collect();
+    let result = DmaBuffer::new_preferring(HeapType::Cached, 4096);
+    match available.first() {
+        Some(&preferred) => assert_eq!(result.unwrap().heap_type(), preferred),
+        None => assert!(matches!(result, Err(Error::HeapUnavailable { .. }))),
+    }
+}
 
-    dst_buf.read_with(|dst_data| {
-        let non_zero = dst_data.iter().filter(|&&b| b != 0).count();
+#[test]
+fn test_dma_from_slice_wrong_length() {
+    let rgba = G2DFormat::try_from(RGBA).unwrap();
+    let nv12 = G2DFormat::try_from(NV12).unwrap();
+    for (format, len) in [
+        (rgba, 16 * 16 * 4 - 1),
+        (rgba, 16 * 16 * 4 + 4),
+        (nv12, 16 * 16),
+    ] {
+        let data = vec![0u8; len];
         assert!(
-            non_zero > dst_size / 4,
-            "Destination appears empty after NV12 conversion"
+            matches!(
+                DmaBuffer::from_slice(HeapType::Uncached, format, 16, 16, &data),
+                Err(Error::InvalidSurface(_))
+            ),
+            "{len} bytes accepted"
         );
+    }
+}
+
+/// `convert_owned` returns a finished RGB888 frame in a pooled buffer,
+/// which goes back to the pool on drop and is reused by the next call.
+fn convert_owned_test(heap_type: HeapType) {
+    let (width, height) = (64usize, 48usize);
+    let rgb = G2DFormat::try_from(RGB).unwrap();
+    let color = [40, 160, 220, 255];
+
+    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    let src_buf =
+        DmaBuffer::new(heap_type, width * height * 4).expect("Failed to allocate src buffer");
+    let mut src = create_surface(&src_buf, width, height, g2d_format_G2D_RGBA8888);
+    g2d.clear(&mut src, color).unwrap();
+
+    let pool = BufferPool::new(heap_type);
+    let frame = g2d
+        .convert_owned(&src, rgb, (width, height), &pool)
+        .expect("convert_owned failed");
+    assert_eq!(frame.surface().format, g2d_format_G2D_RGB888);
+    assert_eq!(frame.surface().dimensions(), (width as i32, height as i32));
+    frame.buffer().read_with(|data| {
+        for (i, px) in data[..width * height * 3].chunks_exact(3).enumerate() {
+            assert_eq!(px, &color[..3], "pixel {i}");
+        }
     });
+    assert_eq!((pool.allocated(), pool.available()), (1, 0));
+
+    let address = frame.buffer().address();
+    drop(frame);
+    assert_eq!(pool.available(), 1, "buffer not returned on drop");
+
+    let frame = g2d
+        .convert_owned(&src, rgb, (width, height), &pool)
+        .expect("convert_owned failed");
+    assert_eq!(
+        frame.buffer().address(),
+        address,
+        "pooled buffer not reused"
+    );
+    assert_eq!((pool.allocated(), pool.available()), (1, 0));
 }
-heap_tests!(test_g2d_blit_nv12_to_rgba, blit_nv12_to_rgba_test);
+heap_tests!(test_g2d_convert_owned, convert_owned_test);
 
 // =============================================================================
 // Cache Coherency Correctness Tests (Phase 2)
@@ -1455,8 +5315,8 @@ fn cpu_gpu_roundtrip_test(heap_type: HeapType) {
     let height = 64;
     let size = width * height * 4;
 
-    let src_buf = DmaBuffer::new(heap_type, size).expect("Failed to allocate src buffer");
-    let dst_buf = DmaBuffer::new(heap_type, size).expect("Failed to allocate dst buffer");
+    let mut src_buf = DmaBuffer::new(heap_type, size).expect("Failed to allocate src buffer");
+    let mut dst_buf = DmaBuffer::new(heap_type, size).expect("Failed to allocate dst buffer");
 
     // CPU writes a known pattern to source
     src_buf.write_with(|data| {
@@ -1557,6 +5417,125 @@ fn sequential_color_cycle_test(heap_type: HeapType) {
 }
 heap_tests!(test_sequential_color_cycle, sequential_color_cycle_test);
 
+// =============================================================================
+// Verification Helper Tests
+// =============================================================================
+
+/// `verify::buffers_equal` reports `None` for a GPU copy and the exact offset
+/// of a single corrupted byte.
+fn verify_buffers_equal_test(heap_type: HeapType) {
+    let width = 32;
+    let height = 32;
+    let size = width * height * 4;
+
+    let mut src_buf = DmaBuffer::new(heap_type, size).expect("Failed to allocate src buffer");
+    let mut dst_buf = DmaBuffer::new(heap_type, size).expect("Failed to allocate dst buffer");
+
+    src_buf.write_with(|data| {
+        for (i, byte) in data.iter_mut().enumerate() {
+            *byte = (i % 251) as u8;
+        }
+    });
+    dst_buf.write_with(|data| data.fill(0));
+
+    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    let src_surface = create_surface(&src_buf, width, height, g2d_format_G2D_RGBA8888);
+    let dst_surface = create_surface(&dst_buf, width, height, g2d_format_G2D_RGBA8888);
+    g2d.blit(&src_surface, &dst_surface).unwrap();
+    g2d.finish().unwrap();
+
+    assert_eq!(
+        verify::buffers_equal(&src_buf, &dst_buf),
+        None,
+        "GPU copy should be bit-identical to its source"
+    );
+
+    let corrupt_offset = 1234;
+    dst_buf.write_with(|data| data[corrupt_offset] ^= 0xFF);
+    assert_eq!(
+        verify::buffers_equal(&src_buf, &dst_buf),
+        Some(corrupt_offset)
+    );
+}
+heap_tests!(test_verify_buffers_equal, verify_buffers_equal_test);
+
+/// The checksum of a red-cleared buffer changes with a different clear and
+/// matches again after re-clearing to red.
+fn verify_crc32_test(heap_type: HeapType) {
+    let (width, height) = (48usize, 16usize);
+    let format = G2DFormat::try_from(RGBA).unwrap();
+    let buf = DmaBuffer::new_scanout(heap_type, format, width, height).expect("Failed to allocate");
+    let pitch = buf.pitch().unwrap();
+    let mut surface = create_surface(&buf, width, height, g2d_format_G2D_RGBA8888);
+    surface.stride = (pitch / 4) as i32;
+
+    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    let mut clear = |color| {
+        g2d.clear(&mut surface, color).unwrap();
+        g2d.finish().unwrap();
+        buf.crc32(format, width, height).unwrap()
+    };
+
+    let red = clear([255, 0, 0, 255]);
+    let expected: Vec<u8> = [255, 0, 0, 255].repeat(width * height);
+    assert_eq!(
+        red,
+        verify::crc32(&expected),
+        "row padding must not be hashed"
+    );
+    assert_ne!(clear([0, 0, 255, 255]), red);
+    assert_eq!(clear([255, 0, 0, 255]), red);
+
+    assert!(matches!(
+        buf.crc32(format, width, buf.size()),
+        Err(Error::InvalidSurface(_))
+    ));
+}
+heap_tests!(test_verify_crc32, verify_crc32_test);
+
+/// A region checksum changes when a pixel inside the region changes and
+/// not when one outside it does.
+fn verify_region_crc32_test(heap_type: HeapType) {
+    let (width, height) = (48usize, 16usize);
+    let format = G2DFormat::try_from(RGBA).unwrap();
+    let mut buf =
+        DmaBuffer::new_scanout(heap_type, format, width, height).expect("Failed to allocate");
+    let pitch = buf.pitch().unwrap();
+    buf.write_with(|data| data.fill(0));
+    let region = Rect::new(8, 4, 24, 12);
+    let crc = |buf: &DmaBuffer| buf.region_crc32(region, format, width, height).unwrap();
+
+    let before = crc(&buf);
+    let expected: Vec<u8> = vec![0; 16 * 4 * 8];
+    assert_eq!(before, verify::crc32(&expected));
+
+    let set_pixel = |buf: &mut DmaBuffer, x: usize, y: usize, value: u8| {
+        buf.write_with(|data| data[y * pitch + x * 4] = value);
+    };
+    set_pixel(&mut buf, 30, 2, 255);
+    set_pixel(&mut buf, 7, 4, 255);
+    set_pixel(&mut buf, 8, 12, 255);
+    assert_eq!(
+        crc(&buf),
+        before,
+        "pixels outside the region changed the checksum"
+    );
+    set_pixel(&mut buf, 23, 11, 255);
+    assert_ne!(
+        crc(&buf),
+        before,
+        "a pixel inside the region did not change it"
+    );
+
+    for rect in [Rect::new(8, 4, 8, 12), Rect::new(40, 0, 49, 16)] {
+        assert!(matches!(
+            buf.region_crc32(rect, format, width, height),
+            Err(Error::InvalidSurface(_))
+        ));
+    }
+}
+heap_tests!(test_verify_region_crc32, verify_region_crc32_test);
+
 // =============================================================================
 // Stress Tests (Phase 5)
 // =============================================================================
@@ -1617,7 +5596,7 @@ fn stress_blit_100_test(heap_type: HeapType) {
     let height = 64;
     let size = width * height * 4;
 
-    let src_buf = DmaBuffer::new(heap_type, size).expect("Failed to allocate src buffer");
+    let mut src_buf = DmaBuffer::new(heap_type, size).expect("Failed to allocate src buffer");
     let dst_buf = DmaBuffer::new(heap_type, size).expect("Failed to allocate dst buffer");
 
     let mut g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
@@ -1686,3 +5665,94 @@ fn test_g2d_format_invalid() {
     let result = G2DFormat::try_from(invalid);
     assert!(result.is_err(), "Invalid format should return error");
 }
+
+/// libg2d has no 10-bit YUV format, so P010 is refused as unsupported
+/// rather than as an unknown code.
+#[test]
+fn test_g2d_format_p010_unsupported() {
+    assert!(matches!(
+        G2DFormat::try_from(P010),
+        Err(Error::Unsupported(_))
+    ));
+}
+
+/// The raw DRM codes, available without the `drm-fourcc` feature.
+#[test]
+fn test_g2d_format_drm_code() {
+    use g2d_sys::fourcc::drm_code;
+
+    let code = |name: &[u8; 4]| Some(u32::from_le_bytes(*name));
+    assert_eq!(drm_code(g2d_format_G2D_RGBA8888), code(b"AB24"));
+    assert_eq!(drm_code(g2d_format_G2D_BGRX8888), code(b"XR24"));
+    assert_eq!(drm_code(g2d_format_G2D_RGB565), code(b"RG16"));
+    assert_eq!(drm_code(g2d_format_G2D_NV12), code(b"NV12"));
+    assert_eq!(drm_code(g2d_format_G2D_I420), code(b"YU12"));
+    assert_eq!(drm_code(g2d_sys::g2d_format_G2D_RGBA5551), None);
+}
+
+/// G2D names RGB formats in byte order and DRM in word order, so the
+/// 8888 names swap; YUV and 565 names carry over.
+#[cfg(feature = "drm-fourcc")]
+#[test]
+fn test_g2d_format_drm_fourcc() {
+    use g2d_sys::fourcc::{drm_code, to_drm_fourcc, DrmFourcc};
+
+    let pairs = [
+        (g2d_format_G2D_RGBA8888, DrmFourcc::Abgr8888),
+        (g2d_format_G2D_RGBX8888, DrmFourcc::Xbgr8888),
+        (g2d_format_G2D_BGRA8888, DrmFourcc::Argb8888),
+        (g2d_format_G2D_BGRX8888, DrmFourcc::Xrgb8888),
+        (g2d_format_G2D_ARGB8888, DrmFourcc::Bgra8888),
+        (g2d_format_G2D_XRGB8888, DrmFourcc::Bgrx8888),
+        (g2d_format_G2D_ABGR8888, DrmFourcc::Rgba8888),
+        (g2d_format_G2D_XBGR8888, DrmFourcc::Rgbx8888),
+        (g2d_format_G2D_RGB565, DrmFourcc::Rgb565),
+        (g2d_format_G2D_NV12, DrmFourcc::Nv12),
+        (g2d_format_G2D_NV21, DrmFourcc::Nv21),
+        (g2d_format_G2D_I420, DrmFourcc::Yuv420),
+        (g2d_format_G2D_YUYV, DrmFourcc::Yuyv),
+        (g2d_format_G2D_UYVY, DrmFourcc::Uyvy),
+    ];
+    for (g2d, drm) in pairs {
+        assert_eq!(to_drm_fourcc(g2d), Some(drm), "g2d_format {g2d}");
+        assert_eq!(drm_code(g2d), Some(drm as u32), "g2d_format {g2d}");
+        let format: G2DFormat = drm.try_into().unwrap();
+        assert_eq!(format.format(), g2d, "{drm}");
+    }
+
+    assert_eq!(to_drm_fourcc(g2d_sys::g2d_format_G2D_RGBA5551), None);
+    assert_eq!(to_drm_fourcc(g2d_sys::g2d_format_G2D_RGBA1010102), None);
+}
+
+#[test]
+fn test_g2d_destination_format_validation() {
+    let rgba = G2DFormat::try_from(RGBA).unwrap();
+    let nv12 = G2DFormat::try_from(NV12).unwrap();
+    assert!(rgba.is_valid_destination());
+    assert!(!nv12.is_valid_destination());
+
+    let g2d = G2D::new("libg2d.so.2").expect("Failed to open G2D");
+    let src = G2DSurface {
+        format: g2d_format_G2D_RGBA8888,
+        right: 16,
+        bottom: 16,
+        stride: 16,
+        width: 16,
+        height: 16,
+        ..Default::default()
+    };
+    let mut dst = G2DSurface {
+        format: g2d_format_G2D_NV12,
+        ..src
+    };
+
+    // Rejected before the driver sees the (null) plane addresses.
+    assert!(matches!(
+        g2d.blit(&src, &dst),
+        Err(Error::UnsupportedDestinationFormat(f)) if f.format() == g2d_format_G2D_NV12
+    ));
+    assert!(matches!(
+        g2d.clear(&mut dst, [0, 0, 0, 255]),
+        Err(Error::UnsupportedDestinationFormat(_))
+    ));
+}
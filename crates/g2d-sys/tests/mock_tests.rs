@@ -0,0 +1,1713 @@
+// SPDX-FileCopyrightText: Copyright 2025 Au-Zone Technologies
+// SPDX-License-Identifier: Apache-2.0
+
+//! Off-target tests running the crate against the CPU mock backend.
+//!
+//! These mirror the expectations of `hardware_tests.rs` for the operations
+//! the mock implements, using host memory instead of DMA-buf heaps.
+//!
+//! Run with: cargo test --features mock --test mock_tests
+
+#![cfg(target_os = "linux")]
+
+#[cfg(feature = "dma-heap")]
+use g2d_sys::plan::{Plan, MAX_PASS_DOWNSCALE};
+use g2d_sys::{
+    buffer::G2DBuffer,
+    g2d_blend_func_G2D_ONE_MINUS_SRC_ALPHA, g2d_blend_func_G2D_SRC_ALPHA, g2d_cap_mode_G2D_BLEND,
+    g2d_cap_mode_G2D_DITHER, g2d_cap_mode_G2D_YUV_BT_601, g2d_cap_mode_G2D_YUV_BT_709, g2d_format,
+    g2d_format_G2D_ARGB8888, g2d_format_G2D_BGRA8888, g2d_format_G2D_NV12, g2d_format_G2D_RGB565,
+    g2d_format_G2D_RGB888, g2d_format_G2D_RGBA8888, g2d_format_G2D_YUYV,
+    g2d_rotation_G2D_ROTATION_90,
+    layout::{Plane, PlaneLayout},
+    mock::MockBuffer,
+    pipeline::{Pipeline, Stage, StageProblem, SurfaceSpec},
+    pool::G2DPool,
+    BlendFunc, Capabilities, Colorspace, ColorspacePolicy, Core, CropAlignment, Error, Feature,
+    Flip, G2DFormat, G2DSurface, Priority, Range, Rect, Rotation, G2D, GREY, MAX_MULTI_BLIT_LAYERS,
+    MAX_SCALE_FACTOR, NV12, RGB, RGBA, YUYV,
+};
+
+// =============================================================================
+// Host surface helpers
+// =============================================================================
+
+/// Host-memory image; the surface planes point into `buf`.
+struct HostImage {
+    buf: MockBuffer,
+    surface: G2DSurface,
+}
+
+impl HostImage {
+    fn new(width: usize, height: usize, format: g2d_format, bytes: usize) -> Self {
+        let buf = MockBuffer::new(bytes);
+        let address = buf.address();
+        let surface = G2DSurface {
+            format,
+            planes: [address, 0, 0],
+            right: width as i32,
+            bottom: height as i32,
+            stride: width as i32,
+            width: width as i32,
+            height: height as i32,
+            global_alpha: 255,
+            ..Default::default()
+        };
+        HostImage { buf, surface }
+    }
+
+    fn rgba(width: usize, height: usize) -> Self {
+        Self::new(width, height, g2d_format_G2D_RGBA8888, width * height * 4)
+    }
+
+    fn nv12(width: usize, height: usize) -> Self {
+        let mut image = Self::new(width, height, g2d_format_G2D_NV12, width * height * 3 / 2);
+        image.surface.planes[1] = image.surface.planes[0] + (width * height) as u64;
+        image
+    }
+
+    fn data(&self) -> Vec<u8> {
+        self.buf.read_with(<[u8]>::to_vec)
+    }
+
+    fn write(&mut self, f: impl FnOnce(&mut [u8])) {
+        self.buf.write_with(f)
+    }
+
+    fn pixel(&self, x: usize, y: usize) -> Vec<u8> {
+        let offset = (y * self.surface.stride as usize + x) * 4;
+        self.buf.read_with(|data| data[offset..offset + 4].to_vec())
+    }
+}
+
+fn assert_near(actual: &[u8], expected: &[u8], tolerance: u8, what: &str) {
+    for (a, e) in actual.iter().zip(expected) {
+        assert!(
+            a.abs_diff(*e) <= tolerance,
+            "{what}: got {actual:?}, expected {expected:?} (±{tolerance})"
+        );
+    }
+}
+
+// =============================================================================
+// Clear Tests
+// =============================================================================
+
+#[test]
+fn test_mock_clear_rgba() {
+    let g2d = G2D::mock().expect("Failed to open mock");
+    let mut image = HostImage::rgba(64, 64);
+
+    g2d.clear(&mut image.surface, [255, 0, 0, 255]).unwrap();
+    g2d.finish().unwrap();
+
+    for px in image.data().chunks_exact(4) {
+        assert_eq!(px, [255, 0, 0, 255]);
+    }
+}
+
+#[test]
+fn test_mock_clear_byte_layouts() {
+    let g2d = G2D::mock().expect("Failed to open mock");
+    let color = [0x11, 0x22, 0x33, 0x44];
+
+    for (format, expected) in [
+        (g2d_format_G2D_RGBA8888, [0x11, 0x22, 0x33, 0x44]),
+        (g2d_format_G2D_BGRA8888, [0x33, 0x22, 0x11, 0x44]),
+        (g2d_format_G2D_ARGB8888, [0x44, 0x11, 0x22, 0x33]),
+    ] {
+        let mut image = HostImage::new(8, 8, format, 8 * 8 * 4);
+        g2d.clear(&mut image.surface, color).unwrap();
+        assert_eq!(image.pixel(3, 3), expected, "format {format}");
+    }
+
+    // Pure red in RGB565 is 0xF800, little-endian.
+    let mut image = HostImage::new(8, 8, g2d_format_G2D_RGB565, 8 * 8 * 2);
+    g2d.clear(&mut image.surface, [255, 0, 0, 255]).unwrap();
+    assert_eq!(&image.data()[..2], [0x00, 0xF8]);
+}
+
+#[test]
+fn test_mock_clear_partial_region() {
+    let g2d = G2D::mock().expect("Failed to open mock");
+    let mut image = HostImage::rgba(64, 64);
+    image.write(|data| data.fill(0));
+
+    image.surface.left = 16;
+    image.surface.top = 8;
+    image.surface.right = 48;
+    image.surface.bottom = 24;
+    g2d.clear(&mut image.surface, [0, 0, 255, 255]).unwrap();
+
+    for y in 0..64 {
+        for x in 0..64 {
+            let inside = (16..48).contains(&x) && (8..24).contains(&y);
+            let expected = if inside { [0, 0, 255, 255] } else { [0; 4] };
+            assert_eq!(image.pixel(x, y), expected, "pixel ({x},{y})");
+        }
+    }
+}
+
+#[test]
+fn test_mock_clear_rect() {
+    let g2d = G2D::mock().expect("Failed to open mock");
+    let mut image = HostImage::rgba(32, 16);
+    image.write(|data| {
+        for (i, byte) in data.iter_mut().enumerate() {
+            *byte = (i * 7) as u8;
+        }
+    });
+    let before = image.data();
+
+    let rect = Rect::new(5, 3, 21, 11);
+    g2d.clear_rect(&image.surface, rect, [9, 8, 7, 6]).unwrap();
+
+    for y in 0..16 {
+        for x in 0..32 {
+            let offset = (y * 32 + x) * 4;
+            let expected = if (5..21).contains(&x) && (3..11).contains(&y) {
+                &[9, 8, 7, 6][..]
+            } else {
+                &before[offset..offset + 4]
+            };
+            assert_eq!(image.pixel(x, y), expected, "pixel ({x},{y})");
+        }
+    }
+    // The caller's crop is left alone.
+    assert_eq!(image.surface.crop_rect(), Rect::new(0, 0, 32, 16));
+
+    for rect in [
+        Rect::new(0, 0, 33, 16),
+        Rect::new(-1, 0, 4, 4),
+        Rect::new(4, 4, 4, 8),
+    ] {
+        assert!(matches!(
+            g2d.clear_rect(&image.surface, rect, [0; 4]),
+            Err(Error::InvalidSurface(_))
+        ));
+    }
+}
+
+#[test]
+fn test_mock_close_rejects_operations() {
+    let mut g2d = G2D::mock().expect("Failed to open mock");
+    let mut image = HostImage::rgba(4, 4);
+    g2d.close().unwrap();
+
+    assert!(matches!(
+        g2d.clear(&mut image.surface, [1, 2, 3, 4]),
+        Err(Error::HandleClosed)
+    ));
+    assert!(matches!(
+        g2d.blit(&image.surface, &image.surface),
+        Err(Error::HandleClosed)
+    ));
+    assert!(matches!(g2d.reset_state(), Err(Error::HandleClosed)));
+    assert!(image.data().iter().all(|&b| b == 0));
+    // Closing twice is harmless.
+    g2d.close().unwrap();
+}
+
+#[test]
+fn test_mock_pool_workers() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<G2DPool>();
+
+    let pool = G2DPool::with_opener(4, G2D::mock).unwrap();
+    assert_eq!(pool.capacity(), 4);
+
+    // Four workers each open a context on their own thread and run four
+    // clears through it.
+    let colors: Vec<Vec<[u8; 4]>> = std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..4)
+            .map(|worker| {
+                let pool = &pool;
+                scope.spawn(move || {
+                    let g2d = pool.get().expect("pool slot");
+                    (0..4)
+                        .map(|i| {
+                            let mut image = HostImage::rgba(4, 4);
+                            let color = [worker as u8, i as u8, 2, 255];
+                            g2d.clear(&mut image.surface, color).unwrap();
+                            g2d.finish().unwrap();
+                            assert_eq!(image.pixel(3, 3), color);
+                            color
+                        })
+                        .collect()
+                })
+            })
+            .collect();
+        workers.into_iter().map(|w| w.join().unwrap()).collect()
+    });
+    assert_eq!(colors.iter().flatten().count(), 16);
+    assert_eq!(pool.in_use(), 0, "contexts return to the pool on drop");
+
+    // Contexts keep separate capability state.
+    let (a, b) = (pool.get().unwrap(), pool.get().unwrap());
+    a.enable(Feature::Blend).unwrap();
+    assert!(!b.is_enabled(Feature::Blend).unwrap());
+
+    let (c, d) = (pool.get().unwrap(), pool.get().unwrap());
+    assert!(matches!(
+        pool.get(),
+        Err(Error::PoolExhausted { capacity: 4 })
+    ));
+    drop(d);
+    assert_eq!(pool.in_use(), 3);
+    let _d = pool.get().unwrap();
+    drop((a, b, c));
+
+    assert!(matches!(
+        G2DPool::with_opener(0, G2D::mock),
+        Err(Error::InvalidPoolCapacity)
+    ));
+}
+
+#[test]
+fn test_mock_shared_handle_state() {
+    let shared = std::rc::Rc::new(G2D::mock().expect("Failed to open mock"));
+    let other = std::rc::Rc::clone(&shared);
+    let separate = G2D::mock().expect("Failed to open mock");
+
+    G2D::builder()
+        .colorspace(Colorspace::Bt709)
+        .configure(&shared)
+        .unwrap();
+    assert_eq!(other.colorspace().unwrap(), Colorspace::Bt709);
+    assert_eq!(separate.colorspace().unwrap(), Colorspace::Bt601);
+
+    G2D::builder()
+        .colorspace(Colorspace::Bt601)
+        .configure(&other)
+        .unwrap();
+    assert_eq!(shared.colorspace().unwrap(), Colorspace::Bt601);
+}
+
+#[test]
+fn test_mock_finish_token_goes_stale() {
+    let g2d = G2D::mock().expect("Failed to open mock");
+    let mut image = HostImage::rgba(4, 4);
+
+    let token = g2d.finish_token().unwrap();
+    assert!(token.is_current());
+    g2d.clear(&mut image.surface, [1, 2, 3, 4]).unwrap();
+    assert!(!token.is_current());
+    assert!(g2d.finish_token().unwrap().is_current());
+}
+
+#[test]
+fn test_mock_blit_cancellable() {
+    let g2d = G2D::mock().expect("Failed to open mock");
+    let mut src = HostImage::rgba(8, 8);
+    src.write(|data| {
+        for px in data.chunks_exact_mut(4) {
+            px.copy_from_slice(&[10, 20, 30, 255]);
+        }
+    });
+    let dst = HostImage::rgba(8, 8);
+
+    let handle = g2d.blit_cancellable(&src.surface, &dst.surface).unwrap();
+    assert!(!handle.cancel(), "libg2d cannot abort submitted work");
+    let token = handle.wait().unwrap();
+    assert!(token.is_current());
+    assert_eq!(dst.pixel(7, 7), [10, 20, 30, 255]);
+}
+
+#[test]
+fn test_mock_priority_submissions() {
+    let mut g2d = G2D::mock().expect("Failed to open mock");
+    assert_eq!(g2d.priority(), Priority::Normal);
+
+    let mut frame = HostImage::rgba(8, 8);
+    g2d.set_priority(Priority::High);
+    g2d.clear(&mut frame.surface, [0, 0, 255, 255]).unwrap();
+
+    let mut src = HostImage::rgba(8, 8);
+    g2d.clear(&mut src.surface, [10, 20, 30, 255]).unwrap();
+    let dst = HostImage::rgba(8, 8);
+    g2d.set_priority(Priority::Normal);
+    g2d.blit(&src.surface, &dst.surface).unwrap();
+    g2d.finish().unwrap();
+
+    assert_eq!(frame.pixel(3, 3), [0, 0, 255, 255]);
+    assert_eq!(dst.pixel(7, 7), [10, 20, 30, 255]);
+}
+
+#[test]
+fn test_mock_colorspace_policy() {
+    let src = HostImage::nv12(4, 2);
+    let dst = HostImage::rgba(4, 2);
+    let rgba = HostImage::rgba(4, 2);
+
+    let mut strict = G2D::mock().expect("Failed to open mock");
+    strict.set_colorspace_policy(ColorspacePolicy::Strict);
+    assert!(matches!(
+        strict.blit(&src.surface, &dst.surface),
+        Err(Error::ColorspaceNotSet)
+    ));
+    // RGB to RGB needs no matrix.
+    strict.blit(&rgba.surface, &dst.surface).unwrap();
+    // Probing a matrix does not select one.
+    assert!(strict.query_cap(Feature::YuvBt709).unwrap());
+    assert!(!strict.is_colorspace_set());
+    assert!(matches!(
+        strict.blit(&src.surface, &dst.surface),
+        Err(Error::ColorspaceNotSet)
+    ));
+    G2D::builder()
+        .colorspace(Colorspace::Bt709)
+        .configure(&strict)
+        .unwrap();
+    strict.blit(&src.surface, &dst.surface).unwrap();
+
+    let mut lenient = G2D::mock().expect("Failed to open mock");
+    lenient.set_colorspace_policy(ColorspacePolicy::Lenient(Colorspace::Bt709));
+    lenient.blit(&src.surface, &dst.surface).unwrap();
+    assert!(lenient.is_colorspace_set());
+    assert_eq!(lenient.colorspace().unwrap(), Colorspace::Bt709);
+}
+
+#[test]
+fn test_mock_set_colorspace() {
+    let mut g2d = G2D::mock().expect("Failed to open mock");
+    let mut src = HostImage::new(2, 1, g2d_format_G2D_YUYV, 4);
+    src.write(|data| data.copy_from_slice(&[110, 80, 110, 200]));
+    let dst = HostImage::rgba(2, 1);
+
+    let mut convert = |colorspace, range| {
+        g2d.set_colorspace(colorspace, range).unwrap();
+        assert_eq!(g2d.colorspace().unwrap(), colorspace);
+        assert_eq!(g2d.range().unwrap(), range);
+        g2d.blit(&src.surface, &dst.surface).unwrap();
+        dst.pixel(0, 0).to_vec()
+    };
+    let bt601 = convert(Colorspace::Bt601, Range::Limited);
+    let bt709 = convert(Colorspace::Bt709, Range::Limited);
+    let bt709_full = convert(Colorspace::Bt709, Range::Full);
+    assert_ne!(bt601, bt709);
+    assert_ne!(bt709, bt709_full);
+
+    // The shorthands select limited range.
+    g2d.set_bt601_colorspace().unwrap();
+    assert_eq!(g2d.range().unwrap(), Range::Limited);
+    g2d.blit(&src.surface, &dst.surface).unwrap();
+    assert_eq!(dst.pixel(0, 0), bt601);
+
+    assert!(matches!(
+        g2d.set_colorspace(Colorspace::Bt2020, Range::Full),
+        Err(Error::Unsupported(_))
+    ));
+    assert_eq!(g2d.colorspace().unwrap(), Colorspace::Bt601);
+}
+
+#[test]
+fn test_mock_query_cap_keeps_matrix() {
+    let mut g2d = G2D::mock().expect("Failed to open mock");
+    g2d.set_colorspace(Colorspace::Bt601, Range::Full).unwrap();
+    for feature in [
+        Feature::YuvBt601,
+        Feature::YuvBt709,
+        Feature::YuvBt709FullRange,
+    ] {
+        assert!(g2d.query_cap(feature).unwrap());
+        assert_eq!(g2d.colorspace().unwrap(), Colorspace::Bt601);
+        assert_eq!(g2d.range().unwrap(), Range::Full);
+    }
+    assert!(g2d.is_enabled(Feature::YuvBt601FullRange).unwrap());
+    assert!(!g2d.is_enabled(Feature::YuvBt709).unwrap());
+}
+
+#[test]
+fn test_mock_g2d_buffer() {
+    let g2d = G2D::mock().expect("Failed to open mock");
+    let mut buf = G2DBuffer::new(&g2d, 4 * 2 * 4, true).unwrap();
+    buf.as_mut_slice().fill(9);
+    buf.clean().unwrap();
+
+    let mut surface = G2DSurface {
+        format: g2d_format_G2D_RGBA8888,
+        planes: [buf.address(), 0, 0],
+        right: 4,
+        bottom: 2,
+        stride: 4,
+        width: 4,
+        height: 2,
+        ..Default::default()
+    };
+    g2d.clear(&mut surface, [10, 20, 30, 40]).unwrap();
+    buf.invalidate().unwrap();
+    assert!(buf
+        .as_slice()
+        .chunks_exact(4)
+        .all(|px| px == [10, 20, 30, 40]));
+
+    assert!(matches!(
+        G2DBuffer::new(&g2d, 0, false),
+        Err(Error::InvalidSurface(_))
+    ));
+}
+
+#[test]
+fn test_mock_make_current() {
+    let mut g2d = G2D::mock().expect("Failed to open mock");
+    assert!(g2d.core_available(Core::Gpu2d).unwrap());
+    g2d.make_current(Core::Gpu2d).unwrap();
+
+    let mut dst = HostImage::rgba(2, 2);
+    g2d.clear(&mut dst.surface, [1, 2, 3, 4]).unwrap();
+    assert_eq!(dst.pixel(1, 1), [1, 2, 3, 4]);
+
+    for core in [Core::Dpu, Core::Pxp] {
+        assert!(!g2d.core_available(core).unwrap());
+        assert!(matches!(g2d.make_current(core), Err(Error::Unsupported(_))));
+    }
+}
+
+#[test]
+fn test_mock_copy() {
+    let g2d = G2D::mock().expect("Failed to open mock");
+
+    let mut src = HostImage::rgba(4, 3);
+    src.write(|data| {
+        for (i, byte) in data.iter_mut().enumerate() {
+            *byte = i as u8;
+        }
+    });
+    let dst = HostImage::rgba(4, 3);
+    g2d.copy(&src.surface, &dst.surface).unwrap();
+    g2d.finish().unwrap();
+    assert_eq!(dst.data(), src.data());
+
+    let mut src = HostImage::nv12(4, 2);
+    src.write(|data| {
+        for (i, byte) in data.iter_mut().enumerate() {
+            *byte = 100 + i as u8;
+        }
+    });
+    let mut dst = HostImage::nv12(4, 2);
+    g2d.copy(&src.surface, &dst.surface).unwrap();
+    assert_eq!(dst.data(), src.data());
+
+    // The chroma plane must follow the luma plane for a single transfer.
+    dst.surface.planes[1] += 4;
+    assert!(matches!(
+        g2d.copy(&src.surface, &dst.surface),
+        Err(Error::InvalidSurface(_))
+    ));
+}
+
+#[test]
+fn test_mock_yuv_range_black_level() {
+    let mut g2d = G2D::mock().expect("Failed to open mock");
+    let mut src = HostImage::new(2, 1, g2d_format_G2D_YUYV, 4);
+    src.write(|data| data.copy_from_slice(&[16, 128, 16, 128]));
+    let dst = HostImage::rgba(2, 1);
+    let copy = HostImage::rgba(2, 1);
+
+    // Y=16 is black in limited range and dark gray in full range.
+    g2d.set_colorspace(Colorspace::Bt601, Range::Limited)
+        .unwrap();
+    g2d.blit(&src.surface, &dst.surface).unwrap();
+    assert_near(&dst.pixel(0, 0), &[0, 0, 0, 255], 2, "limited Y=16");
+
+    g2d.set_colorspace(Colorspace::Bt601, Range::Full).unwrap();
+    g2d.blit(&src.surface, &dst.surface).unwrap();
+    assert_near(&dst.pixel(0, 0), &[16, 16, 16, 255], 2, "full Y=16");
+
+    // RGB to RGB ignores the range.
+    g2d.blit(&dst.surface, &copy.surface).unwrap();
+    assert_eq!(copy.pixel(0, 0), dst.pixel(0, 0));
+}
+
+#[test]
+fn test_mock_ingest_rotated_sensor() {
+    let g2d = G2D::mock().expect("Failed to open mock");
+
+    // 4x2 NV12 frame: bright left half, dark right half, neutral chroma.
+    let mut src = HostImage::nv12(4, 2);
+    src.write(|data| {
+        for (i, y) in data[..8].iter_mut().enumerate() {
+            *y = if i % 4 < 2 { 235 } else { 16 };
+        }
+    });
+    src.write(|data| data[8..].fill(128));
+
+    // A 90° mount turns the left half into the top half of a 2x4 frame.
+    let dst = HostImage::rgba(2, 4);
+    g2d.ingest(&src.surface, &dst.surface, Rotation::Deg90)
+        .unwrap();
+    for y in 0..4 {
+        for x in 0..2 {
+            let expected = if y < 2 {
+                [255, 255, 255, 255]
+            } else {
+                [0, 0, 0, 255]
+            };
+            assert_near(&dst.pixel(x, y), &expected, 2, &format!("pixel ({x}, {y})"));
+        }
+    }
+    assert_eq!(dst.surface.rotation(), 0, "ingest must not modify dst");
+
+    let nv12 = HostImage::nv12(2, 4);
+    assert!(matches!(
+        g2d.ingest(&src.surface, &nv12.surface, Rotation::Deg90),
+        Err(Error::Unsupported(_))
+    ));
+}
+
+#[test]
+fn test_mock_clear_many() {
+    let g2d = G2D::mock().expect("Failed to open mock");
+    let images = [
+        HostImage::rgba(8, 8),
+        HostImage::rgba(8, 8),
+        HostImage::rgba(8, 8),
+    ];
+    let colors = [[255, 0, 0, 255], [0, 255, 0, 255], [0, 0, 255, 255]];
+
+    let clears: Vec<(&G2DSurface, [u8; 4])> =
+        images.iter().map(|i| &i.surface).zip(colors).collect();
+    g2d.clear_many(&clears).unwrap();
+
+    for (image, color) in images.iter().zip(colors) {
+        for px in image.data().chunks_exact(4) {
+            assert_eq!(px, color);
+        }
+    }
+}
+
+#[test]
+fn test_mock_clear_rgb888_rejected() {
+    // Matches the hardware: g2d_clear does not support 3-byte RGB.
+    let g2d = G2D::mock().expect("Failed to open mock");
+    let mut image = HostImage::new(8, 8, g2d_format_G2D_RGB888, 8 * 8 * 3);
+    assert!(g2d.clear(&mut image.surface, [1, 2, 3, 255]).is_err());
+}
+
+// =============================================================================
+// Blit / Convert Tests
+// =============================================================================
+
+#[test]
+fn test_mock_blit_rgba_to_rgb888() {
+    let g2d = G2D::mock().expect("Failed to open mock");
+    let mut src = HostImage::rgba(16, 16);
+    src.write(|data| {
+        for (i, px) in data.chunks_exact_mut(4).enumerate() {
+            px.copy_from_slice(&[i as u8, (i * 3) as u8, 200, 255]);
+        }
+    });
+    let dst = HostImage::new(16, 16, g2d_format_G2D_RGB888, 16 * 16 * 3);
+
+    g2d.blit(&src.surface, &dst.surface).unwrap();
+    g2d.finish().unwrap();
+
+    for (i, px) in dst.data().chunks_exact(3).enumerate() {
+        assert_eq!(px, [i as u8, (i * 3) as u8, 200], "pixel {i}");
+    }
+}
+
+#[test]
+fn test_mock_rejects_unregistered_memory() {
+    let g2d = G2D::mock().expect("Failed to open mock");
+    let mut src = HostImage::rgba(8, 8);
+    let dst = HostImage::rgba(8, 8);
+    g2d.blit(&src.surface, &dst.surface).unwrap();
+
+    // Plain host memory is not dereferenced.
+    let mut plain = vec![0u8; 8 * 8 * 4];
+    let mut stray = src.surface;
+    stray.planes[0] = plain.as_mut_ptr() as u64;
+    assert!(g2d.blit(&stray, &dst.surface).is_err());
+    assert!(g2d.clear(&mut stray, [0; 4]).is_err());
+
+    // Nor is memory past the end of a buffer.
+    let mut tall = src.surface;
+    tall.height = 9;
+    tall.bottom = 9;
+    assert!(g2d.blit(&tall, &dst.surface).is_err());
+
+    // Nor a buffer while it is borrowed.
+    let surface = src.surface;
+    src.write(|_| assert!(g2d.blit(&surface, &dst.surface).is_err()));
+
+    // Nor one that has been dropped.
+    drop(src);
+    assert!(g2d.blit(&surface, &dst.surface).is_err());
+}
+
+#[test]
+fn test_mock_alpha_loss_counted() {
+    let g2d = G2D::mock().expect("Failed to open mock");
+    let rgba = HostImage::rgba(16, 16);
+    let rgb = HostImage::new(16, 16, g2d_format_G2D_RGB888, 16 * 16 * 3);
+    assert_eq!(g2d.alpha_loss_blits(), 0);
+
+    // Gaining alpha loses nothing.
+    g2d.blit(&rgb.surface, &rgba.surface).unwrap();
+    assert_eq!(g2d.alpha_loss_blits(), 0);
+
+    // Plain blits and multi_blit layers are counted on every call.
+    g2d.blit(&rgba.surface, &rgb.surface).unwrap();
+    g2d.blit(&rgba.surface, &rgb.surface).unwrap();
+    g2d.multi_blit(&[(rgba.surface, Rect::new(0, 0, 8, 8))], &rgb.surface)
+        .unwrap();
+    g2d.finish().unwrap();
+    assert_eq!(g2d.alpha_loss_blits(), 3);
+
+    // The count is per handle.
+    let other = G2D::mock().expect("Failed to open mock");
+    assert_eq!(other.alpha_loss_blits(), 0);
+}
+
+#[test]
+fn test_mock_blit_with_scaling() {
+    let g2d = G2D::mock().expect("Failed to open mock");
+    let mut src = HostImage::rgba(128, 128);
+    src.write(|data| {
+        for y in 0..128 {
+            for x in 0..128 {
+                let offset = (y * 128 + x) * 4;
+                data[offset..offset + 4].copy_from_slice(&[x as u8, y as u8, 128, 255]);
+            }
+        }
+    });
+    let dst = HostImage::rgba(64, 64);
+
+    g2d.blit(&src.surface, &dst.surface).unwrap();
+
+    // Nearest sampling of a 2:1 downscale picks the odd source pixel.
+    for y in 0..64 {
+        for x in 0..64 {
+            assert_eq!(
+                dst.pixel(x, y),
+                [(2 * x + 1) as u8, (2 * y + 1) as u8, 128, 255]
+            );
+        }
+    }
+}
+
+#[test]
+fn test_mock_blit_scale_limits() {
+    let g2d = G2D::mock().expect("Failed to open mock");
+    let src = HostImage::rgba(2000, 20);
+    let dst = HostImage::rgba(100, 20);
+    assert!(matches!(
+        g2d.blit(&src.surface, &dst.surface),
+        Err(Error::ScaleOutOfRange { .. })
+    ));
+
+    // Exactly MAX_SCALE_FACTOR is allowed both ways.
+    let mut src_16x = src.surface;
+    src_16x.right = 1600;
+    g2d.blit(&src_16x, &dst.surface).unwrap();
+    let tiny = HostImage::rgba(2, 2);
+    let big = HostImage::rgba(32, 33);
+    let mut big_16x = big.surface;
+    big_16x.bottom = 32;
+    g2d.blit(&tiny.surface, &big_16x).unwrap();
+    assert!(matches!(
+        g2d.blit(&tiny.surface, &big.surface),
+        Err(Error::ScaleOutOfRange { .. })
+    ));
+
+    // Rotation compares each destination axis with the source axis it
+    // comes from: 2000x20 rotated into 20x125 is a 16x downscale.
+    let rotated = HostImage::rgba(20, 125);
+    let dst = rotated.surface.with_rotation(Rotation::Deg90);
+    g2d.blit(&src.surface, &dst).unwrap();
+    g2d.finish().unwrap();
+    assert_eq!(MAX_SCALE_FACTOR, 16);
+}
+
+#[test]
+fn test_mock_multi_blit_quadrants() {
+    let g2d = G2D::mock().expect("Failed to open mock");
+    let colors = [
+        [255, 0, 0, 255],
+        [0, 255, 0, 255],
+        [0, 0, 255, 255],
+        [255, 255, 0, 255],
+    ];
+    let tiles: Vec<HostImage> = colors
+        .iter()
+        .map(|color| {
+            let mut tile = HostImage::rgba(32, 32);
+            tile.write(|data| {
+                for px in data.chunks_exact_mut(4) {
+                    px.copy_from_slice(color);
+                }
+            });
+            tile
+        })
+        .collect();
+    let dst = HostImage::rgba(64, 64);
+    let layers: Vec<(G2DSurface, Rect)> = tiles
+        .iter()
+        .enumerate()
+        .map(|(i, tile)| {
+            let (x, y) = ((i % 2) as i32 * 32, (i / 2) as i32 * 32);
+            (tile.surface, Rect::new(x, y, x + 32, y + 32))
+        })
+        .collect();
+
+    g2d.multi_blit(&layers, &dst.surface).unwrap();
+    g2d.finish().unwrap();
+
+    for (i, color) in colors.iter().enumerate() {
+        let (x0, y0) = ((i % 2) * 32, (i / 2) * 32);
+        for y in y0..y0 + 32 {
+            for x in x0..x0 + 32 {
+                assert_eq!(dst.pixel(x, y), color, "quadrant {i} pixel ({x}, {y})");
+            }
+        }
+    }
+
+    let too_many = vec![layers[0]; MAX_MULTI_BLIT_LAYERS + 1];
+    assert!(matches!(
+        g2d.multi_blit(&too_many, &dst.surface),
+        Err(Error::Unsupported(_))
+    ));
+}
+
+#[cfg(feature = "dma-heap")]
+#[test]
+fn test_mock_plan() {
+    let g2d = G2D::mock().expect("Failed to open mock");
+    let nv12 = G2DFormat::try_from(NV12).unwrap();
+    let rgba = G2DFormat::try_from(RGBA).unwrap();
+    let yuyv = G2DFormat::try_from(YUYV).unwrap();
+    let shape = |plan: &Plan<'_>| -> Vec<(g2d_format, usize, usize, Rotation)> {
+        plan.steps()
+            .iter()
+            .map(|s| (s.dst.format.format(), s.dst.width, s.dst.height, s.rotation))
+            .collect()
+    };
+
+    // 24x reduction: three passes of at most MAX_PASS_DOWNSCALE each.
+    let plan = g2d
+        .plan(
+            SurfaceSpec::new(nv12, 3840, 2160),
+            SurfaceSpec::new(rgba, 160, 160),
+            Rotation::None,
+        )
+        .unwrap();
+    assert_eq!(
+        shape(&plan),
+        [
+            (g2d_format_G2D_RGBA8888, 960, 540, Rotation::None),
+            (g2d_format_G2D_RGBA8888, 240, 160, Rotation::None),
+            (g2d_format_G2D_RGBA8888, 160, 160, Rotation::None),
+        ]
+    );
+    assert_eq!(plan.steps()[0].src.format.format(), g2d_format_G2D_NV12);
+    assert_eq!(3840 / 960, MAX_PASS_DOWNSCALE);
+
+    // Within the limit and unrotated: one blit.
+    let plan = g2d
+        .plan(
+            SurfaceSpec::new(nv12, 640, 480),
+            SurfaceSpec::new(rgba, 320, 240),
+            Rotation::None,
+        )
+        .unwrap();
+    assert_eq!(
+        shape(&plan),
+        [(g2d_format_G2D_RGBA8888, 320, 240, Rotation::None)]
+    );
+
+    // A rotated YUV output is rotated into RGBA, then converted unrotated.
+    let plan = g2d
+        .plan(
+            SurfaceSpec::new(rgba, 640, 480),
+            SurfaceSpec::new(yuyv, 480, 640),
+            Rotation::Deg90,
+        )
+        .unwrap();
+    assert_eq!(
+        shape(&plan),
+        [
+            (g2d_format_G2D_RGBA8888, 480, 640, Rotation::Deg90),
+            (g2d_format_G2D_YUYV, 480, 640, Rotation::None),
+        ]
+    );
+
+    assert!(matches!(
+        g2d.plan(
+            SurfaceSpec::new(rgba, 0, 480),
+            SurfaceSpec::new(rgba, 320, 240),
+            Rotation::None
+        ),
+        Err(Error::InvalidSurface(_))
+    ));
+}
+
+#[test]
+fn test_mock_blit_yuyv_to_rgba() {
+    let mut g2d = G2D::mock().expect("Failed to open mock");
+    g2d.set_bt709_colorspace().unwrap();
+
+    let mut src = HostImage::new(64, 64, g2d_format_G2D_YUYV, 64 * 64 * 2);
+    src.write(|data| data.fill(128));
+    let dst = HostImage::rgba(64, 64);
+
+    g2d.blit(&src.surface, &dst.surface).unwrap();
+
+    // Limited-range Y=128 with neutral chroma is mid gray.
+    for px in dst.data().chunks_exact(4) {
+        assert_near(px, &[130, 130, 130, 255], 1, "YUYV gray");
+    }
+}
+
+#[test]
+fn test_mock_yuv_output_clamp() {
+    let g2d = G2D::mock().expect("Failed to open mock");
+    g2d.reset_state().unwrap();
+
+    let mut src = HostImage::new(2, 2, g2d_format_G2D_YUYV, 2 * 2 * 2);
+    src.write(|data| data[..4].copy_from_slice(&[235, 128, 235, 240]));
+    src.write(|data| data[4..].copy_from_slice(&[16, 16, 16, 128]));
+    let dst = HostImage::rgba(2, 2);
+    g2d.blit(&src.surface, &dst.surface).unwrap();
+
+    assert_eq!(dst.pixel(0, 0)[0], 255, "red clamps high");
+    assert_eq!(dst.pixel(0, 1)[2], 0, "blue clamps low");
+}
+
+#[test]
+fn test_mock_blit_nv12_to_rgba() {
+    let mut g2d = G2D::mock().expect("Failed to open mock");
+    g2d.set_bt709_colorspace().unwrap();
+
+    let mut src = HostImage::nv12(32, 32);
+    src.write(|data| data.fill(128));
+    let dst = HostImage::rgba(32, 32);
+
+    g2d.blit(&src.surface, &dst.surface).unwrap();
+
+    for px in dst.data().chunks_exact(4) {
+        assert_near(px, &[130, 130, 130, 255], 1, "NV12 gray");
+    }
+}
+
+#[test]
+fn test_mock_blit_i420_vs_yv12() {
+    let mut g2d = G2D::mock().expect("Failed to open mock");
+    g2d.set_bt709_colorspace().unwrap();
+
+    let (w, h) = (16usize, 16usize);
+    let (luma, chroma) = (w * h, w * h / 4);
+    let mut src = HostImage::new(w, h, g2d_sys::g2d_format_G2D_I420, luma + 2 * chroma);
+    src.write(|data| data[..luma].fill(128));
+    src.write(|data| data[luma..luma + chroma].fill(90));
+    src.write(|data| data[luma + chroma..].fill(200));
+    let address = src.surface.planes[0];
+
+    let i420 = HostImage::rgba(w, h);
+    let yv12 = HostImage::rgba(w, h);
+    g2d.blit(
+        &G2DSurface::i420(address, w as i32, h as i32),
+        &i420.surface,
+    )
+    .unwrap();
+    g2d.blit(
+        &G2DSurface::yv12(address, w as i32, h as i32),
+        &yv12.surface,
+    )
+    .unwrap();
+
+    let (r, b) = (i420.pixel(8, 8)[0], i420.pixel(8, 8)[2]);
+    assert!(r > b, "I420 should decode reddish, got r={r} b={b}");
+    let (r, b) = (yv12.pixel(8, 8)[0], yv12.pixel(8, 8)[2]);
+    assert!(b > r, "YV12 should decode bluish, got r={r} b={b}");
+}
+
+#[test]
+fn test_mock_blit_yuyv_roundtrip() {
+    let mut g2d = G2D::mock().expect("Failed to open mock");
+    g2d.set_bt709_colorspace().unwrap();
+
+    let mut src = HostImage::rgba(32, 32);
+    src.write(|data| {
+        for px in data.chunks_exact_mut(4) {
+            px.copy_from_slice(&[200, 60, 30, 255]);
+        }
+    });
+    let yuyv = HostImage::new(32, 32, g2d_format_G2D_YUYV, 32 * 32 * 2);
+    let back = HostImage::rgba(32, 32);
+
+    g2d.blit(&src.surface, &yuyv.surface).unwrap();
+    g2d.blit(&yuyv.surface, &back.surface).unwrap();
+
+    for px in back.data().chunks_exact(4) {
+        assert_near(px, &[200, 60, 30, 255], 3, "YUYV round trip");
+    }
+}
+
+/// Reference limited-range Y'CbCr to R'G'B' decode for luma weights `kr`/`kb`.
+fn reference_yuv_to_rgb(kr: f32, kb: f32, [y, u, v]: [u8; 3]) -> [u8; 3] {
+    let y = (y as f32 - 16.0) * 255.0 / 219.0;
+    let u = (u as f32 - 128.0) * 255.0 / 224.0;
+    let v = (v as f32 - 128.0) * 255.0 / 224.0;
+    let r = y + 2.0 * (1.0 - kr) * v;
+    let b = y + 2.0 * (1.0 - kb) * u;
+    let g = (y - kr * r - kb * b) / (1.0 - kr - kb);
+    [r, g, b].map(|c| c.round().clamp(0.0, 255.0) as u8)
+}
+
+/// Reference R'G'B' to limited-range Y'CbCr encode for luma weights `kr`/`kb`.
+fn reference_rgb_to_yuv(kr: f32, kb: f32, [r, g, b]: [u8; 3]) -> [u8; 3] {
+    let (r, g, b) = (r as f32, g as f32, b as f32);
+    let y = kr * r + (1.0 - kr - kb) * g + kb * b;
+    let u = (b - y) / (2.0 * (1.0 - kb));
+    let v = (r - y) / (2.0 * (1.0 - kr));
+    [
+        16.0 + y * 219.0 / 255.0,
+        128.0 + u * 224.0 / 255.0,
+        128.0 + v * 224.0 / 255.0,
+    ]
+    .map(|c| c.round().clamp(0.0, 255.0) as u8)
+}
+
+const BT601: (f32, f32) = (0.299, 0.114);
+const BT709: (f32, f32) = (0.2126, 0.0722);
+
+#[test]
+fn test_mock_convert_csc_bt601_to_bt709() {
+    let g2d = G2D::mock().expect("Failed to open mock");
+    // BT.601 limited-range red.
+    let yuv = [81, 90, 240];
+    let mut src = HostImage::new(16, 16, g2d_format_G2D_YUYV, 16 * 16 * 2);
+    src.write(|data| {
+        for mp in data.chunks_exact_mut(4) {
+            mp.copy_from_slice(&[yuv[0], yuv[1], yuv[0], yuv[2]]);
+        }
+    });
+    let rgba = HostImage::rgba(16, 16);
+
+    g2d.convert_csc(
+        &src.surface,
+        &rgba.surface,
+        Colorspace::Bt601,
+        Colorspace::Bt709,
+    )
+    .unwrap();
+
+    // Decoded with the source (BT.601) matrix, not the BT.709 one.
+    let [r, g, b] = reference_yuv_to_rgb(BT601.0, BT601.1, yuv);
+    assert_near(&rgba.pixel(5, 5), &[r, g, b, 255], 2, "BT.601 decode");
+    let [_, g709, _] = reference_yuv_to_rgb(BT709.0, BT709.1, yuv);
+    assert!(
+        g709.abs_diff(g) > 10,
+        "BT.601 and BT.709 decodes should differ"
+    );
+
+    // Re-encode the RGB result as BT.709 YUYV.
+    let out = HostImage::new(16, 16, g2d_format_G2D_YUYV, 16 * 16 * 2);
+    g2d.convert_csc(
+        &rgba.surface,
+        &out.surface,
+        Colorspace::Bt709,
+        Colorspace::Bt709,
+    )
+    .unwrap();
+    let [y, u, v] = reference_rgb_to_yuv(BT709.0, BT709.1, [r, g, b]);
+    assert_near(&out.data()[..4], &[y, u, y, v], 2, "BT.709 encode");
+}
+
+#[test]
+fn test_mock_convert_csc_rejects_yuv_to_yuv_matrix_change() {
+    let g2d = G2D::mock().expect("Failed to open mock");
+    let src = HostImage::new(16, 16, g2d_format_G2D_YUYV, 16 * 16 * 2);
+    let dst = HostImage::new(16, 16, g2d_format_G2D_YUYV, 16 * 16 * 2);
+
+    let result = g2d.convert_csc(
+        &src.surface,
+        &dst.surface,
+        Colorspace::Bt601,
+        Colorspace::Bt709,
+    );
+    assert!(matches!(result, Err(Error::InvalidFormat(_))), "{result:?}");
+}
+
+#[test]
+fn test_mock_builtin_yuv_matrices_only() {
+    // Every colorspace either selects exactly one of the four built-in
+    // matrix modes or is unsupported; there is no other color state.
+    let matrices = [
+        Feature::YuvBt601,
+        Feature::YuvBt709,
+        Feature::YuvBt601FullRange,
+        Feature::YuvBt709FullRange,
+    ];
+    let mut g2d = G2D::mock().expect("Failed to open mock");
+    for colorspace in [Colorspace::Bt601, Colorspace::Bt709, Colorspace::Bt2020] {
+        for range in [Range::Limited, Range::Full] {
+            match g2d.set_colorspace(colorspace, range) {
+                Ok(()) => {
+                    let enabled = matrices
+                        .iter()
+                        .filter(|&&m| g2d.is_enabled(m).unwrap())
+                        .count();
+                    assert_eq!(enabled, 1, "{colorspace:?} {range:?}");
+                }
+                Err(Error::Unsupported(_)) => assert_eq!(colorspace, Colorspace::Bt2020),
+                Err(e) => panic!("{colorspace:?} {range:?}: {e}"),
+            }
+        }
+    }
+}
+
+#[test]
+fn test_mock_blit_rotation_90() {
+    let g2d = G2D::mock().expect("Failed to open mock");
+    let mut src = HostImage::rgba(4, 2);
+    src.write(|data| {
+        for (i, px) in data.chunks_exact_mut(4).enumerate() {
+            px.copy_from_slice(&[i as u8, 0, 0, 255]);
+        }
+    });
+    let mut dst = HostImage::rgba(2, 4);
+    dst.surface.rot = g2d_rotation_G2D_ROTATION_90;
+
+    g2d.blit(&src.surface, &dst.surface).unwrap();
+
+    // Clockwise: source row 0 becomes the rightmost destination column.
+    assert_eq!(dst.pixel(1, 0)[0], 0);
+    assert_eq!(dst.pixel(1, 3)[0], 3);
+    assert_eq!(dst.pixel(0, 0)[0], 4);
+    assert_eq!(dst.pixel(0, 3)[0], 7);
+}
+
+#[test]
+fn test_mock_blit_with_rotation() {
+    let g2d = G2D::mock().expect("Failed to open mock");
+    let mut src = HostImage::rgba(4, 2);
+    src.write(|data| {
+        for (i, px) in data.chunks_exact_mut(4).enumerate() {
+            px.copy_from_slice(&[i as u8, 0, 0, 255]);
+        }
+    });
+
+    let unswapped = HostImage::rgba(4, 2);
+    let surface = unswapped.surface.with_rotation(Rotation::Deg270);
+    assert!(matches!(
+        g2d.blit(&src.surface, &surface),
+        Err(Error::InvalidSurface(_))
+    ));
+
+    let dst = HostImage::rgba(2, 4);
+    let surface = dst.surface.with_rotation(Rotation::Deg270);
+    assert_eq!(surface.rotation(), u32::from(Rotation::Deg270));
+    g2d.blit(&src.surface, &surface).unwrap();
+
+    // Counter-clockwise: source row 0 becomes the leftmost column, bottom up.
+    assert_eq!(dst.pixel(0, 3)[0], 0);
+    assert_eq!(dst.pixel(0, 0)[0], 3);
+    assert_eq!(dst.pixel(1, 3)[0], 4);
+    assert_eq!(dst.pixel(1, 0)[0], 7);
+}
+
+#[test]
+fn test_mock_surface_from_decoder_layout() {
+    let g2d = G2D::mock().expect("Failed to open mock");
+    let nv12 = G2DFormat::try_from(NV12).unwrap();
+
+    // An 8x4 picture in a 16x8 decoded frame, with a gap before the UV plane.
+    let luma = Plane {
+        offset: 0,
+        pitch: 16,
+        rows: 8,
+    };
+    let chroma = Plane {
+        offset: 160,
+        pitch: 16,
+        rows: 4,
+    };
+    let layout = PlaneLayout::new(nv12, vec![luma, chroma]).unwrap();
+    assert_eq!(layout.size(), 224);
+
+    let mut frame = MockBuffer::new(layout.size());
+    frame.write_with(|data| {
+        data.fill(16);
+        for y in 0..4 {
+            data[y * 16..y * 16 + 8].fill(235);
+        }
+        data[160..].fill(128);
+    });
+    let src =
+        G2DSurface::from_layout(nv12, frame.address(), &layout, Rect::new(0, 0, 8, 4)).unwrap();
+    assert_eq!(src.dimensions(), (16, 8));
+
+    let dst = HostImage::rgba(8, 4);
+    g2d.blit(&src, &dst.surface).unwrap();
+    for px in dst.data().chunks_exact(4) {
+        assert_near(px, &[255, 255, 255, 255], 2, "cropped picture");
+    }
+
+    let halved = Plane { pitch: 8, ..chroma };
+    assert!(matches!(
+        PlaneLayout::new(nv12, vec![luma, halved]),
+        Err(Error::InvalidSurface(_))
+    ));
+    assert!(matches!(
+        G2DSurface::from_layout(nv12, 0, &layout, Rect::new(0, 0, 17, 4)),
+        Err(Error::InvalidSurface(_))
+    ));
+}
+
+#[test]
+fn test_mock_blit_with_flip() {
+    let g2d = G2D::mock().expect("Failed to open mock");
+    let mut src = HostImage::rgba(4, 2);
+    src.write(|data| {
+        for (i, px) in data.chunks_exact_mut(4).enumerate() {
+            px.copy_from_slice(&[i as u8, 0, 0, 255]);
+        }
+    });
+
+    // Flip into the 4x2 ROI at (2, 1) of an 8x4 image.
+    for (flip, expected) in [
+        (Flip::Horizontal, [[3, 2, 1, 0], [7, 6, 5, 4]]),
+        (Flip::Vertical, [[4, 5, 6, 7], [0, 1, 2, 3]]),
+        (Flip::Both, [[7, 6, 5, 4], [3, 2, 1, 0]]),
+    ] {
+        let mut dst = HostImage::rgba(8, 4);
+        dst.surface.left = 2;
+        dst.surface.top = 1;
+        dst.surface.right = 6;
+        dst.surface.bottom = 3;
+        g2d.blit_with_flip(&src.surface, &dst.surface, flip)
+            .unwrap();
+
+        for y in 0..4 {
+            for x in 0..8 {
+                let inside = (2..6).contains(&x) && (1..3).contains(&y);
+                let want = if inside {
+                    [expected[y - 1][x - 2], 0, 0, 255]
+                } else {
+                    [0; 4]
+                };
+                assert_eq!(dst.pixel(x, y), want, "{flip:?} pixel ({x}, {y})");
+            }
+        }
+    }
+}
+
+#[test]
+fn test_mock_blit_clipped_quarter() {
+    let g2d = G2D::mock().expect("Failed to open mock");
+    let mut src = HostImage::rgba(64, 64);
+    src.write(|data| {
+        for px in data.chunks_exact_mut(4) {
+            px.copy_from_slice(&[255, 255, 255, 255]);
+        }
+    });
+    let dst = HostImage::rgba(64, 64);
+
+    g2d.blit_clipped(&src.surface, &dst.surface, Rect::new(0, 0, 32, 32))
+        .unwrap();
+
+    for y in 0..64 {
+        for x in 0..64 {
+            let expected = if x < 32 && y < 32 { [255; 4] } else { [0; 4] };
+            assert_eq!(dst.pixel(x, y), expected, "pixel ({x},{y})");
+        }
+    }
+}
+
+#[test]
+fn test_mock_place_off_right_edge() {
+    let g2d = G2D::mock().expect("Failed to open mock");
+    let mut src = HostImage::rgba(16, 16);
+    src.write(|data| {
+        for (i, px) in data.chunks_exact_mut(4).enumerate() {
+            px.copy_from_slice(&[(i % 16) as u8, (i / 16) as u8, 200, 255]);
+        }
+    });
+    let dst = HostImage::rgba(32, 32);
+
+    g2d.place(&src.surface, &dst.surface, 24, 4).unwrap();
+
+    for y in 0..32 {
+        for x in 0..32 {
+            let expected = if x >= 24 && (4..20).contains(&y) {
+                [(x - 24) as u8, (y - 4) as u8, 200, 255]
+            } else {
+                [0; 4]
+            };
+            assert_eq!(dst.pixel(x, y), expected, "pixel ({x},{y})");
+        }
+    }
+
+    // Entirely off-surface placements write nothing.
+    g2d.place(&src.surface, &dst.surface, 40, 0).unwrap();
+    g2d.place(&src.surface, &dst.surface, -16, 0).unwrap();
+    assert!(dst.data()[..24 * 4].iter().all(|&b| b == 0));
+}
+
+#[test]
+fn test_mock_blit_over_color() {
+    let g2d = G2D::mock().expect("Failed to open mock");
+    let mut src = HostImage::rgba(16, 16);
+    src.write(|data| {
+        for y in 0..16 {
+            for x in 0..16 {
+                let hole = (4..12).contains(&x) && (4..12).contains(&y);
+                let offset = (y * 16 + x) * 4;
+                let px = if hole { [0; 4] } else { [0, 255, 0, 255] };
+                data[offset..offset + 4].copy_from_slice(&px);
+            }
+        }
+    });
+    let mut dst = HostImage::rgba(16, 16);
+
+    g2d.blit_over_color(&src.surface, &mut dst.surface, [255, 0, 0, 255])
+        .unwrap();
+
+    assert_eq!(dst.pixel(8, 8), [255, 0, 0, 255]);
+    assert_eq!(dst.pixel(0, 0), [0, 255, 0, 255]);
+}
+
+#[test]
+fn test_mock_letterbox() {
+    let g2d = G2D::mock().expect("Failed to open mock");
+    let gray = [114, 114, 114, 255];
+    let red = [255, 0, 0, 255];
+    let mut src = HostImage::rgba(16, 9);
+    src.write(|data| {
+        for px in data.chunks_exact_mut(4) {
+            px.copy_from_slice(&red);
+        }
+    });
+
+    // 32x33: an 18-row band leaves 15 rows, 7 above and 8 below.
+    let dst = HostImage::rgba(32, 33);
+    g2d.letterbox(&src.surface, &dst.surface, gray).unwrap();
+    for y in 0..33 {
+        let expected = if (7..25).contains(&y) { red } else { gray };
+        for x in 0..32 {
+            assert_eq!(dst.pixel(x, y), expected, "pixel ({x}, {y})");
+        }
+    }
+
+    // Matching aspect ratio: the blit covers everything and nothing is gray.
+    let dst = HostImage::rgba(32, 18);
+    g2d.letterbox(&src.surface, &dst.surface, gray).unwrap();
+    assert!(dst.data().chunks_exact(4).all(|px| px == red));
+}
+
+#[test]
+fn test_mock_overlay() {
+    let g2d = G2D::mock().expect("Failed to open mock");
+    let mut overlay = HostImage::rgba(16, 8);
+    overlay.write(|data| {
+        for px in data.chunks_exact_mut(4) {
+            px.copy_from_slice(&[255, 255, 255, 128]);
+        }
+    });
+    let frame = HostImage::rgba(64, 48);
+
+    let stats = g2d
+        .scope(|batch| {
+            batch.clear(&frame.surface, [0, 0, 0, 255]).overlay(
+                &frame.surface,
+                &overlay.surface,
+                10,
+                10,
+            );
+        })
+        .unwrap();
+    assert_eq!(
+        (stats.clears, stats.overlays, stats.elided_clears),
+        (1, 1, 0)
+    );
+
+    for y in 0..48 {
+        for x in 0..64 {
+            let px = frame.pixel(x, y);
+            if (10..26).contains(&x) && (10..18).contains(&y) {
+                assert_near(&px[..3], &[128, 128, 128], 1, &format!("pixel ({x},{y})"));
+            } else {
+                assert_eq!(px, [0, 0, 0, 255], "pixel ({x},{y})");
+            }
+        }
+    }
+
+    // Blending is switched off again afterwards.
+    g2d.blit(&overlay.surface, &frame.surface).unwrap();
+    assert_eq!(frame.pixel(0, 0), [255, 255, 255, 128]);
+}
+
+#[test]
+fn test_mock_enable_disable_feature() {
+    let g2d = G2D::mock().expect("Failed to open mock");
+    let mut src = HostImage::rgba(4, 4);
+    src.write(|data| {
+        for px in data.chunks_exact_mut(4) {
+            px.copy_from_slice(&[255, 0, 0, 128]);
+        }
+    });
+    src.surface.set_blend_func(BlendFunc::SrcAlpha);
+    let mut dst = HostImage::rgba(4, 4);
+    dst.surface.set_blend_func(BlendFunc::OneMinusSrcAlpha);
+
+    g2d.enable(Feature::Dither).unwrap();
+    assert!(g2d.is_enabled(Feature::Dither).unwrap());
+    g2d.disable(Feature::Dither).unwrap();
+    assert!(!g2d.is_enabled(Feature::Dither).unwrap());
+
+    // Enabled blending stays on for plain blits until disabled.
+    g2d.enable(Feature::Blend).unwrap();
+    dst.write(|data| {
+        for px in data.chunks_exact_mut(4) {
+            px.copy_from_slice(&[0, 0, 255, 255]);
+        }
+    });
+    g2d.blit(&src.surface, &dst.surface).unwrap();
+    assert_near(&dst.pixel(0, 0)[..3], &[128, 0, 127], 1, "blended");
+
+    g2d.disable(Feature::Blend).unwrap();
+    g2d.blit(&src.surface, &dst.surface).unwrap();
+    assert_eq!(dst.pixel(0, 0), [255, 0, 0, 128]);
+}
+
+#[test]
+fn test_mock_capabilities() {
+    let g2d = G2D::mock().expect("Failed to open mock");
+    g2d.enable(Feature::Dither).unwrap();
+
+    let caps = g2d.capabilities().unwrap();
+    assert_eq!(
+        caps,
+        Capabilities {
+            blend: true,
+            dither: true,
+            global_alpha: true,
+            yuv_input: true,
+            yuv_output: true,
+            scaling: true,
+        }
+    );
+    // Probing leaves enabled modes on and disabled ones off.
+    assert!(g2d.is_enabled(Feature::Dither).unwrap());
+    assert!(!g2d.is_enabled(Feature::Blend).unwrap());
+}
+
+#[test]
+fn test_mock_blend() {
+    let g2d = G2D::mock().expect("Failed to open mock");
+    let mut src = HostImage::rgba(8, 8);
+    src.write(|data| {
+        for px in data.chunks_exact_mut(4) {
+            px.copy_from_slice(&[255, 0, 0, 128]);
+        }
+    });
+    src.surface.set_blend_func(BlendFunc::SrcAlpha);
+    let mut dst = HostImage::rgba(8, 8);
+    dst.surface.set_blend_func(BlendFunc::OneMinusSrcAlpha);
+
+    dst.write(|data| {
+        for px in data.chunks_exact_mut(4) {
+            px.copy_from_slice(&[0, 0, 255, 255]);
+        }
+    });
+    g2d.blend(&src.surface, &dst.surface).unwrap();
+    assert_near(&dst.pixel(3, 3)[..3], &[128, 0, 127], 1, "purple");
+
+    // Half global alpha halves the source's coverage again.
+    src.surface.global_alpha = 128;
+    dst.write(|data| {
+        for px in data.chunks_exact_mut(4) {
+            px.copy_from_slice(&[0, 0, 255, 255]);
+        }
+    });
+    g2d.blend(&src.surface, &dst.surface).unwrap();
+    assert_near(&dst.pixel(3, 3)[..3], &[64, 0, 191], 1, "global alpha");
+
+    // Both caps are switched off again afterwards.
+    src.surface.global_alpha = 255;
+    g2d.blit(&src.surface, &dst.surface).unwrap();
+    assert_eq!(dst.pixel(0, 0), [255, 0, 0, 128]);
+}
+
+#[test]
+fn test_mock_batch_elides_redundant_clear() {
+    let g2d = G2D::mock().expect("Failed to open mock");
+    let mut src = HostImage::rgba(16, 16);
+    src.write(|data| {
+        for px in data.chunks_exact_mut(4) {
+            px.copy_from_slice(&[0, 0, 255, 255]);
+        }
+    });
+    let dst = HostImage::rgba(16, 16);
+
+    // Letterbox-style clear then full overwrite: the clear is dropped.
+    let stats = g2d
+        .scope(|batch| {
+            batch
+                .clear(&dst.surface, [255, 0, 0, 255])
+                .blit(&src.surface, &dst.surface);
+        })
+        .unwrap();
+    assert_eq!(stats.elided_clears, 1);
+    assert_eq!((stats.clears, stats.blits), (0, 1));
+    assert!(dst.data().chunks_exact(4).all(|px| px == [0, 0, 255, 255]));
+
+    // A blit covering only part of the surface keeps the clear.
+    let mut half = dst.surface;
+    half.right = 8;
+    let stats = g2d
+        .scope(|batch| {
+            batch
+                .clear(&dst.surface, [255, 0, 0, 255])
+                .blit(&src.surface, &half);
+        })
+        .unwrap();
+    assert_eq!((stats.clears, stats.elided_clears), (1, 0));
+    assert_eq!(dst.pixel(4, 4), [0, 0, 255, 255]);
+    assert_eq!(dst.pixel(12, 4), [255, 0, 0, 255]);
+
+    // Coalescing can be turned off.
+    let mut batch = g2d.batch().with_coalesce_clears(false);
+    batch
+        .clear(&dst.surface, [0, 255, 0, 255])
+        .blit(&src.surface, &dst.surface);
+    let stats = batch.submit().unwrap();
+    assert_eq!((stats.clears, stats.elided_clears), (1, 0));
+
+    // Blending reads the cleared pixels, so nothing is elided.
+    assert_eq!(
+        unsafe { g2d.lib.g2d_enable(g2d.handle, g2d_cap_mode_G2D_BLEND) },
+        0
+    );
+    let stats = g2d
+        .scope(|batch| {
+            batch
+                .clear(&dst.surface, [255, 0, 0, 255])
+                .blit(&src.surface, &dst.surface);
+        })
+        .unwrap();
+    assert_eq!((stats.clears, stats.elided_clears), (1, 0));
+}
+
+#[test]
+fn test_mock_builder_configure() {
+    let g2d = G2D::mock().expect("Failed to open mock");
+    G2D::builder()
+        .colorspace(Colorspace::Bt709)
+        .dither(true)
+        .configure(&g2d)
+        .unwrap();
+
+    let cap = |cap| {
+        let mut enable = 0;
+        assert_eq!(
+            unsafe { g2d.lib.g2d_query_cap(g2d.handle, cap, &mut enable) },
+            0
+        );
+        enable != 0
+    };
+    assert!(cap(g2d_cap_mode_G2D_YUV_BT_709));
+    assert!(!cap(g2d_cap_mode_G2D_YUV_BT_601));
+    assert!(cap(g2d_cap_mode_G2D_DITHER));
+    assert!(!cap(g2d_cap_mode_G2D_BLEND));
+}
+
+#[test]
+fn test_mock_required_stride_alignment() {
+    for fourcc in [RGBA, RGB, YUYV, NV12] {
+        let format = G2DFormat::try_from(fourcc).unwrap();
+        let align = format.required_stride_alignment();
+        assert!(
+            align.is_power_of_two(),
+            "{fourcc}: {align} not a power of two"
+        );
+        assert!(align >= 16, "{fourcc}: {align} below 16");
+
+        let bpp = format.bytes_per_pixel().unwrap_or(1);
+        let stride = format.aligned_stride(1918);
+        assert!(stride >= 1918);
+        assert_eq!((stride * bpp) % align, 0, "{fourcc}: stride {stride}");
+    }
+}
+
+#[test]
+fn test_mock_reset_state_disables_blend() {
+    let g2d = G2D::mock().expect("Failed to open mock");
+    let mut src = HostImage::rgba(8, 8);
+    src.write(|data| {
+        for px in data.chunks_exact_mut(4) {
+            px.copy_from_slice(&[255, 0, 0, 128]);
+        }
+    });
+    let mut dst = HostImage::rgba(8, 8);
+    dst.write(|data| {
+        for px in data.chunks_exact_mut(4) {
+            px.copy_from_slice(&[0, 0, 255, 255]);
+        }
+    });
+    src.surface.blendfunc = g2d_blend_func_G2D_SRC_ALPHA;
+    dst.surface.blendfunc = g2d_blend_func_G2D_ONE_MINUS_SRC_ALPHA;
+
+    assert_eq!(
+        unsafe { g2d.lib.g2d_enable(g2d.handle, g2d_cap_mode_G2D_BLEND) },
+        0
+    );
+    g2d.reset_state().unwrap();
+    g2d.blit(&src.surface, &dst.surface).unwrap();
+
+    assert_eq!(dst.pixel(3, 3), [255, 0, 0, 128]);
+}
+
+// =============================================================================
+// Validation Tests
+// =============================================================================
+
+#[test]
+fn test_mock_rejects_source_only_destination() {
+    let g2d = G2D::mock().expect("Failed to open mock");
+    let src = HostImage::rgba(16, 16);
+    let dst = HostImage::nv12(16, 16);
+    let mut yuv_dst = dst.surface;
+    yuv_dst.format = g2d_sys::g2d_format_G2D_I420;
+
+    assert!(matches!(
+        g2d.blit(&src.surface, &yuv_dst),
+        Err(Error::UnsupportedDestinationFormat(_))
+    ));
+}
+
+#[test]
+fn test_mock_rejects_out_of_bounds_crop() {
+    let g2d = G2D::mock().expect("Failed to open mock");
+    let src = HostImage::rgba(16, 16);
+    let mut dst = HostImage::rgba(16, 16);
+    dst.surface.right = 17;
+
+    assert!(g2d.blit(&src.surface, &dst.surface).is_err());
+}
+
+#[test]
+fn test_mock_conversion_matrix() {
+    let g2d = G2D::mock().expect("Failed to open mock");
+    let matrix = g2d.conversion_matrix();
+    let format = |fourcc| G2DFormat::try_from(fourcc).unwrap();
+
+    assert_eq!(matrix.formats().count(), 29);
+    assert!(matrix.supports(format(YUYV), format(RGBA)));
+    assert!(matrix.supports(format(RGBA), format(YUYV)));
+    assert!(matrix.supports(format(GREY), format(RGBA)));
+    assert!(!matrix.supports(format(RGBA), format(GREY)));
+    assert!(!matrix.supports(format(RGBA), format(NV12)));
+
+    let sources = matrix.sources(format(RGBA)).count();
+    assert_eq!(sources, matrix.formats().count());
+    assert!(matrix
+        .destinations(format(NV12))
+        .all(|dst| dst.is_valid_destination()));
+}
+
+#[test]
+fn test_mock_clearable_formats() {
+    let g2d = G2D::mock().expect("Failed to open mock");
+    let clearable: Vec<_> = g2d.clearable_formats().iter().map(|f| f.format()).collect();
+
+    assert!(clearable.contains(&g2d_format_G2D_RGBA8888));
+    assert!(clearable.contains(&g2d_format_G2D_RGB565));
+    assert!(clearable.contains(&g2d_format_G2D_YUYV));
+    assert!(!clearable.contains(&g2d_format_G2D_RGB888));
+    assert!(!clearable.contains(&g2d_format_G2D_NV12));
+}
+
+#[test]
+fn test_mock_crop_alignment_policies() {
+    let mut g2d = G2D::mock().expect("Failed to open mock");
+    let mut src = HostImage::nv12(64, 16);
+    src.write(|data| {
+        let (luma, chroma) = data.split_at_mut(64 * 16);
+        for (i, y) in luma.iter_mut().enumerate() {
+            *y = (16 + 3 * (i % 64)) as u8;
+        }
+        for (i, c) in chroma.iter_mut().enumerate() {
+            *c = (64 + 2 * (i % 64)) as u8;
+        }
+    });
+    let crop = |left: i32| {
+        let mut surface = src.surface;
+        surface.left = left;
+        surface.right = left + 32;
+        surface
+    };
+    let blit = |g2d: &G2D, src: &G2DSurface| {
+        let dst = HostImage::rgba(32, 16);
+        g2d.blit(src, &dst.surface).map(|_| dst.data())
+    };
+
+    assert_eq!(g2d.crop_alignment(), CropAlignment::Reject);
+    assert!(matches!(
+        blit(&g2d, &crop(3)),
+        Err(Error::InvalidSurface(_))
+    ));
+
+    g2d.set_crop_alignment(CropAlignment::Floor);
+    let expected = blit(&g2d, &crop(2)).unwrap();
+    assert_eq!(blit(&g2d, &crop(3)).unwrap(), expected);
+
+    g2d.set_crop_alignment(CropAlignment::Round);
+    let expected = blit(&g2d, &crop(4)).unwrap();
+    assert_eq!(blit(&g2d, &crop(3)).unwrap(), expected);
+
+    // RGB sources are never adjusted.
+    g2d.set_crop_alignment(CropAlignment::Reject);
+    let rgba = HostImage::rgba(64, 16);
+    let mut odd = rgba.surface;
+    odd.left = 3;
+    assert!(blit(&g2d, &odd).is_ok());
+}
+
+#[test]
+fn test_mock_pipeline_validate_reports_stage_errors() {
+    let g2d = G2D::mock().expect("Failed to open mock");
+    let format = |fourcc| G2DFormat::try_from(fourcc).unwrap();
+
+    let pipeline = Pipeline::new()
+        .with_stage(Stage::new(
+            "decode",
+            SurfaceSpec::new(format(NV12), 640, 480),
+            SurfaceSpec::new(format(RGBA), 320, 240),
+        ))
+        .with_stage(
+            Stage::new(
+                "rotate",
+                SurfaceSpec::new(format(RGBA), 320, 240),
+                SurfaceSpec::new(format(YUYV), 240, 320),
+            )
+            .with_rotation(Rotation::Deg90),
+        )
+        .with_stage(Stage::new(
+            "empty",
+            SurfaceSpec::new(format(RGBA), 320, 240),
+            SurfaceSpec::new(format(RGBA), 0, 0),
+        ));
+
+    let errors = pipeline.validate(&g2d).unwrap_err();
+    assert_eq!(errors.len(), 2, "{errors:?}");
+    assert_eq!((errors[0].index, errors[0].name.as_str()), (1, "rotate"));
+    assert!(matches!(
+        errors[0].problem,
+        StageProblem::RotationUnsupportedForFormat(Rotation::Deg90, f)
+            if f.format() == g2d_format_G2D_YUYV
+    ));
+    assert_eq!(errors[1].index, 2);
+    assert!(matches!(
+        errors[1].problem,
+        StageProblem::InvalidDimensions {
+            width: 0,
+            height: 0
+        }
+    ));
+
+    let valid = Pipeline::new().with_stage(pipeline.stages()[0].clone());
+    assert!(valid.validate(&g2d).is_ok());
+}